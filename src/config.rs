@@ -0,0 +1,1164 @@
+//! A crate-level input configuration shared by all the readers.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+
+use crate::{InputEvent, KeyCode, KeyEvent, KeyModifiers, KeyProfile};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+lazy_static! {
+    /// A shared input configuration.
+    static ref INPUT_CONFIG: Mutex<InputConfig> = Mutex::new(InputConfig::default());
+}
+
+/// Returns a copy of the shared input configuration.
+pub(crate) fn input_config() -> InputConfig {
+    INPUT_CONFIG.lock().unwrap().clone()
+}
+
+/// Replaces the shared input configuration.
+pub(crate) fn set_input_config(config: InputConfig) {
+    *INPUT_CONFIG.lock().unwrap() = config;
+}
+
+/// Calls the `f` with the shared input configuration.
+pub(crate) fn with_input_config<F, R>(f: F) -> R
+where
+    F: FnOnce(&InputConfig) -> R,
+{
+    f(&INPUT_CONFIG.lock().unwrap())
+}
+
+/// Maps the C0 control `byte` to a possible `KeyEvent` with the shared input configuration.
+pub(crate) fn control_key_event(byte: u8) -> Option<KeyEvent> {
+    with_input_config(|config| config.control_key_event(byte))
+}
+
+/// Normalizes the `key_event` with the key profile from the shared input configuration.
+///
+/// Returns `None` if the event should be dropped (the modifier key events are dropped unless
+/// they're enabled).
+pub(crate) fn normalize_key_event(key_event: KeyEvent) -> Option<KeyEvent> {
+    // The profile is cloned, a custom profile can access the configuration
+    let (profile, modifier_key_events, shifted_chars) = with_input_config(|config| {
+        (
+            config.key_profile.clone(),
+            config.modifier_key_events,
+            config.shifted_chars,
+        )
+    });
+
+    if let KeyCode::Modifier(_) = key_event.code {
+        if !modifier_key_events {
+            return None;
+        }
+    }
+
+    if shifted_chars {
+        profile.normalize(shifted_char_event(key_event))
+    } else {
+        profile.normalize(key_event)
+    }
+}
+
+/// Maps the Shift + character `key_event` to the shifted character without the Shift modifier
+/// (`Shift + KeyCode::Char('1')` is the `KeyCode::Char('!')`), the way it's reported by
+/// the legacy terminal encoding & the Windows console.
+///
+/// The US layout is assumed for the ASCII digits & punctuation, the Shift modifier is kept if
+/// the shifted character isn't known.
+fn shifted_char_event(key_event: KeyEvent) -> KeyEvent {
+    let c = match key_event.code {
+        KeyCode::Char(c) if key_event.modifiers.contains(KeyModifiers::SHIFT) => c,
+        _ => return key_event,
+    };
+
+    let code = match shifted_char(c) {
+        Some(shifted) => KeyCode::Char(shifted),
+        // Already shifted (modifyOtherKeys, ...)
+        None if c.is_uppercase() || US_SHIFTED_CHARS.iter().any(|(_, s)| *s == c) => {
+            KeyCode::Char(c)
+        }
+        None => return key_event,
+    };
+
+    KeyEvent {
+        code,
+        modifiers: key_event.modifiers - KeyModifiers::SHIFT,
+        ..key_event
+    }
+}
+
+/// The ASCII digits & punctuation with their shifted characters (US layout).
+const US_SHIFTED_CHARS: [(char, char); 21] = [
+    ('`', '~'),
+    ('1', '!'),
+    ('2', '@'),
+    ('3', '#'),
+    ('4', '$'),
+    ('5', '%'),
+    ('6', '^'),
+    ('7', '&'),
+    ('8', '*'),
+    ('9', '('),
+    ('0', ')'),
+    ('-', '_'),
+    ('=', '+'),
+    ('[', '{'),
+    (']', '}'),
+    ('\\', '|'),
+    (';', ':'),
+    ('\'', '"'),
+    (',', '<'),
+    ('.', '>'),
+    ('/', '?'),
+];
+
+/// Returns the shifted character of the `c` (if it's known).
+fn shifted_char(c: char) -> Option<char> {
+    if c.is_lowercase() {
+        let mut upper = c.to_uppercase();
+        return match (upper.next(), upper.next()) {
+            (Some(upper), None) => Some(upper),
+            // `ß` is `SS`, ...
+            _ => None,
+        };
+    }
+
+    US_SHIFTED_CHARS
+        .iter()
+        .find(|(base, _)| *base == c)
+        .map(|(_, shifted)| *shifted)
+}
+
+/// Filters the suspicious characters from the pasted or typed `event` with the input filter
+/// from the shared input configuration.
+///
+/// Returns `None` if the event should be dropped.
+pub(crate) fn filter_input_event(event: InputEvent) -> Option<InputEvent> {
+    with_input_config(|config| config.input_filter).apply(event)
+}
+
+/// Translates the wheel `event` into the arrow key events with the shared input configuration.
+///
+/// Returns the `event` itself if it isn't a wheel event or the translation is disabled.
+#[cfg(not(feature = "keyboard-only"))]
+pub(crate) fn wheel_arrow_events(event: InputEvent) -> Vec<InputEvent> {
+    let lines = with_input_config(|config| config.wheel_as_arrows);
+    wheel_events_as_arrows(event, lines)
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+fn wheel_events_as_arrows(event: InputEvent, lines: u16) -> Vec<InputEvent> {
+    let (code, modifiers) = match event {
+        InputEvent::Mouse(MouseEvent {
+            kind: MouseEventKind::Press,
+            button: Some(MouseButton::WheelUp),
+            modifiers,
+            ..
+        }) if lines > 0 => (KeyCode::Up, modifiers),
+        InputEvent::Mouse(MouseEvent {
+            kind: MouseEventKind::Press,
+            button: Some(MouseButton::WheelDown),
+            modifiers,
+            ..
+        }) if lines > 0 => (KeyCode::Down, modifiers),
+        event => return vec![event],
+    };
+
+    (0..lines)
+        .map(|_| InputEvent::Keyboard(KeyEvent::new(code, modifiers)))
+        .collect()
+}
+
+/// Maps the `MouseEvent` coordinates to the origin from the shared input configuration.
+#[cfg(not(feature = "keyboard-only"))]
+pub(crate) fn mouse_event_with_origin(mouse_event: MouseEvent) -> MouseEvent {
+    with_input_config(|config| config.mouse_origin.apply(mouse_event))
+}
+
+/// Represents how a C0 control character (`0x00` - `0x1F`) is delivered.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ControlAction {
+    /// Deliver the dedicated key if there's one, `Ctrl + key` otherwise.
+    ///
+    /// `0x00` is delivered as `KeyCode::Null`, `0x08` as Ctrl + `KeyCode::Backspace`, `0x09` as
+    /// `KeyCode::Tab`, `0x0A` and `0x0D` as `KeyCode::Enter` and `0x1B` as `KeyCode::Esc`.
+    Key,
+    /// Always deliver as `Ctrl + key`.
+    ///
+    /// `0x00` is delivered as `KeyCode::Char(' ')`, `0x01` - `0x1A` as `KeyCode::Char('a')` -
+    /// `KeyCode::Char('z')`, `0x1B` - `0x1F` as `KeyCode::Char('3')` - `KeyCode::Char('7')`,
+    /// all with the `KeyModifiers::CONTROL`.
+    Ctrl,
+    /// Deliver the control character itself as `KeyCode::Char`.
+    Raw,
+    /// Do not deliver anything.
+    Drop,
+}
+
+/// Represents the origin of the mouse event coordinates.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MouseOrigin {
+    /// The upper left corner is reported as `(0, 0)`, synced with the cursor position.
+    ZeroBased,
+    /// The upper left corner is reported as `(1, 1)`, the ANSI convention.
+    OneBased,
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+impl MouseOrigin {
+    /// Maps the zero based `MouseEvent` coordinates to this origin.
+    fn apply(self, mouse_event: MouseEvent) -> MouseEvent {
+        if self == MouseOrigin::ZeroBased || mouse_event.kind == MouseEventKind::Unknown {
+            return mouse_event;
+        }
+
+        MouseEvent {
+            column: mouse_event.column.saturating_add(1),
+            row: mouse_event.row.saturating_add(1),
+            ..mouse_event
+        }
+    }
+}
+
+/// Represents how the input is read on UNIX systems.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ReadMode {
+    /// A background thread waits for the input with `poll`.
+    Poll,
+    /// Non-blocking reads (`O_NONBLOCK`) driven by the caller, no background thread.
+    ///
+    /// Use it in restricted environments (sandboxes, seccomp profiles, ...) where `poll`
+    /// on the tty isn't allowed. The caller must call the
+    /// [`TerminalInput::pump`](struct.TerminalInput.html#method.pump) method periodically
+    /// to read the input.
+    Pump,
+    /// The only reader (`SyncReader`, `AsyncReader`, `EventStream`) reads the input on its own
+    /// thread, no background thread.
+    ///
+    /// It saves the thread hop of the `Poll` mode (lower latency) in the applications with
+    /// a single reader. The background thread is spawned (like in the `Poll` mode) once there
+    /// are more readers.
+    Direct,
+}
+
+/// The default length (in bytes) of the pasted text which is streamed.
+const DEFAULT_PASTE_STREAM_THRESHOLD: usize = 1024 * 1024;
+
+/// Represents the encoding of the characters read on UNIX systems.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum InputEncoding {
+    /// UTF-8, malformed sequences are dropped.
+    Utf8,
+    /// UTF-8, malformed sequences are delivered as the `U+FFFD` replacement character.
+    Utf8Lossy,
+    /// ISO 8859-1 (Latin-1), every byte is a character.
+    ///
+    /// Use it for the legacy terminals sending single-byte encodings.
+    Latin1,
+}
+
+/// Represents how the [`read_until_async`](struct.TerminalInput.html#method.read_until_async)
+/// delimiter is matched in the pasted text.
+///
+/// The pasted text is recognized with the bracketed paste mode enabled only (see the
+/// [`enable_bracketed_paste`](struct.TerminalInput.html#method.enable_bracketed_paste) method),
+/// it's a sequence of key events otherwise.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SentinelPolicy {
+    /// The delimiter is matched outside of the pasted text only.
+    IgnorePaste,
+    /// The reading also stops after the pasted text containing the delimiter.
+    MatchPaste,
+}
+
+impl SentinelPolicy {
+    /// Returns the event stopping the reading at the `delimiter`.
+    ///
+    /// Returns `None` if the `delimiter` can't be typed (not an ASCII byte).
+    pub(crate) fn stop_event(delimiter: u8) -> Option<InputEvent> {
+        match delimiter {
+            b'\n' | b'\r' => Some(KeyEvent::from(KeyCode::Enter)),
+            b'\x1B' => Some(KeyEvent::from(KeyCode::Esc)),
+            c if c.is_ascii() => Some(KeyEvent::from(KeyCode::Char(c as char))),
+            _ => None,
+        }
+        .map(InputEvent::Keyboard)
+    }
+
+    /// Returns `true` if the reading should stop after the `event`.
+    pub(crate) fn is_stop_event(self, event: &InputEvent, stop_event: &InputEvent) -> bool {
+        match event {
+            InputEvent::Paste(text) | InputEvent::PasteChunk(text)
+                if self == SentinelPolicy::MatchPaste =>
+            {
+                match stop_event {
+                    InputEvent::Keyboard(KeyEvent {
+                        code: KeyCode::Enter,
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) => text.contains(&['\r', '\n'][..]),
+                    InputEvent::Keyboard(KeyEvent {
+                        code: KeyCode::Esc,
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) => text.contains('\x1B'),
+                    InputEvent::Keyboard(KeyEvent {
+                        code: KeyCode::Char(c),
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) => text.contains(*c),
+                    _ => false,
+                }
+            }
+            event => event == stop_event,
+        }
+    }
+}
+
+/// Represents how the suspicious characters in the pasted & typed input are handled.
+///
+/// The suspicious characters are the C0 controls (except the tab & new lines) including `ESC`,
+/// `DEL` & the C1 controls (`U+0080` - `U+009F`). An application echoing the input back to
+/// the terminal must not write them, the terminal would interpret the injected sequences.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum InputFilter {
+    /// Deliver the input as is.
+    Off,
+    /// Remove the suspicious characters from the pasted text, drop the typed ones.
+    Strip,
+    /// Replace the suspicious characters with the `U+FFFD` replacement character.
+    Replace,
+}
+
+impl InputFilter {
+    /// Filters the pasted text or the typed character of the `event`.
+    ///
+    /// Returns `None` if the event should be dropped.
+    fn apply(self, event: InputEvent) -> Option<InputEvent> {
+        match (self, event) {
+            (InputFilter::Off, event) => Some(event),
+            (filter, InputEvent::Paste(text)) => Some(InputEvent::Paste(filter.filter_text(&text))),
+            (filter, InputEvent::PasteChunk(text)) => {
+                Some(InputEvent::PasteChunk(filter.filter_text(&text)))
+            }
+            (
+                filter,
+                InputEvent::Keyboard(KeyEvent {
+                    code: KeyCode::Char(c),
+                    modifiers,
+                    kind,
+                    state,
+                }),
+            ) if is_suspicious_char(c) => match filter {
+                InputFilter::Replace => Some(InputEvent::Keyboard(
+                    KeyEvent::new_with_kind(
+                        KeyCode::Char(char::REPLACEMENT_CHARACTER),
+                        modifiers,
+                        kind,
+                    )
+                    .with_state(state),
+                )),
+                _ => None,
+            },
+            (_, event) => Some(event),
+        }
+    }
+
+    fn filter_text(self, text: &str) -> String {
+        text.chars()
+            .filter_map(|c| match self {
+                _ if !is_suspicious_char(c) => Some(c),
+                InputFilter::Replace => Some(char::REPLACEMENT_CHARACTER),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Represents how the ambiguous `ESC` prefix (the Esc key, Alt + key or a sequence start) is
+/// resolved when there're no more bytes to read (UNIX only).
+///
+/// See the [`with_esc_strategy`](struct.InputConfig.html#method.with_esc_strategy) method.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum EscStrategy {
+    /// Hold the prefix for the esc timeout (see the
+    /// [`with_esc_timeout`](struct.InputConfig.html#method.with_esc_timeout) method).
+    Timeout,
+    /// Ask the terminal for its status (DSR) & hold the prefix for the response.
+    ///
+    /// The terminal answers after the bytes it already sent. If the response follows the prefix,
+    /// the prefix was the Esc key (or Alt + key), otherwise it's a part of a longer sequence.
+    /// The prefix is resolved in one round trip, there's no timeout to tune. The esc timeout
+    /// (500ms if not set) is used if the terminal doesn't answer.
+    Probe,
+}
+
+/// Represents how the key events over the key rate limit are delivered.
+///
+/// See the [`with_key_rate_limit`](struct.InputConfig.html#method.with_key_rate_limit) method.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum KeyRateOverflow {
+    /// Coalesce the typed characters into the `InputEvent::Paste` events, other keys are
+    /// delivered as is.
+    Coalesce,
+    /// Drop the key events, the number of dropped events is reported with the
+    /// `InputEvent::Lagged` event.
+    Drop,
+}
+
+/// Represents what happens when a reader is created & the raw mode isn't enabled (UNIX only).
+///
+/// The terminal in the canonical mode delivers the input line by line & handles the control
+/// keys (Ctrl + C, ...) itself. See the
+/// [`with_raw_mode_fallback`](struct.InputConfig.html#method.with_raw_mode_fallback) method.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RawModeFallback {
+    /// Enable the raw mode, it's disabled when the last reader is dropped.
+    Enable,
+    /// Report the error to the [`error hook`](fn.set_error_hook.html).
+    Warn,
+    /// Read the input as is.
+    Proceed,
+}
+
+/// Returns `true` if the `c` can start or be a part of an injected control sequence.
+fn is_suspicious_char(c: char) -> bool {
+    match c {
+        '\t' | '\n' | '\r' => false,
+        c => c.is_control(),
+    }
+}
+
+/// An input configuration.
+///
+/// The configuration is shared by all the readers. Use the
+/// [`TerminalInput::set_config`](struct.TerminalInput.html#method.set_config) method
+/// to change it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{input, ControlAction, InputConfig};
+///
+/// let input = input();
+/// // Deliver Ctrl + M as Ctrl + `KeyCode::Char('m')` instead of the `KeyCode::Enter`
+/// input.set_config(input.config().with_control_action(b'\r', ControlAction::Ctrl));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct InputConfig {
+    control_actions: [ControlAction; 32],
+    mouse_origin: MouseOrigin,
+    replay_capacity: usize,
+    read_mode: ReadMode,
+    keyboard_state_tracking: bool,
+    mouse_state_tracking: bool,
+    disable_modes_on_drop: bool,
+    paste_stream_threshold: usize,
+    input_encoding: InputEncoding,
+    key_profile: KeyProfile,
+    sentinel_policy: SentinelPolicy,
+    key_release_events: bool,
+    esc_timeout: Duration,
+    input_filter: InputFilter,
+    wheel_as_arrows: u16,
+    key_rate_limit: u32,
+    key_rate_overflow: KeyRateOverflow,
+    esc_strategy: EscStrategy,
+    raw_mode_fallback: RawModeFallback,
+    modifier_key_events: bool,
+    shifted_chars: bool,
+    wheel_scroll_lines: u16,
+    watchdog_timeout: Duration,
+    vt_input: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> InputConfig {
+        InputConfig {
+            control_actions: [ControlAction::Key; 32],
+            mouse_origin: MouseOrigin::ZeroBased,
+            replay_capacity: 0,
+            read_mode: ReadMode::Poll,
+            keyboard_state_tracking: false,
+            mouse_state_tracking: false,
+            disable_modes_on_drop: false,
+            paste_stream_threshold: DEFAULT_PASTE_STREAM_THRESHOLD,
+            input_encoding: InputEncoding::Utf8,
+            key_profile: KeyProfile::Raw,
+            sentinel_policy: SentinelPolicy::IgnorePaste,
+            key_release_events: false,
+            esc_timeout: Duration::from_millis(0),
+            input_filter: InputFilter::Off,
+            wheel_as_arrows: 0,
+            key_rate_limit: 0,
+            key_rate_overflow: KeyRateOverflow::Coalesce,
+            esc_strategy: EscStrategy::Timeout,
+            raw_mode_fallback: RawModeFallback::Proceed,
+            modifier_key_events: false,
+            shifted_chars: true,
+            wheel_scroll_lines: 0,
+            watchdog_timeout: Duration::from_millis(0),
+            vt_input: false,
+        }
+    }
+}
+
+impl InputConfig {
+    /// Creates a new `InputConfig` with the default values.
+    pub fn new() -> InputConfig {
+        InputConfig::default()
+    }
+
+    /// Returns the action for the C0 control `byte`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `byte` isn't a C0 control (`0x00` - `0x1F`).
+    pub fn control_action(&self, byte: u8) -> ControlAction {
+        self.control_actions[byte as usize]
+    }
+
+    /// Sets the action for the C0 control `byte`.
+    ///
+    /// The action is applied on both UNIX and Windows. The Backspace key isn't a C0 control
+    /// (it's `0x7F` on UNIX, a dedicated key on Windows) and it's always delivered as
+    /// the `KeyCode::Backspace`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `byte` isn't a C0 control (`0x00` - `0x1F`).
+    pub fn with_control_action(mut self, byte: u8, action: ControlAction) -> InputConfig {
+        self.control_actions[byte as usize] = action;
+        self
+    }
+
+    /// Sets the action for all the C0 controls.
+    pub fn with_control_actions(mut self, action: ControlAction) -> InputConfig {
+        self.control_actions = [action; 32];
+        self
+    }
+
+    /// Returns the origin of the mouse event coordinates.
+    pub fn mouse_origin(&self) -> MouseOrigin {
+        self.mouse_origin
+    }
+
+    /// Sets the origin of the mouse event coordinates.
+    ///
+    /// The default is the `MouseOrigin::ZeroBased`.
+    pub fn with_mouse_origin(mut self, origin: MouseOrigin) -> InputConfig {
+        self.mouse_origin = origin;
+        self
+    }
+
+    /// Returns the number of most recent events kept for the replay.
+    pub fn replay_capacity(&self) -> usize {
+        self.replay_capacity
+    }
+
+    /// Sets the number of most recent events kept for the replay.
+    ///
+    /// The default is `0`, no events are kept. See the
+    /// [`TerminalInput::read_async_with_replay`](struct.TerminalInput.html#method.read_async_with_replay)
+    /// method for more details.
+    pub fn with_replay_capacity(mut self, capacity: usize) -> InputConfig {
+        self.replay_capacity = capacity;
+        self
+    }
+
+    /// Returns how the input is read on UNIX systems.
+    pub fn read_mode(&self) -> ReadMode {
+        self.read_mode
+    }
+
+    /// Sets how the input is read on UNIX systems.
+    ///
+    /// The default is the `ReadMode::Poll`. The mode is applied when a new reader is
+    /// created. It's ignored on Windows.
+    pub fn with_read_mode(mut self, mode: ReadMode) -> InputConfig {
+        self.read_mode = mode;
+        self
+    }
+
+    /// Returns `true` if the held keys are tracked.
+    pub fn keyboard_state_tracking(&self) -> bool {
+        self.keyboard_state_tracking
+    }
+
+    /// Enables or disables the tracking of the held keys.
+    ///
+    /// The default is `false`. See the
+    /// [`TerminalInput::keyboard_state`](struct.TerminalInput.html#method.keyboard_state)
+    /// method for more details.
+    pub fn with_keyboard_state_tracking(mut self, enabled: bool) -> InputConfig {
+        self.keyboard_state_tracking = enabled;
+        self
+    }
+
+    /// Returns `true` if the mouse buttons & position are tracked.
+    pub fn mouse_state_tracking(&self) -> bool {
+        self.mouse_state_tracking
+    }
+
+    /// Enables or disables the tracking of the mouse buttons & position.
+    ///
+    /// The default is `false`. See the
+    /// [`TerminalInput::mouse_state`](struct.TerminalInput.html#method.mouse_state)
+    /// method for more details.
+    pub fn with_mouse_state_tracking(mut self, enabled: bool) -> InputConfig {
+        self.mouse_state_tracking = enabled;
+        self
+    }
+
+    /// Returns `true` if the enabled modes are disabled when the last reader is dropped.
+    pub fn disable_modes_on_drop(&self) -> bool {
+        self.disable_modes_on_drop
+    }
+
+    /// Sets if the enabled modes (mouse, locator, ...) are disabled when the last reader
+    /// ([`AsyncReader`](struct.AsyncReader.html) or [`SyncReader`](struct.SyncReader.html))
+    /// is dropped.
+    ///
+    /// The default is `false`. It prevents the terminal from printing mouse reports after the
+    /// application exits without disabling the mouse mode.
+    pub fn with_disable_modes_on_drop(mut self, enabled: bool) -> InputConfig {
+        self.disable_modes_on_drop = enabled;
+        self
+    }
+
+    /// Returns the length (in bytes) of the pasted text which is streamed.
+    pub fn paste_stream_threshold(&self) -> usize {
+        self.paste_stream_threshold
+    }
+
+    /// Sets the length (in bytes) of the pasted text which is streamed.
+    ///
+    /// The pasted text of at least `bytes` bytes is delivered as the `InputEvent::PasteStart`,
+    /// the `InputEvent::PasteChunk` events of about `bytes` bytes & the `InputEvent::PasteEnd`
+    /// instead of a single `InputEvent::Paste` event, a multi-megabyte paste doesn't have to be
+    /// held in memory. The default is 1 MiB, the read pasted text is never longer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `bytes` is zero.
+    pub fn with_paste_stream_threshold(mut self, bytes: usize) -> InputConfig {
+        assert!(bytes > 0, "the paste stream threshold must be positive");
+        self.paste_stream_threshold = bytes;
+        self
+    }
+
+    /// Returns the encoding of the characters read on UNIX systems.
+    pub fn input_encoding(&self) -> InputEncoding {
+        self.input_encoding
+    }
+
+    /// Sets the encoding of the characters read on UNIX systems.
+    ///
+    /// The default is the `InputEncoding::Utf8`. It's ignored on Windows, the console
+    /// delivers UTF-16 characters.
+    pub fn with_input_encoding(mut self, encoding: InputEncoding) -> InputConfig {
+        self.input_encoding = encoding;
+        self
+    }
+
+    /// Returns the key normalization profile.
+    pub fn key_profile(&self) -> &KeyProfile {
+        &self.key_profile
+    }
+
+    /// Sets the key normalization profile applied to all the key events.
+    ///
+    /// The default is the `KeyProfile::Raw`. The profile is applied after the
+    /// [`ControlAction`](enum.ControlAction.html)s.
+    pub fn with_key_profile(mut self, profile: KeyProfile) -> InputConfig {
+        self.key_profile = profile;
+        self
+    }
+
+    /// Returns how the `read_until_async` delimiter is matched in the pasted text.
+    pub fn sentinel_policy(&self) -> SentinelPolicy {
+        self.sentinel_policy
+    }
+
+    /// Sets how the `read_until_async` delimiter is matched in the pasted text.
+    ///
+    /// The default is the `SentinelPolicy::IgnorePaste`, reading until Enter isn't stopped
+    /// by a new line in the pasted text. The policy is applied when a new reader is created.
+    pub fn with_sentinel_policy(mut self, policy: SentinelPolicy) -> InputConfig {
+        self.sentinel_policy = policy;
+        self
+    }
+
+    /// Returns `true` if the key releases are produced by the readers.
+    pub fn key_release_events(&self) -> bool {
+        self.key_release_events
+    }
+
+    /// Enables or disables the key release events (`KeyEventKind::Release`).
+    ///
+    /// The default is `false`, the applications matching the key code only would handle every
+    /// key twice otherwise. Key releases are always fed to the
+    /// [`keyboard_state`](struct.TerminalInput.html#method.keyboard_state) tracker.
+    pub fn with_key_release_events(mut self, enabled: bool) -> InputConfig {
+        self.key_release_events = enabled;
+        self
+    }
+
+    /// Returns how long an incomplete `ESC` sequence is held for the remaining bytes.
+    pub fn esc_timeout(&self) -> Duration {
+        self.esc_timeout
+    }
+
+    /// Sets how long an incomplete `ESC` sequence is held for the remaining bytes (UNIX only).
+    ///
+    /// The default is zero, the lone `ESC` is the Esc key if there're no more bytes to read
+    /// immediately. Over slow links (SSH, ...) the sequence can be split between reads and
+    /// the Esc key is produced followed by the rest of the sequence as characters. Set it to
+    /// the link latency (50 - 100ms) to reassemble the sequence, the Esc key (& Alt + `[`,
+    /// Alt + `O`) is delayed by this timeout. Longer incomplete sequences are always held until
+    /// the remaining bytes arrive.
+    pub fn with_esc_timeout(mut self, timeout: Duration) -> InputConfig {
+        self.esc_timeout = timeout;
+        self
+    }
+
+    /// Returns how the ambiguous `ESC` prefix is resolved.
+    pub fn esc_strategy(&self) -> EscStrategy {
+        self.esc_strategy
+    }
+
+    /// Sets how the ambiguous `ESC` prefix is resolved (UNIX only).
+    ///
+    /// The default is `EscStrategy::Timeout`. The `EscStrategy::Probe` writes a query to
+    /// the terminal for every held prefix, it requires the raw mode.
+    pub fn with_esc_strategy(mut self, strategy: EscStrategy) -> InputConfig {
+        self.esc_strategy = strategy;
+        self
+    }
+
+    /// Returns the filter of the suspicious characters in the pasted & typed input.
+    pub fn input_filter(&self) -> InputFilter {
+        self.input_filter
+    }
+
+    /// Sets the filter of the suspicious characters in the pasted & typed input.
+    ///
+    /// The default is `InputFilter::Off`. Enable it if you echo the input back to the terminal.
+    /// The `ControlAction::Raw` control characters are filtered as well.
+    pub fn with_input_filter(mut self, filter: InputFilter) -> InputConfig {
+        self.input_filter = filter;
+        self
+    }
+
+    /// Returns how many arrow key events a wheel event is translated into.
+    pub fn wheel_as_arrows(&self) -> u16 {
+        self.wheel_as_arrows
+    }
+
+    /// Translates the wheel events into the Up/Down arrow key events.
+    ///
+    /// Every wheel event is delivered as `lines` `KeyCode::Up` (`MouseButton::WheelUp`) or
+    /// `KeyCode::Down` (`MouseButton::WheelDown`) key events with the mouse event modifiers,
+    /// simple list UIs get scrolling without handling the mouse. The default is zero, the wheel
+    /// events are delivered as mouse events. The mouse mode must be enabled.
+    pub fn with_wheel_as_arrows(mut self, lines: u16) -> InputConfig {
+        self.wheel_as_arrows = lines;
+        self
+    }
+
+    /// Returns the maximum number of key events delivered per second.
+    pub fn key_rate_limit(&self) -> u32 {
+        self.key_rate_limit
+    }
+
+    /// Returns how the key events over the key rate limit are delivered.
+    pub fn key_rate_overflow(&self) -> KeyRateOverflow {
+        self.key_rate_overflow
+    }
+
+    /// Limits the number of key events delivered per second.
+    ///
+    /// The default is zero, the key events aren't limited. Set it if the application does
+    /// expensive work per key stroke, the text pasted into a terminal without the bracketed paste
+    /// mode arrives as thousands of key events. The key events over the `max_per_second` limit
+    /// are coalesced or dropped (`overflow`). Key releases aren't limited.
+    pub fn with_key_rate_limit(
+        mut self,
+        max_per_second: u32,
+        overflow: KeyRateOverflow,
+    ) -> InputConfig {
+        self.key_rate_limit = max_per_second;
+        self.key_rate_overflow = overflow;
+        self
+    }
+
+    /// Returns what happens when a reader is created & the raw mode isn't enabled.
+    pub fn raw_mode_fallback(&self) -> RawModeFallback {
+        self.raw_mode_fallback
+    }
+
+    /// Sets what happens when a reader is created & the raw mode isn't enabled.
+    ///
+    /// The default is the `RawModeFallback::Proceed`. The raw mode is checked (the `ICANON`
+    /// termios flag) when a new reader is created. It's ignored on Windows, the console input
+    /// events aren't line-buffered.
+    pub fn with_raw_mode_fallback(mut self, fallback: RawModeFallback) -> InputConfig {
+        self.raw_mode_fallback = fallback;
+        self
+    }
+
+    /// Returns `true` if the modifier keys pressed & released on their own are reported.
+    pub fn modifier_key_events(&self) -> bool {
+        self.modifier_key_events
+    }
+
+    /// Enables or disables the `KeyCode::Modifier` events (the Shift, Control, ... keys pressed
+    /// & released on their own).
+    ///
+    /// The default is `false`. Enable it for the "hold Ctrl to peek" like interactions, enable
+    /// the key release events as well to see when the key is released.
+    pub fn with_modifier_key_events(mut self, enabled: bool) -> InputConfig {
+        self.modifier_key_events = enabled;
+        self
+    }
+
+    /// Returns `true` if the Shift + character key events are reported as the shifted
+    /// characters.
+    pub fn shifted_chars(&self) -> bool {
+        self.shifted_chars
+    }
+
+    /// Enables or disables reporting the Shift + character key events as the shifted
+    /// characters without the Shift modifier.
+    ///
+    /// The default is `true`, `Shift + 1` is the `KeyCode::Char('!')` on all the platforms
+    /// & in all the terminal encodings (the kitty keyboard protocol reports the base key with
+    /// the Shift modifier). The US layout is assumed for the ASCII digits & punctuation if
+    /// the terminal doesn't report the shifted key, push the
+    /// `KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS` flag to get the actual one.
+    ///
+    /// Disable it to get the key events as they're reported by the terminal or the console.
+    pub fn with_shifted_chars(mut self, enabled: bool) -> InputConfig {
+        self.shifted_chars = enabled;
+        self
+    }
+
+    /// Returns how many lines a wheel notch scrolls.
+    pub fn wheel_scroll_lines(&self) -> u16 {
+        self.wheel_scroll_lines
+    }
+
+    /// Converts the wheel events into the `InputEvent::ScrollLines` events.
+    ///
+    /// Every wheel notch scrolls `lines_per_notch` lines, the high-resolution wheel deltas
+    /// (Windows) are accumulated until they make a whole line. The wheel events with
+    /// the modifiers (Ctrl + wheel zoom, ...) are delivered as the mouse events. The default
+    /// is zero, the wheel events are delivered as the mouse events. The mouse mode must be
+    /// enabled.
+    ///
+    /// It takes precedence over the
+    /// [`with_wheel_as_arrows`](struct.InputConfig.html#method.with_wheel_as_arrows) method.
+    pub fn with_wheel_scroll_lines(mut self, lines_per_notch: u16) -> InputConfig {
+        self.wheel_scroll_lines = lines_per_notch;
+        self
+    }
+
+    /// Returns how long the reading thread can be stuck before it's restarted.
+    pub fn watchdog_timeout(&self) -> Duration {
+        self.watchdog_timeout
+    }
+
+    /// Watches the reading thread (UNIX only), the thread which didn't complete a loop
+    /// iteration for the `timeout` (wedged on a blocking read against a dead terminal, ...)
+    /// is reported to the [error hook](fn.set_error_hook.html) & replaced with a new one.
+    ///
+    /// The default is zero, the reading thread isn't watched. The stuck thread can't be
+    /// stopped, it's detached & exits once it unblocks. It's applied to the reading threads
+    /// spawned after it's set (`ReadMode::Poll`).
+    pub fn with_watchdog_timeout(mut self, timeout: Duration) -> InputConfig {
+        self.watchdog_timeout = timeout;
+        self
+    }
+
+    /// Returns `true` if the Windows console input is read as the VT sequences.
+    pub fn vt_input(&self) -> bool {
+        self.vt_input
+    }
+
+    /// Switches the Windows console to the VT input (`ENABLE_VIRTUAL_TERMINAL_INPUT`), the
+    /// input is parsed by the same parser as on UNIX.
+    ///
+    /// The default is `false`, the console input records are read. Enable it for the Windows
+    /// Terminal & the ConPTY features, the mouse modes, the bracketed paste & the focus
+    /// reporting are enabled with the sequences then. The key release events aren't reported.
+    /// It's ignored on UNIX.
+    pub fn with_vt_input(mut self, enabled: bool) -> InputConfig {
+        self.vt_input = enabled;
+        self
+    }
+
+    /// Maps the C0 control `byte` to a possible `KeyEvent`.
+    pub(crate) fn control_key_event(&self, byte: u8) -> Option<KeyEvent> {
+        match self.control_action(byte) {
+            ControlAction::Key => match byte {
+                b'\0' => Some(KeyEvent::from(KeyCode::Null)),
+                // Ctrl + Backspace in most terminals (the Backspace itself is `0x7F`)
+                b'\x08' => Some(KeyEvent::new(KeyCode::Backspace, KeyModifiers::CONTROL)),
+                b'\t' => Some(KeyEvent::from(KeyCode::Tab)),
+                b'\r' | b'\n' => Some(KeyEvent::from(KeyCode::Enter)),
+                b'\x1B' => Some(KeyEvent::from(KeyCode::Esc)),
+                _ => Some(ctrl_key_event(byte)),
+            },
+            ControlAction::Ctrl => Some(ctrl_key_event(byte)),
+            ControlAction::Raw => Some(KeyEvent::from(KeyCode::Char(byte as char))),
+            ControlAction::Drop => None,
+        }
+    }
+}
+
+/// Maps the C0 control `byte` to the `Ctrl + key` event.
+fn ctrl_key_event(byte: u8) -> KeyEvent {
+    match byte {
+        b'\0' => KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL),
+        c @ b'\x01'..=b'\x1A' => KeyEvent::new(
+            KeyCode::Char((c - 0x1 + b'a') as char),
+            KeyModifiers::CONTROL,
+        ),
+        c @ b'\x1B'..=b'\x1F' => KeyEvent::new(
+            KeyCode::Char((c - 0x1B + b'3') as char),
+            KeyModifiers::CONTROL,
+        ),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ModifierKey;
+
+    use super::*;
+
+    #[test]
+    fn test_input_filter() {
+        let paste = InputEvent::Paste("a\x1B[2J\u{9b}31m\tb\r\n".to_string());
+        assert_eq!(InputFilter::Off.apply(paste.clone()), Some(paste.clone()));
+        assert_eq!(
+            InputFilter::Strip.apply(paste.clone()),
+            Some(InputEvent::Paste("a[2J31m\tb\r\n".to_string()))
+        );
+        assert_eq!(
+            InputFilter::Replace.apply(paste),
+            Some(InputEvent::Paste(
+                "a\u{FFFD}[2J\u{FFFD}31m\tb\r\n".to_string()
+            ))
+        );
+
+        let chunk = InputEvent::PasteChunk("a\x1B[2J".to_string());
+        assert_eq!(
+            InputFilter::Strip.apply(chunk),
+            Some(InputEvent::PasteChunk("a[2J".to_string()))
+        );
+
+        let c1 = InputEvent::Keyboard(KeyCode::Char('\u{9b}').into());
+        assert_eq!(InputFilter::Strip.apply(c1.clone()), None);
+        assert_eq!(
+            InputFilter::Replace.apply(c1),
+            Some(InputEvent::Keyboard(
+                KeyCode::Char(char::REPLACEMENT_CHARACTER).into()
+            ))
+        );
+
+        let esc = InputEvent::Keyboard(KeyCode::Esc.into());
+        assert_eq!(InputFilter::Strip.apply(esc.clone()), Some(esc));
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_wheel_events_as_arrows() {
+        let wheel = |button, modifiers| {
+            InputEvent::Mouse(MouseEvent {
+                modifiers,
+                ..MouseEvent::new(MouseEventKind::Press, Some(button), 1, 2)
+            })
+        };
+
+        let event = wheel(MouseButton::WheelUp, KeyModifiers::empty());
+        assert_eq!(
+            wheel_events_as_arrows(event.clone(), 0),
+            vec![event.clone()]
+        );
+        assert_eq!(
+            wheel_events_as_arrows(event, 2),
+            vec![
+                InputEvent::Keyboard(KeyCode::Up.into()),
+                InputEvent::Keyboard(KeyCode::Up.into()),
+            ]
+        );
+        assert_eq!(
+            wheel_events_as_arrows(wheel(MouseButton::WheelDown, KeyModifiers::SHIFT), 1),
+            vec![InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Down,
+                KeyModifiers::SHIFT
+            ))]
+        );
+
+        let click = wheel(MouseButton::Left, KeyModifiers::empty());
+        assert_eq!(wheel_events_as_arrows(click.clone(), 3), vec![click]);
+    }
+
+    #[test]
+    fn test_default_modifier_key_events() {
+        assert!(!InputConfig::default().modifier_key_events());
+
+        let shift = KeyEvent::new(
+            KeyCode::Modifier(ModifierKey::LeftShift),
+            KeyModifiers::SHIFT,
+        );
+        assert_eq!(normalize_key_event(shift), None);
+        assert_eq!(
+            normalize_key_event(KeyCode::Tab.into()),
+            Some(KeyCode::Tab.into())
+        );
+    }
+
+    #[test]
+    fn test_shifted_char_event() {
+        assert!(InputConfig::default().shifted_chars());
+
+        let shifted = |c, modifiers| shifted_char_event(KeyEvent::new(KeyCode::Char(c), modifiers));
+
+        assert_eq!(
+            shifted('1', KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Char('!'))
+        );
+        assert_eq!(
+            shifted('ž', KeyModifiers::SHIFT | KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char('Ž'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            shifted('A', KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Char('A'))
+        );
+        assert_eq!(
+            shifted('?', KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Char('?'))
+        );
+        assert_eq!(
+            shifted('1', KeyModifiers::empty()),
+            KeyEvent::from(KeyCode::Char('1'))
+        );
+        // Unknown shifted character
+        assert_eq!(
+            shifted(' ', KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Char(' '), KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            shifted_char_event(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)),
+            KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)
+        );
+    }
+
+    #[test]
+    fn test_default_control_key_event() {
+        let config = InputConfig::default();
+
+        assert_eq!(
+            config.control_key_event(b'\0'),
+            Some(KeyEvent::from(KeyCode::Null))
+        );
+        assert_eq!(
+            config.control_key_event(b'\x08'),
+            Some(KeyEvent::new(KeyCode::Backspace, KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            config.control_key_event(b'\t'),
+            Some(KeyEvent::from(KeyCode::Tab))
+        );
+        assert_eq!(
+            config.control_key_event(b'\r'),
+            Some(KeyEvent::from(KeyCode::Enter))
+        );
+        assert_eq!(
+            config.control_key_event(b'\x1B'),
+            Some(KeyEvent::from(KeyCode::Esc))
+        );
+        assert_eq!(
+            config.control_key_event(b'\x03'),
+            Some(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            config.control_key_event(b'\x1D'),
+            Some(KeyEvent::new(KeyCode::Char('5'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn test_control_key_event() {
+        let config = InputConfig::default()
+            .with_control_action(b'\r', ControlAction::Ctrl)
+            .with_control_action(b'\t', ControlAction::Raw)
+            .with_control_action(b'\x03', ControlAction::Drop);
+
+        assert_eq!(
+            config.control_key_event(b'\r'),
+            Some(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            config.control_key_event(b'\t'),
+            Some(KeyEvent::from(KeyCode::Char('\t')))
+        );
+        assert_eq!(config.control_key_event(b'\x03'), None);
+        assert_eq!(
+            config.control_key_event(b'\n'),
+            Some(KeyEvent::from(KeyCode::Enter))
+        );
+    }
+
+    #[test]
+    fn test_sentinel_policy() {
+        let enter = InputEvent::Keyboard(KeyCode::Enter.into());
+        let paste = InputEvent::Paste("first\nsecond".to_string());
+
+        assert!(SentinelPolicy::IgnorePaste.is_stop_event(&enter, &enter));
+        assert!(!SentinelPolicy::IgnorePaste.is_stop_event(&paste, &enter));
+        assert!(SentinelPolicy::MatchPaste.is_stop_event(&paste, &enter));
+        assert!(SentinelPolicy::MatchPaste
+            .is_stop_event(&paste, &InputEvent::Keyboard(KeyCode::Char('s').into())));
+        assert!(!SentinelPolicy::MatchPaste
+            .is_stop_event(&paste, &InputEvent::Keyboard(KeyCode::Esc.into())));
+
+        assert_eq!(SentinelPolicy::stop_event(b'\r'), Some(enter.clone()));
+        assert_eq!(SentinelPolicy::stop_event(b'\n'), Some(enter));
+        assert_eq!(
+            SentinelPolicy::stop_event(b'q'),
+            Some(InputEvent::Keyboard(KeyCode::Char('q').into()))
+        );
+        assert_eq!(SentinelPolicy::stop_event(0xC5), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_mouse_origin() {
+        let event = MouseEvent::new(MouseEventKind::Press, Some(crate::MouseButton::Left), 0, 9);
+
+        assert_eq!(MouseOrigin::ZeroBased.apply(event), event);
+        assert_eq!(
+            MouseOrigin::OneBased.apply(event),
+            MouseEvent::new(MouseEventKind::Press, Some(crate::MouseButton::Left), 1, 10)
+        );
+        assert_eq!(
+            MouseOrigin::OneBased.apply(MouseEvent::new(
+                MouseEventKind::Release,
+                None,
+                u16::MAX,
+                0
+            )),
+            MouseEvent::new(MouseEventKind::Release, None, u16::MAX, 1)
+        );
+    }
+}
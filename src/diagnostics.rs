@@ -0,0 +1,322 @@
+//! A terminal compatibility probe (the crate equivalent of `showkey`).
+//!
+//! The [`run_probe`](fn.run_probe.html) function enables all the supported modes, asks the user
+//! to press keys & click and records the raw bytes sent by the terminal together with the parsed
+//! events. Ask the users to run it & attach the [`ProbeReport::to_json`](struct.ProbeReport.html#method.to_json)
+//! output to the bug report.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use crossterm_input::diagnostics::run_probe;
+//!
+//! let report = run_probe().expect("Unable to run the probe");
+//! println!("{}", report.to_json());
+//! ```
+
+use std::{
+    env,
+    fmt::Write,
+    sync::mpsc::Receiver,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm_screen::RawScreen;
+use crossterm_utils::{write_cout, Result};
+
+use crate::sys::unix::bytes_receiver;
+use crate::{input, AsyncReader, EnableOutcome, InputEvent, KeyboardEnhancementFlags};
+
+/// How long to wait for the user to react to the step prompt.
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+/// The step is complete if there's no input for this long after the first byte.
+const STEP_QUIET_PERIOD: Duration = Duration::from_millis(300);
+/// Polling interval of the probe reader.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The probe steps (`id`, `prompt`).
+const STEPS: &[(&str, &str)] = &[
+    ("up", "Press the Up arrow key"),
+    ("ctrl-left", "Press Ctrl + Left arrow"),
+    ("shift-tab", "Press Shift + Tab"),
+    ("alt-a", "Press Alt + a"),
+    ("ctrl-backspace", "Press Ctrl + Backspace"),
+    ("f5", "Press F5"),
+    ("shift-f5", "Press Shift + F5"),
+    ("home", "Press Home"),
+    ("delete", "Press Delete"),
+    ("keypad-5", "Press 5 on the numeric keypad"),
+    ("left-click", "Click the left mouse button"),
+    ("wheel", "Scroll the mouse wheel"),
+    ("paste", "Paste some text"),
+    ("focus", "Switch to another window and back"),
+];
+
+/// The outcome of enabling a mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeProbe {
+    /// The mode name (`mouse`, `bracketed-paste`, ...).
+    pub mode: String,
+    /// Whether the terminal acknowledged the mode.
+    pub outcome: EnableOutcome,
+}
+
+/// The input recorded in one probe step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeStep {
+    /// The step identifier (`up`, `left-click`, ...).
+    pub id: String,
+    /// The instruction shown to the user.
+    pub prompt: String,
+    /// The raw bytes sent by the terminal, empty if the user didn't react.
+    pub bytes: Vec<u8>,
+    /// The events parsed from the `bytes`.
+    pub events: Vec<InputEvent>,
+}
+
+/// A terminal compatibility report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeReport {
+    /// The `TERM` environment variable.
+    pub term: Option<String>,
+    /// The `TERM_PROGRAM` environment variable.
+    pub term_program: Option<String>,
+    /// The enabled modes.
+    pub modes: Vec<ModeProbe>,
+    /// The recorded steps.
+    pub steps: Vec<ProbeStep>,
+}
+
+impl ProbeReport {
+    /// Formats the report as a JSON object.
+    ///
+    /// Bytes are formatted as lowercase hex numbers, events with the `Debug` format.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+
+        json.push('{');
+        let _ = write!(
+            json,
+            "\"term\":{},\"term_program\":{},\"modes\":[",
+            json_option(&self.term),
+            json_option(&self.term_program)
+        );
+        for (index, mode) in self.modes.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "{{\"mode\":{},\"outcome\":{}}}",
+                json_string(&mode.mode),
+                json_string(&format!("{:?}", mode.outcome))
+            );
+        }
+        json.push_str("],\"steps\":[");
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let bytes = step
+                .bytes
+                .iter()
+                .map(|byte| json_string(&format!("{:02x}", byte)))
+                .collect::<Vec<_>>();
+            let events = step
+                .events
+                .iter()
+                .map(|event| json_string(&format!("{:?}", event)))
+                .collect::<Vec<_>>();
+            let _ = write!(
+                json,
+                "{{\"id\":{},\"prompt\":{},\"bytes\":[{}],\"events\":[{}]}}",
+                json_string(&step.id),
+                json_string(&step.prompt),
+                bytes.join(","),
+                events.join(",")
+            );
+        }
+        json.push_str("]}");
+
+        json
+    }
+}
+
+/// Runs the interactive terminal compatibility probe.
+///
+/// It enables the raw mode and all the supported modes, shows the instructions and records
+/// the input of every step. A step is skipped if the user doesn't react in 10 seconds. All the
+/// modes enabled by the probe are disabled before it returns.
+///
+/// # Notes
+///
+/// * Don't read the input while the probe is running, the recorded events would be incomplete.
+/// * The probe is available on UNIX only, Windows doesn't receive the input as bytes.
+pub fn run_probe() -> Result<ProbeReport> {
+    let _raw = RawScreen::into_raw_mode()?;
+
+    let input = input();
+    let mut reader = input.read_async();
+    let bytes_rx = bytes_receiver();
+
+    let mut modes = Vec::new();
+
+    // The kitty keyboard protocol & modifyOtherKeys change the key encoding, the keys are
+    // recorded with the legacy encoding
+    let outcome =
+        input.enable_keyboard_enhancement(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)?;
+    input.disable_keyboard_enhancement()?;
+    modes.push(mode_probe("keyboard-enhancement", outcome));
+
+    let outcome = input.enable_modify_other_keys()?;
+    input.disable_modify_other_keys()?;
+    modes.push(mode_probe("modify-other-keys", outcome));
+
+    #[cfg(not(feature = "keyboard-only"))]
+    modes.push(mode_probe("mouse", input.enable_mouse_mode()?));
+    modes.push(mode_probe(
+        "bracketed-paste",
+        input.enable_bracketed_paste()?,
+    ));
+    modes.push(mode_probe(
+        "focus-reporting",
+        input.enable_focus_reporting()?,
+    ));
+
+    let steps = STEPS
+        .iter()
+        .map(|(id, prompt)| record_step(id, prompt, &mut reader, &bytes_rx))
+        .collect::<Result<Vec<ProbeStep>>>();
+
+    input.disable_focus_reporting()?;
+    input.disable_bracketed_paste()?;
+    #[cfg(not(feature = "keyboard-only"))]
+    input.disable_mouse_mode()?;
+
+    Ok(ProbeReport {
+        term: env::var("TERM").ok(),
+        term_program: env::var("TERM_PROGRAM").ok(),
+        modes,
+        steps: steps?,
+    })
+}
+
+fn mode_probe(mode: &str, outcome: EnableOutcome) -> ModeProbe {
+    ModeProbe {
+        mode: mode.to_string(),
+        outcome,
+    }
+}
+
+/// Shows the `prompt` and records the input until the user stops typing.
+fn record_step(
+    id: &str,
+    prompt: &str,
+    reader: &mut AsyncReader,
+    bytes_rx: &Receiver<Vec<u8>>,
+) -> Result<ProbeStep> {
+    // Drop the leftovers of the previous step
+    input().pump()?;
+    reader.by_ref().for_each(drop);
+    bytes_rx.try_iter().for_each(drop);
+
+    write_cout!(&format!("{}\r\n", prompt))?;
+
+    let mut bytes = Vec::new();
+    let mut events = Vec::new();
+
+    let started = Instant::now();
+    let mut last_input: Option<Instant> = None;
+
+    loop {
+        // It does nothing in the `ReadMode::Poll` mode
+        input().pump()?;
+
+        for sequence in bytes_rx.try_iter() {
+            bytes.extend(sequence);
+            last_input = Some(Instant::now());
+        }
+        events.extend(reader.by_ref());
+
+        match last_input {
+            Some(last_input) if last_input.elapsed() >= STEP_QUIET_PERIOD => break,
+            None if started.elapsed() >= STEP_TIMEOUT => break,
+            _ => thread::sleep(POLL_INTERVAL),
+        };
+    }
+
+    Ok(ProbeStep {
+        id: id.to_string(),
+        prompt: prompt.to_string(),
+        bytes,
+        events,
+    })
+}
+
+fn json_option(value: &Option<String>) -> String {
+    value
+        .as_ref()
+        .map(|value| json_string(value))
+        .unwrap_or_else(|| "null".to_string())
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        };
+    }
+    json.push('"');
+
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EnableOutcome, InputEvent, KeyCode};
+
+    use super::{json_string, ModeProbe, ProbeReport, ProbeStep};
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\x1B"), "\"a\\\"b\\\\c\\u001b\"");
+    }
+
+    #[test]
+    fn test_report_to_json() {
+        let report = ProbeReport {
+            term: Some("xterm".to_string()),
+            term_program: None,
+            modes: vec![ModeProbe {
+                mode: "mouse".to_string(),
+                outcome: EnableOutcome::Supported,
+            }],
+            steps: vec![ProbeStep {
+                id: "up".to_string(),
+                prompt: "Press the Up arrow key".to_string(),
+                bytes: b"\x1B[A".to_vec(),
+                events: vec![InputEvent::Keyboard(KeyCode::Up.into())],
+            }],
+        };
+
+        assert_eq!(
+            report.to_json(),
+            concat!(
+                r#"{"term":"xterm","term_program":null,"#,
+                r#""modes":[{"mode":"mouse","outcome":"Supported"}],"#,
+                r#""steps":[{"id":"up","prompt":"Press the Up arrow key","#,
+                r#""bytes":["1b","5b","41"],"#,
+                r#""events":["Keyboard(KeyEvent { code: Up, modifiers: NONE, kind: Press, state: NONE })"]}]}"#
+            )
+        );
+    }
+}
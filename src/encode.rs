@@ -0,0 +1,434 @@
+//! Encoding of the input events into the byte sequences a terminal would send.
+//!
+//! It's the inverse of the UNIX input parser. Terminal multiplexers & test harnesses can use it
+//! to synthesize the input for child PTYs.
+//!
+//! # Examples
+//!
+//! ```
+//! use crossterm_input::encode::{encode, MouseEncoding};
+//! use crossterm_input::{InputEvent, KeyCode};
+//!
+//! let bytes = encode(&InputEvent::Keyboard(KeyCode::Up.into()), MouseEncoding::Sgr);
+//! assert_eq!(bytes, Some(b"\x1B[A".to_vec()));
+//! ```
+
+use crate::{
+    sequences::{BRACKETED_PASTE_END, BRACKETED_PASTE_START, FOCUS_GAINED, FOCUS_LOST},
+    InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+/// Represents the mouse event encoding.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MouseEncoding {
+    /// The X10 compatible encoding (`ESC [ M Cb Cx Cy`), coordinates up to 222 only.
+    X10,
+    /// The SGR encoding (`ESC [ < Cb ; Cx ; Cy M`).
+    Sgr,
+}
+
+/// Encodes the `input_event` into the byte sequence.
+///
+/// Returns `None` if the event can't be encoded.
+#[cfg_attr(feature = "keyboard-only", allow(unused_variables))]
+pub fn encode(input_event: &InputEvent, encoding: MouseEncoding) -> Option<Vec<u8>> {
+    match input_event {
+        InputEvent::Keyboard(key_event) => encode_key(key_event),
+        #[cfg(not(feature = "keyboard-only"))]
+        InputEvent::Mouse(mouse_event) => encode_mouse(mouse_event, encoding),
+        InputEvent::Paste(text) => Some(encode_paste(text)),
+        // The streamed text is encoded as it's delivered
+        InputEvent::PasteStart => Some(BRACKETED_PASTE_START.to_vec()),
+        InputEvent::PasteChunk(text) => Some(text.as_bytes().to_vec()),
+        InputEvent::PasteEnd => Some(BRACKETED_PASTE_END.to_vec()),
+        InputEvent::FocusGained => Some(FOCUS_GAINED.to_vec()),
+        InputEvent::FocusLost => Some(FOCUS_LOST.to_vec()),
+        _ => None,
+    }
+}
+
+/// Encodes the `key_event` into the byte sequence.
+///
+/// Returns `None` if the key can't be encoded (`KeyCode::F(0)`, Ctrl + `KeyCode::Char('!')`, key
+/// release, ...). A key repeat is encoded as a key press.
+pub fn encode_key(key_event: &KeyEvent) -> Option<Vec<u8>> {
+    if key_event.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let modifiers = key_event.modifiers;
+
+    match key_event.code {
+        KeyCode::Left => return Some(encode_cursor_key(b'D', modifiers)),
+        KeyCode::Right => return Some(encode_cursor_key(b'C', modifiers)),
+        KeyCode::Up => return Some(encode_cursor_key(b'A', modifiers)),
+        KeyCode::Down => return Some(encode_cursor_key(b'B', modifiers)),
+        KeyCode::Home => return Some(encode_cursor_key(b'H', modifiers)),
+        KeyCode::End => return Some(encode_cursor_key(b'F', modifiers)),
+        KeyCode::Insert => return Some(encode_special_key(2, modifiers)),
+        KeyCode::Delete => return Some(encode_special_key(3, modifiers)),
+        KeyCode::PageUp => return Some(encode_special_key(5, modifiers)),
+        KeyCode::PageDown => return Some(encode_special_key(6, modifiers)),
+        KeyCode::F(n) => return encode_f_key(n, modifiers),
+        _ => {}
+    };
+
+    // Alt + key is sent as Esc followed by the key
+    if modifiers.contains(KeyModifiers::ALT) {
+        let mut bytes = vec![b'\x1B'];
+        bytes.extend(encode_key(&KeyEvent::new(
+            key_event.code,
+            modifiers - KeyModifiers::ALT,
+        ))?);
+        return Some(bytes);
+    }
+
+    let bytes: &[u8] = match (key_event.code, modifiers) {
+        (KeyCode::Backspace, KeyModifiers::NONE) => b"\x7F",
+        (KeyCode::Backspace, KeyModifiers::CONTROL) => b"\x08",
+        (KeyCode::Enter, KeyModifiers::NONE) => b"\r",
+        (KeyCode::Tab, KeyModifiers::NONE) => b"\t",
+        (KeyCode::BackTab, KeyModifiers::NONE) => b"\x1B[Z",
+        (KeyCode::Esc, KeyModifiers::NONE) => b"\x1B",
+        (KeyCode::Null, KeyModifiers::NONE) => b"\0",
+        (KeyCode::Char(c), KeyModifiers::NONE) => return Some(encode_char(c)),
+        (KeyCode::Char(c), KeyModifiers::CONTROL) => return encode_ctrl(c).map(|byte| vec![byte]),
+        _ => return None,
+    };
+
+    Some(bytes.to_vec())
+}
+
+/// Returns the xterm modifier parameter (`1 + Shift (1) + Alt (2) + Ctrl (4)`), the Super, Hyper
+/// & Meta modifiers are encoded like in the kitty keyboard protocol (`Super (8) + Hyper (16) +
+/// Meta (32)`).
+///
+/// Returns `None` if there's no modifier.
+fn modifier_parameter(modifiers: KeyModifiers) -> Option<u8> {
+    if modifiers.is_empty() {
+        return None;
+    }
+
+    let mut parameter = 1;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        parameter += 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        parameter += 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        parameter += 4;
+    }
+    if modifiers.contains(KeyModifiers::SUPER) {
+        parameter += 8;
+    }
+    if modifiers.contains(KeyModifiers::HYPER) {
+        parameter += 16;
+    }
+    if modifiers.contains(KeyModifiers::META) {
+        parameter += 32;
+    }
+    Some(parameter)
+}
+
+fn encode_cursor_key(key: u8, modifiers: KeyModifiers) -> Vec<u8> {
+    match modifier_parameter(modifiers) {
+        Some(parameter) => format!("\x1B[1;{}{}", parameter, key as char).into_bytes(),
+        None => vec![b'\x1B', b'[', key],
+    }
+}
+
+fn encode_special_key(code: u8, modifiers: KeyModifiers) -> Vec<u8> {
+    match modifier_parameter(modifiers) {
+        Some(parameter) => format!("\x1B[{};{}~", code, parameter).into_bytes(),
+        None => format!("\x1B[{}~", code).into_bytes(),
+    }
+}
+
+fn encode_char(c: char) -> Vec<u8> {
+    let mut buffer = [0; 4];
+    c.encode_utf8(&mut buffer).as_bytes().to_vec()
+}
+
+fn encode_f_key(n: u8, modifiers: KeyModifiers) -> Option<Vec<u8>> {
+    let code = match n {
+        // F1-F4
+        1..=4 => {
+            return Some(match modifier_parameter(modifiers) {
+                Some(parameter) => {
+                    format!("\x1B[1;{}{}", parameter, (b'P' + n - 1) as char).into_bytes()
+                }
+                None => vec![b'\x1B', b'O', b'P' + n - 1],
+            })
+        }
+        5 => 15,
+        6..=10 => n + 11,
+        11..=12 => n + 12,
+        // F13-F20 (VT220)
+        13..=14 => n + 12,
+        15..=16 => n + 13,
+        17..=20 => n + 14,
+        _ => return None,
+    };
+
+    Some(encode_special_key(code, modifiers))
+}
+
+fn encode_ctrl(c: char) -> Option<u8> {
+    match c {
+        ' ' => Some(b'\0'),
+        'a'..='z' => Some(c as u8 - b'a' + 0x1),
+        '3'..='7' => Some(c as u8 - b'3' + 0x1B),
+        _ => None,
+    }
+}
+
+/// Encodes the pasted `text` into the bracketed paste byte sequence.
+pub fn encode_paste(text: &str) -> Vec<u8> {
+    let mut bytes = BRACKETED_PASTE_START.to_vec();
+    bytes.extend(text.as_bytes());
+    bytes.extend(BRACKETED_PASTE_END);
+    bytes
+}
+
+/// Encodes the `mouse_event` into the byte sequence.
+///
+/// The coordinates are expected to be zero based (see the
+/// [`MouseOrigin`](../enum.MouseOrigin.html)). Returns `None` if the event can't be encoded
+/// (`MouseEventKind::Unknown`, coordinates out of the X10 range, ...).
+#[cfg(not(feature = "keyboard-only"))]
+pub fn encode_mouse(mouse_event: &MouseEvent, encoding: MouseEncoding) -> Option<Vec<u8>> {
+    let button = match mouse_event.button {
+        Some(MouseButton::Left) => 0,
+        Some(MouseButton::Middle) => 1,
+        Some(MouseButton::Right) => 2,
+        Some(MouseButton::WheelUp) => 64,
+        Some(MouseButton::WheelDown) => 65,
+        Some(MouseButton::Back) => 128,
+        Some(MouseButton::Forward) => 129,
+        Some(MouseButton::Other(n @ 10..=11)) => 128 + n - 8,
+        Some(MouseButton::Other(_)) => return None,
+        None => 3,
+    };
+
+    let (mut cb, release) = match (mouse_event.kind, encoding) {
+        // Terminals report the double click as two presses
+        (MouseEventKind::Press, _) | (MouseEventKind::DoubleClick, _) => (button, false),
+        // X10 doesn't report the released button
+        (MouseEventKind::Release, MouseEncoding::X10) => (3, true),
+        // SGR reports the released button with `m`, any button if it's unknown
+        (MouseEventKind::Release, MouseEncoding::Sgr) => {
+            (if button == 3 { 0 } else { button }, true)
+        }
+        (MouseEventKind::Drag, _) | (MouseEventKind::Moved, _) => (button + 32, false),
+        (MouseEventKind::Unknown, _) => return None,
+    };
+
+    if mouse_event.modifiers.contains(KeyModifiers::SHIFT) {
+        cb += 4;
+    }
+    if mouse_event.modifiers.contains(KeyModifiers::ALT) {
+        cb += 8;
+    }
+    if mouse_event.modifiers.contains(KeyModifiers::CONTROL) {
+        cb += 16;
+    }
+
+    let (x, y) = (mouse_event.column, mouse_event.row);
+
+    match encoding {
+        MouseEncoding::X10 => {
+            // The upper left character position is 1,1 & everything is offset by 32
+            if x > 222 || y > 222 {
+                return None;
+            }
+            Some(vec![
+                b'\x1B',
+                b'[',
+                b'M',
+                cb + 32,
+                x as u8 + 33,
+                y as u8 + 33,
+            ])
+        }
+        MouseEncoding::Sgr => Some(
+            format!(
+                "\x1B[<{};{};{}{}",
+                cb,
+                u32::from(x) + 1,
+                u32::from(y) + 1,
+                if release { 'm' } else { 'M' }
+            )
+            .into_bytes(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(
+            encode_key(&KeyEvent::new_with_kind(
+                KeyCode::Up,
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            )),
+            None
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(KeyCode::Backspace, KeyModifiers::CONTROL)),
+            Some(b"\x08".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::Up)),
+            Some(b"\x1B[A".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::F(2))),
+            Some(b"\x1BOQ".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::F(5))),
+            Some(b"\x1B[15~".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::F(12))),
+            Some(b"\x1B[24~".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::F(13))),
+            Some(b"\x1B[25~".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::F(20))),
+            Some(b"\x1B[34~".to_vec())
+        );
+        assert_eq!(encode_key(&KeyEvent::from(KeyCode::F(21))), None);
+        assert_eq!(
+            encode_key(&KeyEvent::from(KeyCode::Char('ž'))),
+            Some("ž".as_bytes().to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT)),
+            Some(b"\x1Bx".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(b"\x03".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(KeyCode::Char('!'), KeyModifiers::CONTROL)),
+            None
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)),
+            Some(b"\x1B[1;2D".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(
+                KeyCode::Delete,
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )),
+            Some(b"\x1B[3;7~".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(KeyCode::F(1), KeyModifiers::CONTROL)),
+            Some(b"\x1B[1;5P".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(
+                KeyCode::Up,
+                KeyModifiers::SHIFT | KeyModifiers::SUPER
+            )),
+            Some(b"\x1B[1;10A".to_vec())
+        );
+        assert_eq!(
+            encode_key(&KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )),
+            Some(b"\x1B\x03".to_vec())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_encode_mouse() {
+        let press = MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Right), 9, 19);
+
+        assert_eq!(
+            encode_mouse(&press, MouseEncoding::Sgr),
+            Some(b"\x1B[<2;10;20M".to_vec())
+        );
+        assert_eq!(
+            encode_mouse(&press, MouseEncoding::X10),
+            Some(vec![b'\x1B', b'[', b'M', 34, 42, 52])
+        );
+        assert_eq!(
+            encode_mouse(
+                &MouseEvent::new(MouseEventKind::Release, None, 0, 0),
+                MouseEncoding::Sgr
+            ),
+            Some(b"\x1B[<0;1;1m".to_vec())
+        );
+        assert_eq!(
+            encode_mouse(
+                &MouseEvent::new(MouseEventKind::Release, Some(MouseButton::Right), 0, 0),
+                MouseEncoding::Sgr
+            ),
+            Some(b"\x1B[<2;1;1m".to_vec())
+        );
+        assert_eq!(
+            encode_mouse(
+                &MouseEvent {
+                    modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                    ..press
+                },
+                MouseEncoding::Sgr
+            ),
+            Some(b"\x1B[<22;10;20M".to_vec())
+        );
+        assert_eq!(
+            encode_mouse(
+                &MouseEvent::new(MouseEventKind::Drag, Some(MouseButton::Left), 300, 0),
+                MouseEncoding::X10
+            ),
+            None
+        );
+        assert_eq!(
+            encode_mouse(
+                &MouseEvent::new(MouseEventKind::DoubleClick, Some(MouseButton::Right), 9, 19),
+                MouseEncoding::Sgr
+            ),
+            encode_mouse(&press, MouseEncoding::Sgr)
+        );
+        assert_eq!(
+            encode_mouse(
+                &MouseEvent::new(MouseEventKind::Unknown, None, 0, 0),
+                MouseEncoding::Sgr
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_encode_paste() {
+        assert_eq!(encode_paste("a\nb"), b"\x1B[200~a\nb\x1B[201~".to_vec());
+
+        let streamed = [
+            InputEvent::PasteStart,
+            InputEvent::PasteChunk("a\n".to_string()),
+            InputEvent::PasteChunk("b".to_string()),
+            InputEvent::PasteEnd,
+        ]
+        .iter()
+        .flat_map(|event| encode(event, MouseEncoding::Sgr).unwrap())
+        .collect::<Vec<u8>>();
+        assert_eq!(streamed, encode_paste("a\nb"));
+    }
+}
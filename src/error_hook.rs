@@ -0,0 +1,84 @@
+//! An application hook notified about the input errors.
+
+use std::sync::{Arc, Mutex};
+
+use crossterm_utils::ErrorKind;
+use lazy_static::lazy_static;
+
+type ErrorHook = Arc<dyn Fn(&ErrorKind) + Send + Sync>;
+
+lazy_static! {
+    /// The installed error hook.
+    static ref ERROR_HOOK: Mutex<Option<ErrorHook>> = Mutex::new(None);
+}
+
+/// Installs the `hook` notified about the errors which can't be returned to the caller
+/// (the raw mode isn't enabled, ...), it replaces the previous hook.
+///
+/// The hook can be called from any thread (the reading thread, the thread creating a reader, ...).
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{input, set_error_hook, InputConfig, RawModeFallback};
+///
+/// set_error_hook(|error| eprintln!("input: {}", error));
+///
+/// let input = input();
+/// input.set_config(InputConfig::new().with_raw_mode_fallback(RawModeFallback::Warn));
+/// // Reports the error if the raw mode isn't enabled
+/// let _reader = input.read_sync();
+/// ```
+pub fn set_error_hook<F>(hook: F)
+where
+    F: Fn(&ErrorKind) + Send + Sync + 'static,
+{
+    *ERROR_HOOK.lock().unwrap() = Some(Arc::new(hook));
+}
+
+/// Removes the installed error hook.
+pub fn clear_error_hook() {
+    ERROR_HOOK.lock().unwrap().take();
+}
+
+/// Calls the installed error hook (if any) with the `error`.
+pub(crate) fn report_error(error: &ErrorKind) {
+    let hook = ERROR_HOOK.lock().unwrap().clone();
+
+    // Called with the unlocked hook, the hook can replace itself
+    if let Some(hook) = hook {
+        hook(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use crossterm_utils::ErrorKind;
+
+    use super::{clear_error_hook, report_error, set_error_hook};
+
+    #[test]
+    fn test_report_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let error = ErrorKind::IoError(io::Error::new(io::ErrorKind::Interrupted, "error"));
+
+        let counter = calls.clone();
+        set_error_hook(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        report_error(&error);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        clear_error_hook();
+        report_error(&error);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
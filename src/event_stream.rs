@@ -0,0 +1,290 @@
+//! An asynchronous event stream (`event-stream` feature).
+
+use std::{
+    collections::VecDeque,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crossterm_utils::{ErrorKind, Result};
+use futures_core::Stream;
+
+use crate::{input, InputEvent};
+
+/// A stream of the input events implementing the
+/// [`futures_core::Stream`](https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html)
+/// trait.
+///
+/// The events are read by a background thread which wakes the task polling the stream, the
+/// stream can be used in any executor (`tokio::select!`, `async-std`, ...) without busy-polling.
+///
+/// # Notes
+///
+/// * It requires enabled raw mode (see the
+///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more).
+/// * The background thread is blocked until the next event is read, it exits after the next event
+///   once you drop the `EventStream` (this event is lost).
+/// * Call the [`TerminalInput::pump`](struct.TerminalInput.html#method.pump) method periodically
+///   in the [`ReadMode::Pump`](enum.ReadMode.html#variant.Pump) mode.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{future::poll_fn, pin::Pin};
+///
+/// use crossterm_input::{EventStream, InputEvent, KeyCode, KeyEvent, Result};
+/// use futures_core::Stream;
+///
+/// async fn print_events() -> Result<()> {
+///     let mut stream = EventStream::new();
+///
+///     while let Some(event) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+///         match event? {
+///             InputEvent::Keyboard(KeyEvent {
+///                 code: KeyCode::Esc, ..
+///             }) => break,
+///             event => println!("{:?}\r", event),
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct EventStream {
+    shared: Arc<Mutex<SharedState>>,
+}
+
+#[derive(Default)]
+struct SharedState {
+    events: VecDeque<Result<InputEvent>>,
+    waker: Option<Waker>,
+    /// The reading thread exited (no more events).
+    finished: bool,
+    /// The `EventStream` was dropped, the reading thread should exit.
+    dropped: bool,
+}
+
+impl SharedState {
+    fn push(&mut self, event: Result<InputEvent>) {
+        self.events.push_back(event);
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl EventStream {
+    /// Creates a new `EventStream` and spawns the reading thread.
+    ///
+    /// If the thread can't be spawned, the error is returned as the first item and the stream
+    /// ends.
+    pub fn new() -> EventStream {
+        let shared = Arc::new(Mutex::new(SharedState::default()));
+        let mut reader = input().read_sync();
+
+        let thread_shared = shared.clone();
+        let spawned = thread::Builder::new()
+            .name("crossterm_input event stream".to_string())
+            .spawn(move || loop {
+                // Blocking, the reports of the terminal queries are skipped by the reader
+                let event = reader.next();
+
+                let mut shared = thread_shared.lock().unwrap();
+                if shared.dropped {
+                    break;
+                }
+
+                match event {
+                    Some(event) => shared.push(Ok(event)),
+                    // There're no more events
+                    None if reader.is_disconnected() => {
+                        shared.finish();
+                        break;
+                    }
+                    // The input couldn't be read (Windows), the reading continues
+                    None => shared.push(Err(ErrorKind::IoError(io::Error::new(
+                        io::ErrorKind::Other,
+                        "Unable to read the input event",
+                    )))),
+                };
+            });
+
+        if let Err(error) = spawned {
+            let mut state = shared.lock().unwrap();
+            state.push(Err(ErrorKind::IoError(error)));
+            state.finish();
+        }
+
+        EventStream { shared }
+    }
+
+    /// Transforms every event of the stream with the `f` function.
+    ///
+    /// The function is called lazily when the event is polled, other streams (and readers)
+    /// receive the original events. It allows the subsystems of one application to consume
+    /// differently shaped views of the same events (keys translated by a user keymap, ...).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{EventStream, InputEvent, KeyCode, KeyEvent};
+    ///
+    /// // Vim-like navigation
+    /// let stream = EventStream::new().map_events(|event| match event {
+    ///     InputEvent::Keyboard(KeyEvent {
+    ///         code: KeyCode::Char('k'),
+    ///         ..
+    ///     }) => InputEvent::Keyboard(KeyCode::Up.into()),
+    ///     event => event,
+    /// });
+    /// ```
+    pub fn map_events<T, F>(self, f: F) -> MapEvents<F>
+    where
+        F: FnMut(InputEvent) -> T + Unpin,
+    {
+        MapEvents { stream: self, f }
+    }
+}
+
+/// A stream of the transformed input events.
+///
+/// See the [`EventStream::map_events`](struct.EventStream.html#method.map_events) method.
+pub struct MapEvents<F> {
+    stream: EventStream,
+    f: F,
+}
+
+impl<T, F> Stream for MapEvents<F>
+where
+    F: FnMut(InputEvent) -> T + Unpin,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        match Pin::new(&mut this.stream).poll_next(cx) {
+            Poll::Ready(Some(event)) => Poll::Ready(Some(event.map(&mut this.f))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Default for EventStream {
+    fn default() -> Self {
+        EventStream::new()
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<InputEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.lock().unwrap();
+
+        if let Some(event) = shared.events.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+
+        if shared.finished {
+            return Poll::Ready(None);
+        }
+
+        // Register the current task, the reading thread wakes it up
+        match shared.waker {
+            Some(ref waker) if waker.will_wake(cx.waker()) => {}
+            _ => shared.waker = Some(cx.waker().clone()),
+        };
+
+        Poll::Pending
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().dropped = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use futures_core::Stream;
+
+    use crate::{InputEvent, KeyCode, KeyEvent};
+
+    use super::{EventStream, SharedState};
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_poll_next_wakes_registered_task() {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut stream = EventStream {
+            shared: Arc::new(Mutex::new(SharedState::default())),
+        };
+
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+
+        let event = InputEvent::Keyboard(KeyEvent::from(KeyCode::Esc));
+        stream.shared.lock().unwrap().push(Ok(event.clone()));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        match Pin::new(&mut stream).poll_next(&mut cx) {
+            Poll::Ready(Some(Ok(polled))) => assert_eq!(polled, event),
+            _ => panic!("event expected"),
+        };
+
+        stream.shared.lock().unwrap().finish();
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn test_map_events() {
+        let waker = Waker::from(Arc::new(CountingWaker(AtomicUsize::new(0))));
+        let mut cx = Context::from_waker(&waker);
+
+        let stream = EventStream {
+            shared: Arc::new(Mutex::new(SharedState::default())),
+        };
+        let shared = stream.shared.clone();
+        let mut mapped = stream.map_events(|event| event == InputEvent::FocusGained);
+
+        assert!(Pin::new(&mut mapped).poll_next(&mut cx).is_pending());
+
+        shared.lock().unwrap().push(Ok(InputEvent::FocusGained));
+        match Pin::new(&mut mapped).poll_next(&mut cx) {
+            Poll::Ready(Some(Ok(mapped))) => assert!(mapped),
+            _ => panic!("event expected"),
+        };
+    }
+}
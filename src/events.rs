@@ -0,0 +1,89 @@
+//! A blocking event iterator for quick scripts.
+
+use crossterm_screen::RawScreen;
+use crossterm_utils::{ErrorKind, Result};
+
+#[cfg(not(feature = "keyboard-only"))]
+use crate::modes::{is_mode_enabled, Mode};
+use crate::{input, InputEvent, SyncReader};
+
+/// Returns a blocking iterator over the input events.
+///
+/// It enables the raw mode, disables the mouse events and creates the
+/// [`SyncReader`](struct.SyncReader.html) in one call. The raw mode is disabled once you drop
+/// the returned `Events`.
+///
+/// If the setup fails, the error is returned as the first item and the iteration ends.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{events, InputEvent, KeyCode, KeyEvent, Result};
+///
+/// fn main() -> Result<()> {
+///     for event in events() {
+///         match event? {
+///             InputEvent::Keyboard(KeyEvent {
+///                 code: KeyCode::Esc, ..
+///             }) => break,
+///             event => println!("{:?}\r", event),
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn events() -> Events {
+    match Events::new() {
+        Ok(events) => events,
+        Err(error) => Events {
+            reader: None,
+            error: Some(error),
+            _raw_screen: None,
+        },
+    }
+}
+
+/// A blocking iterator over the input events.
+///
+/// See the [`events`](fn.events.html) function documentation.
+pub struct Events {
+    reader: Option<SyncReader>,
+    error: Option<ErrorKind>,
+    // Declared last, the raw mode is disabled after the reader is dropped
+    _raw_screen: Option<RawScreen>,
+}
+
+impl Events {
+    fn new() -> Result<Events> {
+        let raw_screen = RawScreen::into_raw_mode()?;
+
+        let input = input();
+        #[cfg(not(feature = "keyboard-only"))]
+        if is_mode_enabled(Mode::Mouse) {
+            input.disable_mouse_mode()?;
+        }
+
+        Ok(Events {
+            reader: Some(input.read_sync()),
+            error: None,
+            _raw_screen: Some(raw_screen),
+        })
+    }
+}
+
+impl Iterator for Events {
+    type Item = Result<InputEvent>;
+
+    /// Reads the next input event (blocking).
+    ///
+    /// The iteration ends once the input can't be read anymore (the terminal was closed, ...).
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+
+        // The reader skips the reports of the terminal queries, `None` means that there're
+        // no more events
+        self.reader.as_mut()?.next().map(Ok)
+    }
+}
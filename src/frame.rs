@@ -0,0 +1,131 @@
+//! A per frame summary of the input events.
+
+use std::collections::HashSet;
+
+use crate::{InputEvent, KeyCode, KeyEvent, KeyEventKind};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseEvent, MouseEventKind};
+
+/// A summary of the input events read during one frame (render tick).
+///
+/// It's designed for immediate mode & game loop consumers which don't want to iterate over
+/// the raw events. See the [`AsyncReader::drain_frame`](struct.AsyncReader.html#method.drain_frame)
+/// method.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct FrameInput {
+    /// The last known mouse position (if any mouse event was read).
+    #[cfg(not(feature = "keyboard-only"))]
+    pub mouse_position: Option<(u16, u16)>,
+    /// All the keys pressed (or repeated) during the frame, reported as pressed.
+    pub keys: HashSet<KeyEvent>,
+    /// All the characters typed during the frame (`KeyCode::Char` without the Ctrl & Alt modifiers only).
+    pub text: String,
+    /// All the mouse events read during the frame.
+    #[cfg(not(feature = "keyboard-only"))]
+    pub mouse_events: Vec<MouseEvent>,
+}
+
+impl FrameInput {
+    /// Creates a new `FrameInput` summary of all the `events`.
+    pub(crate) fn from_events<I>(events: I) -> FrameInput
+    where
+        I: IntoIterator<Item = InputEvent>,
+    {
+        let mut frame = FrameInput::default();
+
+        for event in events {
+            match event {
+                InputEvent::Keyboard(KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                }) => {}
+                InputEvent::Keyboard(key_event) => {
+                    if let KeyCode::Char(c) = key_event.code {
+                        if key_event.modifiers.is_empty() {
+                            frame.text.push(c);
+                        }
+                    }
+                    frame
+                        .keys
+                        .insert(KeyEvent::new(key_event.code, key_event.modifiers));
+                }
+                #[cfg(not(feature = "keyboard-only"))]
+                InputEvent::Mouse(mouse_event) => {
+                    if mouse_event.kind != MouseEventKind::Unknown {
+                        frame.mouse_position = Some((mouse_event.column, mouse_event.row));
+                    }
+                    frame.mouse_events.push(mouse_event);
+                }
+                _ => {}
+            };
+        }
+
+        frame
+    }
+
+    /// Returns `true` if the `key_event` was pressed during the frame.
+    pub fn is_pressed(&self, key_event: &KeyEvent) -> bool {
+        self.keys.contains(key_event)
+    }
+
+    /// Returns `true` if no event was read during the frame.
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.mouse_events.is_empty()
+    }
+
+    /// Returns `true` if no event was read during the frame.
+    #[cfg(feature = "keyboard-only")]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+#[cfg(all(test, not(feature = "keyboard-only")))]
+mod tests {
+    use super::*;
+
+    use crate::{KeyModifiers, MouseButton};
+
+    #[test]
+    fn test_frame_input_from_events() {
+        let frame = FrameInput::from_events(vec![
+            InputEvent::Keyboard(KeyCode::Char('h').into()),
+            InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                1,
+                2,
+            )),
+            InputEvent::Keyboard(KeyCode::Char('i').into()),
+            InputEvent::Keyboard(KeyCode::Up.into()),
+            InputEvent::Keyboard(KeyEvent::new_with_kind(
+                KeyCode::Up,
+                KeyModifiers::NONE,
+                KeyEventKind::Repeat,
+            )),
+            InputEvent::Keyboard(KeyEvent::new_with_kind(
+                KeyCode::Char('x'),
+                KeyModifiers::NONE,
+                KeyEventKind::Release,
+            )),
+            InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Drag,
+                Some(MouseButton::Left),
+                3,
+                4,
+            )),
+            InputEvent::Mouse(MouseEvent::new(MouseEventKind::Unknown, None, 0, 0)),
+        ]);
+
+        assert_eq!(frame.mouse_position, Some((3, 4)));
+        assert_eq!(frame.text, "hi");
+        assert_eq!(frame.keys.len(), 3);
+        assert!(frame.is_pressed(&KeyEvent::from(KeyCode::Up)));
+        assert!(!frame.is_pressed(&KeyEvent::from(KeyCode::Down)));
+        assert_eq!(frame.mouse_events.len(), 3);
+        assert!(!frame.is_empty());
+
+        assert!(FrameInput::from_events(vec![]).is_empty());
+    }
+}
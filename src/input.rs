@@ -5,6 +5,11 @@ use std::io;
 
 use crossterm_utils::Result;
 
+use crate::terminal_info::TerminalIdentity;
+#[cfg(not(feature = "keyboard-only"))]
+use crate::MouseCaptureOptions;
+use crate::{EnableOutcome, KeyboardEnhancementFlags};
+
 // TODO Create a new common AsyncReader structure (like TerminalCursor, TerminalInput, ...).
 //      To avoid copy & pasting of the documentation, to sync the code organization, ...
 #[cfg(unix)]
@@ -57,12 +62,51 @@ pub(crate) trait Input {
     fn read_char(&self) -> Result<char>;
     /// Read the input asynchronously from the user.
     fn read_async(&self) -> AsyncReader;
+    /// Read the input asynchronously from the user, starting with the `count` most recent events.
+    fn read_async_with_replay(&self, count: usize) -> AsyncReader;
     ///  Read the input asynchronously until a certain character is hit.
     fn read_until_async(&self, delimiter: u8) -> AsyncReader;
     /// Read the input synchronously from the user.
     fn read_sync(&self) -> SyncReader;
-    /// Start monitoring mouse events.
-    fn enable_mouse_mode(&self) -> Result<()>;
+    /// Start monitoring mouse events with the capture `options`.
+    #[cfg(not(feature = "keyboard-only"))]
+    fn enable_mouse_mode(&self, options: &MouseCaptureOptions) -> Result<EnableOutcome>;
     /// Stop monitoring mouse events.
+    #[cfg(not(feature = "keyboard-only"))]
     fn disable_mouse_mode(&self) -> Result<()>;
+    /// Start monitoring mouse events with the DEC locator.
+    #[cfg(not(feature = "keyboard-only"))]
+    fn enable_locator_mode(&self) -> Result<EnableOutcome>;
+    /// Stop monitoring mouse events with the DEC locator.
+    #[cfg(not(feature = "keyboard-only"))]
+    fn disable_locator_mode(&self) -> Result<()>;
+    /// Start reporting the pasted text as a single event.
+    fn enable_bracketed_paste(&self) -> Result<EnableOutcome>;
+    /// Stop reporting the pasted text as a single event.
+    fn disable_bracketed_paste(&self) -> Result<()>;
+    /// Start reporting the terminal focus changes.
+    fn enable_focus_reporting(&self) -> Result<EnableOutcome>;
+    /// Stop reporting the terminal focus changes.
+    fn disable_focus_reporting(&self) -> Result<()>;
+    /// Push the kitty keyboard protocol enhancement flags.
+    fn enable_keyboard_enhancement(&self, flags: KeyboardEnhancementFlags)
+        -> Result<EnableOutcome>;
+    /// Pop the kitty keyboard protocol enhancement flags.
+    fn disable_keyboard_enhancement(&self) -> Result<()>;
+    /// Start reporting the modified keys with the xterm modifyOtherKeys sequences.
+    fn enable_modify_other_keys(&self) -> Result<EnableOutcome>;
+    /// Stop reporting the modified keys with the xterm modifyOtherKeys sequences.
+    fn disable_modify_other_keys(&self) -> Result<()>;
+    /// Start reporting the key records with the win32-input-mode sequences.
+    fn enable_win32_input_mode(&self) -> Result<EnableOutcome>;
+    /// Stop reporting the key records with the win32-input-mode sequences.
+    fn disable_win32_input_mode(&self) -> Result<()>;
+    /// Ask the terminal for the termcap/terminfo capability value.
+    fn query_termcap(&self, name: &str) -> Result<Option<String>>;
+    /// Ask the terminal for the cursor position.
+    fn query_cursor_position(&self) -> Result<Option<(u16, u16)>>;
+    /// Ask the terminal for the XTVERSION & the secondary device attributes.
+    fn query_terminal_identity(&self) -> Result<TerminalIdentity>;
+    /// Read all the available input without blocking (`ReadMode::Pump` only).
+    fn pump(&self) -> Result<usize>;
 }
@@ -1,12 +1,48 @@
 //! This is a UNIX specific implementation for input related action.
 
 use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 use std::{char, sync::mpsc};
 
-use crossterm_utils::{csi, write_cout, Result};
+use crossterm_utils::{write_cout, Result};
 
-use crate::sys::unix::internal_event_receiver;
-use crate::{input::Input, InputEvent, InternalEvent, KeyEvent};
+use crate::sys::ansi::keyboard_enhancement_flags;
+use crate::sys::unix::{
+    cursor_position_receiver, internal_event_receiver, internal_event_receiver_with_replay,
+    pump_internal_events, read_direct, set_keyboard_enhancement_flags, write_sequences,
+    SourcedEvent,
+};
+use crate::{
+    config::{with_input_config, InputConfig, ReadMode, SentinelPolicy},
+    input::Input,
+    keyboard_state::set_key_releases_reported,
+    modes::ReaderGuard,
+    sequences::{
+        BRACKETED_PASTE_DISABLE, BRACKETED_PASTE_ENABLE, CURSOR_POSITION_QUERY,
+        DEVICE_ATTRIBUTES_QUERY, FOCUS_REPORTING_DISABLE, FOCUS_REPORTING_ENABLE,
+        KEYBOARD_ENHANCEMENT_POP, KEYBOARD_ENHANCEMENT_QUERY, MODIFY_OTHER_KEYS_DISABLE,
+        MODIFY_OTHER_KEYS_ENABLE, TERMINAL_VERSION_QUERY, WIN32_INPUT_MODE_DISABLE,
+        WIN32_INPUT_MODE_ENABLE,
+    },
+    terminal_info::TerminalIdentity,
+    EnableOutcome, FrameInput, InputEvent, InternalEvent, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, KeyboardEnhancementFlags, SourceId,
+};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{
+    sequences::{
+        LOCATOR_DESELECT_EVENTS, LOCATOR_DISABLE, LOCATOR_ENABLE, LOCATOR_SELECT_EVENTS,
+        MOUSE_DISABLE_CLICKS, MOUSE_DISABLE_DRAG, MOUSE_DISABLE_MOTION, MOUSE_DISABLE_SGR,
+        MOUSE_DISABLE_URXVT,
+    },
+    MouseCaptureOptions,
+};
+
+/// How long to wait for the terminal mode report.
+const MODE_REPORT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the direct reader waits for the input before it checks if there are more readers.
+const DIRECT_READ_TIMEOUT: Duration = Duration::from_millis(20);
 
 pub(crate) struct UnixInput;
 
@@ -20,53 +56,317 @@ impl Input for UnixInput {
     fn read_char(&self) -> Result<char> {
         let mut reader = self.read_sync();
         loop {
-            if let Some(InputEvent::Keyboard(KeyEvent::Char(ch))) = reader.next() {
-                return Ok(ch);
+            if let Some(InputEvent::Keyboard(KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers: KeyModifiers::NONE,
+                kind,
+                ..
+            })) = reader.next()
+            {
+                if kind != KeyEventKind::Release {
+                    return Ok(ch);
+                }
             }
         }
     }
 
     fn read_async(&self) -> AsyncReader {
-        AsyncReader::new(None)
+        AsyncReader::new(None, 0)
     }
 
-    fn read_until_async(&self, delimiter: u8) -> AsyncReader {
-        let sentinel = match delimiter {
-            b'\n' | b'\r' => Some(KeyEvent::Enter),
-            b'\x1B' => Some(KeyEvent::Esc),
-            c if c.is_ascii() => Some(KeyEvent::Char(c as char)),
-            _ => None,
-        }
-        .map(InputEvent::Keyboard);
+    fn read_async_with_replay(&self, count: usize) -> AsyncReader {
+        AsyncReader::new(None, count)
+    }
 
-        AsyncReader::new(sentinel)
+    fn read_until_async(&self, delimiter: u8) -> AsyncReader {
+        AsyncReader::new(SentinelPolicy::stop_event(delimiter), 0)
     }
 
     fn read_sync(&self) -> SyncReader {
         SyncReader::new()
     }
 
-    fn enable_mouse_mode(&self) -> Result<()> {
-        write_cout!(&format!(
-            "{}h{}h{}h{}h",
-            csi!("?1000"),
-            csi!("?1002"),
-            csi!("?1015"),
-            csi!("?1006")
-        ))?;
-        Ok(())
+    #[cfg(not(feature = "keyboard-only"))]
+    fn enable_mouse_mode(&self, options: &MouseCaptureOptions) -> Result<EnableOutcome> {
+        write_sequences(&options.enable_sequences())?;
+
+        match options.tracking_mode() {
+            Some(mode) => query_private_mode(mode),
+            None => Ok(EnableOutcome::Unknown),
+        }
     }
 
+    #[cfg(not(feature = "keyboard-only"))]
     fn disable_mouse_mode(&self) -> Result<()> {
-        write_cout!(&format!(
-            "{}l{}l{}l{}l",
-            csi!("?1006"),
-            csi!("?1015"),
-            csi!("?1002"),
-            csi!("?1000")
-        ))?;
+        write_sequences(&[
+            MOUSE_DISABLE_SGR,
+            MOUSE_DISABLE_URXVT,
+            MOUSE_DISABLE_MOTION,
+            MOUSE_DISABLE_DRAG,
+            MOUSE_DISABLE_CLICKS,
+        ])?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    fn enable_locator_mode(&self) -> Result<EnableOutcome> {
+        // Enable the locator with character cell coordinates & report button down/up
+        write_sequences(&[LOCATOR_ENABLE, LOCATOR_SELECT_EVENTS])?;
+        // The locator isn't a DEC private mode, it can't be queried with DECRQM
+        Ok(EnableOutcome::Unknown)
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    fn disable_locator_mode(&self) -> Result<()> {
+        write_sequences(&[LOCATOR_DESELECT_EVENTS, LOCATOR_DISABLE])?;
+        Ok(())
+    }
+
+    fn enable_bracketed_paste(&self) -> Result<EnableOutcome> {
+        write_sequences(&[BRACKETED_PASTE_ENABLE])?;
+        query_private_mode(2004)
+    }
+
+    fn disable_bracketed_paste(&self) -> Result<()> {
+        write_sequences(&[BRACKETED_PASTE_DISABLE])?;
+        Ok(())
+    }
+
+    fn enable_focus_reporting(&self) -> Result<EnableOutcome> {
+        write_sequences(&[FOCUS_REPORTING_ENABLE])?;
+        query_private_mode(1004)
+    }
+
+    fn disable_focus_reporting(&self) -> Result<()> {
+        write_sequences(&[FOCUS_REPORTING_DISABLE])?;
         Ok(())
     }
+
+    fn enable_keyboard_enhancement(
+        &self,
+        flags: KeyboardEnhancementFlags,
+    ) -> Result<EnableOutcome> {
+        // The flags are applied once the terminal reports them, the keys typed before are
+        // encoded with the previous flags
+        write_sequences(&[&format!("\x1B[>{}u", flags.bits())])?;
+        query_keyboard_enhancement_flags()
+    }
+
+    fn disable_keyboard_enhancement(&self) -> Result<()> {
+        // The releases of the keys held now won't be reported, the remaining flags (nested
+        // pushes) are applied once the terminal reports them
+        write_sequences(&[KEYBOARD_ENHANCEMENT_POP, KEYBOARD_ENHANCEMENT_QUERY])?;
+        set_keyboard_enhancement_flags(KeyboardEnhancementFlags::empty());
+        Ok(())
+    }
+
+    fn enable_modify_other_keys(&self) -> Result<EnableOutcome> {
+        write_sequences(&[MODIFY_OTHER_KEYS_ENABLE])?;
+        // The resource value can't be queried reliably
+        Ok(EnableOutcome::Unknown)
+    }
+
+    fn disable_modify_other_keys(&self) -> Result<()> {
+        write_sequences(&[MODIFY_OTHER_KEYS_DISABLE])?;
+        Ok(())
+    }
+
+    fn enable_win32_input_mode(&self) -> Result<EnableOutcome> {
+        write_sequences(&[WIN32_INPUT_MODE_ENABLE])?;
+        set_key_releases_reported(true);
+        query_private_mode(9001)
+    }
+
+    fn disable_win32_input_mode(&self) -> Result<()> {
+        write_sequences(&[WIN32_INPUT_MODE_DISABLE])?;
+        // The releases are reported if the kitty keyboard protocol flags ask for them
+        set_keyboard_enhancement_flags(keyboard_enhancement_flags());
+        Ok(())
+    }
+
+    fn query_termcap(&self, name: &str) -> Result<Option<String>> {
+        let rx = internal_event_receiver()?;
+
+        // DCS + q Pt ST, the capability name is hex encoded
+        let hex_name = name
+            .bytes()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<String>();
+        write_cout!(&format!("\x1BP+q{}\x1B\\", hex_name))?;
+
+        let started = Instant::now();
+
+        while started.elapsed() < MODE_REPORT_TIMEOUT {
+            pump_query_response()?;
+
+            match rx.recv_timeout(Duration::from_millis(10)) {
+                Ok((_, InternalEvent::TermcapReport(reported_name, value), _))
+                    if reported_name == name =>
+                {
+                    return Ok(value);
+                }
+                Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+        }
+
+        Ok(None)
+    }
+
+    fn query_cursor_position(&self) -> Result<Option<(u16, u16)>> {
+        // Keeps the reading thread running
+        let _events = internal_event_receiver()?;
+        let rx = cursor_position_receiver();
+        write_sequences(&[CURSOR_POSITION_QUERY])?;
+
+        let started = Instant::now();
+
+        while started.elapsed() < MODE_REPORT_TIMEOUT {
+            pump_query_response()?;
+
+            match rx.recv_timeout(Duration::from_millis(10)) {
+                Ok(position) => return Ok(Some(position)),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+        }
+
+        Ok(None)
+    }
+
+    fn query_terminal_identity(&self) -> Result<TerminalIdentity> {
+        let rx = internal_event_receiver()?;
+        // Answered in order, the DA2 (answered by all the terminals) ends the XTVERSION wait
+        write_sequences(&[TERMINAL_VERSION_QUERY, DEVICE_ATTRIBUTES_QUERY])?;
+
+        let started = Instant::now();
+        let mut version = None;
+
+        while started.elapsed() < MODE_REPORT_TIMEOUT {
+            pump_query_response()?;
+
+            match rx.recv_timeout(Duration::from_millis(10)) {
+                Ok((_, InternalEvent::TerminalVersion(reported_version), _)) => {
+                    version = Some(reported_version);
+                }
+                Ok((_, InternalEvent::DeviceAttributes(terminal_type, firmware_version), _)) => {
+                    return Ok((version, Some((terminal_type, firmware_version))));
+                }
+                Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+        }
+
+        Ok((version, None))
+    }
+
+    fn pump(&self) -> Result<usize> {
+        pump_internal_events()
+    }
+}
+
+/// Reads the available input on the caller's thread if there's no reading thread, the query
+/// response wouldn't be read otherwise.
+fn pump_query_response() -> Result<()> {
+    match with_input_config(InputConfig::read_mode) {
+        ReadMode::Poll => {}
+        ReadMode::Pump => {
+            pump_internal_events()?;
+        }
+        ReadMode::Direct => {
+            read_direct(Duration::from_millis(0))?;
+        }
+    };
+    Ok(())
+}
+
+/// Receives the next event, the input is read on the caller's thread while it's the only
+/// reader (`ReadMode::Direct`).
+fn recv_direct(rx: &Receiver<SourcedEvent>) -> std::result::Result<SourcedEvent, mpsc::RecvError> {
+    loop {
+        match rx.try_recv() {
+            Ok(event) => return Ok(event),
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => return Err(mpsc::RecvError),
+        };
+
+        match read_direct(DIRECT_READ_TIMEOUT) {
+            Ok(true) => {}
+            // Another reader appeared, the events are sent by the reading thread
+            Ok(false) => return rx.recv(),
+            // The tty can't be read anymore
+            Err(_) => return Err(mpsc::RecvError),
+        };
+    }
+}
+
+/// Checks that the input event `sequence` number follows the `last_sequence` one.
+///
+/// The receivers get all the input events in order, a gap means a lost or reordered event.
+fn check_sequence(last_sequence: &mut Option<u64>, sequence: u64) {
+    // Not an input event
+    if sequence == 0 {
+        return;
+    }
+    if let Some(last_sequence) = *last_sequence {
+        debug_assert_eq!(sequence, last_sequence + 1, "input event lost or reordered");
+    }
+    *last_sequence = Some(sequence);
+}
+
+/// Asks the terminal for the DEC private `mode` state (DECRQM) and waits for the report.
+fn query_private_mode(mode: u16) -> Result<EnableOutcome> {
+    let rx = internal_event_receiver()?;
+    write_sequences(&[&format!("\x1B[?{}$p", mode)])?;
+
+    let started = Instant::now();
+
+    while started.elapsed() < MODE_REPORT_TIMEOUT {
+        pump_query_response()?;
+
+        match rx.recv_timeout(Duration::from_millis(10)) {
+            Ok((_, InternalEvent::ModeReport(reported_mode, state), _))
+                if reported_mode == mode =>
+            {
+                return Ok(match state {
+                    // Set or permanently set
+                    1 | 3 => EnableOutcome::Supported,
+                    // Not recognized, reset or permanently reset
+                    _ => EnableOutcome::Unsupported,
+                });
+            }
+            Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+    }
+
+    Ok(EnableOutcome::Unknown)
+}
+
+/// Asks the terminal for the current kitty keyboard protocol flags and waits for the report.
+fn query_keyboard_enhancement_flags() -> Result<EnableOutcome> {
+    let rx = internal_event_receiver()?;
+    write_sequences(&[KEYBOARD_ENHANCEMENT_QUERY])?;
+
+    let started = Instant::now();
+
+    while started.elapsed() < MODE_REPORT_TIMEOUT {
+        pump_query_response()?;
+
+        match rx.recv_timeout(Duration::from_millis(10)) {
+            Ok((_, InternalEvent::KeyboardEnhancementFlags(flags), _)) => {
+                return Ok(if flags == 0 {
+                    EnableOutcome::Unsupported
+                } else {
+                    EnableOutcome::Supported
+                });
+            }
+            Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+    }
+
+    Ok(EnableOutcome::Unknown)
 }
 
 /// An asynchronous input reader (not blocking).
@@ -97,7 +397,7 @@ impl Input for UnixInput {
 /// ```no_run
 /// use std::{thread, time::Duration};
 ///
-/// use crossterm_input::{input, InputEvent, KeyEvent, RawScreen};
+/// use crossterm_input::{input, InputEvent, KeyCode, KeyEvent, RawScreen};
 ///
 /// fn main() {
 ///     println!("Press 'ESC' to quit.");
@@ -114,11 +414,11 @@ impl Input for UnixInput {
 ///     loop {
 ///         if let Some(event) = reader.next() { // Not a blocking call
 ///             match event {
-///                 InputEvent::Keyboard(KeyEvent::Esc) => {
+///                 InputEvent::Keyboard(KeyEvent { code: KeyCode::Esc, .. }) => {
 ///                     println!("Program closing ...");
 ///                     break;
 ///                  }
-///                  InputEvent::Mouse(event) => { /* Mouse event */ }
+///                  InputEvent::Keyboard(event) => { /* Key event */ }
 ///                  _ => { /* Other events */ }
 ///             }
 ///         }
@@ -127,8 +427,12 @@ impl Input for UnixInput {
 /// } // `reader` dropped <- thread cleaned up, `_raw` dropped <- raw mode disabled
 /// ```
 pub struct AsyncReader {
-    rx: Option<Receiver<InternalEvent>>,
+    rx: Option<Receiver<SourcedEvent>>,
     stop_event: Option<InputEvent>,
+    sentinel_policy: SentinelPolicy,
+    /// Sequence number of the last received input event.
+    last_sequence: Option<u64>,
+    _guard: ReaderGuard,
 }
 
 impl AsyncReader {
@@ -137,16 +441,22 @@ impl AsyncReader {
     /// # Arguments
     ///
     /// * `stop_event` - if set, no more events will be produced if this exact event is reached.
+    /// * `replay` - number of most recent events to produce first.
     ///
     /// # Notes
     ///
     /// * A thread is spawned/reused to read the input.
     /// * The reading thread is cleaned up when you drop the `AsyncReader`.
-    fn new(stop_event: Option<InputEvent>) -> AsyncReader {
+    fn new(stop_event: Option<InputEvent>, replay: usize) -> AsyncReader {
         // TODO 1.0: Following expect is here to keep the API compatible (no Result)
         AsyncReader {
-            rx: Some(internal_event_receiver().expect("Unable to get event receiver")),
+            rx: Some(
+                internal_event_receiver_with_replay(replay).expect("Unable to get event receiver"),
+            ),
             stop_event,
+            sentinel_policy: with_input_config(InputConfig::sentinel_policy),
+            last_sequence: None,
+            _guard: ReaderGuard::new(),
         }
     }
 
@@ -161,16 +471,54 @@ impl AsyncReader {
     pub fn stop(&mut self) {
         self.rx = None;
     }
-}
 
-impl Iterator for AsyncReader {
-    type Item = InputEvent;
+    /// Drains all the pending input events and returns their summary.
+    ///
+    /// Call it once per frame (render tick) if you don't want to iterate over the raw events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{thread, time::Duration};
+    /// use crossterm_input::{input, KeyCode, KeyEvent};
+    ///
+    /// let mut reader = input().read_async();
+    ///
+    /// loop {
+    ///     let frame = reader.drain_frame();
+    ///
+    ///     if frame.is_pressed(&KeyEvent::from(KeyCode::Esc)) {
+    ///         break;
+    ///     }
+    ///     /* Render the frame here */
+    ///
+    ///     thread::sleep(Duration::from_millis(16));
+    /// }
+    /// ```
+    pub fn drain_frame(&mut self) -> FrameInput {
+        FrameInput::from_events(self.by_ref())
+    }
 
-    /// Tries to read the next input event (not blocking).
+    /// Tries to read the next input event together with its source (not blocking).
     ///
-    /// `None` doesn't mean that the iteration is finished. See the
-    /// [`AsyncReader`](struct.AsyncReader.html) documentation for more information.
-    fn next(&mut self) -> Option<Self::Item> {
+    /// It behaves in the same way as the `next` method. Use the source to reject events
+    /// which weren't produced by the user (security sensitive confirmation prompts, ...).
+    pub fn next_with_source(&mut self) -> Option<(SourceId, InputEvent)> {
+        self.next_numbered()
+            .map(|(source, input_event, _)| (source, input_event))
+    }
+
+    /// Tries to read the next input event together with its sequence number (not blocking).
+    ///
+    /// It behaves in the same way as the `next` method. The input events are numbered in
+    /// the order they were produced, every reader gets consecutive numbers (use them to check
+    /// that no event was lost or reordered).
+    pub fn next_with_sequence(&mut self) -> Option<(u64, InputEvent)> {
+        self.next_numbered()
+            .map(|(_, input_event, sequence)| (sequence, input_event))
+    }
+
+    fn next_numbered(&mut self) -> Option<(SourceId, InputEvent, u64)> {
         // TODO 1.0: This whole `InternalEvent` -> `InputEvent` mapping should be shared
         //           between UNIX & Windows implementations
 
@@ -179,16 +527,24 @@ impl Iterator for AsyncReader {
             None => return None,
         };
 
+        if with_input_config(InputConfig::read_mode) == ReadMode::Direct {
+            // Errors are ignored, the reader can't report them (`None` means no event)
+            let _ = read_direct(Duration::from_millis(0));
+        }
+
         match rx.try_recv() {
-            Ok(internal_event) => {
-                let input_event = internal_event.into();
+            Ok((source, internal_event, sequence)) => {
+                check_sequence(&mut self.last_sequence, sequence);
+                let input_event: Option<InputEvent> = internal_event.into();
 
-                if self.stop_event.is_some() && input_event == self.stop_event {
-                    // Drop the receiver, stop event received
-                    self.rx = None;
+                if let (Some(input_event), Some(stop_event)) = (&input_event, &self.stop_event) {
+                    if self.sentinel_policy.is_stop_event(input_event, stop_event) {
+                        // Drop the receiver, stop event received
+                        self.rx = None;
+                    }
                 }
 
-                input_event
+                input_event.map(|input_event| (source, input_event, sequence))
             }
             Err(mpsc::TryRecvError::Empty) => None,
             Err(mpsc::TryRecvError::Disconnected) => {
@@ -200,6 +556,18 @@ impl Iterator for AsyncReader {
     }
 }
 
+impl Iterator for AsyncReader {
+    type Item = InputEvent;
+
+    /// Tries to read the next input event (not blocking).
+    ///
+    /// `None` doesn't mean that the iteration is finished. See the
+    /// [`AsyncReader`](struct.AsyncReader.html) documentation for more information.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_source().map(|(_, input_event)| input_event)
+    }
+}
+
 /// A synchronous input reader (blocking).
 ///
 /// `SyncReader` implements the [`Iterator`](https://doc.rust-lang.org/std/iter/index.html#iterator)
@@ -226,7 +594,7 @@ impl Iterator for AsyncReader {
 /// ```no_run
 /// use std::{thread, time::Duration};
 ///
-/// use crossterm_input::{input, InputEvent, KeyEvent, RawScreen};
+/// use crossterm_input::{input, InputEvent, KeyCode, KeyEvent, RawScreen};
 ///
 /// fn main() {
 ///     println!("Press 'ESC' to quit.");
@@ -243,11 +611,11 @@ impl Iterator for AsyncReader {
 ///     loop {
 ///         if let Some(event) = reader.next() { // Blocking call
 ///             match event {
-///                 InputEvent::Keyboard(KeyEvent::Esc) => {
+///                 InputEvent::Keyboard(KeyEvent { code: KeyCode::Esc, .. }) => {
 ///                     println!("Program closing ...");
 ///                     break;
 ///                  }
-///                  InputEvent::Mouse(event) => { /* Mouse event */ }
+///                  InputEvent::Keyboard(event) => { /* Key event */ }
 ///                  _ => { /* Other events */ }
 ///             }
 ///         }
@@ -256,7 +624,10 @@ impl Iterator for AsyncReader {
 /// } // `_raw` dropped <- raw mode disabled
 /// ```
 pub struct SyncReader {
-    rx: Option<Receiver<InternalEvent>>,
+    rx: Option<Receiver<SourcedEvent>>,
+    /// Sequence number of the last received input event.
+    last_sequence: Option<u64>,
+    _guard: ReaderGuard,
 }
 
 impl SyncReader {
@@ -264,18 +635,37 @@ impl SyncReader {
         // TODO 1.0: Following expect is here to keep the API compatible (no Result)
         SyncReader {
             rx: Some(internal_event_receiver().expect("Unable to get event receiver")),
+            last_sequence: None,
+            _guard: ReaderGuard::new(),
         }
     }
-}
 
-impl Iterator for SyncReader {
-    type Item = InputEvent;
+    /// Tries to read the next input event together with its source (blocking).
+    ///
+    /// It behaves in the same way as the `next` method. Use the source to reject events
+    /// which weren't produced by the user (security sensitive confirmation prompts, ...).
+    pub fn next_with_source(&mut self) -> Option<(SourceId, InputEvent)> {
+        self.next_numbered()
+            .map(|(source, input_event, _)| (source, input_event))
+    }
 
-    /// Tries to read the next input event (blocking).
+    /// Tries to read the next input event together with its sequence number (blocking).
     ///
-    /// `None` doesn't mean that the iteration is finished. See the
-    /// [`SyncReader`](struct.SyncReader.html) documentation for more information.
-    fn next(&mut self) -> Option<Self::Item> {
+    /// It behaves in the same way as the `next` method. The input events are numbered in
+    /// the order they were produced, every reader gets consecutive numbers (use them to check
+    /// that no event was lost or reordered).
+    pub fn next_with_sequence(&mut self) -> Option<(u64, InputEvent)> {
+        self.next_numbered()
+            .map(|(_, input_event, sequence)| (sequence, input_event))
+    }
+
+    /// Returns `true` if the input can't be read anymore (the event provider was dropped).
+    #[cfg(feature = "event-stream")]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.rx.is_none()
+    }
+
+    fn next_numbered(&mut self) -> Option<(SourceId, InputEvent, u64)> {
         // TODO 1.0: This whole `InternalEvent` -> `InputEvent` mapping should be shared
         //           between UNIX & Windows implementations
 
@@ -284,13 +674,40 @@ impl Iterator for SyncReader {
             None => return None,
         };
 
-        match rx.recv() {
-            Ok(internal_event) => internal_event.into(),
-            Err(mpsc::RecvError) => {
-                // Sender is dropped, drop the receiver
-                self.rx = None;
-                None
+        loop {
+            let received = match with_input_config(InputConfig::read_mode) {
+                ReadMode::Direct => recv_direct(rx),
+                _ => rx.recv(),
+            };
+
+            match received {
+                Ok((source, internal_event, sequence)) => {
+                    check_sequence(&mut self.last_sequence, sequence);
+
+                    // The reports of the terminal queries (mode reports, ...) aren't input
+                    // events, they're skipped
+                    if let Some(input_event) = Option::<InputEvent>::from(internal_event) {
+                        return Some((source, input_event, sequence));
+                    }
+                }
+                Err(mpsc::RecvError) => {
+                    // Sender is dropped, drop the receiver
+                    self.rx = None;
+                    return None;
+                }
             }
         }
     }
 }
+
+impl Iterator for SyncReader {
+    type Item = InputEvent;
+
+    /// Tries to read the next input event (blocking).
+    ///
+    /// `None` doesn't mean that the iteration is finished. See the
+    /// [`SyncReader`](struct.SyncReader.html) documentation for more information.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_source().map(|(_, input_event)| input_event)
+    }
+}
@@ -1,7 +1,10 @@
 //! This is a WINDOWS specific implementation for input related action.
 
 use std::{
-    char, io,
+    char,
+    collections::VecDeque,
+    io::{self, Read},
+    ptr,
     sync::{
         atomic::{AtomicBool, Ordering},
         mpsc::{self, Receiver, Sender},
@@ -11,34 +14,148 @@ use std::{
     time::Duration,
 };
 
-use crossterm_utils::Result;
+use crossterm_utils::{write_cout, Result};
+use winapi::shared::minwindef::{FALSE, TRUE};
 use winapi::um::{
+    consoleapi::GetConsoleMode,
+    handleapi::CloseHandle,
+    processenv::GetStdHandle,
+    synchapi::{CreateEventW, SetEvent, WaitForMultipleObjects},
+    winbase::{INFINITE, STD_INPUT_HANDLE, WAIT_OBJECT_0},
     wincon::{
-        LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED,
+        ENHANCED_KEY, FOCUS_EVENT_RECORD, LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED,
+        RIGHT_CTRL_PRESSED, SHIFT_PRESSED, WINDOW_BUFFER_SIZE_RECORD,
     },
-    winnt::INT,
+    winnt::{HANDLE, INT},
     winuser::{
-        VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F10, VK_F11, VK_F12,
-        VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9, VK_HOME, VK_INSERT, VK_LEFT,
-        VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_UP,
+        VK_APPS, VK_BACK, VK_CONTROL, VK_DELETE, VK_DIVIDE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1,
+        VK_F24, VK_HOME, VK_INSERT, VK_LEFT, VK_LWIN, VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE,
+        VK_MEDIA_PREV_TRACK, VK_MEDIA_STOP, VK_MENU, VK_NEXT, VK_NUMPAD0, VK_PRIOR, VK_RETURN,
+        VK_RIGHT, VK_RWIN, VK_SHIFT, VK_UP, VK_VOLUME_DOWN, VK_VOLUME_MUTE, VK_VOLUME_UP,
     },
 };
 
+#[cfg(not(feature = "keyboard-only"))]
+use crossterm_winapi::{ButtonState, EventFlags, MouseEvent};
 use crossterm_winapi::{
-    ButtonState, Console, ConsoleMode, EventFlags, Handle, InputEventType, KeyEventRecord,
-    MouseEvent, ScreenBuffer,
+    Console, ConsoleMode, ControlKeyState, Handle, InputEventType, InputRecord, KeyEventRecord,
+    ScreenBuffer,
 };
 use lazy_static::lazy_static;
 
-use crate::{input::Input, InputEvent, KeyEvent, MouseButton};
+use crate::{
+    config::{
+        control_key_event, filter_input_event, normalize_key_event, with_input_config, InputConfig,
+        SentinelPolicy,
+    },
+    input::Input,
+    keyboard_state::{key_released, track_key_event},
+    modes::{is_mode_enabled, Mode, ReaderGuard},
+    rate_limit::KeyRateLimiter,
+    sequences::{
+        BRACKETED_PASTE_DISABLE, BRACKETED_PASTE_ENABLE, FOCUS_REPORTING_DISABLE,
+        FOCUS_REPORTING_ENABLE,
+    },
+    shortcuts::dispatch_shortcuts,
+    terminal_info::TerminalIdentity,
+    EnableOutcome, FrameInput, InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyEventState,
+    KeyModifiers, KeyboardEnhancementFlags, MediaKey, ModifierKey, Parser, SourceId,
+};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{
+    config::{mouse_event_with_origin, wheel_arrow_events},
+    mouse_state::track_mouse_event,
+    scroll::ScrollAccumulator,
+    sequences::{
+        MOUSE_DISABLE_CLICKS, MOUSE_DISABLE_DRAG, MOUSE_DISABLE_MOTION, MOUSE_DISABLE_SGR,
+        MOUSE_DISABLE_URXVT,
+    },
+    MouseButton, MouseCaptureOptions, MouseEventKind,
+};
 
+#[cfg(not(feature = "keyboard-only"))]
 const ENABLE_MOUSE_MODE: u32 = 0x0010 | 0x0080 | 0x0008;
 
+/// The console handle was invalidated (`ERROR_INVALID_HANDLE`).
+const ERROR_INVALID_HANDLE: i32 = 6;
+/// The console was closed (`ERROR_BROKEN_PIPE`).
+const ERROR_BROKEN_PIPE: i32 = 109;
+/// The scan code of the right Shift key.
+const RIGHT_SHIFT_SCAN_CODE: u16 = 0x36;
+/// The console input is reported as the VT sequences.
+const ENABLE_VIRTUAL_TERMINAL_INPUT: u32 = 0x0200;
+/// The maximum number of the console input records read in one batch.
+const MAX_INPUT_RECORDS: usize = 4096;
+
+/// A manual-reset event interrupting the wait for the console input.
+///
+/// It's signaled once the `AsyncReader` is stopped, the reading thread doesn't wait for
+/// the next console event then.
+struct CancelEvent(HANDLE);
+
+// The event handle can be signaled & waited for from any thread
+unsafe impl Send for CancelEvent {}
+unsafe impl Sync for CancelEvent {}
+
+impl CancelEvent {
+    /// Creates a new non-signaled event.
+    ///
+    /// The wait can't be interrupted if the event couldn't be created.
+    fn new() -> CancelEvent {
+        CancelEvent(unsafe { CreateEventW(ptr::null_mut(), TRUE, FALSE, ptr::null()) })
+    }
+
+    /// Signals the event, the current & all the future waits are interrupted.
+    fn cancel(&self) {
+        if !self.0.is_null() {
+            unsafe { SetEvent(self.0) };
+        }
+    }
+}
+
+impl Drop for CancelEvent {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+#[cfg(not(feature = "keyboard-only"))]
 lazy_static! {
     static ref ORIGINAL_CONSOLE_MODE: Mutex<Option<u32>> = Mutex::new(None);
+    /// The wheel scroll accumulator of the console input.
+    static ref SCROLL_ACCUMULATOR: Mutex<ScrollAccumulator> =
+        Mutex::new(ScrollAccumulator::default());
 }
 
+lazy_static! {
+    /// The key rate limiter of the console input.
+    static ref KEY_RATE_LIMITER: Mutex<KeyRateLimiter> = Mutex::new(KeyRateLimiter::default());
+    /// The high surrogates waiting for the low surrogate of the next key event, the key up &
+    /// key down events are paired separately (indexed by the `key_down`).
+    static ref PENDING_HIGH_SURROGATES: Mutex<[Option<u16>; 2]> = Mutex::new([None, None]);
+    /// The parser of the VT input, it keeps the sequences split between the reads.
+    static ref VT_INPUT_PARSER: Mutex<Parser> = Mutex::new(Parser::new());
+    /// The stdin is a console (not a pipe, an MSYS2 / Cygwin pty, ...).
+    static ref CONSOLE_INPUT: bool = is_console_input();
+}
+
+/// The console reports all the mouse events, the drag is dropped unless it was enabled with
+/// the `MouseCaptureOptions`.
+#[cfg(not(feature = "keyboard-only"))]
+static MOUSE_DRAG: AtomicBool = AtomicBool::new(false);
+/// The motion without a pressed button is dropped unless it was enabled with the
+/// `MouseCaptureOptions`.
+#[cfg(not(feature = "keyboard-only"))]
+static MOUSE_ANY_MOTION: AtomicBool = AtomicBool::new(false);
+/// The control key state doesn't contain the Windows keys, they're tracked by their key events.
+static SUPER_PRESSED: AtomicBool = AtomicBool::new(false);
+/// The console was switched to the VT input (`InputConfig::with_vt_input`).
+static VT_INPUT: AtomicBool = AtomicBool::new(false);
+
 /// Initializes the default console color. It will will be skipped if it has already been initialized.
+#[cfg(not(feature = "keyboard-only"))]
 fn init_original_console_mode(original_mode: u32) {
     let mut lock = ORIGINAL_CONSOLE_MODE.lock().unwrap();
 
@@ -48,6 +165,7 @@ fn init_original_console_mode(original_mode: u32) {
 }
 
 /// Returns the original console color, make sure to call `init_console_color` before calling this function. Otherwise this function will panic.
+#[cfg(not(feature = "keyboard-only"))]
 fn original_console_mode() -> u32 {
     // safe unwrap, initial console color was set with `init_console_color` in `WinApiColor::new()`
     ORIGINAL_CONSOLE_MODE
@@ -85,61 +203,221 @@ impl Input for WindowsInput {
     }
 
     fn read_async(&self) -> AsyncReader {
-        AsyncReader::new(Box::new(move |event_tx, cancellation_token| loop {
-            for i in read_input_events().unwrap().1 {
-                if event_tx.send(i).is_err() {
+        let cancel_event = Arc::new(CancelEvent::new());
+        let thread_cancel_event = cancel_event.clone();
+
+        AsyncReader::with_cancel_event(
+            Box::new(move |event_tx, cancellation_token| loop {
+                for i in read_input_events(&thread_cancel_event).unwrap().1 {
+                    let disconnected = i == InputEvent::Disconnected;
+
+                    if event_tx.send(i).is_err() || disconnected {
+                        cancellation_token.store(true, Ordering::SeqCst);
+                        return;
+                    }
+                }
+
+                if cancellation_token.load(Ordering::SeqCst) {
                     return;
                 }
-            }
 
-            if cancellation_token.load(Ordering::SeqCst) {
-                return;
-            }
+                thread::sleep(Duration::from_millis(1));
+            }),
+            cancel_event,
+        )
+    }
 
-            thread::sleep(Duration::from_millis(1));
-        }))
+    fn read_async_with_replay(&self, _count: usize) -> AsyncReader {
+        // Every Windows `AsyncReader` reads the console on its own, there's no shared
+        // event history to replay
+        self.read_async()
     }
 
     fn read_until_async(&self, delimiter: u8) -> AsyncReader {
-        AsyncReader::new(Box::new(move |event_tx, cancellation_token| loop {
-            for event in read_input_events().unwrap().1 {
-                if let InputEvent::Keyboard(KeyEvent::Char(key)) = event {
-                    if (key as u8) == delimiter {
+        let cancel_event = Arc::new(CancelEvent::new());
+        let thread_cancel_event = cancel_event.clone();
+
+        // The same stop event & policy as on UNIX, the pasted text is read with the VT input
+        let stop_event = SentinelPolicy::stop_event(delimiter);
+        let sentinel_policy = with_input_config(InputConfig::sentinel_policy);
+
+        AsyncReader::with_cancel_event(
+            Box::new(move |event_tx, cancellation_token| loop {
+                for event in read_input_events(&thread_cancel_event).unwrap().1 {
+                    if cancellation_token.load(Ordering::SeqCst) {
+                        return;
+                    }
+
+                    let stop = match stop_event {
+                        Some(ref stop_event) => sentinel_policy.is_stop_event(&event, stop_event),
+                        None => false,
+                    };
+                    let disconnected = event == InputEvent::Disconnected;
+
+                    // The stop event is delivered, the reading stops after it
+                    if event_tx.send(event).is_err() || disconnected || stop {
+                        cancellation_token.store(true, Ordering::SeqCst);
                         return;
                     }
                 }
 
+                // Nothing was read, the wait was interrupted
                 if cancellation_token.load(Ordering::SeqCst) {
                     return;
-                } else {
-                    if event_tx.send(event).is_err() {
-                        return;
-                    }
                 }
 
                 thread::sleep(Duration::from_millis(1));
-            }
-        }))
+            }),
+            cancel_event,
+        )
     }
 
     fn read_sync(&self) -> SyncReader {
-        SyncReader
+        SyncReader {
+            pending: VecDeque::new(),
+            disconnected: false,
+            console_wait: CancelEvent::new(),
+            sequence: 0,
+            _guard: ReaderGuard::new(),
+        }
     }
 
-    fn enable_mouse_mode(&self) -> Result<()> {
+    #[cfg(not(feature = "keyboard-only"))]
+    fn enable_mouse_mode(&self, options: &MouseCaptureOptions) -> Result<EnableOutcome> {
+        if sync_vt_input_mode()? {
+            write_cout!(&options.enable_sequences().concat())?;
+            // The mode reports can't be awaited, they're parsed on the reading thread
+            return Ok(EnableOutcome::Unknown);
+        }
+
         let mode = ConsoleMode::from(Handle::current_in_handle()?);
 
         init_original_console_mode(mode.mode()?);
         mode.set_mode(ENABLE_MOUSE_MODE)?;
+        // The any-motion tracking reports the drag as well (like the terminals)
+        MOUSE_DRAG.store(options.drag() || options.motion(), Ordering::SeqCst);
+        MOUSE_ANY_MOTION.store(options.motion(), Ordering::SeqCst);
 
-        Ok(())
+        // The console accepted the mode
+        Ok(EnableOutcome::Supported)
     }
 
+    #[cfg(not(feature = "keyboard-only"))]
     fn disable_mouse_mode(&self) -> Result<()> {
+        if is_vt_input() {
+            write_cout!(&[
+                MOUSE_DISABLE_SGR,
+                MOUSE_DISABLE_URXVT,
+                MOUSE_DISABLE_MOTION,
+                MOUSE_DISABLE_DRAG,
+                MOUSE_DISABLE_CLICKS,
+            ]
+            .concat())?;
+            return Ok(());
+        }
+
         let mode = ConsoleMode::from(Handle::current_in_handle()?);
         mode.set_mode(original_console_mode())?;
+        MOUSE_DRAG.store(false, Ordering::SeqCst);
+        MOUSE_ANY_MOTION.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    fn enable_locator_mode(&self) -> Result<EnableOutcome> {
+        // The DEC locator isn't supported by the Windows console, mouse events are
+        // reported with the `enable_mouse_mode`.
+        Ok(EnableOutcome::Unsupported)
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    fn disable_locator_mode(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn enable_bracketed_paste(&self) -> Result<EnableOutcome> {
+        if sync_vt_input_mode()? {
+            write_cout!(BRACKETED_PASTE_ENABLE)?;
+            return Ok(EnableOutcome::Unknown);
+        }
+
+        // The pasted text is reported as key events by the Windows console
+        Ok(EnableOutcome::Unsupported)
+    }
+
+    fn disable_bracketed_paste(&self) -> Result<()> {
+        if is_vt_input() {
+            write_cout!(BRACKETED_PASTE_DISABLE)?;
+        }
+        Ok(())
+    }
+
+    fn enable_focus_reporting(&self) -> Result<EnableOutcome> {
+        if sync_vt_input_mode()? {
+            write_cout!(FOCUS_REPORTING_ENABLE)?;
+            return Ok(EnableOutcome::Unknown);
+        }
+
+        // Focus events are always delivered by the console, they're just filtered
+        Ok(EnableOutcome::Supported)
+    }
+
+    fn disable_focus_reporting(&self) -> Result<()> {
+        if is_vt_input() {
+            write_cout!(FOCUS_REPORTING_DISABLE)?;
+        }
         Ok(())
     }
+
+    fn enable_keyboard_enhancement(
+        &self,
+        _flags: KeyboardEnhancementFlags,
+    ) -> Result<EnableOutcome> {
+        // Modifiers & key releases are always reported by the console
+        Ok(EnableOutcome::Unsupported)
+    }
+
+    fn disable_keyboard_enhancement(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn enable_modify_other_keys(&self) -> Result<EnableOutcome> {
+        // Modifiers are always reported by the console
+        Ok(EnableOutcome::Unsupported)
+    }
+
+    fn disable_modify_other_keys(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn enable_win32_input_mode(&self) -> Result<EnableOutcome> {
+        // The key records are read from the console directly
+        Ok(EnableOutcome::Unsupported)
+    }
+
+    fn disable_win32_input_mode(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn query_termcap(&self, _name: &str) -> Result<Option<String>> {
+        // The console has no capability database
+        Ok(None)
+    }
+
+    fn query_terminal_identity(&self) -> Result<TerminalIdentity> {
+        // The console doesn't answer the identification queries
+        Ok((None, None))
+    }
+
+    fn query_cursor_position(&self) -> Result<Option<(u16, u16)>> {
+        let position = ScreenBuffer::current()?.info()?.cursor_pos();
+        Ok(Some((position.x as u16, position.y as u16)))
+    }
+
+    fn pump(&self) -> Result<usize> {
+        // Readers are always fed by their own threads
+        Ok(0)
+    }
 }
 
 /// A synchronous input reader (blocking).
@@ -168,7 +446,7 @@ impl Input for WindowsInput {
 /// ```no_run
 /// use std::{thread, time::Duration};
 ///
-/// use crossterm_input::{input, InputEvent, KeyEvent, RawScreen};
+/// use crossterm_input::{input, InputEvent, KeyCode, KeyEvent, RawScreen};
 ///
 /// fn main() {
 ///     println!("Press 'ESC' to quit.");
@@ -185,11 +463,11 @@ impl Input for WindowsInput {
 ///     loop {
 ///         if let Some(event) = reader.next() { // Blocking call
 ///             match event {
-///                 InputEvent::Keyboard(KeyEvent::Esc) => {
+///                 InputEvent::Keyboard(KeyEvent { code: KeyCode::Esc, .. }) => {
 ///                     println!("Program closing ...");
 ///                     break;
 ///                  }
-///                  InputEvent::Mouse(event) => { /* Mouse event */ }
+///                  InputEvent::Keyboard(event) => { /* Key event */ }
 ///                  _ => { /* Other events */ }
 ///             }
 ///         }
@@ -197,7 +475,43 @@ impl Input for WindowsInput {
 ///     }
 /// } // `_raw` dropped <- raw mode disabled
 /// ```
-pub struct SyncReader;
+pub struct SyncReader {
+    /// Events read from the console, but not returned yet.
+    pending: VecDeque<InputEvent>,
+    /// The `Disconnected` event was returned, the console isn't read anymore.
+    disconnected: bool,
+    /// The reader waits for the console input with it, it's never signaled.
+    console_wait: CancelEvent,
+    /// Sequence number of the last returned event.
+    sequence: u64,
+    _guard: ReaderGuard,
+}
+
+impl SyncReader {
+    /// Returns `true` if the input can't be read anymore (the console was closed).
+    #[cfg(feature = "event-stream")]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.disconnected && self.pending.is_empty()
+    }
+
+    /// Tries to read the next input event together with its source (blocking).
+    ///
+    /// It behaves in the same way as the `next` method. Use the source to reject events
+    /// which weren't produced by the user (security sensitive confirmation prompts, ...).
+    pub fn next_with_source(&mut self) -> Option<(SourceId, InputEvent)> {
+        self.next()
+            .map(|input_event| (SourceId::Terminal, input_event))
+    }
+
+    /// Tries to read the next input event together with its sequence number (blocking).
+    ///
+    /// It behaves in the same way as the `next` method. The input events are numbered in
+    /// the order they were produced, every reader gets consecutive numbers (use them to check
+    /// that no event was lost or reordered).
+    pub fn next_with_sequence(&mut self) -> Option<(u64, InputEvent)> {
+        self.next().map(|input_event| (self.sequence, input_event))
+    }
+}
 
 impl Iterator for SyncReader {
     type Item = InputEvent;
@@ -207,9 +521,27 @@ impl Iterator for SyncReader {
     /// `None` doesn't mean that the iteration is finished. See the
     /// [`SyncReader`](struct.SyncReader.html) documentation for more information.
     fn next(&mut self) -> Option<Self::Item> {
-        // This synces the behaviour with the unix::SyncReader (& documentation) where
-        // None is returned in case of error.
-        read_single_event().unwrap_or(None)
+        // All the available console events are read at once, the key rate limiter coalesces
+        // the keys of the whole batch. The console records without an input event (key
+        // releases, ...) don't end the wait.
+        while self.pending.is_empty() {
+            if self.disconnected {
+                return None;
+            }
+
+            // This synces the behaviour with the unix::SyncReader (& documentation) where
+            // None is returned in case of error.
+            let (_, events) = read_input_events(&self.console_wait).ok()?;
+            self.pending.extend(events);
+        }
+
+        let event = self.pending.pop_front()?;
+        if event == InputEvent::Disconnected {
+            self.disconnected = true;
+        }
+        // Every reader reads the console on its own, the events are numbered per reader
+        self.sequence += 1;
+        Some(event)
     }
 }
 
@@ -241,7 +573,7 @@ impl Iterator for SyncReader {
 /// ```no_run
 /// use std::{thread, time::Duration};
 ///
-/// use crossterm_input::{input, InputEvent, KeyEvent, RawScreen};
+/// use crossterm_input::{input, InputEvent, KeyCode, KeyEvent, RawScreen};
 ///
 /// fn main() {
 ///     println!("Press 'ESC' to quit.");
@@ -258,11 +590,11 @@ impl Iterator for SyncReader {
 ///     loop {
 ///         if let Some(event) = reader.next() { // Not a blocking call
 ///             match event {
-///                 InputEvent::Keyboard(KeyEvent::Esc) => {
+///                 InputEvent::Keyboard(KeyEvent { code: KeyCode::Esc, .. }) => {
 ///                     println!("Program closing ...");
 ///                     break;
 ///                  }
-///                  InputEvent::Mouse(event) => { /* Mouse event */ }
+///                  InputEvent::Keyboard(event) => { /* Key event */ }
 ///                  _ => { /* Other events */ }
 ///             }
 ///         }
@@ -273,6 +605,11 @@ impl Iterator for SyncReader {
 pub struct AsyncReader {
     event_rx: Receiver<InputEvent>,
     shutdown: Arc<AtomicBool>,
+    /// Interrupts the wait of the reading thread for the console input.
+    cancel_event: Arc<CancelEvent>,
+    /// Sequence number of the last returned event.
+    sequence: u64,
+    _guard: ReaderGuard,
 }
 
 impl AsyncReader {
@@ -284,18 +621,32 @@ impl AsyncReader {
     /// * A thread is spawned to read the input.
     /// * The reading thread is cleaned up when you drop the `AsyncReader`.
     pub fn new(function: Box<dyn Fn(&Sender<InputEvent>, &Arc<AtomicBool>) + Send>) -> AsyncReader {
+        AsyncReader::with_cancel_event(function, Arc::new(CancelEvent::new()))
+    }
+
+    /// Creates a new `AsyncReader`, the `cancel_event` is signaled once it's stopped.
+    fn with_cancel_event(
+        function: Box<dyn Fn(&Sender<InputEvent>, &Arc<AtomicBool>) + Send>,
+        cancel_event: Arc<CancelEvent>,
+    ) -> AsyncReader {
         let shutdown_handle = Arc::new(AtomicBool::new(false));
 
         let (event_tx, event_rx) = mpsc::channel();
         let thread_shutdown = shutdown_handle.clone();
 
-        thread::spawn(move || loop {
-            function(&event_tx, &thread_shutdown);
+        // The function returns once the reader is stopped (or the console is disconnected)
+        thread::spawn(move || {
+            while !thread_shutdown.load(Ordering::SeqCst) {
+                function(&event_tx, &thread_shutdown);
+            }
         });
 
         AsyncReader {
             event_rx,
             shutdown: shutdown_handle,
+            cancel_event,
+            sequence: 0,
+            _guard: ReaderGuard::new(),
         }
     }
 
@@ -310,6 +661,53 @@ impl AsyncReader {
     ///   `AsyncReader` is dropped.
     pub fn stop(&mut self) {
         self.shutdown.store(true, Ordering::SeqCst);
+        // The reading thread doesn't wait for the next key press
+        self.cancel_event.cancel();
+    }
+
+    /// Drains all the pending input events and returns their summary.
+    ///
+    /// Call it once per frame (render tick) if you don't want to iterate over the raw events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{thread, time::Duration};
+    /// use crossterm_input::{input, KeyCode, KeyEvent};
+    ///
+    /// let mut reader = input().read_async();
+    ///
+    /// loop {
+    ///     let frame = reader.drain_frame();
+    ///
+    ///     if frame.is_pressed(&KeyEvent::from(KeyCode::Esc)) {
+    ///         break;
+    ///     }
+    ///     /* Render the frame here */
+    ///
+    ///     thread::sleep(Duration::from_millis(16));
+    /// }
+    /// ```
+    pub fn drain_frame(&mut self) -> FrameInput {
+        FrameInput::from_events(self.by_ref())
+    }
+
+    /// Tries to read the next input event together with its source (not blocking).
+    ///
+    /// It behaves in the same way as the `next` method. Use the source to reject events
+    /// which weren't produced by the user (security sensitive confirmation prompts, ...).
+    pub fn next_with_source(&mut self) -> Option<(SourceId, InputEvent)> {
+        self.next()
+            .map(|input_event| (SourceId::Terminal, input_event))
+    }
+
+    /// Tries to read the next input event together with its sequence number (not blocking).
+    ///
+    /// It behaves in the same way as the `next` method. The input events are numbered in
+    /// the order they were produced, every reader gets consecutive numbers (use them to check
+    /// that no event was lost or reordered).
+    pub fn next_with_sequence(&mut self) -> Option<(u64, InputEvent)> {
+        self.next().map(|input_event| (self.sequence, input_event))
     }
 }
 
@@ -328,7 +726,9 @@ impl Iterator for AsyncReader {
     /// [`AsyncReader`](struct.AsyncReader.html) documentation for more information.
     fn next(&mut self) -> Option<Self::Item> {
         let mut iterator = self.event_rx.try_iter();
-        iterator.next()
+        let event = iterator.next()?;
+        self.sequence += 1;
+        Some(event)
     }
 }
 
@@ -336,35 +736,192 @@ extern "C" {
     fn _getwche() -> INT;
 }
 
-fn read_single_event() -> Result<Option<InputEvent>> {
-    let console = Console::from(Handle::current_in_handle()?);
+/// Returns the current console input mode as a comparable snapshot.
+pub(crate) fn console_mode() -> Result<Vec<u64>> {
+    let mode = ConsoleMode::from(Handle::current_in_handle()?);
+    Ok(vec![u64::from(mode.mode()?)])
+}
 
-    let input = console.read_single_input_event()?;
+/// Returns `true` if the stdin is a console, the console API can't be used otherwise.
+fn is_console_input() -> bool {
+    let mut mode = 0;
+    // The `CONIN$` of the `Handle::current_in_handle` exists (or blocks) even if the stdin
+    // is redirected, the standard handle is checked
+    unsafe { GetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), &mut mode) != 0 }
+}
 
-    match input.event_type {
-        InputEventType::KeyEvent => {
-            handle_key_event(unsafe { KeyEventRecord::from(*input.event.KeyEvent()) })
-        }
-        InputEventType::MouseEvent => {
-            handle_mouse_event(unsafe { MouseEvent::from(*input.event.MouseEvent()) })
-        }
-        // NOTE (@imdaveho): ignore below
-        InputEventType::WindowBufferSizeEvent => return Ok(None), // TODO implement terminal resize event
-        InputEventType::FocusEvent => Ok(None),
-        InputEventType::MenuEvent => Ok(None),
+/// Returns `true` if the input is read as the VT sequences (the VT input of the console or
+/// the stdin which isn't a console).
+fn is_vt_input() -> bool {
+    !*CONSOLE_INPUT || VT_INPUT.load(Ordering::SeqCst)
+}
+
+/// Switches the console to (or back from) the VT input as configured.
+///
+/// Returns `true` if the input is read as the VT sequences.
+fn sync_vt_input_mode() -> Result<bool> {
+    // The input which isn't a console is always the VT sequences
+    if !*CONSOLE_INPUT {
+        return Ok(true);
+    }
+
+    let enabled = with_input_config(InputConfig::vt_input);
+    if enabled == VT_INPUT.load(Ordering::SeqCst) {
+        return Ok(enabled);
+    }
+
+    let mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let current = mode.mode()?;
+    if enabled {
+        mode.set_mode(current | ENABLE_VIRTUAL_TERMINAL_INPUT)?;
+    } else {
+        mode.set_mode(current & !ENABLE_VIRTUAL_TERMINAL_INPUT)?;
+        // The incomplete sequence is dropped
+        VT_INPUT_PARSER.lock().unwrap().reset();
+    }
+    VT_INPUT.store(enabled, Ordering::SeqCst);
+
+    Ok(enabled)
+}
+
+/// Parses the VT input `text` collected from the key records, the events are appended to
+/// the `events`.
+fn parse_vt_input(text: &mut String, events: &mut Vec<InputEvent>) {
+    if !text.is_empty() {
+        VT_INPUT_PARSER
+            .lock()
+            .unwrap()
+            .parse_into(text.as_bytes(), events);
+        text.clear();
+    }
+}
+
+/// Waits for the console input, the wait is interrupted by the `cancel_event`.
+///
+/// Returns `false` if the wait was interrupted.
+fn wait_for_console_input(cancel_event: &CancelEvent) -> Result<bool> {
+    if cancel_event.0.is_null() {
+        return Ok(true);
+    }
+
+    let console_handle = Handle::current_in_handle()?;
+    let handles = [*console_handle, cancel_event.0];
+
+    match unsafe { WaitForMultipleObjects(handles.len() as u32, handles.as_ptr(), FALSE, INFINITE) }
+    {
+        WAIT_OBJECT_0 => Ok(true),
+        result if result == WAIT_OBJECT_0 + 1 => Ok(false),
+        // The console was closed, the read reports it
+        _ => Ok(true),
+    }
+}
+
+/// Reads the available input events, the wait for the console input is interrupted by
+/// the `cancel_event` (no events are returned then).
+fn read_input_events(cancel_event: &CancelEvent) -> Result<(u32, Vec<InputEvent>)> {
+    // The stdin which isn't a console can't be waited for, the pipe is always signaled
+    if *CONSOLE_INPUT && !wait_for_console_input(cancel_event)? {
+        return Ok((0, Vec::new()));
+    }
+
+    let (count, input_events) = if *CONSOLE_INPUT {
+        read_console_input_events()?
+    } else {
+        read_stdin_input_events()?
+    };
+
+    let mut limiter = KEY_RATE_LIMITER.lock().unwrap();
+    let mut limited_events = Vec::with_capacity(input_events.len());
+    // Events consumed by a global shortcut aren't delivered
+    for event in input_events.into_iter().filter(dispatch_shortcuts) {
+        limited_events.extend(limiter.limit(event));
+    }
+    limited_events.extend(limiter.flush());
+
+    Ok((count, limited_events))
+}
+
+/// Reads the bytes of the stdin which isn't a console (a pipe, an MSYS2 / Cygwin pty, ...),
+/// they're parsed as the VT sequences.
+fn read_stdin_input_events() -> Result<(u32, Vec<InputEvent>)> {
+    let mut buffer = [0; 1024];
+
+    let count = match io::stdin().read(&mut buffer) {
+        // The pipe was closed, there's no more input
+        Ok(0) => return Ok((0, vec![InputEvent::Disconnected])),
+        Ok(count) => count,
+        Err(ref e) if e.kind() == io::ErrorKind::Interrupted => return Ok((0, Vec::new())),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut input_events = Vec::new();
+    VT_INPUT_PARSER
+        .lock()
+        .unwrap()
+        .parse_into(&buffer[..count], &mut input_events);
+
+    Ok((count as u32, input_events))
+}
+
+/// Reads all the pending console input records, the records arriving while they're read are
+/// read as well (up to the `MAX_INPUT_RECORDS`).
+///
+/// The events of the whole batch are delivered at once, the rapid mouse drags don't lag.
+fn read_pending_input_records(console: &Console) -> io::Result<(u32, Vec<InputRecord>)> {
+    let (mut count, mut records) = console.read_console_input()?;
+
+    while records.len() < MAX_INPUT_RECORDS && console.number_of_console_input_events()? > 0 {
+        let (batch_count, batch) = console.read_console_input()?;
+        count += batch_count;
+        records.extend(batch);
     }
+
+    Ok((count, records))
 }
 
 /// partially inspired by: https://github.com/retep998/wio-rs/blob/master/src/console.rs#L130
-fn read_input_events() -> Result<(u32, Vec<InputEvent>)> {
+fn read_console_input_events() -> Result<(u32, Vec<InputEvent>)> {
+    let vt_input = sync_vt_input_mode()?;
     let console = Console::from(Handle::current_in_handle()?);
 
-    let result = console.read_console_input()?;
+    let result = match read_pending_input_records(&console) {
+        Ok(result) => result,
+        // The console was closed, the handle isn't valid anymore
+        Err(ref e)
+            if e.raw_os_error() == Some(ERROR_INVALID_HANDLE)
+                || e.raw_os_error() == Some(ERROR_BROKEN_PIPE) =>
+        {
+            return Ok((0, vec![InputEvent::Disconnected]));
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     let mut input_events = Vec::with_capacity(result.0 as usize);
+    // The characters of the VT input, they're parsed together (a sequence spans many records)
+    let mut vt_text = String::new();
 
     for input in result.1 {
+        // The records are parsed in order
+        let key_record = match input.event_type {
+            InputEventType::KeyEvent => true,
+            _ => false,
+        };
+        if vt_input && !key_record {
+            parse_vt_input(&mut vt_text, &mut input_events);
+        }
+
         match input.event_type {
+            InputEventType::KeyEvent if vt_input => {
+                let key_event = unsafe { KeyEventRecord::from(*input.event.KeyEvent()) };
+                let unit = unsafe { *key_event.u_char.UnicodeChar() } as u16;
+
+                // The key up & the keys without a character (Shift, ...) carry no input
+                if key_event.key_down && unit != 0 {
+                    if let Some(character) = decode_utf16_unit(unit, true) {
+                        vt_text.push(character);
+                    }
+                }
+            }
             InputEventType::KeyEvent => {
                 if let Ok(Some(event)) =
                     handle_key_event(unsafe { KeyEventRecord::from(*input.event.KeyEvent()) })
@@ -372,164 +929,290 @@ fn read_input_events() -> Result<(u32, Vec<InputEvent>)> {
                     input_events.push(event)
                 }
             }
+            #[cfg(not(feature = "keyboard-only"))]
             InputEventType::MouseEvent => {
+                let record = unsafe { *input.event.MouseEvent() };
+                if let Ok(Some(event)) = handle_mouse_event(MouseEvent::from(record)) {
+                    // The high word is the wheel delta, a fraction of the notch is reported by
+                    // the high-resolution wheels
+                    let delta = i32::from((record.dwButtonState >> 16) as u16 as i16).abs();
+                    let delta = if delta > 0 { Some(delta as u16) } else { None };
+
+                    let events = SCROLL_ACCUMULATOR.lock().unwrap().accumulate(event, delta);
+                    input_events.extend(events.into_iter().flat_map(wheel_arrow_events))
+                }
+            }
+            InputEventType::WindowBufferSizeEvent => {
                 if let Ok(Some(event)) =
-                    handle_mouse_event(unsafe { MouseEvent::from(*input.event.MouseEvent()) })
+                    handle_resize_event(unsafe { *input.event.WindowBufferSizeEvent() })
                 {
                     input_events.push(event)
                 }
             }
+            // The focus is reported with the sequences in the VT input
+            InputEventType::FocusEvent if vt_input => (),
+            InputEventType::FocusEvent => {
+                if let Ok(Some(event)) = handle_focus_event(unsafe { *input.event.FocusEvent() }) {
+                    input_events.push(event)
+                }
+            }
+            // The mouse events aren't parsed in the keyboard-only build
+            #[cfg(feature = "keyboard-only")]
+            InputEventType::MouseEvent => (),
             // NOTE (@imdaveho): ignore below
-            InputEventType::WindowBufferSizeEvent => (), // TODO implement terminal resize event
-            InputEventType::FocusEvent => (),
             InputEventType::MenuEvent => (),
         }
     }
+    parse_vt_input(&mut vt_text, &mut input_events);
+
+    Ok((result.0, input_events))
+}
+
+fn handle_resize_event(record: WINDOW_BUFFER_SIZE_RECORD) -> Result<Option<InputEvent>> {
+    // Negative sizes are never reported, just a sanity check
+    if record.dwSize.X < 0 || record.dwSize.Y < 0 {
+        return Ok(None);
+    }
+    Ok(Some(InputEvent::Resize(
+        record.dwSize.X as u16,
+        record.dwSize.Y as u16,
+    )))
+}
+
+fn handle_focus_event(record: FOCUS_EVENT_RECORD) -> Result<Option<InputEvent>> {
+    // Reported only if enabled, like on UNIX systems
+    if !is_mode_enabled(Mode::FocusReporting) {
+        return Ok(None);
+    }
 
-    return Ok((result.0, input_events));
+    if record.bSetFocus != 0 {
+        Ok(Some(InputEvent::FocusGained))
+    } else {
+        Ok(Some(InputEvent::FocusLost))
+    }
 }
 
+#[cfg(not(feature = "keyboard-only"))]
 fn handle_mouse_event(mouse_event: MouseEvent) -> Result<Option<InputEvent>> {
     if let Ok(Some(event)) = parse_mouse_event_record(&mouse_event) {
+        let event = mouse_event_with_origin(event);
+        track_mouse_event(&event);
         return Ok(Some(InputEvent::Mouse(event)));
     }
     Ok(None)
 }
 
 fn handle_key_event(key_event: KeyEventRecord) -> Result<Option<InputEvent>> {
-    if key_event.key_down {
-        if let Some(event) = parse_key_event_record(&key_event) {
-            return Ok(Some(InputEvent::Keyboard(event)));
+    // Keys are tracked by the virtual key code, because the character can differ
+    // between the key down & up (Shift pressed in the meantime, ...)
+    let key_id = u32::from(key_event.virtual_key_code);
+
+    let virtual_key_code = key_event.virtual_key_code as i32;
+    if virtual_key_code == VK_LWIN || virtual_key_code == VK_RWIN {
+        SUPER_PRESSED.store(key_event.key_down, Ordering::SeqCst);
+    }
+
+    let kind = if key_event.key_down {
+        KeyEventKind::Press
+    } else {
+        KeyEventKind::Release
+    };
+
+    let event = match parse_key_event_record(&key_event).and_then(normalize_key_event) {
+        Some(event) => KeyEvent { kind, ..event },
+        None => {
+            // Released even if the key event can't be parsed
+            if kind == KeyEventKind::Release {
+                key_released(key_id);
+            }
+            return Ok(None);
         }
+    };
+    track_key_event(key_id, &event);
+
+    if kind == KeyEventKind::Release && !with_input_config(InputConfig::key_release_events) {
+        return Ok(None);
     }
 
-    return Ok(None);
+    Ok(filter_input_event(InputEvent::Keyboard(event)))
+}
+
+/// Maps the control key state of the key or mouse event to the `KeyModifiers`.
+fn parse_modifiers(key_state: &ControlKeyState) -> KeyModifiers {
+    let mut modifiers = KeyModifiers::empty();
+
+    if key_state.has_state(SHIFT_PRESSED) {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if key_state.has_state(LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED) {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    if key_state.has_state(LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if SUPER_PRESSED.load(Ordering::SeqCst) {
+        modifiers |= KeyModifiers::SUPER;
+    }
+
+    modifiers
 }
 
 fn parse_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
+    let parsed = parse_key_code_event_record(key_event)?;
+
     let key_code = key_event.virtual_key_code as i32;
-    match key_code {
-        VK_SHIFT | VK_CONTROL | VK_MENU => None,
-        VK_BACK => Some(KeyEvent::Backspace),
-        VK_ESCAPE => Some(KeyEvent::Esc),
-        VK_RETURN => Some(KeyEvent::Enter),
-        VK_F1 | VK_F2 | VK_F3 | VK_F4 | VK_F5 | VK_F6 | VK_F7 | VK_F8 | VK_F9 | VK_F10 | VK_F11
-        | VK_F12 => Some(KeyEvent::F((key_event.virtual_key_code - 111) as u8)),
-        VK_LEFT | VK_UP | VK_RIGHT | VK_DOWN => {
-            // Modifier Keys (Ctrl, Shift) Support
-            let key_state = &key_event.control_key_state;
-            let ctrl_pressed = key_state.has_state(RIGHT_CTRL_PRESSED | LEFT_CTRL_PRESSED);
-            let shift_pressed = key_state.has_state(SHIFT_PRESSED);
-
-            let event = match key_code {
-                VK_LEFT => {
-                    if ctrl_pressed {
-                        Some(KeyEvent::CtrlLeft)
-                    } else if shift_pressed {
-                        Some(KeyEvent::ShiftLeft)
-                    } else {
-                        Some(KeyEvent::Left)
-                    }
-                }
-                VK_UP => {
-                    if ctrl_pressed {
-                        Some(KeyEvent::CtrlUp)
-                    } else if shift_pressed {
-                        Some(KeyEvent::ShiftUp)
-                    } else {
-                        Some(KeyEvent::Up)
-                    }
-                }
-                VK_RIGHT => {
-                    if ctrl_pressed {
-                        Some(KeyEvent::CtrlRight)
-                    } else if shift_pressed {
-                        Some(KeyEvent::ShiftRight)
-                    } else {
-                        Some(KeyEvent::Right)
-                    }
-                }
-                VK_DOWN => {
-                    if ctrl_pressed {
-                        Some(KeyEvent::CtrlDown)
-                    } else if shift_pressed {
-                        Some(KeyEvent::ShiftDown)
-                    } else {
-                        Some(KeyEvent::Down)
-                    }
-                }
-                _ => None,
-            };
+    // Keypad digits & operators have dedicated virtual key codes, the keypad Enter is
+    // the `VK_RETURN` with the enhanced key flag
+    let keypad = match key_code {
+        VK_NUMPAD0..=VK_DIVIDE => true,
+        VK_RETURN => key_event.control_key_state.has_state(ENHANCED_KEY),
+        _ => false,
+    };
+
+    if keypad {
+        Some(parsed.with_state(KeyEventState::KEYPAD))
+    } else {
+        Some(parsed)
+    }
+}
 
-            event
+fn parse_key_code_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
+    let key_code = key_event.virtual_key_code as i32;
+    let code = match key_code {
+        VK_SHIFT | VK_CONTROL | VK_MENU | VK_LWIN | VK_RWIN => {
+            KeyCode::Modifier(parse_modifier_key(key_event))
         }
-        VK_PRIOR | VK_NEXT => {
-            if key_code == VK_PRIOR {
-                Some(KeyEvent::PageUp)
-            } else if key_code == VK_NEXT {
-                Some(KeyEvent::PageDown)
+        VK_BACK => KeyCode::Backspace,
+        VK_ESCAPE => return control_key_event(b'\x1B'),
+        VK_RETURN => return control_key_event(b'\r'),
+        // F1-F24 have consecutive virtual key codes
+        VK_F1..=VK_F24 => KeyCode::F((key_code - VK_F1 + 1) as u8),
+        VK_LEFT => KeyCode::Left,
+        VK_UP => KeyCode::Up,
+        VK_RIGHT => KeyCode::Right,
+        VK_DOWN => KeyCode::Down,
+        VK_PRIOR => KeyCode::PageUp,
+        VK_NEXT => KeyCode::PageDown,
+        VK_HOME => KeyCode::Home,
+        VK_END => KeyCode::End,
+        VK_DELETE => KeyCode::Delete,
+        VK_INSERT => KeyCode::Insert,
+        VK_APPS => KeyCode::Menu,
+        VK_VOLUME_MUTE => KeyCode::Media(MediaKey::MuteVolume),
+        VK_VOLUME_DOWN => KeyCode::Media(MediaKey::LowerVolume),
+        VK_VOLUME_UP => KeyCode::Media(MediaKey::RaiseVolume),
+        VK_MEDIA_NEXT_TRACK => KeyCode::Media(MediaKey::TrackNext),
+        VK_MEDIA_PREV_TRACK => KeyCode::Media(MediaKey::TrackPrevious),
+        VK_MEDIA_STOP => KeyCode::Media(MediaKey::Stop),
+        VK_MEDIA_PLAY_PAUSE => KeyCode::Media(MediaKey::PlayPause),
+        _ => return parse_character_key_event_record(key_event),
+    };
+
+    Some(KeyEvent::new(
+        code,
+        parse_modifiers(&key_event.control_key_state),
+    ))
+}
+
+/// Maps the modifier key (`VK_SHIFT`, `VK_CONTROL`, `VK_MENU`, `VK_LWIN` or `VK_RWIN`) event
+/// to the `ModifierKey`.
+fn parse_modifier_key(key_event: &KeyEventRecord) -> ModifierKey {
+    // The right Control & Alt keys are the enhanced keys, the Shift keys differ by the scan code
+    let right = match key_event.virtual_key_code as i32 {
+        VK_SHIFT => key_event.virtual_scan_code == RIGHT_SHIFT_SCAN_CODE,
+        _ => key_event.control_key_state.has_state(ENHANCED_KEY),
+    };
+
+    match (key_event.virtual_key_code as i32, right) {
+        (VK_SHIFT, false) => ModifierKey::LeftShift,
+        (VK_SHIFT, true) => ModifierKey::RightShift,
+        (VK_CONTROL, false) => ModifierKey::LeftControl,
+        (VK_CONTROL, true) => ModifierKey::RightControl,
+        (VK_MENU, false) => ModifierKey::LeftAlt,
+        (VK_MENU, true) => ModifierKey::RightAlt,
+        (VK_LWIN, _) => ModifierKey::LeftSuper,
+        (VK_RWIN, _) => ModifierKey::RightSuper,
+        _ => unreachable!(),
+    }
+}
+
+fn parse_character_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
+    // Modifier Keys (Ctrl, Alt, Shift) Support
+    let character_raw = { (unsafe { *key_event.u_char.UnicodeChar() } as u16) };
+
+    if character_raw < 255 {
+        let character = character_raw as u8 as char;
+
+        let key_state = &key_event.control_key_state;
+        let ctrl_pressed = key_state.has_state(LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED);
+
+        if key_state.has_state(LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) {
+            // If the ALT key is held down, pressing the A key produces ALT+A, which the system does not treat as a character at all, but rather as a system command.
+            // The pressed command is stored in `virtual_key_code`.
+            let command = key_event.virtual_key_code as u8 as char;
+
+            if (command).is_alphabetic() {
+                Some(KeyEvent::new(KeyCode::Char(command), KeyModifiers::ALT))
             } else {
                 None
             }
-        }
-        VK_END | VK_HOME => {
-            if key_code == VK_HOME {
-                Some(KeyEvent::Home)
-            } else if key_code == VK_END {
-                Some(KeyEvent::End)
-            } else {
-                None
+        } else if key_state.has_state(SHIFT_PRESSED) && character == '\t' {
+            Some(KeyEvent::from(KeyCode::BackTab))
+        } else if character_raw == 0 && !ctrl_pressed {
+            // A key without a character (Caps Lock, Windows key, ...)
+            None
+        } else if character_raw < 0x20 {
+            // C0 control (Ctrl + key, Tab, ...), mapped in the same way as on UNIX
+            match control_key_event(character_raw as u8) {
+                // The Backspace key is `VK_BACK`, it's Ctrl + H
+                Some(KeyEvent {
+                    code: KeyCode::Backspace,
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => Some(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)),
+                key_event => key_event,
             }
+        } else if ctrl_pressed {
+            None
+        } else {
+            // Shift + key press, essentially the same as single key press
+            // Separating to be explicit about the Shift press.
+            Some(KeyEvent::from(KeyCode::Char(character)))
         }
-        VK_DELETE => Some(KeyEvent::Delete),
-        VK_INSERT => Some(KeyEvent::Insert),
-        _ => {
-            // Modifier Keys (Ctrl, Alt, Shift) Support
-            let character_raw = { (unsafe { *key_event.u_char.UnicodeChar() } as u16) };
-
-            if character_raw < 255 {
-                let character = character_raw as u8 as char;
-
-                let key_state = &key_event.control_key_state;
-
-                if key_state.has_state(LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) {
-                    // If the ALT key is held down, pressing the A key produces ALT+A, which the system does not treat as a character at all, but rather as a system command.
-                    // The pressed command is stored in `virtual_key_code`.
-                    let command = key_event.virtual_key_code as u8 as char;
-
-                    if (command).is_alphabetic() {
-                        Some(KeyEvent::Alt(command))
-                    } else {
-                        None
-                    }
-                } else if key_state.has_state(LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED) {
-                    match character_raw as u8 {
-                        c @ b'\x01'..=b'\x1A' => {
-                            Some(KeyEvent::Ctrl((c as u8 - 0x1 + b'a') as char))
-                        }
-                        c @ b'\x1C'..=b'\x1F' => {
-                            Some(KeyEvent::Ctrl((c as u8 - 0x1C + b'4') as char))
-                        }
-                        _ => None,
-                    }
-                } else if key_state.has_state(SHIFT_PRESSED) && character == '\t' {
-                    Some(KeyEvent::BackTab)
-                } else {
-                    if character == '\t' {
-                        Some(KeyEvent::Tab)
-                    } else {
-                        // Shift + key press, essentially the same as single key press
-                        // Separating to be explicit about the Shift press.
-                        Some(KeyEvent::Char(character))
-                    }
-                }
-            } else {
-                None
-            }
+    } else {
+        // A character over Latin-1 (IME, Unicode input, AltGr + key, ...)
+        decode_utf16_unit(character_raw, key_event.key_down)
+            .map(|character| KeyEvent::from(KeyCode::Char(character)))
+    }
+}
+
+/// Decodes the UTF-16 code unit of the key event.
+///
+/// The characters outside of the BMP (emoji, ...) are reported as two key events, the high
+/// surrogate is held until the low surrogate arrives. Unpaired surrogates are dropped.
+fn decode_utf16_unit(unit: u16, key_down: bool) -> Option<char> {
+    let mut pending = PENDING_HIGH_SURROGATES.lock().unwrap();
+    let pending = &mut pending[key_down as usize];
+
+    match unit {
+        0xD800..=0xDBFF => {
+            *pending = Some(unit);
+            None
+        }
+        0xDC00..=0xDFFF => {
+            let high = pending.take()?;
+            char::decode_utf16([high, unit].iter().cloned())
+                .next()
+                .and_then(|character| character.ok())
+        }
+        unit => {
+            *pending = None;
+            char::from_u32(u32::from(unit))
         }
     }
 }
 
+#[cfg(not(feature = "keyboard-only"))]
 fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::MouseEvent>> {
     // NOTE (@imdaveho): xterm emulation takes the digits of the coords and passes them
     // individually as bytes into a buffer; the below cxbs and cybs replicates that and
@@ -538,53 +1221,64 @@ fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::MouseEve
 
     let window_size = ScreenBuffer::current()?.info()?.terminal_window();
 
-    let xpos = event.mouse_position.x;
+    let xpos = event.mouse_position.x.max(0);
     let mut ypos = event.mouse_position.y;
 
     // The 'y' position of a mouse event is not relative to the window but absolute to screen buffer.
     // This means that when the mouse cursor is at the top left it will be x: 0, y: 2295 (e.g. y = number of cells counting from the absolute buffer height) instead of relative x: 0, y: 0 to the window.
 
-    ypos = ypos - window_size.top;
+    ypos = ypos.saturating_sub(window_size.top).max(0);
 
-    Ok(match event.event_flags {
+    let (kind, button) = match event.event_flags {
         EventFlags::PressOrRelease => {
             // Single click
             match event.button_state {
-                ButtonState::Release => Some(crate::MouseEvent::Release(xpos as u16, ypos as u16)),
+                // The console doesn't report which button was released
+                ButtonState::Release => (MouseEventKind::Release, None),
                 ButtonState::FromLeft1stButtonPressed => {
                     // left click
-                    Some(crate::MouseEvent::Press(
-                        MouseButton::Left,
-                        xpos as u16,
-                        ypos as u16,
-                    ))
+                    (MouseEventKind::Press, Some(MouseButton::Left))
                 }
                 ButtonState::RightmostButtonPressed => {
                     // right click
-                    Some(crate::MouseEvent::Press(
-                        MouseButton::Right,
-                        xpos as u16,
-                        ypos as u16,
-                    ))
+                    (MouseEventKind::Press, Some(MouseButton::Right))
                 }
                 ButtonState::FromLeft2ndButtonPressed => {
                     // middle click
-                    Some(crate::MouseEvent::Press(
-                        MouseButton::Middle,
-                        xpos as u16,
-                        ypos as u16,
-                    ))
+                    (MouseEventKind::Press, Some(MouseButton::Middle))
+                }
+                // The X buttons
+                ButtonState::FromLeft3rdButtonPressed => {
+                    (MouseEventKind::Press, Some(MouseButton::Back))
                 }
-                _ => None,
+                ButtonState::FromLeft4thButtonPressed => {
+                    (MouseEventKind::Press, Some(MouseButton::Forward))
+                }
+                _ => return Ok(None),
             }
         }
         EventFlags::MouseMoved => {
             // Click + Move
             // NOTE (@imdaveho) only register when mouse is not released
-            if event.button_state != ButtonState::Release {
-                Some(crate::MouseEvent::Hold(xpos as u16, ypos as u16))
-            } else {
-                None
+            match event.button_state {
+                ButtonState::Release if MOUSE_ANY_MOTION.load(Ordering::SeqCst) => {
+                    (MouseEventKind::Moved, None)
+                }
+                ButtonState::Release => return Ok(None),
+                _ if !MOUSE_DRAG.load(Ordering::SeqCst) => return Ok(None),
+                ButtonState::RightmostButtonPressed => {
+                    (MouseEventKind::Drag, Some(MouseButton::Right))
+                }
+                ButtonState::FromLeft2ndButtonPressed => {
+                    (MouseEventKind::Drag, Some(MouseButton::Middle))
+                }
+                ButtonState::FromLeft3rdButtonPressed => {
+                    (MouseEventKind::Drag, Some(MouseButton::Back))
+                }
+                ButtonState::FromLeft4thButtonPressed => {
+                    (MouseEventKind::Drag, Some(MouseButton::Forward))
+                }
+                _ => (MouseEventKind::Drag, Some(MouseButton::Left)),
             }
         }
         EventFlags::MouseWheeled => {
@@ -592,21 +1286,28 @@ fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::MouseEve
             // NOTE (@imdaveho) from https://docs.microsoft.com/en-us/windows/console/mouse-event-record-str
             // if `button_state` is negative then the wheel was rotated backward, toward the user.
             if event.button_state != ButtonState::Negative {
-                Some(crate::MouseEvent::Press(
-                    MouseButton::WheelUp,
-                    xpos as u16,
-                    ypos as u16,
-                ))
+                (MouseEventKind::Press, Some(MouseButton::WheelUp))
             } else {
-                Some(crate::MouseEvent::Press(
-                    MouseButton::WheelDown,
-                    xpos as u16,
-                    ypos as u16,
-                ))
+                (MouseEventKind::Press, Some(MouseButton::WheelDown))
             }
         }
-        EventFlags::DoubleClick => None, // NOTE (@imdaveho): double click not supported by unix terminals
-        EventFlags::MouseHwheeled => None, // NOTE (@imdaveho): horizontal scroll not supported by unix terminals
-                                           // TODO: Handle Ctrl + Mouse, Alt + Mouse, etc.
-    })
+        EventFlags::DoubleClick => {
+            // The second press of the double click
+            let button = match event.button_state {
+                ButtonState::FromLeft1stButtonPressed => MouseButton::Left,
+                ButtonState::RightmostButtonPressed => MouseButton::Right,
+                ButtonState::FromLeft2ndButtonPressed => MouseButton::Middle,
+                ButtonState::FromLeft3rdButtonPressed => MouseButton::Back,
+                ButtonState::FromLeft4thButtonPressed => MouseButton::Forward,
+                _ => return Ok(None),
+            };
+            (MouseEventKind::DoubleClick, Some(button))
+        }
+        EventFlags::MouseHwheeled => return Ok(None), // NOTE (@imdaveho): horizontal scroll not supported by unix terminals
+    };
+
+    Ok(Some(crate::MouseEvent {
+        modifiers: parse_modifiers(&event.control_key_state),
+        ..crate::MouseEvent::new(kind, button, xpos as u16, ypos as u16)
+    }))
 }
@@ -0,0 +1,6 @@
+//! Conversions between the `crossterm_input` events and the events of other crates.
+
+#[cfg(feature = "interop-crossterm")]
+mod crossterm;
+#[cfg(feature = "interop-termwiz")]
+mod termwiz;
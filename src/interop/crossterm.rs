@@ -0,0 +1,394 @@
+//! Conversions between the `crossterm_input` events and the unified `crossterm` events.
+//!
+//! Both crates represent keys as a key code plus modifiers. The `crossterm` crate reports
+//! Shift + Tab as the `BackTab` key with the Shift modifier, the `crossterm_input` crate as the
+//! `KeyCode::BackTab` without modifiers.
+
+use ::crossterm::event as ct;
+
+use crate::{InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+/// Converts an `InputEvent` into a possible `crossterm` `Event`.
+///
+/// `InputEvent::Unknown`, `InputEvent::Unsupported`, `InputEvent::Lagged`,
+/// `InputEvent::Disconnected`, key releases, the menu & media keys and unknown mouse events have no `crossterm` counterpart and are converted into `None`.
+impl From<InputEvent> for Option<ct::Event> {
+    fn from(event: InputEvent) -> Self {
+        match event {
+            InputEvent::Keyboard(KeyEvent {
+                kind: KeyEventKind::Release,
+                ..
+            }) => None,
+            InputEvent::Keyboard(KeyEvent {
+                code: KeyCode::Menu,
+                ..
+            })
+            | InputEvent::Keyboard(KeyEvent {
+                code: KeyCode::Media(_),
+                ..
+            })
+            | InputEvent::Keyboard(KeyEvent {
+                code: KeyCode::Modifier(_),
+                ..
+            }) => None,
+            InputEvent::Keyboard(key_event) => Some(ct::Event::Key(key_event.into())),
+            #[cfg(not(feature = "keyboard-only"))]
+            InputEvent::Mouse(mouse_event) => {
+                Option::<ct::MouseEvent>::from(mouse_event).map(ct::Event::Mouse)
+            }
+            InputEvent::Resize(columns, rows) => Some(ct::Event::Resize(columns, rows)),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a `crossterm` `Event` into an `InputEvent`.
+impl From<ct::Event> for InputEvent {
+    fn from(event: ct::Event) -> Self {
+        match event {
+            ct::Event::Key(key_event) => key_event.into(),
+            #[cfg(not(feature = "keyboard-only"))]
+            ct::Event::Mouse(mouse_event) => InputEvent::Mouse(mouse_event.into()),
+            // The mouse events aren't delivered in the keyboard-only build
+            #[cfg(feature = "keyboard-only")]
+            ct::Event::Mouse(_) => InputEvent::Unknown,
+            ct::Event::Resize(columns, rows) => InputEvent::Resize(columns, rows),
+        }
+    }
+}
+
+impl From<KeyEvent> for ct::KeyEvent {
+    fn from(key_event: KeyEvent) -> Self {
+        let mut modifiers = ct::KeyModifiers::from(key_event.modifiers);
+
+        let code = match key_event.code {
+            KeyCode::Backspace => ct::KeyCode::Backspace,
+            KeyCode::Enter => ct::KeyCode::Enter,
+            KeyCode::Left => ct::KeyCode::Left,
+            KeyCode::Right => ct::KeyCode::Right,
+            KeyCode::Up => ct::KeyCode::Up,
+            KeyCode::Down => ct::KeyCode::Down,
+            KeyCode::Home => ct::KeyCode::Home,
+            KeyCode::End => ct::KeyCode::End,
+            KeyCode::PageUp => ct::KeyCode::PageUp,
+            KeyCode::PageDown => ct::KeyCode::PageDown,
+            KeyCode::Tab => ct::KeyCode::Tab,
+            KeyCode::BackTab => {
+                modifiers |= ct::KeyModifiers::SHIFT;
+                ct::KeyCode::BackTab
+            }
+            KeyCode::Delete => ct::KeyCode::Delete,
+            KeyCode::Insert => ct::KeyCode::Insert,
+            KeyCode::F(n) => ct::KeyCode::F(n),
+            KeyCode::Char(c) => ct::KeyCode::Char(c),
+            KeyCode::Null => ct::KeyCode::Null,
+            KeyCode::Esc => ct::KeyCode::Esc,
+            // No counterpart, filtered out by the `InputEvent` conversion
+            KeyCode::Menu | KeyCode::Media(_) | KeyCode::Modifier(_) => ct::KeyCode::Null,
+        };
+
+        ct::KeyEvent::new(code, modifiers)
+    }
+}
+
+/// Converts a `crossterm` `KeyEvent` into a `KeyEvent`.
+///
+/// The Shift modifier of the characters is dropped, it's already reflected in the character
+/// itself.
+impl From<ct::KeyEvent> for KeyEvent {
+    fn from(key_event: ct::KeyEvent) -> Self {
+        let modifiers = KeyModifiers::from(key_event.modifiers);
+
+        let code = match key_event.code {
+            ct::KeyCode::Char(c) => {
+                return KeyEvent::new(KeyCode::Char(c), modifiers - KeyModifiers::SHIFT)
+            }
+            ct::KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                return KeyEvent::new(KeyCode::BackTab, modifiers - KeyModifiers::SHIFT)
+            }
+            ct::KeyCode::BackTab => {
+                return KeyEvent::new(KeyCode::BackTab, modifiers - KeyModifiers::SHIFT)
+            }
+            ct::KeyCode::Backspace => KeyCode::Backspace,
+            ct::KeyCode::Enter => KeyCode::Enter,
+            ct::KeyCode::Left => KeyCode::Left,
+            ct::KeyCode::Right => KeyCode::Right,
+            ct::KeyCode::Up => KeyCode::Up,
+            ct::KeyCode::Down => KeyCode::Down,
+            ct::KeyCode::Home => KeyCode::Home,
+            ct::KeyCode::End => KeyCode::End,
+            ct::KeyCode::PageUp => KeyCode::PageUp,
+            ct::KeyCode::PageDown => KeyCode::PageDown,
+            ct::KeyCode::Tab => KeyCode::Tab,
+            ct::KeyCode::Delete => KeyCode::Delete,
+            ct::KeyCode::Insert => KeyCode::Insert,
+            ct::KeyCode::F(n) => KeyCode::F(n),
+            ct::KeyCode::Null => KeyCode::Null,
+            ct::KeyCode::Esc => KeyCode::Esc,
+        };
+
+        KeyEvent::new(code, modifiers)
+    }
+}
+
+/// Converts a `crossterm` `KeyEvent` into an `InputEvent`.
+impl From<ct::KeyEvent> for InputEvent {
+    fn from(key_event: ct::KeyEvent) -> Self {
+        InputEvent::Keyboard(key_event.into())
+    }
+}
+
+/// The Super, Hyper & Meta modifiers aren't supported by `crossterm`, they're dropped.
+impl From<KeyModifiers> for ct::KeyModifiers {
+    fn from(modifiers: KeyModifiers) -> Self {
+        let mut ct_modifiers = ct::KeyModifiers::empty();
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            ct_modifiers |= ct::KeyModifiers::SHIFT;
+        }
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            ct_modifiers |= ct::KeyModifiers::CONTROL;
+        }
+        if modifiers.contains(KeyModifiers::ALT) {
+            ct_modifiers |= ct::KeyModifiers::ALT;
+        }
+        ct_modifiers
+    }
+}
+
+impl From<ct::KeyModifiers> for KeyModifiers {
+    fn from(ct_modifiers: ct::KeyModifiers) -> Self {
+        let mut modifiers = KeyModifiers::empty();
+        if ct_modifiers.contains(ct::KeyModifiers::SHIFT) {
+            modifiers |= KeyModifiers::SHIFT;
+        }
+        if ct_modifiers.contains(ct::KeyModifiers::CONTROL) {
+            modifiers |= KeyModifiers::CONTROL;
+        }
+        if ct_modifiers.contains(ct::KeyModifiers::ALT) {
+            modifiers |= KeyModifiers::ALT;
+        }
+        modifiers
+    }
+}
+
+/// Converts a `MouseEvent` into a possible `crossterm` `MouseEvent`.
+///
+/// The release of an unknown button is reported as the left button release in the same way
+/// as the `crossterm` does it when the terminal doesn't report the button. The mouse motion
+/// without a pressed button and unknown mouse events are converted into `None`.
+#[cfg(not(feature = "keyboard-only"))]
+impl From<MouseEvent> for Option<ct::MouseEvent> {
+    fn from(mouse_event: MouseEvent) -> Self {
+        let MouseEvent {
+            kind,
+            button,
+            column: x,
+            row: y,
+            modifiers,
+        } = mouse_event;
+        let modifiers = modifiers.into();
+
+        let ct_button = match button {
+            Some(MouseButton::WheelUp) if kind == MouseEventKind::Press => {
+                return Some(ct::MouseEvent::ScrollUp(x, y, modifiers))
+            }
+            Some(MouseButton::WheelDown) if kind == MouseEventKind::Press => {
+                return Some(ct::MouseEvent::ScrollDown(x, y, modifiers))
+            }
+            Some(MouseButton::Right) => ct::MouseButton::Right,
+            Some(MouseButton::Middle) => ct::MouseButton::Middle,
+            // No counterpart
+            Some(MouseButton::Back) | Some(MouseButton::Forward) | Some(MouseButton::Other(_)) => {
+                return None
+            }
+            _ => ct::MouseButton::Left,
+        };
+
+        match kind {
+            MouseEventKind::Press | MouseEventKind::DoubleClick => {
+                Some(ct::MouseEvent::Down(ct_button, x, y, modifiers))
+            }
+            MouseEventKind::Release => Some(ct::MouseEvent::Up(ct_button, x, y, modifiers)),
+            MouseEventKind::Drag => Some(ct::MouseEvent::Drag(ct_button, x, y, modifiers)),
+            MouseEventKind::Moved | MouseEventKind::Unknown => None,
+        }
+    }
+}
+
+/// Converts a `crossterm` `MouseEvent` into a `MouseEvent`.
+#[cfg(not(feature = "keyboard-only"))]
+impl From<ct::MouseEvent> for MouseEvent {
+    fn from(mouse_event: ct::MouseEvent) -> Self {
+        let (kind, button, x, y, modifiers) = match mouse_event {
+            ct::MouseEvent::Down(button, x, y, modifiers) => {
+                (MouseEventKind::Press, button.into(), x, y, modifiers)
+            }
+            ct::MouseEvent::Up(button, x, y, modifiers) => {
+                (MouseEventKind::Release, button.into(), x, y, modifiers)
+            }
+            ct::MouseEvent::Drag(button, x, y, modifiers) => {
+                (MouseEventKind::Drag, button.into(), x, y, modifiers)
+            }
+            ct::MouseEvent::ScrollUp(x, y, modifiers) => {
+                (MouseEventKind::Press, MouseButton::WheelUp, x, y, modifiers)
+            }
+            ct::MouseEvent::ScrollDown(x, y, modifiers) => (
+                MouseEventKind::Press,
+                MouseButton::WheelDown,
+                x,
+                y,
+                modifiers,
+            ),
+        };
+
+        MouseEvent {
+            modifiers: modifiers.into(),
+            ..MouseEvent::new(kind, Some(button), x, y)
+        }
+    }
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+impl From<ct::MouseButton> for MouseButton {
+    fn from(button: ct::MouseButton) -> Self {
+        match button {
+            ct::MouseButton::Left => MouseButton::Left,
+            ct::MouseButton::Right => MouseButton::Right,
+            ct::MouseButton::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MediaKey, ModifierKey};
+
+    #[test]
+    fn test_key_event_round_trip() {
+        let events = vec![
+            KeyEvent::from(KeyCode::Char('a')),
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::BackTab),
+            KeyEvent::from(KeyCode::F(5)),
+            KeyEvent::from(KeyCode::Esc),
+            KeyEvent::new(
+                KeyCode::Char('a'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            ),
+            KeyEvent::new(KeyCode::Delete, KeyModifiers::SHIFT),
+        ];
+
+        for event in events {
+            let ct_event: ct::KeyEvent = event.clone().into();
+            assert_eq!(InputEvent::from(ct_event), InputEvent::Keyboard(event));
+        }
+    }
+
+    #[test]
+    fn test_shifted_key_event() {
+        let ct_event = ct::KeyEvent::new(ct::KeyCode::Char('A'), ct::KeyModifiers::SHIFT);
+        assert_eq!(
+            InputEvent::from(ct_event),
+            InputEvent::Keyboard(KeyCode::Char('A').into())
+        );
+
+        let ct_event = ct::KeyEvent::new(ct::KeyCode::Tab, ct::KeyModifiers::SHIFT);
+        assert_eq!(
+            InputEvent::from(ct_event),
+            InputEvent::Keyboard(KeyCode::BackTab.into())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_mouse_event() {
+        assert_eq!(
+            Option::<ct::Event>::from(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::WheelDown),
+                1,
+                2
+            ))),
+            Some(ct::Event::Mouse(ct::MouseEvent::ScrollDown(
+                1,
+                2,
+                ct::KeyModifiers::NONE
+            )))
+        );
+        assert_eq!(
+            InputEvent::from(ct::Event::Mouse(ct::MouseEvent::Down(
+                ct::MouseButton::Right,
+                3,
+                4,
+                ct::KeyModifiers::CONTROL
+            ))),
+            InputEvent::Mouse(MouseEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Right), 3, 4)
+            })
+        );
+        assert_eq!(
+            Option::<ct::MouseEvent>::from(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Middle),
+                1,
+                2
+            )),
+            Some(ct::MouseEvent::Up(
+                ct::MouseButton::Middle,
+                1,
+                2,
+                ct::KeyModifiers::NONE
+            ))
+        );
+        assert_eq!(Option::<ct::Event>::from(InputEvent::Unknown), None);
+    }
+
+    #[test]
+    fn test_key_release_event() {
+        assert_eq!(
+            Option::<ct::Event>::from(InputEvent::Keyboard(KeyEvent::new_with_kind(
+                KeyCode::Up,
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            ))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_media_key_event() {
+        assert_eq!(
+            Option::<ct::Event>::from(InputEvent::Keyboard(KeyCode::Media(MediaKey::Play).into())),
+            None
+        );
+        assert_eq!(
+            Option::<ct::Event>::from(InputEvent::Keyboard(KeyCode::Menu.into())),
+            None
+        );
+        assert_eq!(
+            Option::<ct::Event>::from(InputEvent::Keyboard(
+                KeyCode::Modifier(ModifierKey::LeftControl).into()
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resize_event() {
+        assert_eq!(
+            Option::<ct::Event>::from(InputEvent::Resize(80, 24)),
+            Some(ct::Event::Resize(80, 24))
+        );
+        assert_eq!(
+            InputEvent::from(ct::Event::Resize(80, 24)),
+            InputEvent::Resize(80, 24)
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Conversions between the `crossterm_input` events and the `termwiz` events.
+//!
+//! The `termwiz` crate represents keys as a key code plus modifiers and mouse events as a set
+//! of currently pressed buttons. Events which can't be represented on the other side are
+//! converted into the `None` or the `InputEvent::Unknown`.
+//!
+//! Mouse coordinates are passed through unchanged.
+
+use ::termwiz::input as tw;
+
+use crate::{InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MediaKey, ModifierKey};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+/// Converts an `InputEvent` into a possible `termwiz` `InputEvent`.
+///
+/// `InputEvent::Unknown`, `InputEvent::Unsupported`, `InputEvent::Lagged`,
+/// `InputEvent::Disconnected`, key releases, some media keys (play, pause, ...) and unknown mouse events have no `termwiz` counterpart and are converted into `None`.
+impl From<InputEvent> for Option<tw::InputEvent> {
+    fn from(event: InputEvent) -> Self {
+        match event {
+            InputEvent::Keyboard(KeyEvent {
+                kind: KeyEventKind::Release,
+                ..
+            }) => None,
+            InputEvent::Keyboard(KeyEvent {
+                code: KeyCode::Media(media_key),
+                ..
+            }) if tw_media_key(media_key).is_none() => None,
+            InputEvent::Keyboard(KeyEvent {
+                code: KeyCode::Modifier(modifier_key),
+                ..
+            }) if tw_modifier_key(modifier_key).is_none() => None,
+            InputEvent::Keyboard(key_event) => Some(tw::InputEvent::Key(key_event.into())),
+            #[cfg(not(feature = "keyboard-only"))]
+            InputEvent::Mouse(mouse_event) => {
+                Option::<tw::MouseEvent>::from(mouse_event).map(tw::InputEvent::Mouse)
+            }
+            InputEvent::Paste(text) => Some(tw::InputEvent::Paste(text)),
+            InputEvent::Resize(columns, rows) => Some(tw::InputEvent::Resized {
+                cols: usize::from(columns),
+                rows: usize::from(rows),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a `termwiz` `InputEvent` into an `InputEvent`.
+///
+/// Events which can't be represented by the `InputEvent` are converted into the
+/// `InputEvent::Unknown`.
+impl From<tw::InputEvent> for InputEvent {
+    fn from(event: tw::InputEvent) -> Self {
+        match event {
+            tw::InputEvent::Key(key_event) => key_event.into(),
+            #[cfg(not(feature = "keyboard-only"))]
+            tw::InputEvent::Mouse(mouse_event) => InputEvent::Mouse(mouse_event.into()),
+            tw::InputEvent::Paste(text) => InputEvent::Paste(text),
+            // Sizes out of the `u16` range are clamped
+            tw::InputEvent::Resized { cols, rows } => InputEvent::Resize(
+                cols.min(usize::from(u16::MAX)) as u16,
+                rows.min(usize::from(u16::MAX)) as u16,
+            ),
+            _ => InputEvent::Unknown,
+        }
+    }
+}
+
+impl From<KeyEvent> for tw::KeyEvent {
+    fn from(key_event: KeyEvent) -> Self {
+        let mut modifiers = tw::Modifiers::from(key_event.modifiers);
+
+        let key = match key_event.code {
+            KeyCode::Backspace => tw::KeyCode::Backspace,
+            KeyCode::Enter => tw::KeyCode::Enter,
+            KeyCode::Left => tw::KeyCode::LeftArrow,
+            KeyCode::Right => tw::KeyCode::RightArrow,
+            KeyCode::Up => tw::KeyCode::UpArrow,
+            KeyCode::Down => tw::KeyCode::DownArrow,
+            KeyCode::Home => tw::KeyCode::Home,
+            KeyCode::End => tw::KeyCode::End,
+            KeyCode::PageUp => tw::KeyCode::PageUp,
+            KeyCode::PageDown => tw::KeyCode::PageDown,
+            KeyCode::Tab => tw::KeyCode::Tab,
+            KeyCode::BackTab => {
+                modifiers |= tw::Modifiers::SHIFT;
+                tw::KeyCode::Tab
+            }
+            KeyCode::Delete => tw::KeyCode::Delete,
+            KeyCode::Insert => tw::KeyCode::Insert,
+            KeyCode::F(n) => tw::KeyCode::Function(n),
+            KeyCode::Char(c) => tw::KeyCode::Char(c),
+            KeyCode::Null => tw::KeyCode::Char('\0'),
+            KeyCode::Esc => tw::KeyCode::Escape,
+            KeyCode::Menu => tw::KeyCode::Applications,
+            // No counterpart, filtered out by the `InputEvent` conversion
+            KeyCode::Media(media_key) => tw_media_key(media_key).unwrap_or(tw::KeyCode::Char('\0')),
+            KeyCode::Modifier(modifier_key) => {
+                tw_modifier_key(modifier_key).unwrap_or(tw::KeyCode::Char('\0'))
+            }
+        };
+
+        tw::KeyEvent { key, modifiers }
+    }
+}
+
+/// Converts a `termwiz` `KeyEvent` into an `InputEvent`.
+///
+/// Keys which can't be represented by the `KeyEvent` are converted into the
+/// `InputEvent::Unknown`.
+impl From<tw::KeyEvent> for InputEvent {
+    fn from(key_event: tw::KeyEvent) -> Self {
+        let shift = tw::Modifiers::SHIFT;
+        let ctrl = tw::Modifiers::CTRL;
+        let alt = tw::Modifiers::ALT;
+        // Ignore left/right specific & lock state modifiers
+        let tw_modifiers = key_event.modifiers & (shift | ctrl | alt | tw::Modifiers::SUPER);
+        let modifiers = KeyModifiers::from(tw_modifiers);
+
+        let code = match key_event.key {
+            tw::KeyCode::Char('\0') => KeyCode::Null,
+            // Shift is already reflected in the character itself
+            tw::KeyCode::Char(c) => {
+                return InputEvent::Keyboard(KeyEvent::new(
+                    KeyCode::Char(c),
+                    modifiers - KeyModifiers::SHIFT,
+                ))
+            }
+            tw::KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                return InputEvent::Keyboard(KeyEvent::new(
+                    KeyCode::BackTab,
+                    modifiers - KeyModifiers::SHIFT,
+                ))
+            }
+            tw::KeyCode::Backspace => KeyCode::Backspace,
+            tw::KeyCode::Enter => KeyCode::Enter,
+            tw::KeyCode::LeftArrow => KeyCode::Left,
+            tw::KeyCode::RightArrow => KeyCode::Right,
+            tw::KeyCode::UpArrow => KeyCode::Up,
+            tw::KeyCode::DownArrow => KeyCode::Down,
+            tw::KeyCode::Home => KeyCode::Home,
+            tw::KeyCode::End => KeyCode::End,
+            tw::KeyCode::PageUp => KeyCode::PageUp,
+            tw::KeyCode::PageDown => KeyCode::PageDown,
+            tw::KeyCode::Tab => KeyCode::Tab,
+            tw::KeyCode::Delete => KeyCode::Delete,
+            tw::KeyCode::Insert => KeyCode::Insert,
+            tw::KeyCode::Function(n) => KeyCode::F(n),
+            tw::KeyCode::Escape => KeyCode::Esc,
+            tw::KeyCode::Applications => KeyCode::Menu,
+            tw::KeyCode::VolumeMute => KeyCode::Media(MediaKey::MuteVolume),
+            tw::KeyCode::VolumeDown => KeyCode::Media(MediaKey::LowerVolume),
+            tw::KeyCode::VolumeUp => KeyCode::Media(MediaKey::RaiseVolume),
+            tw::KeyCode::MediaNextTrack => KeyCode::Media(MediaKey::TrackNext),
+            tw::KeyCode::MediaPrevTrack => KeyCode::Media(MediaKey::TrackPrevious),
+            tw::KeyCode::MediaStop => KeyCode::Media(MediaKey::Stop),
+            tw::KeyCode::MediaPlayPause => KeyCode::Media(MediaKey::PlayPause),
+            tw::KeyCode::LeftShift => KeyCode::Modifier(ModifierKey::LeftShift),
+            tw::KeyCode::RightShift => KeyCode::Modifier(ModifierKey::RightShift),
+            tw::KeyCode::LeftControl => KeyCode::Modifier(ModifierKey::LeftControl),
+            tw::KeyCode::RightControl => KeyCode::Modifier(ModifierKey::RightControl),
+            tw::KeyCode::LeftAlt => KeyCode::Modifier(ModifierKey::LeftAlt),
+            tw::KeyCode::RightAlt => KeyCode::Modifier(ModifierKey::RightAlt),
+            tw::KeyCode::LeftWindows => KeyCode::Modifier(ModifierKey::LeftSuper),
+            tw::KeyCode::RightWindows => KeyCode::Modifier(ModifierKey::RightSuper),
+            _ => return InputEvent::Unknown,
+        };
+
+        InputEvent::Keyboard(KeyEvent::new(code, modifiers))
+    }
+}
+
+/// Maps the `media_key` to the `termwiz` key code (if there's one).
+fn tw_media_key(media_key: MediaKey) -> Option<tw::KeyCode> {
+    let key = match media_key {
+        MediaKey::MuteVolume => tw::KeyCode::VolumeMute,
+        MediaKey::LowerVolume => tw::KeyCode::VolumeDown,
+        MediaKey::RaiseVolume => tw::KeyCode::VolumeUp,
+        MediaKey::TrackNext => tw::KeyCode::MediaNextTrack,
+        MediaKey::TrackPrevious => tw::KeyCode::MediaPrevTrack,
+        MediaKey::Stop => tw::KeyCode::MediaStop,
+        MediaKey::PlayPause => tw::KeyCode::MediaPlayPause,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Maps the `modifier_key` to the `termwiz` key code (if there's one).
+fn tw_modifier_key(modifier_key: ModifierKey) -> Option<tw::KeyCode> {
+    let key = match modifier_key {
+        ModifierKey::LeftShift => tw::KeyCode::LeftShift,
+        ModifierKey::RightShift => tw::KeyCode::RightShift,
+        ModifierKey::LeftControl => tw::KeyCode::LeftControl,
+        ModifierKey::RightControl => tw::KeyCode::RightControl,
+        ModifierKey::LeftAlt => tw::KeyCode::LeftAlt,
+        ModifierKey::RightAlt => tw::KeyCode::RightAlt,
+        ModifierKey::LeftSuper => tw::KeyCode::LeftWindows,
+        ModifierKey::RightSuper => tw::KeyCode::RightWindows,
+        _ => return None,
+    };
+    Some(key)
+}
+
+impl From<KeyModifiers> for tw::Modifiers {
+    fn from(modifiers: KeyModifiers) -> Self {
+        let mut tw_modifiers = tw::Modifiers::NONE;
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            tw_modifiers |= tw::Modifiers::SHIFT;
+        }
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            tw_modifiers |= tw::Modifiers::CTRL;
+        }
+        if modifiers.contains(KeyModifiers::ALT) {
+            tw_modifiers |= tw::Modifiers::ALT;
+        }
+        if modifiers.contains(KeyModifiers::SUPER) {
+            tw_modifiers |= tw::Modifiers::SUPER;
+        }
+        tw_modifiers
+    }
+}
+
+impl From<tw::Modifiers> for KeyModifiers {
+    fn from(tw_modifiers: tw::Modifiers) -> Self {
+        let mut modifiers = KeyModifiers::empty();
+        if tw_modifiers.contains(tw::Modifiers::SHIFT) {
+            modifiers |= KeyModifiers::SHIFT;
+        }
+        if tw_modifiers.contains(tw::Modifiers::CTRL) {
+            modifiers |= KeyModifiers::CONTROL;
+        }
+        if tw_modifiers.contains(tw::Modifiers::ALT) {
+            modifiers |= KeyModifiers::ALT;
+        }
+        if tw_modifiers.contains(tw::Modifiers::SUPER) {
+            modifiers |= KeyModifiers::SUPER;
+        }
+        modifiers
+    }
+}
+
+/// Converts a `MouseEvent` into a possible `termwiz` `MouseEvent`.
+///
+/// The `termwiz` mouse event holds a set of currently pressed buttons. The press & drag are
+/// reported with the button pressed, the release & motion with no buttons.
+#[cfg(not(feature = "keyboard-only"))]
+impl From<MouseEvent> for Option<tw::MouseEvent> {
+    fn from(mouse_event: MouseEvent) -> Self {
+        let mouse_buttons = match (mouse_event.kind, mouse_event.button) {
+            (MouseEventKind::Unknown, _) => return None,
+            (MouseEventKind::Release, _) | (MouseEventKind::Moved, _) | (_, None) => {
+                tw::MouseButtons::NONE
+            }
+            (_, Some(MouseButton::Left)) => tw::MouseButtons::LEFT,
+            (_, Some(MouseButton::Right)) => tw::MouseButtons::RIGHT,
+            (_, Some(MouseButton::Middle)) => tw::MouseButtons::MIDDLE,
+            (_, Some(MouseButton::WheelUp)) => {
+                tw::MouseButtons::VERT_WHEEL | tw::MouseButtons::WHEEL_POSITIVE
+            }
+            (_, Some(MouseButton::WheelDown)) => tw::MouseButtons::VERT_WHEEL,
+            // No counterpart
+            (_, Some(MouseButton::Back))
+            | (_, Some(MouseButton::Forward))
+            | (_, Some(MouseButton::Other(_))) => return None,
+        };
+
+        Some(tw::MouseEvent {
+            x: mouse_event.column,
+            y: mouse_event.row,
+            mouse_buttons,
+            modifiers: mouse_event.modifiers.into(),
+        })
+    }
+}
+
+/// Converts a `termwiz` `MouseEvent` into a `MouseEvent`.
+///
+/// The `termwiz` doesn't distinguish a press from a drag, both are converted into the
+/// `MouseEventKind::Press`. No pressed buttons are converted into the `MouseEventKind::Release`
+/// without a button.
+#[cfg(not(feature = "keyboard-only"))]
+impl From<tw::MouseEvent> for MouseEvent {
+    fn from(mouse_event: tw::MouseEvent) -> Self {
+        let buttons = mouse_event.mouse_buttons;
+
+        let (kind, button) = if buttons.contains(tw::MouseButtons::VERT_WHEEL) {
+            if buttons.contains(tw::MouseButtons::WHEEL_POSITIVE) {
+                (MouseEventKind::Press, Some(MouseButton::WheelUp))
+            } else {
+                (MouseEventKind::Press, Some(MouseButton::WheelDown))
+            }
+        } else if buttons.contains(tw::MouseButtons::LEFT) {
+            (MouseEventKind::Press, Some(MouseButton::Left))
+        } else if buttons.contains(tw::MouseButtons::RIGHT) {
+            (MouseEventKind::Press, Some(MouseButton::Right))
+        } else if buttons.contains(tw::MouseButtons::MIDDLE) {
+            (MouseEventKind::Press, Some(MouseButton::Middle))
+        } else if buttons.is_empty() {
+            (MouseEventKind::Release, None)
+        } else {
+            (MouseEventKind::Unknown, None)
+        };
+
+        MouseEvent {
+            modifiers: mouse_event.modifiers.into(),
+            ..MouseEvent::new(kind, button, mouse_event.x, mouse_event.y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_event_round_trip() {
+        let events = vec![
+            KeyEvent::from(KeyCode::Char('a')),
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::SUPER),
+            KeyEvent::new(KeyCode::Down, KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::BackTab),
+            KeyEvent::from(KeyCode::F(12)),
+            KeyEvent::from(KeyCode::Null),
+            KeyEvent::from(KeyCode::Esc),
+            KeyEvent::new(
+                KeyCode::Char('a'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            ),
+            KeyEvent::new(KeyCode::Home, KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Menu),
+            KeyEvent::from(KeyCode::Media(MediaKey::PlayPause)),
+            KeyEvent::from(KeyCode::Modifier(ModifierKey::RightControl)),
+        ];
+
+        for event in events {
+            let tw_event: tw::KeyEvent = event.clone().into();
+            assert_eq!(InputEvent::from(tw_event), InputEvent::Keyboard(event));
+        }
+    }
+
+    #[test]
+    fn test_unrepresentable_key_event() {
+        let tw_event = tw::KeyEvent {
+            key: tw::KeyCode::Numpad5,
+            modifiers: tw::Modifiers::NONE,
+        };
+        assert_eq!(InputEvent::from(tw_event), InputEvent::Unknown);
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_mouse_event_round_trip() {
+        let events = vec![
+            MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), 1, 2),
+            MouseEvent::new(MouseEventKind::Press, Some(MouseButton::WheelUp), 3, 4),
+            MouseEvent::new(MouseEventKind::Press, Some(MouseButton::WheelDown), 5, 6),
+            MouseEvent::new(MouseEventKind::Release, None, 7, 8),
+            MouseEvent {
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Right), 9, 10)
+            },
+        ];
+
+        for event in events {
+            let tw_event = Option::<tw::MouseEvent>::from(event).unwrap();
+            assert_eq!(MouseEvent::from(tw_event), event);
+        }
+    }
+
+    #[test]
+    fn test_unrepresentable_media_key() {
+        assert_eq!(
+            Option::<tw::InputEvent>::from(InputEvent::Keyboard(
+                KeyCode::Media(MediaKey::Record).into()
+            )),
+            None
+        );
+        assert_eq!(
+            Option::<tw::InputEvent>::from(InputEvent::Keyboard(
+                KeyCode::Modifier(ModifierKey::LeftHyper).into()
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_key_release_event() {
+        assert_eq!(
+            Option::<tw::InputEvent>::from(InputEvent::Keyboard(KeyEvent::new_with_kind(
+                KeyCode::Up,
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            ))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resize_event() {
+        let tw_event = Option::<tw::InputEvent>::from(InputEvent::Resize(80, 24)).unwrap();
+        assert_eq!(InputEvent::from(tw_event), InputEvent::Resize(80, 24));
+        assert_eq!(
+            InputEvent::from(tw::InputEvent::Resized {
+                cols: 100_000,
+                rows: 24
+            }),
+            InputEvent::Resize(u16::MAX, 24)
+        );
+    }
+}
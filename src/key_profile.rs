@@ -0,0 +1,213 @@
+//! Key normalization profiles applied to all the produced key events.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A key event normalization function.
+type Normalize = dyn Fn(KeyEvent) -> Option<KeyEvent> + Send + Sync;
+
+/// Represents a key normalization profile.
+///
+/// The profile is applied to all the key events before they're delivered to the readers, so
+/// all the consumers across a codebase see the same canonical form. It's set with the
+/// [`InputConfig::with_key_profile`](struct.InputConfig.html#method.with_key_profile) method.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{input, KeyCode, KeyEvent, KeyModifiers, KeyProfile};
+///
+/// let input = input();
+/// // Treat Ctrl + Q as Esc, everything else stays canonical
+/// let profile = KeyProfile::custom(|key_event| match key_event {
+///     KeyEvent {
+///         code: KeyCode::Char('q'),
+///         modifiers: KeyModifiers::CONTROL,
+///         kind,
+///         ..
+///     } => Some(KeyEvent::new_with_kind(KeyCode::Esc, KeyModifiers::NONE, kind)),
+///     key_event => KeyProfile::Canonical.normalize(key_event),
+/// });
+/// input.set_config(input.config().with_key_profile(profile));
+/// ```
+#[derive(Clone)]
+pub enum KeyProfile {
+    /// Key events are delivered as they were parsed.
+    Raw,
+    /// Key combinations with a dedicated key are delivered as the dedicated key.
+    ///
+    /// * Ctrl + `KeyCode::Char('m')`, Ctrl + `KeyCode::Char('j')`, `KeyCode::Char('\r')` and
+    ///   `KeyCode::Char('\n')` as the `KeyCode::Enter`
+    /// * Ctrl + `KeyCode::Char('i')` and `KeyCode::Char('\t')` as the `KeyCode::Tab`
+    /// * Ctrl + `KeyCode::Char('h')` and `KeyCode::Char('\x7F')` as the `KeyCode::Backspace`
+    /// * Ctrl + `KeyCode::Char('3')` and `KeyCode::Char('\x1B')` as the `KeyCode::Esc`
+    /// * Ctrl + `KeyCode::Char(' ')` and `KeyCode::Char('\0')` as the `KeyCode::Null`
+    /// * Ctrl + an uppercase letter as Ctrl + a lowercase letter
+    Canonical,
+    /// A custom normalization, the key event is dropped if `None` is returned.
+    ///
+    /// Use the `KeyProfile::custom` method to create it.
+    Custom(Arc<Normalize>),
+}
+
+impl KeyProfile {
+    /// Creates a new custom `KeyProfile`.
+    ///
+    /// The key event is dropped if the `normalize` function returns `None`.
+    pub fn custom<F>(normalize: F) -> KeyProfile
+    where
+        F: Fn(KeyEvent) -> Option<KeyEvent> + Send + Sync + 'static,
+    {
+        KeyProfile::Custom(Arc::new(normalize))
+    }
+
+    /// Normalizes the `key_event` with this profile.
+    ///
+    /// Returns `None` if the event should be dropped.
+    pub fn normalize(&self, key_event: KeyEvent) -> Option<KeyEvent> {
+        match self {
+            KeyProfile::Raw => Some(key_event),
+            KeyProfile::Canonical => Some(canonical_key_event(key_event)),
+            KeyProfile::Custom(normalize) => normalize(key_event),
+        }
+    }
+}
+
+impl fmt::Debug for KeyProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyProfile::Raw => write!(f, "Raw"),
+            KeyProfile::Canonical => write!(f, "Canonical"),
+            KeyProfile::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Custom profiles are equal if they share the same function.
+impl PartialEq for KeyProfile {
+    fn eq(&self, other: &KeyProfile) -> bool {
+        match (self, other) {
+            (KeyProfile::Raw, KeyProfile::Raw) => true,
+            (KeyProfile::Canonical, KeyProfile::Canonical) => true,
+            (KeyProfile::Custom(a), KeyProfile::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for KeyProfile {}
+
+impl Hash for KeyProfile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            KeyProfile::Raw => 0.hash(state),
+            KeyProfile::Canonical => 1.hash(state),
+            KeyProfile::Custom(normalize) => {
+                2.hash(state);
+                (&**normalize as *const Normalize as *const () as usize).hash(state);
+            }
+        };
+    }
+}
+
+/// Maps the `key_event` to the dedicated key (if there's one).
+fn canonical_key_event(key_event: KeyEvent) -> KeyEvent {
+    let code =
+        match (key_event.code, key_event.modifiers) {
+            (KeyCode::Char('m'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('j'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('\r'), KeyModifiers::NONE)
+            | (KeyCode::Char('\n'), KeyModifiers::NONE) => KeyCode::Enter,
+            (KeyCode::Char('i'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('\t'), KeyModifiers::NONE) => KeyCode::Tab,
+            (KeyCode::Char('h'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('\x7F'), KeyModifiers::NONE) => KeyCode::Backspace,
+            (KeyCode::Char('3'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('\x1B'), KeyModifiers::NONE) => KeyCode::Esc,
+            (KeyCode::Char(' '), KeyModifiers::CONTROL)
+            | (KeyCode::Char('\0'), KeyModifiers::NONE) => KeyCode::Null,
+            (KeyCode::Char(c), KeyModifiers::CONTROL) if c.is_ascii_uppercase() => {
+                return canonical_key_event(
+                    KeyEvent::new_with_kind(
+                        KeyCode::Char(c.to_ascii_lowercase()),
+                        KeyModifiers::CONTROL,
+                        key_event.kind,
+                    )
+                    .with_state(key_event.state),
+                );
+            }
+            _ => return key_event,
+        };
+
+    KeyEvent::new_with_kind(code, KeyModifiers::NONE, key_event.kind).with_state(key_event.state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_profile() {
+        let profile = KeyProfile::Canonical;
+
+        assert_eq!(
+            profile.normalize(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::CONTROL)),
+            Some(KeyEvent::from(KeyCode::Enter))
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::new(KeyCode::Char('M'), KeyModifiers::CONTROL)),
+            Some(KeyEvent::from(KeyCode::Enter))
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::from(KeyCode::Char('\t'))),
+            Some(KeyEvent::from(KeyCode::Tab))
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)),
+            Some(KeyEvent::from(KeyCode::Backspace))
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::new(KeyCode::Char('C'), KeyModifiers::CONTROL)),
+            Some(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::from(KeyCode::Char('M'))),
+            Some(KeyEvent::from(KeyCode::Char('M')))
+        );
+        assert_eq!(
+            KeyProfile::Raw.normalize(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::CONTROL)),
+            Some(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn test_custom_profile() {
+        let profile = KeyProfile::custom(|key_event| match key_event.code {
+            KeyCode::Char('q') if key_event.modifiers == KeyModifiers::CONTROL => {
+                Some(KeyCode::Esc.into())
+            }
+            KeyCode::Char('z') if key_event.modifiers == KeyModifiers::CONTROL => None,
+            _ => Some(key_event),
+        });
+
+        assert_eq!(
+            profile.normalize(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(KeyEvent::from(KeyCode::Esc))
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            None
+        );
+        assert_eq!(
+            profile.normalize(KeyEvent::from(KeyCode::Up)),
+            Some(KeyEvent::from(KeyCode::Up))
+        );
+
+        assert_eq!(profile, profile.clone());
+        assert_ne!(profile, KeyProfile::custom(Some));
+        assert_ne!(profile, KeyProfile::Raw);
+    }
+}
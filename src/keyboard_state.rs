@@ -0,0 +1,123 @@
+//! A tracker of the currently held keys.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    config::{with_input_config, InputConfig},
+    KeyEvent, KeyEventKind,
+};
+
+lazy_static! {
+    /// Currently held keys indexed by the platform key identifier.
+    static ref HELD_KEYS: Mutex<HashMap<u32, KeyEvent>> = Mutex::new(HashMap::new());
+}
+
+/// `true` if the key releases are reported (always on Windows, with the kitty keyboard protocol
+/// on UNIX). Pressed keys aren't tracked otherwise, they'd be held forever.
+static KEY_RELEASES_REPORTED: AtomicBool = AtomicBool::new(cfg!(windows));
+
+/// Marks whether the terminal reports the key releases, all held keys are forgotten if not.
+#[cfg(unix)]
+pub(crate) fn set_key_releases_reported(reported: bool) {
+    KEY_RELEASES_REPORTED.store(reported, Ordering::SeqCst);
+
+    if !reported {
+        HELD_KEYS.lock().unwrap().clear();
+    }
+}
+
+/// Marks the key identified by the `key_id` as held or released, depending on the `key_event`
+/// kind.
+pub(crate) fn track_key_event(key_id: u32, key_event: &KeyEvent) {
+    match key_event.kind {
+        KeyEventKind::Press | KeyEventKind::Repeat => key_pressed(key_id, key_event.clone()),
+        KeyEventKind::Release => key_released(key_id),
+    };
+}
+
+/// Marks the key identified by the `key_id` as held (if the tracking is enabled).
+fn key_pressed(key_id: u32, key_event: KeyEvent) {
+    if KEY_RELEASES_REPORTED.load(Ordering::SeqCst)
+        && with_input_config(InputConfig::keyboard_state_tracking)
+    {
+        // Held keys are always reported as pressed
+        let key_event = KeyEvent {
+            kind: KeyEventKind::Press,
+            ..key_event
+        };
+        HELD_KEYS.lock().unwrap().insert(key_id, key_event);
+    }
+}
+
+/// Marks the key identified by the `key_id` as released.
+pub(crate) fn key_released(key_id: u32) {
+    HELD_KEYS.lock().unwrap().remove(&key_id);
+}
+
+/// Returns a snapshot of the currently held keys.
+pub(crate) fn keyboard_state() -> KeyboardState {
+    KeyboardState::new(HELD_KEYS.lock().unwrap().values().cloned())
+}
+
+/// A snapshot of the currently held keys.
+///
+/// See the [`TerminalInput::keyboard_state`](struct.TerminalInput.html#method.keyboard_state)
+/// method.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct KeyboardState {
+    held: HashSet<KeyEvent>,
+}
+
+impl KeyboardState {
+    fn new<I>(held: I) -> KeyboardState
+    where
+        I: IntoIterator<Item = KeyEvent>,
+    {
+        KeyboardState {
+            held: held.into_iter().collect(),
+        }
+    }
+
+    /// Returns `true` if the `key_event` is held.
+    pub fn is_held(&self, key_event: &KeyEvent) -> bool {
+        self.held.contains(key_event)
+    }
+
+    /// Returns an iterator over all the held keys.
+    pub fn held_keys(&self) -> impl Iterator<Item = &KeyEvent> {
+        self.held.iter()
+    }
+
+    /// Returns `true` if no key is held.
+    pub fn is_empty(&self) -> bool {
+        self.held.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyCode;
+
+    #[test]
+    fn test_keyboard_state() {
+        let state = KeyboardState::new(vec![
+            KeyEvent::from(KeyCode::Char('w')),
+            KeyEvent::from(KeyCode::Up),
+        ]);
+
+        assert!(state.is_held(&KeyEvent::from(KeyCode::Char('w'))));
+        assert!(state.is_held(&KeyEvent::from(KeyCode::Up)));
+        assert!(!state.is_held(&KeyEvent::from(KeyCode::Char('s'))));
+        assert_eq!(state.held_keys().count(), 2);
+        assert!(!state.is_empty());
+
+        assert!(KeyboardState::default().is_empty());
+    }
+}
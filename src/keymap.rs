@@ -0,0 +1,280 @@
+//! Keymaps binding the key events to the application actions.
+
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use crate::{InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// The keymaps of a `KeymapStack` with their identifiers (the base keymap first).
+type KeymapLayers<A> = Arc<Mutex<Vec<(usize, Keymap<A>)>>>;
+
+/// A set of the key bindings.
+///
+/// The key events are matched by the code & modifiers, the kind of the event is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::{KeyCode, KeyEvent, KeyModifiers, Keymap};
+///
+/// let keymap = Keymap::new()
+///     .with_binding(KeyCode::Char('q').into(), "quit")
+///     .with_binding(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL), "save");
+///
+/// assert_eq!(keymap.action(&KeyCode::Char('q').into()), Some(&"quit"));
+/// assert_eq!(keymap.action(&KeyCode::Char('s').into()), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keymap<A> {
+    bindings: HashMap<(KeyCode, KeyModifiers), A>,
+    fallthrough: bool,
+}
+
+impl<A> Default for Keymap<A> {
+    fn default() -> Keymap<A> {
+        Keymap {
+            bindings: HashMap::new(),
+            fallthrough: true,
+        }
+    }
+}
+
+impl<A> Keymap<A> {
+    /// Creates a new empty `Keymap`.
+    pub fn new() -> Keymap<A> {
+        Keymap::default()
+    }
+
+    /// Returns the `Keymap` with the `key_event` bound to the `action`.
+    pub fn with_binding(mut self, key_event: KeyEvent, action: A) -> Keymap<A> {
+        self.bind(key_event, action);
+        self
+    }
+
+    /// Binds the `key_event` to the `action`, returns the previously bound action (if any).
+    pub fn bind(&mut self, key_event: KeyEvent, action: A) -> Option<A> {
+        self.bindings
+            .insert((key_event.code, key_event.modifiers), action)
+    }
+
+    /// Removes the binding of the `key_event`, returns the bound action (if any).
+    pub fn unbind(&mut self, key_event: &KeyEvent) -> Option<A> {
+        self.bindings.remove(&(key_event.code, key_event.modifiers))
+    }
+
+    /// Returns the action bound to the `key_event`.
+    pub fn action(&self, key_event: &KeyEvent) -> Option<&A> {
+        self.bindings.get(&(key_event.code, key_event.modifiers))
+    }
+
+    /// Returns the number of bindings.
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// Returns `true` if there's no binding.
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Returns whether the unbound keys are looked up in the keymaps below.
+    pub fn fallthrough(&self) -> bool {
+        self.fallthrough
+    }
+
+    /// Looks the unbound keys up in the keymaps below in the
+    /// [`KeymapStack`](struct.KeymapStack.html).
+    ///
+    /// The default is `true`. Disable it for the modal states (a dialog, ...), the keys
+    /// without a binding in this keymap have no action then.
+    pub fn with_fallthrough(mut self, enabled: bool) -> Keymap<A> {
+        self.fallthrough = enabled;
+        self
+    }
+}
+
+/// A stack of the keymaps, the temporary keymaps shadow the bindings of the keymaps below.
+///
+/// The temporary keymap is pushed while a UI state is active (a menu is open, ...), it's
+/// removed once the returned [`KeymapGuard`](struct.KeymapGuard.html) is dropped. Every guard
+/// removes its own keymap only, the nested UI states can be closed in any order. Clones of
+/// the `KeymapStack` share the keymaps.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::{InputEvent, KeyCode, Keymap, KeymapStack};
+///
+/// let stack = KeymapStack::new(
+///     Keymap::new()
+///         .with_binding(KeyCode::Esc.into(), "quit")
+///         .with_binding(KeyCode::Char('m').into(), "menu"),
+/// );
+///
+/// let esc = InputEvent::Keyboard(KeyCode::Esc.into());
+/// assert_eq!(stack.dispatch(&esc), Some("quit"));
+///
+/// // The menu is open
+/// let menu = stack.push(Keymap::new().with_binding(KeyCode::Esc.into(), "close menu"));
+/// assert_eq!(stack.dispatch(&esc), Some("close menu"));
+///
+/// drop(menu);
+/// assert_eq!(stack.dispatch(&esc), Some("quit"));
+/// ```
+#[derive(Debug)]
+pub struct KeymapStack<A> {
+    layers: KeymapLayers<A>,
+    next_id: Arc<AtomicUsize>,
+}
+
+impl<A> Clone for KeymapStack<A> {
+    fn clone(&self) -> KeymapStack<A> {
+        KeymapStack {
+            layers: self.layers.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+
+impl<A> Default for KeymapStack<A> {
+    fn default() -> KeymapStack<A> {
+        KeymapStack::new(Keymap::new())
+    }
+}
+
+impl<A> KeymapStack<A> {
+    /// Creates a new `KeymapStack` with the `base` keymap, it's never removed.
+    pub fn new(base: Keymap<A>) -> KeymapStack<A> {
+        KeymapStack {
+            layers: Arc::new(Mutex::new(vec![(0, base)])),
+            next_id: Arc::new(AtomicUsize::new(1)),
+        }
+    }
+
+    /// Pushes the temporary `keymap` on the top of the stack.
+    ///
+    /// The `keymap` is removed once the returned guard is dropped.
+    pub fn push(&self, keymap: Keymap<A>) -> KeymapGuard<A> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.layers.lock().unwrap().push((id, keymap));
+
+        KeymapGuard {
+            id,
+            layers: self.layers.clone(),
+        }
+    }
+
+    /// Returns the number of the temporary keymaps.
+    pub fn depth(&self) -> usize {
+        self.layers.lock().unwrap().len() - 1
+    }
+}
+
+impl<A: Clone> KeymapStack<A> {
+    /// Returns the action bound to the `key_event` in the topmost keymap.
+    ///
+    /// The keymaps are searched from the top until the keymap without the fallthrough.
+    pub fn action(&self, key_event: &KeyEvent) -> Option<A> {
+        let layers = self.layers.lock().unwrap();
+
+        for (_, keymap) in layers.iter().rev() {
+            if let Some(action) = keymap.action(key_event) {
+                return Some(action.clone());
+            }
+            if !keymap.fallthrough() {
+                break;
+            }
+        }
+
+        None
+    }
+
+    /// Returns the action bound to the key `event`.
+    ///
+    /// The key releases & the other events have no action.
+    pub fn dispatch(&self, event: &InputEvent) -> Option<A> {
+        match event {
+            InputEvent::Keyboard(key_event) if key_event.kind != KeyEventKind::Release => {
+                self.action(key_event)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A temporary keymap pushed on the [`KeymapStack`](struct.KeymapStack.html).
+///
+/// The keymap is removed from the stack once the guard is dropped.
+#[derive(Debug)]
+pub struct KeymapGuard<A> {
+    id: usize,
+    layers: KeymapLayers<A>,
+}
+
+impl<A> Drop for KeymapGuard<A> {
+    fn drop(&mut self) {
+        self.layers.lock().unwrap().retain(|(id, _)| *id != self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+    use super::{Keymap, KeymapStack};
+
+    fn key(c: char) -> InputEvent {
+        InputEvent::Keyboard(KeyCode::Char(c).into())
+    }
+
+    #[test]
+    fn test_keymap_stack() {
+        let stack = KeymapStack::new(
+            Keymap::new()
+                .with_binding(KeyCode::Char('a').into(), 1)
+                .with_binding(KeyCode::Char('b').into(), 2),
+        );
+
+        let first = stack.push(Keymap::new().with_binding(KeyCode::Char('a').into(), 3));
+        let second = stack.push(
+            Keymap::new()
+                .with_binding(KeyCode::Char('c').into(), 4)
+                .with_fallthrough(false),
+        );
+        assert_eq!(stack.depth(), 2);
+
+        // The modal keymap hides the keymaps below
+        assert_eq!(stack.dispatch(&key('c')), Some(4));
+        assert_eq!(stack.dispatch(&key('a')), None);
+
+        // Closed out of order, the second keymap is still active
+        drop(first);
+        assert_eq!(stack.depth(), 1);
+        assert_eq!(stack.dispatch(&key('a')), None);
+
+        drop(second);
+        assert_eq!(stack.dispatch(&key('a')), Some(1));
+        assert_eq!(stack.dispatch(&key('c')), None);
+
+        let shadow = stack
+            .clone()
+            .push(Keymap::new().with_binding(KeyCode::Char('b').into(), 5));
+        assert_eq!(stack.dispatch(&key('b')), Some(5));
+        assert_eq!(stack.dispatch(&key('a')), Some(1));
+
+        let release = KeyEvent::new_with_kind(
+            KeyCode::Char('b'),
+            KeyModifiers::empty(),
+            KeyEventKind::Release,
+        );
+        assert_eq!(stack.dispatch(&InputEvent::Keyboard(release)), None);
+        assert_eq!(stack.dispatch(&InputEvent::FocusLost), None);
+
+        drop(shadow);
+        assert_eq!(stack.depth(), 0);
+        assert_eq!(stack.dispatch(&key('b')), Some(2));
+    }
+}
@@ -0,0 +1,213 @@
+//! Composable input event processing layers.
+
+use crate::InputEvent;
+
+/// Represents what an [`InputLayer`](trait.InputLayer.html) did with the event.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum LayerResult {
+    /// The (possibly transformed) event is passed to the next layer.
+    Continue(InputEvent),
+    /// The event is replaced with the events (none, one or more), they're passed to the next
+    /// layer in order.
+    Emit(Vec<InputEvent>),
+    /// The event is consumed (or held by the layer), nothing is passed to the next layer.
+    Consume,
+}
+
+/// An input event processing layer (keymap, chords, throttling, ...).
+///
+/// Layers are stacked in the [`LayerStack`](struct.LayerStack.html), every layer gets the events
+/// produced by the previous one. Closures `FnMut(InputEvent) -> LayerResult` are layers too.
+pub trait InputLayer {
+    /// Processes the `event`.
+    fn process(&mut self, event: InputEvent) -> LayerResult;
+
+    /// Returns the events held by the layer (a pending chord, ...).
+    ///
+    /// It's called by the [`LayerStack::flush`](struct.LayerStack.html#method.flush) method,
+    /// nothing is held by default.
+    fn flush(&mut self) -> Vec<InputEvent> {
+        Vec::new()
+    }
+}
+
+impl<F> InputLayer for F
+where
+    F: FnMut(InputEvent) -> LayerResult,
+{
+    fn process(&mut self, event: InputEvent) -> LayerResult {
+        self(event)
+    }
+}
+
+/// A stack of the input layers.
+///
+/// The events are processed by the layers in the order they were added.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::{InputEvent, KeyCode, KeyEvent, LayerResult, LayerStack};
+///
+/// let mut stack = LayerStack::new()
+///     // Vim-like navigation
+///     .with_layer(|event| match event {
+///         InputEvent::Keyboard(KeyEvent {
+///             code: KeyCode::Char('k'),
+///             ..
+///         }) => LayerResult::Continue(InputEvent::Keyboard(KeyCode::Up.into())),
+///         event => LayerResult::Continue(event),
+///     })
+///     // No focus events
+///     .with_layer(|event| match event {
+///         InputEvent::FocusGained | InputEvent::FocusLost => LayerResult::Consume,
+///         event => LayerResult::Continue(event),
+///     });
+///
+/// assert_eq!(
+///     stack.process(InputEvent::Keyboard(KeyCode::Char('k').into())),
+///     vec![InputEvent::Keyboard(KeyCode::Up.into())]
+/// );
+/// assert_eq!(stack.process(InputEvent::FocusLost), vec![]);
+/// ```
+#[derive(Default)]
+pub struct LayerStack {
+    layers: Vec<Box<dyn InputLayer + Send>>,
+}
+
+impl LayerStack {
+    /// Creates a new empty `LayerStack`, the events are passed through.
+    pub fn new() -> LayerStack {
+        LayerStack::default()
+    }
+
+    /// Returns the `LayerStack` with the `layer` on the top.
+    pub fn with_layer<L>(mut self, layer: L) -> LayerStack
+    where
+        L: InputLayer + Send + 'static,
+    {
+        self.push(layer);
+        self
+    }
+
+    /// Adds the `layer` on the top of the stack.
+    pub fn push<L>(&mut self, layer: L)
+    where
+        L: InputLayer + Send + 'static,
+    {
+        self.layers.push(Box::new(layer));
+    }
+
+    /// Returns the number of layers.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns `true` if there's no layer.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Processes the `event` with all the layers and returns the resulting events.
+    pub fn process(&mut self, event: InputEvent) -> Vec<InputEvent> {
+        process_from(&mut self.layers, vec![event])
+    }
+
+    /// Flushes the events held by the layers, the events of every layer are processed by
+    /// the layers above it.
+    ///
+    /// Call it when the input is idle (no event for a while) to release the pending chords, ...
+    pub fn flush(&mut self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        for index in 0..self.layers.len() {
+            // Events held by the lower layers are processed by this one first
+            let mut layer_events = process_from(&mut self.layers[index..index + 1], events);
+            layer_events.extend(self.layers[index].flush());
+            events = layer_events;
+        }
+
+        events
+    }
+}
+
+/// Processes the `events` with the `layers` in order.
+fn process_from(
+    layers: &mut [Box<dyn InputLayer + Send>],
+    events: Vec<InputEvent>,
+) -> Vec<InputEvent> {
+    let mut events = events;
+
+    for layer in layers.iter_mut() {
+        let mut next = Vec::with_capacity(events.len());
+
+        for event in events {
+            match layer.process(event) {
+                LayerResult::Continue(event) => next.push(event),
+                LayerResult::Emit(emitted) => next.extend(emitted),
+                LayerResult::Consume => {}
+            };
+        }
+
+        events = next;
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InputEvent, KeyCode};
+
+    use super::{InputLayer, LayerResult, LayerStack};
+
+    fn key(c: char) -> InputEvent {
+        InputEvent::Keyboard(KeyCode::Char(c).into())
+    }
+
+    /// Holds the `a` key until the next key (or flush).
+    #[derive(Default)]
+    struct HoldA {
+        held: Option<InputEvent>,
+    }
+
+    impl InputLayer for HoldA {
+        fn process(&mut self, event: InputEvent) -> LayerResult {
+            if event == key('a') {
+                self.held = Some(event);
+                return LayerResult::Consume;
+            }
+
+            let mut events = self.flush();
+            events.push(event);
+            LayerResult::Emit(events)
+        }
+
+        fn flush(&mut self) -> Vec<InputEvent> {
+            self.held.take().into_iter().collect()
+        }
+    }
+
+    #[test]
+    fn test_layer_stack() {
+        let mut stack = LayerStack::new()
+            .with_layer(HoldA::default())
+            .with_layer(|event| {
+                if event == key('a') {
+                    LayerResult::Emit(vec![key('x'), key('y')])
+                } else {
+                    LayerResult::Continue(event)
+                }
+            });
+        assert_eq!(stack.len(), 2);
+
+        assert_eq!(stack.process(key('a')), vec![]);
+        assert_eq!(stack.process(key('b')), vec![key('x'), key('y'), key('b')]);
+
+        assert_eq!(stack.process(key('a')), vec![]);
+        assert_eq!(stack.flush(), vec![key('x'), key('y')]);
+        assert_eq!(stack.flush(), vec![]);
+
+        assert_eq!(LayerStack::new().process(key('a')), vec![key('a')]);
+    }
+}
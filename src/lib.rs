@@ -42,62 +42,326 @@
 //! The terminal has to be in the raw mode, raw mode prevents the input of the user to be displayed
 //! on the terminal screen. See the
 //! [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more.
+//!
+//! ### Event ordering
+//!
+//! Every reader gets the input events in the order they were produced, no event is skipped
+//! (the events over the key rate limit are reported with the `InputEvent::Lagged`). The events
+//! are numbered, see the
+//! [`SyncReader::next_with_sequence`](struct.SyncReader.html#method.next_with_sequence) and
+//! [`AsyncReader::next_with_sequence`](struct.AsyncReader.html#method.next_with_sequence)
+//! methods. The order isn't guaranteed between the readers.
+
+use std::time::Duration;
 
+use bitflags::bitflags;
 #[doc(no_inline)]
 pub use crossterm_screen::{IntoRawMode, RawScreen};
 #[doc(no_inline)]
 pub use crossterm_utils::Result;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub use self::config::{
+    ControlAction, EscStrategy, InputConfig, InputEncoding, InputFilter, KeyRateOverflow,
+    MouseOrigin, RawModeFallback, ReadMode, SentinelPolicy,
+};
+pub use self::error_hook::{clear_error_hook, set_error_hook};
+#[cfg(feature = "event-stream")]
+pub use self::event_stream::{EventStream, MapEvents};
+pub use self::events::{events, Events};
+pub use self::frame::FrameInput;
 #[cfg(unix)]
 use self::input::unix::UnixInput;
 #[cfg(windows)]
 use self::input::windows::WindowsInput;
 use self::input::Input;
 pub use self::input::{AsyncReader, SyncReader};
+pub use self::key_profile::KeyProfile;
+pub use self::keyboard_state::KeyboardState;
+pub use self::keymap::{Keymap, KeymapGuard, KeymapStack};
+pub use self::layer::{InputLayer, LayerResult, LayerStack};
+pub use self::mode_guard::{
+    BracketedPaste, BracketedPasteGuard, FocusReporting, FocusReportingGuard, KeyboardEnhancement,
+    KeyboardEnhancementGuard,
+};
+#[cfg(not(feature = "keyboard-only"))]
+pub use self::mode_guard::{MouseCapture, MouseCaptureGuard};
+pub use self::mode_watch::ModeWatch;
+use self::modes::Mode;
+#[cfg(not(feature = "keyboard-only"))]
+pub use self::mouse_capture::MouseCaptureOptions;
+#[cfg(not(feature = "keyboard-only"))]
+pub use self::mouse_state::MouseState;
+pub use self::ownership::InputOwnership;
+pub use self::parser::Parser;
+pub use self::passthrough::SequenceSubscription;
+pub use self::poll::{poll, read, warm_up};
+pub use self::repeat::KeyRepeat;
+pub use self::restore::install_restore_hook;
+#[cfg(unix)]
+pub use self::session::{SessionManager, SessionReader};
+pub use self::shortcuts::{GlobalShortcut, ShortcutDelivery};
+pub use self::terminal_info::{
+    register_terminal_quirks, terminal_info, TerminalInfo, TerminalMatch, TerminalQuirks,
+};
 
+mod config;
+#[cfg(unix)]
+pub mod diagnostics;
+pub mod encode;
+mod error_hook;
+#[cfg(feature = "event-stream")]
+mod event_stream;
+mod events;
+mod frame;
 mod input;
+mod interop;
+mod key_profile;
+mod keyboard_state;
+mod keymap;
+mod layer;
+mod mode_guard;
+mod mode_watch;
+mod modes;
+#[cfg(not(feature = "keyboard-only"))]
+mod mouse_capture;
+#[cfg(not(feature = "keyboard-only"))]
+mod mouse_state;
+mod ownership;
+mod parser;
+mod passthrough;
+mod poll;
+mod rate_limit;
+#[cfg(feature = "remote")]
+pub mod remote;
+mod repeat;
+mod restore;
+#[cfg(not(feature = "keyboard-only"))]
+mod scroll;
+pub mod sequences;
+#[cfg(unix)]
+mod session;
+mod shortcuts;
+#[cfg(feature = "testing")]
+pub mod simulate;
 mod sys;
+mod terminal_info;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 /// Represents an input event.
+///
+/// # Comparison & hashing
+///
+/// All the event types (`InputEvent`, `KeyEvent`, `KeyCode`, `KeyModifiers`, `KeyEventKind`,
+/// `MouseEvent`, `MouseButton`) implement
+/// `Eq`, `Ord` and `Hash`, so they can be used as `HashMap`/`BTreeMap` keys (keymaps, recorded
+/// logs, ...). Events are ordered by the variant declaration order first and by the variant
+/// fields then.
+///
+/// The `Hash` implementation feeds the variant index and the variant fields only. The hash is
+/// stable between runs when computed with a deterministic hasher (the `HashMap` default one is
+/// randomly seeded). Both the hash and the ordering can change between versions of this crate
+/// (new variants, ...), don't persist them.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use crossterm_input::{InputEvent, KeyCode, KeyEvent, KeyModifiers};
+///
+/// let quit = InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL));
+///
+/// let mut keymap = BTreeMap::new();
+/// keymap.insert(quit.clone(), "quit");
+/// keymap.insert(InputEvent::Keyboard(KeyCode::Char('?').into()), "help");
+///
+/// assert_eq!(keymap.get(&quit), Some(&"quit"));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, PartialEq, Hash, Clone)]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone)]
 pub enum InputEvent {
     /// A single key or a combination of keys.
     Keyboard(KeyEvent),
     /// A mouse event.
+    ///
+    /// It isn't available in the `keyboard-only` build.
+    #[cfg(not(feature = "keyboard-only"))]
     Mouse(MouseEvent),
+    /// The terminal was resized to the size (columns, rows).
+    ///
+    /// Reported when the `SIGWINCH` signal is received on UNIX. Reported from the
+    /// `WINDOW_BUFFER_SIZE_EVENT` on Windows (the screen buffer size, requires the enabled
+    /// mouse mode).
+    Resize(u16, u16),
+    /// A text pasted with the bracketed paste mode enabled.
+    ///
+    /// See the [`enable_bracketed_paste`](struct.TerminalInput.html#method.enable_bracketed_paste)
+    /// method.
+    Paste(String),
+    /// The start of a pasted text longer than the paste stream threshold.
+    ///
+    /// The text is delivered in the `PasteChunk` events followed by the `PasteEnd` event
+    /// instead of a single `Paste` event. See the
+    /// [`with_paste_stream_threshold`](struct.InputConfig.html#method.with_paste_stream_threshold)
+    /// method.
+    PasteStart,
+    /// A part of the streamed pasted text.
+    PasteChunk(String),
+    /// The end of the streamed pasted text.
+    PasteEnd,
+    /// The terminal gained the focus.
+    ///
+    /// See the [`enable_focus_reporting`](struct.TerminalInput.html#method.enable_focus_reporting)
+    /// method.
+    FocusGained,
+    /// The terminal lost the focus.
+    FocusLost,
+    /// A wheel scroll by the number of lines, negative is up.
+    ///
+    /// Reported instead of the wheel events if enabled with the
+    /// [`with_wheel_scroll_lines`](struct.InputConfig.html#method.with_wheel_scroll_lines)
+    /// method. It isn't available in the `keyboard-only` build.
+    #[cfg(not(feature = "keyboard-only"))]
+    ScrollLines(i16),
+    /// A desktop notification sent by the terminal (`OSC 9` or `OSC 777`).
+    ///
+    /// The `OSC 777` notification is reported as `title: body` (or just the `body` if there's no
+    /// title). Reported on UNIX only (requires the `notifications` feature).
+    #[cfg(feature = "notifications")]
+    Notification(String),
+    /// The number of key events dropped by the key rate limiter.
+    ///
+    /// See the [`with_key_rate_limit`](struct.InputConfig.html#method.with_key_rate_limit)
+    /// method.
+    Lagged(usize),
+    /// The terminal hung up (the SSH session dropped, the terminal window closed, ...).
+    ///
+    /// It's the last event, the reading stops. Reported when the TTY read reaches the end of
+    /// file (or fails with `EIO`) on UNIX and when the console handle is invalidated on Windows.
+    Disconnected,
     /// An unsupported event.
     ///
     /// You can ignore this type of event, because it isn't used.
     Unsupported(Vec<u8>), // TODO Not used, should be removed.
     /// An unknown event.
     Unknown,
-    /// Internal cursor position event. Don't use it, it will be removed in the
-    /// `crossterm` 1.0.
+    /// Internal cursor position event. It isn't reported anymore, use the
+    /// [`query_cursor_position`](struct.TerminalInput.html#method.query_cursor_position)
+    /// method. It will be removed in the `crossterm` 1.0.
     #[doc(hidden)]
     #[cfg(unix)]
     CursorPosition(u16, u16), // TODO 1.0: Remove
 }
 
 /// Represents a mouse event.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+///
+/// fn is_ctrl_click(mouse_event: &MouseEvent) -> bool {
+///     mouse_event.kind == MouseEventKind::Press
+///         && mouse_event.button == Some(MouseButton::Left)
+///         && mouse_event.modifiers.contains(KeyModifiers::CONTROL)
+/// }
+///
+/// let click = MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), 10, 5);
+/// assert!(!is_ctrl_click(&click));
+/// assert!(is_ctrl_click(&MouseEvent {
+///     modifiers: KeyModifiers::CONTROL,
+///     ..click
+/// }));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg(not(feature = "keyboard-only"))]
+pub struct MouseEvent {
+    /// The kind of the mouse event.
+    pub kind: MouseEventKind,
+    /// The pressed, released or held button.
+    ///
+    /// It's `None` if the terminal doesn't report the button (legacy encodings report
+    /// the release of any button), the mouse moved without a pressed button or the event
+    /// is unknown.
+    pub button: Option<MouseButton>,
+    /// The column (zero based unless configured with the
+    /// [`InputConfig::with_mouse_origin`](struct.InputConfig.html#method.with_mouse_origin)).
+    pub column: u16,
+    /// The row (zero based unless configured with the
+    /// [`InputConfig::with_mouse_origin`](struct.InputConfig.html#method.with_mouse_origin)).
+    pub row: u16,
+    /// The keyboard modifiers (Shift, Alt & Control) held during the event.
+    ///
+    /// Terminals usually reserve some of them (Shift + click selects the text, ...).
+    pub modifiers: KeyModifiers,
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+impl MouseEvent {
+    /// Creates a new `MouseEvent` without the keyboard modifiers.
+    pub fn new(
+        kind: MouseEventKind,
+        button: Option<MouseButton>,
+        column: u16,
+        row: u16,
+    ) -> MouseEvent {
+        MouseEvent {
+            kind,
+            button,
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        }
+    }
+}
+
+/// Represents a mouse event kind.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, PartialEq, Hash, Clone, Copy)]
-pub enum MouseEvent {
-    /// Pressed mouse button at the location (column, row).
-    Press(MouseButton, u16, u16),
-    /// Released mouse button at the location (column, row).
-    Release(u16, u16),
-    /// Mouse moved with a pressed left button to the new location (column, row).
-    Hold(u16, u16),
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg(not(feature = "keyboard-only"))]
+pub enum MouseEventKind {
+    /// Pressed mouse button (or scrolled wheel).
+    Press,
+    /// Released mouse button.
+    Release,
+    /// Mouse moved with a pressed button.
+    Drag,
+    /// Mouse moved without a pressed button.
+    Moved,
+    /// The second press of a double click.
+    ///
+    /// It's reported instead of the `MouseEventKind::Press` on Windows only, the terminals
+    /// report two presses.
+    DoubleClick,
     /// An unknown mouse event.
     Unknown,
 }
 
+/// Represents which mouse motion is reported.
+///
+/// See the [`enable_mouse_tracking`](struct.TerminalInput.html#method.enable_mouse_tracking)
+/// method.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg(not(feature = "keyboard-only"))]
+pub enum MouseTracking {
+    /// Report the button presses, releases & the motion with a pressed button
+    /// (`MouseEventKind::Drag`).
+    ButtonEvent,
+    /// Report the button events & all the motion, the motion without a pressed button is
+    /// reported as the `MouseEventKind::Moved`.
+    AnyMotion,
+}
+
 /// Represents a mouse button/wheel.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, PartialEq, Hash, Clone, Copy)]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg(not(feature = "keyboard-only"))]
 pub enum MouseButton {
     /// Left mouse button.
     Left,
@@ -109,12 +373,135 @@ pub enum MouseButton {
     WheelUp,
     /// Wheel scrolled down.
     WheelDown,
+    /// Back (browser navigation) mouse button, the X11 button 8.
+    Back,
+    /// Forward (browser navigation) mouse button, the X11 button 9.
+    Forward,
+    /// Other mouse button with the X11 button number.
+    ///
+    /// Terminals report the buttons 10 & 11 only.
+    Other(u8),
+}
+
+/// Represents a key event.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::{KeyCode, KeyEvent, KeyModifiers};
+///
+/// fn is_quit(key_event: &KeyEvent) -> bool {
+///     key_event.code == KeyCode::Char('q') && key_event.modifiers.contains(KeyModifiers::CONTROL)
+/// }
+///
+/// assert!(is_quit(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)));
+/// assert!(!is_quit(&KeyCode::Char('q').into()));
+/// ```
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyEvent {
+    /// The key itself.
+    pub code: KeyCode,
+    /// Additional key modifiers.
+    pub modifiers: KeyModifiers,
+    /// The kind of the key event (press, repeat or release).
+    pub kind: KeyEventKind,
+    /// Additional key state (keypad, ...).
+    pub state: KeyEventState,
+}
+
+impl KeyEvent {
+    /// Creates a new key press `KeyEvent`.
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+    }
+
+    /// Creates a new `KeyEvent` of the given `kind`.
+    pub fn new_with_kind(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    /// Returns the `KeyEvent` with the given `state`.
+    pub fn with_state(mut self, state: KeyEventState) -> KeyEvent {
+        self.state = state;
+        self
+    }
+}
+
+impl From<KeyCode> for KeyEvent {
+    fn from(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+}
+
+bitflags! {
+    /// Represents key modifiers (Shift, Control, Alt, Super, Hyper, Meta).
+    ///
+    /// The Super, Hyper & Meta modifiers are reported by the kitty keyboard protocol terminals,
+    /// the Super (Windows key) by the Windows console as well.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct KeyModifiers: u8 {
+        /// Shift key.
+        const SHIFT = 0b0000_0001;
+        /// Control key.
+        const CONTROL = 0b0000_0010;
+        /// Alt key.
+        const ALT = 0b0000_0100;
+        /// Super key (Windows, Command, ...).
+        const SUPER = 0b0000_1000;
+        /// Hyper key.
+        const HYPER = 0b0001_0000;
+        /// Meta key.
+        const META = 0b0010_0000;
+        /// No modifier.
+        const NONE = 0b0000_0000;
+    }
 }
 
-/// Represents a key or a combination of keys.
-#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Hash)]
+bitflags! {
+    /// Represents additional key state.
+    ///
+    /// The keypad keys are reported in the application keypad mode (`ESC =`), with the kitty
+    /// keyboard protocol (see the
+    /// [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    /// method) and on Windows. They're reported as the main keyboard keys otherwise.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct KeyEventState: u8 {
+        /// The key is on the keypad (keypad Enter, digits, operators, ...).
+        const KEYPAD = 0b0000_0001;
+        /// No additional state.
+        const NONE = 0b0000_0000;
+    }
+}
+
+/// Represents the kind of a key event.
+///
+/// Repeats & releases are reported on UNIX systems with the kitty keyboard protocol only (see
+/// the [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+/// method), releases are reported on Windows. Releases are produced by the readers only if
+/// enabled with the
+/// [`InputConfig::with_key_release_events`](struct.InputConfig.html#method.with_key_release_events)
+/// method.
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    Press,
+    /// The key is held and it was repeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+/// Represents a key.
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub enum KeyEvent {
+pub enum KeyCode {
     /// Backspace key.
     Backspace,
     /// Enter key.
@@ -145,64 +532,209 @@ pub enum KeyEvent {
     Insert,
     /// F key.
     ///
-    /// `KeyEvent::F(1)` represents F1 key, etc.
+    /// `KeyCode::F(1)` represents F1 key, etc.
     F(u8),
     /// A character.
     ///
-    /// `KeyEvent::Char('c')` represents `c` character, etc.
+    /// `KeyCode::Char('c')` represents `c` character, etc.
     Char(char),
-    /// Alt key + character.
-    ///
-    /// `KeyEvent::Alt('c')` represents `Alt + c`, etc.
-    Alt(char),
-    /// Ctrl key + character.
-    ///
-    /// `KeyEvent::Ctrl('c') ` represents `Ctrl + c`, etc.
-    Ctrl(char),
     /// Null.
     Null,
     /// Escape key.
     Esc,
-    /// Ctrl + up arrow key.
-    CtrlUp,
-    /// Ctrl + down arrow key.
-    CtrlDown,
-    /// Ctrl + right arrow key.
-    CtrlRight,
-    /// Ctrl + left arrow key.
-    CtrlLeft,
-    /// Shift + up arrow key.
-    ShiftUp,
-    /// Shift + down arrow key.
-    ShiftDown,
-    /// Shift + right arrow key.
-    ShiftRight,
-    /// Shift + left arrow key.
-    ShiftLeft,
+    /// Menu (context menu) key.
+    ///
+    /// Reported on Windows & on UNIX systems with the kitty keyboard protocol only (see the
+    /// [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    /// method).
+    Menu,
+    /// A media key.
+    ///
+    /// Reported like the `KeyCode::Menu`, the Windows console reports the volume, track,
+    /// stop & play/pause keys only.
+    Media(MediaKey),
+    /// A modifier key pressed or released on its own.
+    ///
+    /// Reported on Windows & on UNIX systems with the kitty keyboard protocol (the
+    /// `REPORT_ALL_KEYS_AS_ESCAPE_CODES` flag) if enabled with the
+    /// [`InputConfig::with_modifier_key_events`](struct.InputConfig.html#method.with_modifier_key_events)
+    /// method. The Windows console reports the Shift, Control, Alt & Super keys only.
+    Modifier(ModifierKey),
+}
+
+/// Represents a media key (as part of the `KeyCode::Media`).
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MediaKey {
+    /// Play media key.
+    Play,
+    /// Pause media key.
+    Pause,
+    /// Play/Pause media key.
+    PlayPause,
+    /// Reverse media key.
+    Reverse,
+    /// Stop media key.
+    Stop,
+    /// Fast-forward media key.
+    FastForward,
+    /// Rewind media key.
+    Rewind,
+    /// Next-track media key.
+    TrackNext,
+    /// Previous-track media key.
+    TrackPrevious,
+    /// Record media key.
+    Record,
+    /// Lower volume media key.
+    LowerVolume,
+    /// Raise volume media key.
+    RaiseVolume,
+    /// Mute volume media key.
+    MuteVolume,
+}
+
+/// Represents a modifier key (as part of the `KeyCode::Modifier`).
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ModifierKey {
+    /// Left Shift key.
+    LeftShift,
+    /// Left Control key.
+    LeftControl,
+    /// Left Alt key.
+    LeftAlt,
+    /// Left Super key.
+    LeftSuper,
+    /// Left Hyper key.
+    LeftHyper,
+    /// Left Meta key.
+    LeftMeta,
+    /// Right Shift key.
+    RightShift,
+    /// Right Control key.
+    RightControl,
+    /// Right Alt key.
+    RightAlt,
+    /// Right Super key.
+    RightSuper,
+    /// Right Hyper key.
+    RightHyper,
+    /// Right Meta key.
+    RightMeta,
+    /// Iso Level3 Shift key (AltGr).
+    IsoLevel3Shift,
+    /// Iso Level5 Shift key.
+    IsoLevel5Shift,
+}
+
+/// Represents the source of an input event.
+///
+/// See the [`AsyncReader::next_with_source`](struct.AsyncReader.html#method.next_with_source) and
+/// [`SyncReader::next_with_source`](struct.SyncReader.html#method.next_with_source) methods.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SourceId {
+    /// The event was produced by the user in the terminal (TTY or Windows console).
+    Terminal,
+    /// The event was played back from a recording.
+    Playback,
+    /// The event was injected by the application itself.
+    Synthetic,
+    /// The event was forwarded from another process.
+    ///
+    /// See the [`remote`](remote/index.html) module (requires the `remote` feature).
+    Remote,
+}
+
+/// Represents whether the terminal supports the enabled mode.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum EnableOutcome {
+    /// The terminal acknowledged the mode.
+    Supported,
+    /// The terminal doesn't recognize the mode or it can't be enabled.
+    Unsupported,
+    /// The terminal didn't report the mode state.
+    ///
+    /// The terminal doesn't support the mode queries or the raw mode isn't enabled.
+    Unknown,
+}
+
+bitflags! {
+    /// Represents the kitty keyboard protocol progressive enhancement flags.
+    ///
+    /// See the [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    /// method.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct KeyboardEnhancementFlags: u8 {
+        /// Report the ambiguous keys (Esc, Alt + key, Ctrl + key, ...) with the `CSI u` sequences.
+        const DISAMBIGUATE_ESCAPE_CODES = 0b0000_0001;
+        /// Report the key repeats & releases.
+        const REPORT_EVENT_TYPES = 0b0000_0010;
+        /// Report the shifted key along with the base key.
+        const REPORT_ALTERNATE_KEYS = 0b0000_0100;
+        /// Report all the keys (including Enter, Tab & Backspace) with the `CSI u` sequences.
+        const REPORT_ALL_KEYS_AS_ESCAPE_CODES = 0b0000_1000;
+    }
+}
+
+/// Represents the keyboard protocol (how the keys are encoded by the terminal).
+///
+/// See the [`set_keyboard_protocol`](struct.TerminalInput.html#method.set_keyboard_protocol)
+/// method.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum KeyboardProtocol {
+    /// The legacy encoding.
+    Legacy,
+    /// The xterm modifyOtherKeys mode (level 2).
+    ModifyOtherKeys,
+    /// The kitty keyboard protocol with the progressive enhancement flags.
+    Enhanced(KeyboardEnhancementFlags),
+    /// The Windows Terminal win32-input-mode (the key records over the VT stream).
+    Win32Input,
 }
 
 /// An internal event.
 ///
 /// Encapsulates publicly available `InputEvent` with additional internal
 /// events that shouldn't be publicly available to the crate users.
-#[cfg(unix)]
 #[derive(Debug, PartialOrd, PartialEq, Hash, Clone)]
 pub(crate) enum InternalEvent {
     /// An input event.
     Input(InputEvent),
     /// A cursor position (`x`, `y`).
     CursorPosition(u16, u16),
+    /// A DEC private mode report (`mode`, `state`).
+    ModeReport(u16, u8),
+    /// An XTGETTCAP report (`name`, `value`), the value is `None` if the capability is unknown.
+    TermcapReport(String, Option<String>),
+    /// The kitty keyboard protocol enhancement flags report.
+    KeyboardEnhancementFlags(u8),
+    /// The secondary device attributes report (`terminal type`, `firmware version`).
+    DeviceAttributes(u16, u32),
+    /// The XTVERSION report (the terminal name & version).
+    TerminalVersion(String),
+    /// A sequence consumed without an event (dropped by a terminal quirk workaround).
+    #[cfg(not(feature = "keyboard-only"))]
+    Consumed,
 }
 
 /// Converts an `InternalEvent` into a possible `InputEvent`.
-#[cfg(unix)]
 impl From<InternalEvent> for Option<InputEvent> {
     fn from(ie: InternalEvent) -> Self {
         match ie {
             InternalEvent::Input(input_event) => Some(input_event),
-            // TODO 1.0: Swallow `CursorPosition` and return `None`.
-            // `cursor::pos_raw()` will be able to use this module `internal_event_receiver()`
-            InternalEvent::CursorPosition(x, y) => Some(InputEvent::CursorPosition(x, y)),
+            // Routed to the `query_cursor_position` callers only
+            InternalEvent::CursorPosition(_, _) => None,
+            InternalEvent::ModeReport(_, _) => None,
+            InternalEvent::TermcapReport(_, _) => None,
+            InternalEvent::KeyboardEnhancementFlags(_) => None,
+            InternalEvent::DeviceAttributes(_, _) => None,
+            InternalEvent::TerminalVersion(_) => None,
+            #[cfg(not(feature = "keyboard-only"))]
+            InternalEvent::Consumed => None,
         }
     }
 }
@@ -216,6 +748,9 @@ impl From<InternalEvent> for Option<InputEvent> {
 /// // if you're using the `crossterm` crate with the `input` feature enabled.
 /// use crossterm_input::{Result, TerminalInput, RawScreen};
 ///
+/// # #[cfg(feature = "keyboard-only")]
+/// # fn main() {}
+/// # #[cfg(not(feature = "keyboard-only"))]
 /// fn main() -> Result<()> {
 ///     let input = TerminalInput::new();
 ///     // Read a single character
@@ -327,11 +862,43 @@ impl TerminalInput {
         self.input.read_async()
     }
 
+    /// Creates a new `AsyncReader` allowing to read the input asynchronously (not blocking), starting
+    /// with up to `count` most recent events.
+    ///
+    /// It behaves in the same way as the [`read_async`](struct.TerminalInput.html#method.read_async)
+    /// method, but the reader produces the recent history first. It's useful for widgets created in
+    /// response to an event which need the event itself too.
+    ///
+    /// # Notes
+    ///
+    /// * The history must be enabled with the
+    ///   [`InputConfig::with_replay_capacity`](struct.InputConfig.html#method.with_replay_capacity)
+    ///   method, nothing is replayed otherwise.
+    /// * UNIX only, no events are replayed on Windows.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::input;
+    ///
+    /// let input = input();
+    /// input.set_config(input.config().with_replay_capacity(16));
+    ///
+    /// // Starts with the most recent event (if any)
+    /// let mut async_stdin = input.read_async_with_replay(1);
+    /// ```
+    pub fn read_async_with_replay(&self, count: usize) -> AsyncReader {
+        self.input.read_async_with_replay(count)
+    }
+
     /// Creates a new `AsyncReader` allowing to read the input asynchronously (not blocking) until the
     /// given `delimiter`.
     ///
     /// It behaves in the same way as the [`read_async`](struct.TerminalInput.html#method.read_async)
-    /// method, but it stops reading when the `delimiter` is hit.
+    /// method, but it stops reading when the `delimiter` is hit. The `delimiter` in the pasted
+    /// text doesn't stop the reading by default, see the
+    /// [`InputConfig::with_sentinel_policy`](struct.InputConfig.html#method.with_sentinel_policy)
+    /// method.
     ///
     /// # Notes
     ///
@@ -386,16 +953,585 @@ impl TerminalInput {
     ///
     /// Mouse events will be produced by the
     /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html).
-    pub fn enable_mouse_mode(&self) -> Result<()> {
-        self.input.enable_mouse_mode()
+    ///
+    /// Returns whether the terminal supports the mouse events. The terminal is asked for the
+    /// mode state (DECRQM) on UNIX systems.
+    ///
+    /// # Notes
+    ///
+    /// * It waits up to 100ms for the terminal response on UNIX systems.
+    /// * The response is read only if the raw mode is enabled (see the
+    ///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more),
+    ///   `EnableOutcome::Unknown` is returned otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, EnableOutcome, RawScreen};
+    ///
+    /// let _raw = RawScreen::into_raw_mode();
+    ///
+    /// if input().enable_mouse_mode().unwrap() == EnableOutcome::Unsupported {
+    ///     println!("Mouse isn't supported, use the keyboard.");
+    /// }
+    /// ```
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn enable_mouse_mode(&self) -> Result<EnableOutcome> {
+        self.enable_mouse_tracking(MouseTracking::ButtonEvent)
+    }
+
+    /// Enables mouse events with the `tracking` of the mouse motion.
+    ///
+    /// The [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method
+    /// reports the motion with a pressed button only. The `MouseTracking::AnyMotion` reports
+    /// the passive mouse movement as well (hover effects, tooltips, ...).
+    ///
+    /// # Notes
+    ///
+    /// * Terminals send a lot of motion events with the `MouseTracking::AnyMotion`, coalesce
+    ///   them if you redraw the screen on every event.
+    /// * The terminal is asked if the mouse is supported on UNIX systems (see the
+    ///   [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, MouseTracking, RawScreen};
+    ///
+    /// let _raw = RawScreen::into_raw_mode();
+    ///
+    /// input().enable_mouse_tracking(MouseTracking::AnyMotion).unwrap();
+    /// ```
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn enable_mouse_tracking(&self, tracking: MouseTracking) -> Result<EnableOutcome> {
+        self.enable_mouse_capture(tracking.into())
+    }
+
+    /// Enables mouse events with the capture `options`.
+    ///
+    /// Only the modes of the enabled options are written to the terminal, request the click
+    /// tracking only if you don't need the drag events or skip the encodings the terminal
+    /// doesn't support.
+    ///
+    /// # Notes
+    ///
+    /// * The terminal is asked about the most specific enabled tracking mode on UNIX systems
+    ///   (see the [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode)
+    ///   method), `EnableOutcome::Unknown` is returned if there's no tracking mode enabled.
+    /// * The encodings are ignored on Windows.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, MouseCaptureOptions, RawScreen};
+    ///
+    /// let _raw = RawScreen::into_raw_mode();
+    ///
+    /// let options = MouseCaptureOptions::new().with_drag(false).with_urxvt(false);
+    /// input().enable_mouse_capture(options).unwrap();
+    /// ```
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn enable_mouse_capture(&self, options: MouseCaptureOptions) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_mouse_mode(&options)?;
+        modes::mode_enabled(Mode::Mouse);
+        Ok(outcome)
     }
 
     /// Disables mouse events.
     ///
     /// Mouse events wont be produced by the
     /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html).
+    #[cfg(not(feature = "keyboard-only"))]
     pub fn disable_mouse_mode(&self) -> Result<()> {
-        self.input.disable_mouse_mode()
+        self.input.disable_mouse_mode()?;
+        modes::mode_disabled(Mode::Mouse);
+        Ok(())
+    }
+
+    /// Enables mouse events reported with the DEC locator.
+    ///
+    /// Some terminals (and DEC-compatible emulators) don't support the xterm mouse reporting,
+    /// but they do support the DEC locator. Mouse events will be produced by the
+    /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html).
+    ///
+    /// # Notes
+    ///
+    /// * Only button presses & releases are reported, there're no drag or wheel events.
+    /// * The locator state can't be queried, `EnableOutcome::Unknown` is returned on UNIX systems.
+    /// * It does nothing on Windows (`EnableOutcome::Unsupported`), use the
+    ///   [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method.
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn enable_locator_mode(&self) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_locator_mode()?;
+        modes::mode_enabled(Mode::Locator);
+        Ok(outcome)
+    }
+
+    /// Disables mouse events reported with the DEC locator.
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn disable_locator_mode(&self) -> Result<()> {
+        self.input.disable_locator_mode()?;
+        modes::mode_disabled(Mode::Locator);
+        Ok(())
+    }
+
+    /// Enables the bracketed paste mode.
+    ///
+    /// The pasted text will be produced as a single `InputEvent::Paste` event by the
+    /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html) instead
+    /// of the `KeyCode::Char` key events. Escape sequences in the pasted text aren't interpreted.
+    /// A long pasted text is streamed in multiple events (see the
+    /// [`with_paste_stream_threshold`](struct.InputConfig.html#method.with_paste_stream_threshold)
+    /// method).
+    ///
+    /// # Notes
+    ///
+    /// * The terminal is asked if the mode is supported on UNIX systems (see the
+    ///   [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method).
+    /// * It does nothing on Windows (`EnableOutcome::Unsupported`), the console doesn't support
+    ///   the bracketed paste.
+    pub fn enable_bracketed_paste(&self) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_bracketed_paste()?;
+        modes::mode_enabled(Mode::BracketedPaste);
+        Ok(outcome)
+    }
+
+    /// Disables the bracketed paste mode.
+    pub fn disable_bracketed_paste(&self) -> Result<()> {
+        self.input.disable_bracketed_paste()?;
+        modes::mode_disabled(Mode::BracketedPaste);
+        Ok(())
+    }
+
+    /// Enables the focus reporting.
+    ///
+    /// `InputEvent::FocusGained` & `InputEvent::FocusLost` events will be produced by the
+    /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html) when the
+    /// terminal gains or loses the focus.
+    ///
+    /// # Notes
+    ///
+    /// The terminal is asked if the mode is supported on UNIX systems (see the
+    /// [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method).
+    pub fn enable_focus_reporting(&self) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_focus_reporting()?;
+        modes::mode_enabled(Mode::FocusReporting);
+        Ok(outcome)
+    }
+
+    /// Disables the focus reporting.
+    pub fn disable_focus_reporting(&self) -> Result<()> {
+        self.input.disable_focus_reporting()?;
+        modes::mode_disabled(Mode::FocusReporting);
+        Ok(())
+    }
+
+    /// Enables the kitty keyboard protocol with the progressive enhancement `flags`.
+    ///
+    /// Keys which are ambiguous in the legacy encoding (Ctrl + `KeyCode::Char('i')` vs
+    /// `KeyCode::Tab`, Ctrl + Shift combinations, ...) will be produced by the
+    /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html) with
+    /// all their modifiers.
+    ///
+    /// # Notes
+    ///
+    /// * The flags are pushed on the terminal stack, the terminal is asked for the current flags
+    ///   on UNIX systems and `EnableOutcome::Unknown` is returned if it doesn't answer.
+    /// * Key repeats & releases (`KeyboardEnhancementFlags::REPORT_EVENT_TYPES`) are reported
+    ///   with the [`KeyEventKind`](enum.KeyEventKind.html), they're fed to the
+    ///   [`keyboard_state`](struct.TerminalInput.html#method.keyboard_state) tracker.
+    /// * It does nothing on Windows (`EnableOutcome::Unsupported`), the console reports
+    ///   the modifiers & key releases on its own.
+    pub fn enable_keyboard_enhancement(
+        &self,
+        flags: KeyboardEnhancementFlags,
+    ) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_keyboard_enhancement(flags)?;
+        modes::mode_enabled(Mode::KeyboardEnhancement);
+        Ok(outcome)
+    }
+
+    /// Disables the kitty keyboard protocol (pops the flags pushed by the
+    /// [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    /// method).
+    pub fn disable_keyboard_enhancement(&self) -> Result<()> {
+        self.input.disable_keyboard_enhancement()?;
+        modes::mode_disabled(Mode::KeyboardEnhancement);
+        Ok(())
+    }
+
+    /// Enables the xterm modifyOtherKeys mode (level 2).
+    ///
+    /// Modified keys which are ambiguous in the legacy encoding (Ctrl + `KeyCode::Char('i')` vs
+    /// `KeyCode::Tab`, Ctrl + `KeyCode::Enter`, ...) will be produced by the
+    /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html) with
+    /// all their modifiers. It's supported by more terminals than the kitty keyboard protocol
+    /// (see the
+    /// [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    /// method).
+    ///
+    /// # Notes
+    ///
+    /// * The mode state can't be queried, `EnableOutcome::Unknown` is returned on UNIX systems.
+    /// * It does nothing on Windows (`EnableOutcome::Unsupported`), the console reports
+    ///   the modifiers on its own.
+    pub fn enable_modify_other_keys(&self) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_modify_other_keys()?;
+        modes::mode_enabled(Mode::ModifyOtherKeys);
+        Ok(outcome)
+    }
+
+    /// Disables the xterm modifyOtherKeys mode.
+    pub fn disable_modify_other_keys(&self) -> Result<()> {
+        self.input.disable_modify_other_keys()?;
+        modes::mode_disabled(Mode::ModifyOtherKeys);
+        Ok(())
+    }
+
+    /// Enables the Windows Terminal win32-input-mode.
+    ///
+    /// The terminal (ConPTY) sends every key as the whole Windows key record
+    /// (`ESC [ Vk ; Sc ; Uc ; Kd ; Cs ; Rc _`), the keys will be produced by the
+    /// [`AsyncReader`](struct.AsyncReader.html)/[`SyncReader`](struct.SyncReader.html) with
+    /// all their modifiers & the key releases (see the
+    /// [`with_key_release_events`](struct.InputConfig.html#method.with_key_release_events)
+    /// method).
+    ///
+    /// # Notes
+    ///
+    /// * It's meant for the applications reading the Windows Terminal input over the VT stream
+    ///   (WSL, SSH, ...), the terminals without the mode ignore it.
+    /// * It does nothing on Windows (`EnableOutcome::Unsupported`), the key records are read
+    ///   from the console directly.
+    pub fn enable_win32_input_mode(&self) -> Result<EnableOutcome> {
+        let outcome = self.input.enable_win32_input_mode()?;
+        modes::mode_enabled(Mode::Win32Input);
+        Ok(outcome)
+    }
+
+    /// Disables the Windows Terminal win32-input-mode.
+    pub fn disable_win32_input_mode(&self) -> Result<()> {
+        self.input.disable_win32_input_mode()?;
+        modes::mode_disabled(Mode::Win32Input);
+        Ok(())
+    }
+
+    /// Switches the keyboard `protocol` at runtime.
+    ///
+    /// The enabled enhancements (kitty keyboard protocol, modifyOtherKeys, win32-input-mode)
+    /// are disabled and the `protocol` is enabled. Switch to the `KeyboardProtocol::Legacy`
+    /// while an embedded child terminal is focused & back to the enhanced protocol in
+    /// the editor view, ...
+    ///
+    /// # Notes
+    ///
+    /// * The parser applies the kitty keyboard protocol flags once the terminal reports them,
+    ///   the keys typed before the terminal switched the protocol are parsed & tracked
+    ///   (key releases, [`keyboard_state`](struct.TerminalInput.html#method.keyboard_state))
+    ///   with the previous flags.
+    /// * The `KeyboardProtocol::Legacy` is always `EnableOutcome::Supported`, see the
+    ///   [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    ///   & [`enable_modify_other_keys`](struct.TerminalInput.html#method.enable_modify_other_keys)
+    ///   methods for the other outcomes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, KeyboardEnhancementFlags, KeyboardProtocol, RawScreen};
+    ///
+    /// let _raw = RawScreen::into_raw_mode();
+    /// let input = input();
+    ///
+    /// // Editor view
+    /// let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+    /// input.set_keyboard_protocol(KeyboardProtocol::Enhanced(flags)).unwrap();
+    ///
+    /// // Embedded terminal focused
+    /// input.set_keyboard_protocol(KeyboardProtocol::Legacy).unwrap();
+    /// ```
+    pub fn set_keyboard_protocol(&self, protocol: KeyboardProtocol) -> Result<EnableOutcome> {
+        if modes::is_mode_enabled(Mode::KeyboardEnhancement) {
+            self.disable_keyboard_enhancement()?;
+        }
+        if modes::is_mode_enabled(Mode::ModifyOtherKeys) {
+            self.disable_modify_other_keys()?;
+        }
+        if modes::is_mode_enabled(Mode::Win32Input) {
+            self.disable_win32_input_mode()?;
+        }
+
+        match protocol {
+            KeyboardProtocol::Legacy => Ok(EnableOutcome::Supported),
+            KeyboardProtocol::ModifyOtherKeys => self.enable_modify_other_keys(),
+            KeyboardProtocol::Enhanced(flags) => self.enable_keyboard_enhancement(flags),
+            KeyboardProtocol::Win32Input => self.enable_win32_input_mode(),
+        }
+    }
+
+    /// Returns the active keyboard protocol.
+    ///
+    /// The kitty keyboard protocol is active once the terminal reports the flags, it's
+    /// always the `KeyboardProtocol::Legacy` on Windows (the console reports the key records).
+    pub fn keyboard_protocol(&self) -> KeyboardProtocol {
+        #[cfg(unix)]
+        {
+            let flags = crate::sys::ansi::keyboard_enhancement_flags();
+            if !flags.is_empty() {
+                return KeyboardProtocol::Enhanced(flags);
+            }
+            if modes::is_mode_enabled(Mode::ModifyOtherKeys) {
+                return KeyboardProtocol::ModifyOtherKeys;
+            }
+            if modes::is_mode_enabled(Mode::Win32Input) {
+                return KeyboardProtocol::Win32Input;
+            }
+        }
+
+        KeyboardProtocol::Legacy
+    }
+
+    /// Asks the terminal for the termcap/terminfo capability `name` (XTGETTCAP).
+    ///
+    /// Returns the capability value or `None` if the terminal doesn't know the capability or
+    /// it doesn't answer.
+    ///
+    /// # Notes
+    ///
+    /// * It requires enabled raw mode (see the
+    ///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more).
+    /// * It does nothing on Windows (`None`).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, RawScreen, Result};
+    ///
+    /// fn main() -> Result<()> {
+    ///     let _raw = RawScreen::into_raw_mode()?;
+    ///
+    ///     if let Some(colors) = input().query_termcap("colors")? {
+    ///         println!("Number of colors: {}\r", colors);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn query_termcap(&self, name: &str) -> Result<Option<String>> {
+        self.input.query_termcap(name)
+    }
+
+    /// Asks the terminal for the cursor position (DSR).
+    ///
+    /// Returns the zero-based (`x`, `y`) position or `None` if the terminal doesn't answer.
+    /// The report is delivered to this call only (it isn't broadcast to the readers), the
+    /// concurrent queries get their own reports.
+    ///
+    /// # Notes
+    ///
+    /// * It requires enabled raw mode (see the
+    ///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more).
+    /// * The console screen buffer is asked on Windows.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, RawScreen, Result};
+    ///
+    /// fn main() -> Result<()> {
+    ///     let _raw = RawScreen::into_raw_mode()?;
+    ///
+    ///     if let Some((x, y)) = input().query_cursor_position()? {
+    ///         println!("Cursor at {}x{}\r", x, y);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn query_cursor_position(&self) -> Result<Option<(u16, u16)>> {
+        self.input.query_cursor_position()
+    }
+
+    /// Returns the identification of the terminal (XTVERSION & DA2), it's cached.
+    ///
+    /// See the [`terminal_info`](fn.terminal_info.html) function.
+    pub fn terminal_info(&self) -> Result<TerminalInfo> {
+        terminal_info::identify_terminal(&self.input)
+    }
+
+    /// Reads all the available input without blocking and feeds all the readers.
+    ///
+    /// It must be called periodically if the input is read in the
+    /// [`ReadMode::Pump`](enum.ReadMode.html#variant.Pump) mode, no input is read otherwise.
+    /// Returns the number of read events.
+    ///
+    /// # Notes
+    ///
+    /// * It does nothing in the [`ReadMode::Poll`](enum.ReadMode.html#variant.Poll) mode and
+    ///   on Windows.
+    /// * Use the [`AsyncReader`](struct.AsyncReader.html) in the `ReadMode::Pump` mode. The
+    ///   [`SyncReader`](struct.SyncReader.html) blocks forever if there's no one else pumping.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{thread, time::Duration};
+    /// use crossterm_input::{input, ReadMode};
+    ///
+    /// let input = input();
+    /// input.set_config(input.config().with_read_mode(ReadMode::Pump));
+    ///
+    /// let mut async_stdin = input.read_async();
+    ///
+    /// loop {
+    ///     input.pump().expect("Unable to read the input");
+    ///
+    ///     while let Some(event) = async_stdin.next() {
+    ///         /* Check which event occurred here */
+    ///     }
+    ///
+    ///     thread::sleep(Duration::from_millis(50));
+    /// }
+    /// ```
+    pub fn pump(&self) -> Result<usize> {
+        self.input.pump()
+    }
+
+    /// Measures the keyboard auto-repeat delay & interval while the user holds a key.
+    ///
+    /// Waits up to `timeout` for the first key event and then collects the repeated events
+    /// until the key is released. Returns `None` if the key wasn't held long enough to repeat
+    /// at least twice. It's useful to calibrate your own key-held logic on platforms which don't
+    /// report key releases.
+    ///
+    /// # Notes
+    ///
+    /// * It requires enabled raw mode (see the
+    ///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more).
+    /// * It blocks until the measurement is done.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use crossterm_input::{input, RawScreen};
+    ///
+    /// let _raw = RawScreen::into_raw_mode();
+    ///
+    /// println!("Hold any key ...");
+    /// if let Some(repeat) = input().measure_key_repeat(Duration::from_secs(10)) {
+    ///     println!("delay: {:?}, rate: {:.1}/s", repeat.delay, repeat.rate());
+    /// }
+    /// ```
+    pub fn measure_key_repeat(&self, timeout: Duration) -> Option<KeyRepeat> {
+        repeat::measure_key_repeat(&mut self.read_async(), timeout)
+    }
+
+    /// Returns a snapshot of the currently held keys.
+    ///
+    /// It allows to handle the movement in terminal games (`while W held`, ...). The tracking
+    /// must be enabled with the
+    /// [`InputConfig::with_keyboard_state_tracking`](struct.InputConfig.html#method.with_keyboard_state_tracking)
+    /// method and the keys are tracked only while there's a reader reading the input.
+    ///
+    /// # Notes
+    ///
+    /// * Key releases are reported on UNIX systems only if the kitty keyboard protocol is enabled
+    ///   with the `KeyboardEnhancementFlags::REPORT_EVENT_TYPES` flag (see the
+    ///   [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    ///   method), no key is held otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, KeyCode, KeyEvent};
+    ///
+    /// let input = input();
+    /// input.set_config(input.config().with_keyboard_state_tracking(true));
+    ///
+    /// let mut reader = input.read_async();
+    ///
+    /// loop {
+    ///     let _ = reader.drain_frame();
+    ///
+    ///     if input.keyboard_state().is_held(&KeyEvent::from(KeyCode::Char('w'))) {
+    ///         /* Move forward */
+    ///     }
+    /// }
+    /// ```
+    pub fn keyboard_state(&self) -> KeyboardState {
+        keyboard_state::keyboard_state()
+    }
+
+    /// Returns a snapshot of the currently pressed mouse buttons & the last known mouse position.
+    ///
+    /// It allows widgets to check if the mouse button is pressed over them without replaying
+    /// the event history. The tracking must be enabled with the
+    /// [`InputConfig::with_mouse_state_tracking`](struct.InputConfig.html#method.with_mouse_state_tracking)
+    /// method and the mouse is tracked only while there's a reader reading the input.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_input::{input, MouseButton};
+    ///
+    /// let input = input();
+    /// input.set_config(input.config().with_mouse_state_tracking(true));
+    /// input.enable_mouse_mode().unwrap();
+    ///
+    /// let mut reader = input.read_async();
+    /// let _ = reader.drain_frame();
+    ///
+    /// let state = input.mouse_state();
+    /// if state.is_pressed(MouseButton::Left) && state.position() == Some((0, 0)) {
+    ///     /* Left button down over the upper left corner */
+    /// }
+    /// ```
+    #[cfg(not(feature = "keyboard-only"))]
+    pub fn mouse_state(&self) -> MouseState {
+        mouse_state::mouse_state()
+    }
+
+    /// Watches the terminal mode (raw mode, ...) and calls the `callback` when someone else
+    /// (another library, ...) changes it.
+    ///
+    /// The current mode is the expected one. It's compared every `interval` and the `callback`
+    /// is called once the mode diverges. The `callback` is called again only after the expected
+    /// mode is restored (by the application re-asserting it, ...) and changed again.
+    ///
+    /// # Notes
+    ///
+    /// * The termios is compared on UNIX, the console input mode on Windows.
+    /// * A thread is spawned to compare the mode.
+    /// * The thread is cleaned up when you drop the [`ModeWatch`](struct.ModeWatch.html).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use crossterm_input::{input, RawScreen};
+    ///
+    /// let _raw = RawScreen::into_raw_mode();
+    ///
+    /// let _watch = input().watch_mode(Duration::from_millis(500), || {
+    ///     eprintln!("Someone changed the terminal mode");
+    /// });
+    /// ```
+    pub fn watch_mode<F>(&self, interval: Duration, callback: F) -> Result<ModeWatch>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        ModeWatch::new(interval, callback)
+    }
+
+    /// Returns the current input configuration.
+    pub fn config(&self) -> InputConfig {
+        config::input_config()
+    }
+
+    /// Sets the input configuration.
+    ///
+    /// The configuration is shared by all the readers and it's applied to all the events
+    /// read after this call.
+    pub fn set_config(&self, config: InputConfig) {
+        config::set_input_config(config)
     }
 }
 
@@ -408,6 +1544,9 @@ impl TerminalInput {
 /// // if you're using the `crossterm` crate with the `input` feature enabled.
 /// use crossterm_input::{input, RawScreen, Result};
 ///
+/// # #[cfg(feature = "keyboard-only")]
+/// # fn main() {}
+/// # #[cfg(not(feature = "keyboard-only"))]
 /// fn main() -> Result<()> {
 ///     let input = input();
 ///     // Read a single character
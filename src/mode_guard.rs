@@ -0,0 +1,161 @@
+//! Guards disabling the terminal modes on drop.
+
+use crossterm_utils::Result;
+
+#[cfg(not(feature = "keyboard-only"))]
+use crate::MouseCaptureOptions;
+use crate::{
+    input,
+    modes::{disable_modes, is_mode_enabled, Mode},
+    EnableOutcome, KeyboardEnhancementFlags,
+};
+
+/// Disables the `mode` on drop (if it's still enabled).
+#[derive(Debug)]
+struct ModeGuard {
+    mode: Mode,
+    outcome: EnableOutcome,
+}
+
+impl Drop for ModeGuard {
+    fn drop(&mut self) {
+        // The mode could be disabled already (`TerminalInput`, the last dropped reader, ...),
+        // the keyboard enhancement flags would be popped twice
+        if is_mode_enabled(self.mode) {
+            disable_modes(vec![self.mode]);
+        }
+    }
+}
+
+/// The mouse events capture.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{MouseCapture, RawScreen, Result};
+///
+/// fn main() -> Result<()> {
+///     let _raw = RawScreen::into_raw_mode()?;
+///     let _mouse = MouseCapture::enable()?;
+///
+///     // Read the mouse events, the capture is disabled even if the code panics
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+#[cfg(not(feature = "keyboard-only"))]
+pub struct MouseCapture;
+
+/// Disables the mouse events capture on drop.
+#[derive(Debug)]
+#[cfg(not(feature = "keyboard-only"))]
+pub struct MouseCaptureGuard(ModeGuard);
+
+#[cfg(not(feature = "keyboard-only"))]
+impl MouseCapture {
+    /// Enables the mouse events (see the
+    /// [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method).
+    pub fn enable() -> Result<MouseCaptureGuard> {
+        MouseCapture::enable_with(MouseCaptureOptions::new())
+    }
+
+    /// Enables the mouse events with the capture `options` (see the
+    /// [`enable_mouse_capture`](struct.TerminalInput.html#method.enable_mouse_capture) method).
+    pub fn enable_with(options: MouseCaptureOptions) -> Result<MouseCaptureGuard> {
+        let outcome = input().enable_mouse_capture(options)?;
+        Ok(MouseCaptureGuard(ModeGuard {
+            mode: Mode::Mouse,
+            outcome,
+        }))
+    }
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+impl MouseCaptureGuard {
+    /// Returns whether the terminal supports the mouse events.
+    pub fn outcome(&self) -> EnableOutcome {
+        self.0.outcome
+    }
+}
+
+/// The bracketed paste mode.
+#[derive(Debug)]
+pub struct BracketedPaste;
+
+/// Disables the bracketed paste mode on drop.
+#[derive(Debug)]
+pub struct BracketedPasteGuard(ModeGuard);
+
+impl BracketedPaste {
+    /// Enables the bracketed paste mode (see the
+    /// [`enable_bracketed_paste`](struct.TerminalInput.html#method.enable_bracketed_paste) method).
+    pub fn enable() -> Result<BracketedPasteGuard> {
+        let outcome = input().enable_bracketed_paste()?;
+        Ok(BracketedPasteGuard(ModeGuard {
+            mode: Mode::BracketedPaste,
+            outcome,
+        }))
+    }
+}
+
+impl BracketedPasteGuard {
+    /// Returns whether the terminal supports the bracketed paste mode.
+    pub fn outcome(&self) -> EnableOutcome {
+        self.0.outcome
+    }
+}
+
+/// The focus reporting mode.
+#[derive(Debug)]
+pub struct FocusReporting;
+
+/// Disables the focus reporting mode on drop.
+#[derive(Debug)]
+pub struct FocusReportingGuard(ModeGuard);
+
+impl FocusReporting {
+    /// Enables the focus reporting mode (see the
+    /// [`enable_focus_reporting`](struct.TerminalInput.html#method.enable_focus_reporting) method).
+    pub fn enable() -> Result<FocusReportingGuard> {
+        let outcome = input().enable_focus_reporting()?;
+        Ok(FocusReportingGuard(ModeGuard {
+            mode: Mode::FocusReporting,
+            outcome,
+        }))
+    }
+}
+
+impl FocusReportingGuard {
+    /// Returns whether the terminal supports the focus reporting mode.
+    pub fn outcome(&self) -> EnableOutcome {
+        self.0.outcome
+    }
+}
+
+/// The kitty keyboard protocol.
+#[derive(Debug)]
+pub struct KeyboardEnhancement;
+
+/// Pops the kitty keyboard protocol flags on drop.
+#[derive(Debug)]
+pub struct KeyboardEnhancementGuard(ModeGuard);
+
+impl KeyboardEnhancement {
+    /// Pushes the kitty keyboard protocol `flags` (see the
+    /// [`enable_keyboard_enhancement`](struct.TerminalInput.html#method.enable_keyboard_enhancement)
+    /// method).
+    pub fn enable(flags: KeyboardEnhancementFlags) -> Result<KeyboardEnhancementGuard> {
+        let outcome = input().enable_keyboard_enhancement(flags)?;
+        Ok(KeyboardEnhancementGuard(ModeGuard {
+            mode: Mode::KeyboardEnhancement,
+            outcome,
+        }))
+    }
+}
+
+impl KeyboardEnhancementGuard {
+    /// Returns whether the terminal supports the kitty keyboard protocol.
+    pub fn outcome(&self) -> EnableOutcome {
+        self.0.outcome
+    }
+}
@@ -0,0 +1,128 @@
+//! A watch of the terminal mode (raw mode, ...) changed by someone else.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossterm_utils::Result;
+
+/// A comparable snapshot of the terminal mode (termios on UNIX, console mode on Windows).
+pub(crate) type TerminalMode = Vec<u64>;
+
+/// Returns the current terminal mode.
+fn terminal_mode() -> Result<TerminalMode> {
+    #[cfg(unix)]
+    let mode = crate::sys::unix::tty_mode();
+
+    #[cfg(windows)]
+    let mode = crate::input::windows::console_mode();
+
+    mode
+}
+
+/// Detects when the terminal mode diverges from the expected one.
+struct ModeDivergence {
+    expected: TerminalMode,
+    diverged: bool,
+}
+
+impl ModeDivergence {
+    fn new(expected: TerminalMode) -> ModeDivergence {
+        ModeDivergence {
+            expected,
+            diverged: false,
+        }
+    }
+
+    /// Returns `true` if the `current` mode diverged from the expected one since the
+    /// last check.
+    ///
+    /// The divergence is reported once, again only after the expected mode is restored
+    /// and changed again.
+    fn check(&mut self, current: &TerminalMode) -> bool {
+        let diverged = *current != self.expected;
+        let report = diverged && !self.diverged;
+        self.diverged = diverged;
+        report
+    }
+}
+
+/// A terminal mode watch.
+///
+/// The watch is stopped once you drop the `ModeWatch`. See the
+/// [`TerminalInput::watch_mode`](struct.TerminalInput.html#method.watch_mode) method.
+pub struct ModeWatch {
+    shutdown: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ModeWatch {
+    /// Creates a new `ModeWatch` expecting the current terminal mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - how often the mode is compared.
+    /// * `callback` - called when the mode diverges from the expected one.
+    pub(crate) fn new<F>(interval: Duration, mut callback: F) -> Result<ModeWatch>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let mut divergence = ModeDivergence::new(terminal_mode()?);
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let shutdown = shutdown.clone();
+            move || {
+                while !shutdown.load(Ordering::SeqCst) {
+                    thread::sleep(interval);
+
+                    // Errors are ignored, the terminal can be temporarily unavailable
+                    if let Ok(mode) = terminal_mode() {
+                        if divergence.check(&mode) {
+                            callback();
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(ModeWatch {
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for ModeWatch {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        // Safe to unwrap, it's taken in the drop() only
+        let handle = self.handle.take().unwrap();
+        let _ = handle.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_divergence() {
+        let mut divergence = ModeDivergence::new(vec![1, 2]);
+
+        assert!(!divergence.check(&vec![1, 2]));
+        assert!(divergence.check(&vec![1, 3]));
+        // Reported once only
+        assert!(!divergence.check(&vec![1, 3]));
+        assert!(!divergence.check(&vec![1, 4]));
+        // Expected mode restored & changed again
+        assert!(!divergence.check(&vec![1, 2]));
+        assert!(divergence.check(&vec![0, 2]));
+    }
+}
@@ -0,0 +1,263 @@
+//! A tracker of the enabled terminal modes & living readers.
+
+use std::sync::Mutex;
+
+use crossterm_screen::RawScreen;
+use lazy_static::lazy_static;
+
+use crate::{
+    config::{with_input_config, InputConfig},
+    TerminalInput,
+};
+
+lazy_static! {
+    /// The enabled modes & the number of living readers.
+    static ref MODES: Mutex<Modes> = Mutex::new(Modes::default());
+    /// The raw mode enabled for the living readers (`RawModeFallback::Enable`).
+    static ref READERS_RAW_SCREEN: Mutex<Option<RawScreen>> = Mutex::new(None);
+}
+
+/// Represents a terminal mode enabled with the `TerminalInput`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Mode {
+    Mouse,
+    Locator,
+    BracketedPaste,
+    FocusReporting,
+    KeyboardEnhancement,
+    ModifyOtherKeys,
+    Win32Input,
+}
+
+#[derive(Debug, Default)]
+struct Modes {
+    readers: usize,
+    mouse: bool,
+    locator: bool,
+    bracketed_paste: bool,
+    focus_reporting: bool,
+    keyboard_enhancement: bool,
+    modify_other_keys: bool,
+    win32_input: bool,
+}
+
+impl Modes {
+    fn set(&mut self, mode: Mode, enabled: bool) {
+        match mode {
+            Mode::Mouse => self.mouse = enabled,
+            Mode::Locator => self.locator = enabled,
+            Mode::BracketedPaste => self.bracketed_paste = enabled,
+            Mode::FocusReporting => self.focus_reporting = enabled,
+            Mode::KeyboardEnhancement => self.keyboard_enhancement = enabled,
+            Mode::ModifyOtherKeys => self.modify_other_keys = enabled,
+            Mode::Win32Input => self.win32_input = enabled,
+        };
+    }
+
+    /// Returns `true` if the `mode` is enabled.
+    fn is_enabled(&self, mode: Mode) -> bool {
+        match mode {
+            Mode::Mouse => self.mouse,
+            Mode::Locator => self.locator,
+            Mode::BracketedPaste => self.bracketed_paste,
+            Mode::FocusReporting => self.focus_reporting,
+            Mode::KeyboardEnhancement => self.keyboard_enhancement,
+            Mode::ModifyOtherKeys => self.modify_other_keys,
+            Mode::Win32Input => self.win32_input,
+        }
+    }
+
+    /// Returns all the enabled modes.
+    fn enabled(&self) -> Vec<Mode> {
+        let mut modes = Vec::new();
+        if self.mouse {
+            modes.push(Mode::Mouse);
+        }
+        if self.locator {
+            modes.push(Mode::Locator);
+        }
+        if self.bracketed_paste {
+            modes.push(Mode::BracketedPaste);
+        }
+        if self.focus_reporting {
+            modes.push(Mode::FocusReporting);
+        }
+        if self.keyboard_enhancement {
+            modes.push(Mode::KeyboardEnhancement);
+        }
+        if self.modify_other_keys {
+            modes.push(Mode::ModifyOtherKeys);
+        }
+        if self.win32_input {
+            modes.push(Mode::Win32Input);
+        }
+        modes
+    }
+}
+
+/// Marks the `mode` as enabled.
+pub(crate) fn mode_enabled(mode: Mode) {
+    MODES.lock().unwrap().set(mode, true);
+}
+
+/// Returns `true` if the `mode` is enabled.
+pub(crate) fn is_mode_enabled(mode: Mode) -> bool {
+    MODES.lock().unwrap().is_enabled(mode)
+}
+
+/// Marks the `mode` as disabled.
+pub(crate) fn mode_disabled(mode: Mode) {
+    MODES.lock().unwrap().set(mode, false);
+}
+
+/// Returns all the enabled modes, nothing if the tracker is locked (the panic hook runs with
+/// the lock held if the tracker panicked).
+pub(crate) fn try_enabled_modes() -> Vec<Mode> {
+    match MODES.try_lock() {
+        Ok(modes) => modes.enabled(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Returns the number of living readers.
+#[cfg_attr(windows, allow(dead_code))]
+pub(crate) fn reader_count() -> usize {
+    MODES.lock().unwrap().readers
+}
+
+/// Disables all the enabled modes.
+pub(crate) fn disable_modes(modes: Vec<Mode>) {
+    // One write on UNIX, the terminal isn't left with partially disabled modes
+    #[cfg(unix)]
+    let _ = crate::sys::unix::batch_csi_sequences(|| disable_each_mode(modes));
+    #[cfg(windows)]
+    disable_each_mode(modes);
+}
+
+fn disable_each_mode(modes: Vec<Mode>) {
+    let input = TerminalInput::new();
+
+    for mode in modes {
+        // Errors are ignored, there's nothing we can do in the drop
+        let _ = match mode {
+            #[cfg(not(feature = "keyboard-only"))]
+            Mode::Mouse => input.disable_mouse_mode(),
+            #[cfg(not(feature = "keyboard-only"))]
+            Mode::Locator => input.disable_locator_mode(),
+            // The mouse modes can't be enabled in the keyboard-only build
+            #[cfg(feature = "keyboard-only")]
+            Mode::Mouse | Mode::Locator => Ok(()),
+            Mode::BracketedPaste => input.disable_bracketed_paste(),
+            Mode::FocusReporting => input.disable_focus_reporting(),
+            Mode::KeyboardEnhancement => input.disable_keyboard_enhancement(),
+            Mode::ModifyOtherKeys => input.disable_modify_other_keys(),
+            Mode::Win32Input => input.disable_win32_input_mode(),
+        };
+    }
+}
+
+/// A living reader marker.
+///
+/// All the enabled modes are disabled when the last reader is dropped (if configured).
+#[derive(Debug)]
+pub(crate) struct ReaderGuard;
+
+impl ReaderGuard {
+    pub(crate) fn new() -> ReaderGuard {
+        MODES.lock().unwrap().readers += 1;
+        #[cfg(unix)]
+        apply_raw_mode_fallback();
+        ReaderGuard
+    }
+}
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        // The raw mode enabled for the readers is disabled last (after the modes)
+        let (modes, _raw_screen) = {
+            let mut guard = MODES.lock().unwrap();
+            guard.readers -= 1;
+
+            if guard.readers > 0 {
+                return;
+            }
+
+            let modes = if with_input_config(InputConfig::disable_modes_on_drop) {
+                guard.enabled()
+            } else {
+                Vec::new()
+            };
+
+            (modes, READERS_RAW_SCREEN.lock().unwrap().take())
+        };
+
+        // Modes are disabled with unlocked `MODES`, the `TerminalInput` marks them as disabled
+        if !modes.is_empty() {
+            disable_modes(modes);
+        }
+    }
+}
+
+/// Checks the raw mode of the new reader & applies the configured `RawModeFallback`.
+#[cfg(unix)]
+fn apply_raw_mode_fallback() {
+    use std::io;
+
+    use crossterm_utils::ErrorKind;
+
+    use crate::{error_hook::report_error, sys::unix::is_raw_mode_enabled, RawModeFallback};
+
+    let fallback = with_input_config(InputConfig::raw_mode_fallback);
+    if fallback == RawModeFallback::Proceed {
+        return;
+    }
+
+    // The tty can't be checked (no terminal, ...), the reading reports the error
+    if is_raw_mode_enabled().unwrap_or(true) {
+        return;
+    }
+
+    match fallback {
+        RawModeFallback::Enable => {
+            let mut raw_screen = READERS_RAW_SCREEN.lock().unwrap();
+            if raw_screen.is_none() {
+                match RawScreen::into_raw_mode() {
+                    Ok(enabled) => *raw_screen = Some(enabled),
+                    Err(error) => {
+                        drop(raw_screen);
+                        report_error(&error);
+                    }
+                };
+            }
+        }
+        RawModeFallback::Warn => report_error(&ErrorKind::IoError(io::Error::new(
+            io::ErrorKind::Other,
+            "The raw mode isn't enabled, the input is line-buffered",
+        ))),
+        RawModeFallback::Proceed => unreachable!(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_modes() {
+        let mut modes = Modes::default();
+        assert!(modes.enabled().is_empty());
+
+        modes.set(Mode::Mouse, true);
+        modes.set(Mode::Locator, true);
+        modes.set(Mode::BracketedPaste, true);
+        assert_eq!(
+            modes.enabled(),
+            vec![Mode::Mouse, Mode::Locator, Mode::BracketedPaste]
+        );
+
+        modes.set(Mode::Mouse, false);
+        assert_eq!(modes.enabled(), vec![Mode::Locator, Mode::BracketedPaste]);
+        assert!(!modes.is_enabled(Mode::Mouse));
+        assert!(modes.is_enabled(Mode::Locator));
+    }
+}
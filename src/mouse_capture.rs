@@ -0,0 +1,200 @@
+//! Options of the captured mouse events.
+
+use crate::{
+    sequences::{
+        MOUSE_ENABLE_CLICKS, MOUSE_ENABLE_DRAG, MOUSE_ENABLE_MOTION, MOUSE_ENABLE_SGR,
+        MOUSE_ENABLE_URXVT,
+    },
+    MouseTracking,
+};
+
+/// Represents which mouse events are captured & how they're encoded.
+///
+/// See the [`enable_mouse_capture`](struct.TerminalInput.html#method.enable_mouse_capture)
+/// method. Only the modes of the enabled options are written to the terminal.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{input, MouseCaptureOptions, RawScreen};
+///
+/// let _raw = RawScreen::into_raw_mode();
+///
+/// // Clicks only, no drag events
+/// let options = MouseCaptureOptions::new().with_drag(false);
+/// input().enable_mouse_capture(options).unwrap();
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct MouseCaptureOptions {
+    clicks: bool,
+    drag: bool,
+    motion: bool,
+    sgr: bool,
+    urxvt: bool,
+}
+
+impl Default for MouseCaptureOptions {
+    fn default() -> MouseCaptureOptions {
+        MouseCaptureOptions {
+            clicks: true,
+            drag: true,
+            motion: false,
+            sgr: true,
+            urxvt: true,
+        }
+    }
+}
+
+impl MouseCaptureOptions {
+    /// Creates a new `MouseCaptureOptions` with the default values (the options of the
+    /// [`enable_mouse_mode`](struct.TerminalInput.html#method.enable_mouse_mode) method).
+    pub fn new() -> MouseCaptureOptions {
+        MouseCaptureOptions::default()
+    }
+
+    /// Returns whether the button presses & releases are captured.
+    pub fn clicks(&self) -> bool {
+        self.clicks
+    }
+
+    /// Captures the button presses & releases (`?1000h`).
+    ///
+    /// The default is `true`.
+    pub fn with_clicks(mut self, enabled: bool) -> MouseCaptureOptions {
+        self.clicks = enabled;
+        self
+    }
+
+    /// Returns whether the motion with a pressed button is captured.
+    pub fn drag(&self) -> bool {
+        self.drag
+    }
+
+    /// Captures the motion with a pressed button as the `MouseEventKind::Drag` (`?1002h`).
+    ///
+    /// The default is `true`. Terminals report the clicks as well.
+    pub fn with_drag(mut self, enabled: bool) -> MouseCaptureOptions {
+        self.drag = enabled;
+        self
+    }
+
+    /// Returns whether all the motion is captured.
+    pub fn motion(&self) -> bool {
+        self.motion
+    }
+
+    /// Captures all the motion, the motion without a pressed button is reported as
+    /// the `MouseEventKind::Moved` (`?1003h`).
+    ///
+    /// The default is `false`. Terminals report the clicks & drag as well.
+    pub fn with_motion(mut self, enabled: bool) -> MouseCaptureOptions {
+        self.motion = enabled;
+        self
+    }
+
+    /// Returns whether the SGR encoding is requested.
+    pub fn sgr(&self) -> bool {
+        self.sgr
+    }
+
+    /// Requests the SGR encoding (`?1006h`) reporting the released button & coordinates
+    /// over 223.
+    ///
+    /// The default is `true`. Disable it for terminals which don't support it.
+    pub fn with_sgr(mut self, enabled: bool) -> MouseCaptureOptions {
+        self.sgr = enabled;
+        self
+    }
+
+    /// Returns whether the urxvt encoding is requested.
+    pub fn urxvt(&self) -> bool {
+        self.urxvt
+    }
+
+    /// Requests the urxvt encoding (`?1015h`) reporting coordinates over 223 in terminals
+    /// without the SGR encoding.
+    ///
+    /// The default is `true`.
+    pub fn with_urxvt(mut self, enabled: bool) -> MouseCaptureOptions {
+        self.urxvt = enabled;
+        self
+    }
+
+    /// Returns the sequences enabling the options.
+    pub(crate) fn enable_sequences(&self) -> Vec<&'static str> {
+        let modes = [
+            (self.clicks, MOUSE_ENABLE_CLICKS),
+            (self.drag, MOUSE_ENABLE_DRAG),
+            (self.motion, MOUSE_ENABLE_MOTION),
+            (self.urxvt, MOUSE_ENABLE_URXVT),
+            (self.sgr, MOUSE_ENABLE_SGR),
+        ];
+
+        modes
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, sequence)| *sequence)
+            .collect()
+    }
+
+    /// Returns the most specific enabled tracking mode (if any).
+    ///
+    /// The terminal is asked about this mode to check if the mouse is supported.
+    pub(crate) fn tracking_mode(&self) -> Option<u16> {
+        if self.motion {
+            Some(1003)
+        } else if self.drag {
+            Some(1002)
+        } else if self.clicks {
+            Some(1000)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<MouseTracking> for MouseCaptureOptions {
+    fn from(tracking: MouseTracking) -> Self {
+        MouseCaptureOptions::new().with_motion(tracking == MouseTracking::AnyMotion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sequences::{MOUSE_ENABLE_CLICKS, MOUSE_ENABLE_DRAG, MOUSE_ENABLE_SGR, MOUSE_ENABLE_URXVT},
+        MouseTracking,
+    };
+
+    use super::MouseCaptureOptions;
+
+    #[test]
+    fn test_enable_sequences() {
+        let options = MouseCaptureOptions::new();
+        assert_eq!(
+            options.enable_sequences(),
+            vec![
+                MOUSE_ENABLE_CLICKS,
+                MOUSE_ENABLE_DRAG,
+                MOUSE_ENABLE_URXVT,
+                MOUSE_ENABLE_SGR
+            ]
+        );
+        assert_eq!(options.tracking_mode(), Some(1002));
+
+        let options = MouseCaptureOptions::new()
+            .with_drag(false)
+            .with_sgr(false)
+            .with_urxvt(false);
+        assert_eq!(options.enable_sequences(), vec![MOUSE_ENABLE_CLICKS]);
+        assert_eq!(options.tracking_mode(), Some(1000));
+
+        let options = MouseCaptureOptions::from(MouseTracking::AnyMotion);
+        assert_eq!(options.tracking_mode(), Some(1003));
+
+        let options = MouseCaptureOptions::new()
+            .with_clicks(false)
+            .with_drag(false);
+        assert_eq!(options.tracking_mode(), None);
+    }
+}
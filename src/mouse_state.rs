@@ -0,0 +1,117 @@
+//! A tracker of the currently pressed mouse buttons & the mouse position.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::{
+    config::{with_input_config, InputConfig},
+    MouseButton, MouseEvent, MouseEventKind,
+};
+
+lazy_static! {
+    /// The current mouse state.
+    static ref MOUSE_STATE: Mutex<MouseState> = Mutex::new(MouseState::default());
+}
+
+/// Updates the mouse state with the `mouse_event` (if the tracking is enabled).
+pub(crate) fn track_mouse_event(mouse_event: &MouseEvent) {
+    if with_input_config(InputConfig::mouse_state_tracking) {
+        MOUSE_STATE.lock().unwrap().apply(mouse_event);
+    }
+}
+
+/// Returns a snapshot of the mouse state.
+pub(crate) fn mouse_state() -> MouseState {
+    MOUSE_STATE.lock().unwrap().clone()
+}
+
+/// A snapshot of the currently pressed mouse buttons & the last known mouse position.
+///
+/// See the [`TerminalInput::mouse_state`](struct.TerminalInput.html#method.mouse_state)
+/// method.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct MouseState {
+    pressed: HashSet<MouseButton>,
+    position: Option<(u16, u16)>,
+}
+
+impl MouseState {
+    /// Updates the state with the `mouse_event`.
+    fn apply(&mut self, mouse_event: &MouseEvent) {
+        match (mouse_event.kind, mouse_event.button) {
+            (MouseEventKind::Unknown, _) => return,
+            // Wheel has no pressed state
+            (MouseEventKind::Press, Some(MouseButton::WheelUp))
+            | (MouseEventKind::Press, Some(MouseButton::WheelDown)) => {}
+            (MouseEventKind::Press, Some(button)) | (MouseEventKind::DoubleClick, Some(button)) => {
+                self.pressed.insert(button);
+            }
+            (MouseEventKind::Release, Some(button)) => {
+                self.pressed.remove(&button);
+            }
+            // Legacy encodings don't report which button was released
+            (MouseEventKind::Release, None) => self.pressed.clear(),
+            _ => {}
+        };
+        self.position = Some((mouse_event.column, mouse_event.row));
+    }
+
+    /// Returns `true` if the mouse `button` is pressed.
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// Returns the last known mouse position (if any mouse event was read).
+    pub fn position(&self) -> Option<(u16, u16)> {
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mouse_state() {
+        let mut state = MouseState::default();
+        assert_eq!(state.position(), None);
+
+        let event = |kind, button, x, y| MouseEvent::new(kind, button, x, y);
+
+        state.apply(&event(MouseEventKind::Press, Some(MouseButton::Left), 1, 2));
+        state.apply(&event(
+            MouseEventKind::Press,
+            Some(MouseButton::WheelUp),
+            1,
+            2,
+        ));
+        state.apply(&event(
+            MouseEventKind::Press,
+            Some(MouseButton::Right),
+            1,
+            2,
+        ));
+        assert!(state.is_pressed(MouseButton::Left));
+        assert!(!state.is_pressed(MouseButton::WheelUp));
+        assert_eq!(state.position(), Some((1, 2)));
+
+        state.apply(&event(MouseEventKind::Drag, Some(MouseButton::Left), 3, 4));
+        assert!(state.is_pressed(MouseButton::Left));
+        assert_eq!(state.position(), Some((3, 4)));
+
+        state.apply(&event(
+            MouseEventKind::Release,
+            Some(MouseButton::Right),
+            3,
+            4,
+        ));
+        assert!(state.is_pressed(MouseButton::Left));
+        assert!(!state.is_pressed(MouseButton::Right));
+
+        state.apply(&event(MouseEventKind::Release, None, 5, 6));
+        assert!(!state.is_pressed(MouseButton::Left));
+        assert_eq!(state.position(), Some((5, 6)));
+    }
+}
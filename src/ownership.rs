@@ -0,0 +1,88 @@
+//! A process-wide advisory input ownership.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of currently living `InputOwnership` tokens.
+static OWNERS: AtomicUsize = AtomicUsize::new(0);
+
+/// An advisory process-wide input ownership token.
+///
+/// Multiple libraries in one process can read the input (an application with an embedded REPL,
+/// ...). Every such library should acquire the token before it starts reading and keep it around
+/// as long as it reads. The second (and any further) acquisition detects the conflict and can
+/// share the existing readers instead of creating new ones.
+///
+/// The ownership is released when the token is dropped.
+///
+/// # Notes
+///
+/// * It's advisory only, no reader checks it.
+/// * All the UNIX readers share one reading thread, it's safe to create them from multiple
+///   libraries even without the token. Windows readers compete for the console events.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::InputOwnership;
+///
+/// let ownership = InputOwnership::acquire();
+///
+/// if !ownership.is_primary() {
+///     // Someone else is reading the input already, hook into their event loop
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InputOwnership {
+    primary: bool,
+}
+
+impl InputOwnership {
+    /// Acquires the input ownership.
+    pub fn acquire() -> InputOwnership {
+        InputOwnership {
+            primary: OWNERS.fetch_add(1, Ordering::SeqCst) == 0,
+        }
+    }
+
+    /// Returns `true` if there was no other living token when this one was acquired.
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /// Returns `true` if there's at least one living token.
+    pub fn is_acquired() -> bool {
+        OWNERS.load(Ordering::SeqCst) > 0
+    }
+}
+
+impl Drop for InputOwnership {
+    fn drop(&mut self) {
+        OWNERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_ownership() {
+        assert!(!InputOwnership::is_acquired());
+
+        let first = InputOwnership::acquire();
+        let second = InputOwnership::acquire();
+
+        assert!(first.is_primary());
+        assert!(!second.is_primary());
+
+        drop(first);
+        assert!(InputOwnership::is_acquired());
+
+        // Not primary even if the first one was dropped already
+        assert!(!second.is_primary());
+
+        drop(second);
+        assert!(!InputOwnership::is_acquired());
+        assert!(InputOwnership::acquire().is_primary());
+    }
+}
@@ -0,0 +1,264 @@
+//! An incremental parser of the terminal input bytes.
+
+use crate::sys::ansi::{parse_session_event, ParserState};
+use crate::InputEvent;
+
+/// An incremental parser of the terminal input bytes.
+///
+/// It parses the captured bytes without the TTY & the reading thread (proxy tools, terminal
+/// multiplexers, tests, ...). The bytes of an incomplete sequence are kept until the rest of
+/// the sequence is fed to the parser.
+///
+/// # Notes
+///
+/// * The [`InputConfig`](struct.InputConfig.html) is applied (control actions, mouse origin,
+///   key profile, ...).
+/// * The end of the fed bytes is the end of the input, the lone `ESC` at the end is
+///   the Esc key.
+/// * The keyboard & mouse state trackers aren't fed.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::{InputEvent, KeyCode, Parser};
+///
+/// let mut parser = Parser::new();
+///
+/// // Incomplete sequence
+/// assert_eq!(parser.advance(b"a\x1B[").count(), 1);
+///
+/// let events = parser.advance(b"A").collect::<Vec<_>>();
+/// assert_eq!(events, vec![InputEvent::Keyboard(KeyCode::Up.into())]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Parser {
+    /// Bytes of a partially parsed event.
+    buffer: Vec<u8>,
+    /// The state carried between the parsed events.
+    state: ParserState,
+}
+
+impl Parser {
+    /// Creates a new `Parser`.
+    pub fn new() -> Parser {
+        Parser::default()
+    }
+
+    /// Feeds the `bytes` to the parser and returns the parsed events.
+    ///
+    /// Malformed sequences are dropped.
+    pub fn advance(&mut self, bytes: &[u8]) -> impl Iterator<Item = InputEvent> {
+        let mut events = Vec::new();
+        self.parse_into(bytes, &mut events);
+        events.into_iter()
+    }
+
+    /// Feeds the `bytes` to the parser and appends the parsed events to the `events`.
+    ///
+    /// Malformed sequences are dropped. The `events` aren't cleared, reuse the same `Vec`
+    /// (and the parser) to avoid allocations in the tight loops, the parser buffer keeps its
+    /// capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossterm_input::{InputEvent, KeyCode, Parser};
+    ///
+    /// let mut parser = Parser::new();
+    /// let mut events = Vec::with_capacity(64);
+    ///
+    /// for chunk in &[&b"\x1B[A"[..], b"\x1B[B"] {
+    ///     events.clear();
+    ///     parser.parse_into(chunk, &mut events);
+    ///     assert_eq!(events.len(), 1);
+    /// }
+    /// assert_eq!(events, vec![InputEvent::Keyboard(KeyCode::Down.into())]);
+    /// ```
+    pub fn parse_into(&mut self, bytes: &[u8], events: &mut Vec<InputEvent>) {
+        for (index, byte) in bytes.iter().enumerate() {
+            // We need this information to distinguish between Esc key and possible
+            // Esc sequence.
+            let input_available = index + 1 < bytes.len();
+
+            self.buffer.push(*byte);
+            parse_session_event(&mut self.buffer, &mut self.state, input_available, events);
+        }
+    }
+
+    /// Returns `true` if there're bytes of an incomplete sequence.
+    pub fn is_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Drops the bytes of an incomplete sequence.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.state = ParserState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use crate::{InputEvent, KeyCode, KeyEvent, KeyModifiers, SequenceSubscription};
+
+    use super::Parser;
+
+    #[test]
+    fn test_advance_split_sequence() {
+        let mut parser = Parser::new();
+
+        assert_eq!(parser.advance(b"\x1B[1;").count(), 0);
+        assert!(parser.is_pending());
+        assert_eq!(
+            parser.advance(b"5Dx").collect::<Vec<_>>(),
+            vec![
+                InputEvent::Keyboard(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL)),
+                InputEvent::Keyboard(KeyCode::Char('x').into()),
+            ]
+        );
+        assert!(!parser.is_pending());
+    }
+
+    #[test]
+    fn test_advance_esc() {
+        let mut parser = Parser::new();
+
+        assert_eq!(
+            parser.advance(b"\x1B").collect::<Vec<_>>(),
+            vec![InputEvent::Keyboard(KeyCode::Esc.into())]
+        );
+        assert_eq!(
+            parser.advance(b"\x1Bb").collect::<Vec<_>>(),
+            vec![InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('b'),
+                KeyModifiers::ALT
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_advance_alt_p() {
+        let alt_p = InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('P'), KeyModifiers::ALT));
+
+        // Not a DCS sequence, the bytes following the ESC P are parsed again
+        assert_eq!(
+            Parser::new().advance(b"\x1BPx").collect::<Vec<_>>(),
+            vec![
+                alt_p.clone(),
+                InputEvent::Keyboard(KeyCode::Char('x').into())
+            ]
+        );
+        assert_eq!(
+            Parser::new().advance(b"\x1BP1+\x1B[A").collect::<Vec<_>>(),
+            vec![
+                alt_p,
+                InputEvent::Keyboard(KeyCode::Char('1').into()),
+                InputEvent::Keyboard(KeyCode::Char('+').into()),
+                InputEvent::Keyboard(KeyCode::Up.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_advance_alt_bracket() {
+        // Neither a notification nor a subscribed OSC sequence
+        assert_eq!(
+            Parser::new().advance(b"\x1B]x").collect::<Vec<_>>(),
+            vec![
+                InputEvent::Keyboard(KeyEvent::new(KeyCode::Char(']'), KeyModifiers::ALT)),
+                InputEvent::Keyboard(KeyCode::Char('x').into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_advance_subscribed_osc() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let _subscription = SequenceSubscription::register(&b"\x1B]1337;"[..], {
+            let received = received.clone();
+            move |sequence: &[u8]| received.lock().unwrap().push(sequence.to_vec())
+        });
+
+        // Passed through whatever the features, not parsed as the Alt + ] & the characters
+        assert_eq!(
+            Parser::new()
+                .advance(b"\x1B]1337;x\x07")
+                .collect::<Vec<_>>(),
+            vec![]
+        );
+        assert_eq!(*received.lock().unwrap(), vec![b"\x1B]1337;x\x07".to_vec()]);
+    }
+
+    #[test]
+    fn test_advance_zero_cursor_position() {
+        // Malformed, it's dropped
+        assert_eq!(
+            Parser::new().advance(b"\x1B[0;0Rx").collect::<Vec<_>>(),
+            vec![InputEvent::Keyboard(KeyCode::Char('x').into())]
+        );
+    }
+
+    #[test]
+    fn test_parse_into() {
+        let mut parser = Parser::new();
+        let mut events = vec![InputEvent::FocusGained];
+
+        parser.parse_into(b"a\x1B[", &mut events);
+        assert_eq!(
+            events,
+            vec![
+                InputEvent::FocusGained,
+                InputEvent::Keyboard(KeyCode::Char('a').into())
+            ]
+        );
+        assert!(parser.is_pending());
+
+        events.clear();
+        parser.parse_into(b"C", &mut events);
+        assert_eq!(events, vec![InputEvent::Keyboard(KeyCode::Right.into())]);
+        assert!(!parser.is_pending());
+    }
+
+    #[test]
+    fn test_advance_surrogate_pair() {
+        let mut parser = Parser::new();
+
+        // The win32-input-mode high surrogate is held by the parser, even if it's moved to
+        // another thread
+        assert_eq!(
+            parser.advance(b"\x1B[0;0;55357;1;0;1_").collect::<Vec<_>>(),
+            vec![InputEvent::Unknown]
+        );
+        let events =
+            thread::spawn(move || parser.advance(b"\x1B[0;0;56832;1;0;1_").collect::<Vec<_>>())
+                .join()
+                .unwrap();
+        assert_eq!(
+            events,
+            vec![InputEvent::Keyboard(KeyCode::Char('\u{1F600}').into())]
+        );
+
+        // Not paired with the high surrogate of another parser
+        assert_eq!(
+            Parser::new()
+                .advance(b"\x1B[0;0;56832;1;0;1_")
+                .collect::<Vec<_>>(),
+            vec![InputEvent::Unknown]
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut parser = Parser::new();
+
+        assert_eq!(parser.advance(b"\x1B[").count(), 0);
+        parser.reset();
+        assert_eq!(
+            parser.advance(b"A").collect::<Vec<_>>(),
+            vec![InputEvent::Keyboard(KeyCode::Char('A').into())]
+        );
+    }
+}
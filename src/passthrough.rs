@@ -0,0 +1,157 @@
+//! Subscriptions to the raw escape sequences the parser doesn't understand.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use lazy_static::lazy_static;
+
+type SequenceCallback = Arc<dyn Fn(&[u8]) + Send + Sync>;
+
+lazy_static! {
+    /// The registered sequence subscriptions.
+    static ref SUBSCRIPTIONS: Mutex<Vec<Subscription>> = Mutex::new(Vec::new());
+}
+
+/// Identifier of the next registered subscription.
+static NEXT_SUBSCRIPTION_ID: AtomicUsize = AtomicUsize::new(0);
+
+struct Subscription {
+    id: usize,
+    prefix: Vec<u8>,
+    callback: SequenceCallback,
+}
+
+/// A subscription to the raw escape sequences starting with a prefix.
+///
+/// The sequences the parser doesn't understand (they'd be delivered as the
+/// `InputEvent::Unknown` or dropped as malformed) are passed to the callback instead, the
+/// applications & plugins can handle the new terminal extensions without forking the parser.
+/// The subscription is unregistered when it's dropped.
+///
+/// # Notes
+///
+/// * The sequence is passed to the subscription with the longest matching prefix, the
+///   earliest registered one wins the tie.
+/// * The sequences understood by the parser aren't passed, they're parsed as usual.
+/// * The callback is called on the reading thread, don't block in it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::SequenceSubscription;
+///
+/// // An OSC the parser doesn't understand (`ESC ] 1337 ; ... BEL`)
+/// let _subscription = SequenceSubscription::register(&b"\x1B]1337;"[..], |sequence| {
+///     println!("{:?}\r", String::from_utf8_lossy(sequence));
+/// });
+/// ```
+#[derive(Debug)]
+pub struct SequenceSubscription {
+    id: usize,
+}
+
+impl SequenceSubscription {
+    /// Registers the `callback` called with the unknown sequences starting with the `prefix`.
+    pub fn register<P, F>(prefix: P, callback: F) -> SequenceSubscription
+    where
+        P: Into<Vec<u8>>,
+        F: Fn(&[u8]) + Send + Sync + 'static,
+    {
+        let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::SeqCst);
+
+        SUBSCRIPTIONS.lock().unwrap().push(Subscription {
+            id,
+            prefix: prefix.into(),
+            callback: Arc::new(callback),
+        });
+
+        SequenceSubscription { id }
+    }
+}
+
+impl Drop for SequenceSubscription {
+    fn drop(&mut self) {
+        SUBSCRIPTIONS
+            .lock()
+            .unwrap()
+            .retain(|subscription| subscription.id != self.id);
+    }
+}
+
+/// Passes the unknown `sequence` to the subscription with the longest matching prefix.
+///
+/// Returns `true` if the `sequence` was passed to a subscription.
+pub(crate) fn dispatch_sequence(sequence: &[u8]) -> bool {
+    let callback = {
+        let subscriptions = SUBSCRIPTIONS.lock().unwrap();
+
+        let mut matching: Option<&Subscription> = None;
+        for subscription in subscriptions.iter() {
+            if !sequence.starts_with(&subscription.prefix) {
+                continue;
+            }
+            match matching {
+                Some(best) if best.prefix.len() >= subscription.prefix.len() => {}
+                _ => matching = Some(subscription),
+            }
+        }
+
+        match matching {
+            Some(subscription) => subscription.callback.clone(),
+            None => return false,
+        }
+    };
+
+    // Called with the unlocked registry, the callback can register subscriptions
+    callback(sequence);
+    true
+}
+
+/// Returns `true` if the incomplete `sequence` can continue as a subscribed sequence.
+pub(crate) fn is_subscribed_prefix(sequence: &[u8]) -> bool {
+    SUBSCRIPTIONS.lock().unwrap().iter().any(|subscription| {
+        sequence.starts_with(&subscription.prefix) || subscription.prefix.starts_with(sequence)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{dispatch_sequence, SequenceSubscription};
+
+    #[test]
+    fn test_dispatch_sequence() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let subscribe = |prefix: &[u8], name: &'static str| {
+            let received = received.clone();
+            SequenceSubscription::register(prefix, move |sequence: &[u8]| {
+                received.lock().unwrap().push((name, sequence.to_vec()))
+            })
+        };
+
+        let general = subscribe(b"\x1B[?7701", "general");
+        let specific = subscribe(b"\x1B[?7701;2", "specific");
+
+        assert!(dispatch_sequence(b"\x1B[?7701;2x"));
+        assert!(dispatch_sequence(b"\x1B[?7701;3x"));
+        assert!(!dispatch_sequence(b"\x1B[?7702x"));
+
+        drop(specific);
+        assert!(dispatch_sequence(b"\x1B[?7701;2y"));
+        drop(general);
+        assert!(!dispatch_sequence(b"\x1B[?7701;2y"));
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                ("specific", b"\x1B[?7701;2x".to_vec()),
+                ("general", b"\x1B[?7701;3x".to_vec()),
+                ("general", b"\x1B[?7701;2y".to_vec()),
+            ]
+        );
+    }
+}
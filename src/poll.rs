@@ -0,0 +1,161 @@
+//! A shared event pool behind the `poll` & `read` functions.
+
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm_utils::Result;
+use lazy_static::lazy_static;
+
+use crate::{input, AsyncReader, InputEvent};
+
+/// Polling interval of the shared `AsyncReader`.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+lazy_static! {
+    /// The shared reader (created by the first `poll` or `read` call).
+    static ref EVENT_POOL: Mutex<Option<EventPool>> = Mutex::new(None);
+}
+
+struct EventPool {
+    reader: AsyncReader,
+    /// An event read by the `poll` function, not yet returned by the `read` function.
+    pending: Option<InputEvent>,
+}
+
+impl EventPool {
+    fn new() -> EventPool {
+        EventPool {
+            reader: input().read_async(),
+            pending: None,
+        }
+    }
+
+    /// Waits up to `timeout` for an event, returns `true` if there's one pending.
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        let started = Instant::now();
+
+        loop {
+            if self.pending.is_some() {
+                return Ok(true);
+            }
+
+            // It does nothing in the `ReadMode::Poll` mode
+            input().pump()?;
+            self.pending = self.reader.next();
+
+            let elapsed = started.elapsed();
+            if self.pending.is_none() {
+                if elapsed >= timeout {
+                    return Ok(false);
+                }
+                thread::sleep(POLL_INTERVAL.min(timeout - elapsed));
+            }
+        }
+    }
+}
+
+/// Checks if there's an input event available, waits up to `timeout`.
+///
+/// Returns `true` if the event is available, read it with the [`read`](fn.read.html) function.
+/// Use `Duration::from_secs(0)` to check without waiting.
+///
+/// # Notes
+///
+/// * It requires enabled raw mode (see the
+///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more).
+/// * The `poll` & `read` functions share one [`AsyncReader`](struct.AsyncReader.html) created
+///   by the first call. It lives until the program exits, the enabled modes aren't disabled by
+///   the [`InputConfig::with_disable_modes_on_drop`](struct.InputConfig.html#method.with_disable_modes_on_drop)
+///   option.
+/// * The input is pumped in the [`ReadMode::Pump`](enum.ReadMode.html#variant.Pump) mode.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use crossterm_input::{poll, read, InputEvent, KeyCode, KeyEvent, Result};
+///
+/// fn main() -> Result<()> {
+///     loop {
+///         if poll(Duration::from_millis(500))? {
+///             match read()? {
+///                 InputEvent::Keyboard(KeyEvent {
+///                     code: KeyCode::Esc, ..
+///                 }) => break,
+///                 event => println!("{:?}\r", event),
+///             }
+///         } else {
+///             /* Timeout expired, no event */
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn poll(timeout: Duration) -> Result<bool> {
+    EVENT_POOL
+        .lock()
+        .unwrap()
+        .get_or_insert_with(EventPool::new)
+        .poll(timeout)
+}
+
+/// Reads the next input event (blocking).
+///
+/// It returns the event immediately if the [`poll`](fn.poll.html) function returned `true`,
+/// it waits for the event otherwise. See the `poll` function for more details.
+pub fn read() -> Result<InputEvent> {
+    let mut pool = EVENT_POOL.lock().unwrap();
+    let pool = pool.get_or_insert_with(EventPool::new);
+
+    loop {
+        if pool.poll(Duration::from_millis(100))? {
+            if let Some(event) = pool.pending.take() {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+/// Creates the shared reader of the [`poll`](fn.poll.html) & [`read`](fn.read.html) functions
+/// ahead of time.
+///
+/// The first `poll` call opens the TTY, spawns the reading thread & initializes the shared
+/// state otherwise. Call it at the start of latency sensitive applications, so the first
+/// event isn't delayed by the cold start.
+///
+/// # Notes
+///
+/// * The reading thread isn't spawned in the [`ReadMode::Pump`](enum.ReadMode.html#variant.Pump)
+///   mode, the TTY is opened by the first pump.
+/// * It does nothing if the shared reader exists already.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use crossterm_input::{poll, warm_up, Result};
+///
+/// fn main() -> Result<()> {
+///     warm_up()?;
+///
+///     // Render the first frame, the input is read in the background already
+///
+///     poll(Duration::from_millis(16))?;
+///     Ok(())
+/// }
+/// ```
+pub fn warm_up() -> Result<()> {
+    let mut pool = EVENT_POOL.lock().unwrap();
+
+    if pool.is_none() {
+        // The reader doesn't report the TTY errors, they're reported here
+        #[cfg(unix)]
+        drop(crate::sys::unix::internal_event_receiver()?);
+
+        *pool = Some(EventPool::new());
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! A key event rate limiter.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::{with_input_config, KeyRateOverflow},
+    InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
+
+/// Length of the window in which the key events are counted.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Limits the number of key events delivered per second.
+///
+/// The producer feeds the events with the `limit` method and calls the `flush` method
+/// once all the available input was read.
+#[derive(Debug, Default)]
+pub(crate) struct KeyRateLimiter {
+    /// Start of the current window.
+    window_start: Option<Instant>,
+    /// Number of key events in the current window.
+    count: u32,
+    /// Characters of the coalesced key events.
+    text: String,
+    /// Number of the dropped key events.
+    dropped: usize,
+}
+
+impl KeyRateLimiter {
+    /// Limits the `event` with the shared input configuration.
+    ///
+    /// Returns the events to deliver.
+    pub(crate) fn limit(&mut self, event: InputEvent) -> Vec<InputEvent> {
+        let (max_per_second, overflow) =
+            with_input_config(|config| (config.key_rate_limit(), config.key_rate_overflow()));
+
+        self.limit_at(event, Instant::now(), max_per_second, overflow)
+    }
+
+    fn limit_at(
+        &mut self,
+        event: InputEvent,
+        now: Instant,
+        max_per_second: u32,
+        overflow: KeyRateOverflow,
+    ) -> Vec<InputEvent> {
+        let key_event = match event {
+            InputEvent::Keyboard(ref key_event)
+                if max_per_second > 0 && key_event.kind != KeyEventKind::Release =>
+            {
+                key_event
+            }
+            event => return self.flush_with(event),
+        };
+
+        match self.window_start {
+            Some(window_start) if now.duration_since(window_start) < WINDOW => self.count += 1,
+            _ => {
+                self.window_start = Some(now);
+                self.count = 1;
+            }
+        };
+
+        if self.count <= max_per_second {
+            return self.flush_with(event);
+        }
+
+        match (overflow, key_char(key_event)) {
+            (KeyRateOverflow::Coalesce, Some(c)) => self.text.push(c),
+            // Keys which can't be coalesced (Ctrl + C, ...) are delivered
+            (KeyRateOverflow::Coalesce, None) => return self.flush_with(event),
+            (KeyRateOverflow::Drop, _) => self.dropped += 1,
+        };
+        Vec::new()
+    }
+
+    /// Returns the coalesced text & the dropped events marker (if any).
+    pub(crate) fn flush(&mut self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        if !self.text.is_empty() {
+            events.push(InputEvent::Paste(std::mem::take(&mut self.text)));
+        }
+        if self.dropped > 0 {
+            events.push(InputEvent::Lagged(self.dropped));
+            self.dropped = 0;
+        }
+
+        events
+    }
+
+    /// Returns the pending events followed by the `event`.
+    fn flush_with(&mut self, event: InputEvent) -> Vec<InputEvent> {
+        let mut events = self.flush();
+        events.push(event);
+        events
+    }
+}
+
+/// Returns the character typed with the `key_event` (if any).
+fn key_char(key_event: &KeyEvent) -> Option<char> {
+    if !(key_event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return None;
+    }
+
+    match key_event.code {
+        KeyCode::Char(c) => Some(c),
+        KeyCode::Enter => Some('\n'),
+        KeyCode::Tab => Some('\t'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{config::KeyRateOverflow, InputEvent, KeyCode, KeyEvent, KeyModifiers};
+
+    use super::KeyRateLimiter;
+
+    fn key(code: KeyCode) -> InputEvent {
+        InputEvent::Keyboard(code.into())
+    }
+
+    #[test]
+    fn test_limit_coalesce() {
+        let mut limiter = KeyRateLimiter::default();
+        let now = Instant::now();
+        let mut limit = |event, now| limiter.limit_at(event, now, 2, KeyRateOverflow::Coalesce);
+
+        assert_eq!(
+            limit(key(KeyCode::Char('a')), now),
+            vec![key(KeyCode::Char('a'))]
+        );
+        assert_eq!(
+            limit(key(KeyCode::Char('b')), now),
+            vec![key(KeyCode::Char('b'))]
+        );
+        assert_eq!(limit(key(KeyCode::Char('c')), now), vec![]);
+        assert_eq!(limit(key(KeyCode::Enter), now), vec![]);
+
+        let ctrl_c = InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert_eq!(
+            limit(ctrl_c.clone(), now),
+            vec![InputEvent::Paste("c\n".to_string()), ctrl_c]
+        );
+
+        // New window
+        let later = now + Duration::from_secs(1);
+        assert_eq!(
+            limit(key(KeyCode::Char('d')), later),
+            vec![key(KeyCode::Char('d'))]
+        );
+        assert_eq!(
+            limit(InputEvent::FocusLost, later),
+            vec![InputEvent::FocusLost]
+        );
+    }
+
+    #[test]
+    fn test_limit_drop() {
+        let mut limiter = KeyRateLimiter::default();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            limiter.limit_at(key(KeyCode::Up), now, 1, KeyRateOverflow::Drop);
+        }
+        assert_eq!(limiter.flush(), vec![InputEvent::Lagged(2)]);
+        assert_eq!(limiter.flush(), vec![]);
+    }
+
+    #[test]
+    fn test_limit_disabled() {
+        let mut limiter = KeyRateLimiter::default();
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert_eq!(
+                limiter.limit_at(key(KeyCode::Up), now, 0, KeyRateOverflow::Drop),
+                vec![key(KeyCode::Up)]
+            );
+        }
+    }
+}
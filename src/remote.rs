@@ -0,0 +1,195 @@
+//! Forwarding of the input events over a byte stream (socket, pipe, ...).
+//!
+//! It allows to drive the application input from another process (pair programming tools,
+//! remote test drivers, ...). Events are serialized as a newline delimited JSON, one
+//! `InputEvent` per line.
+//!
+//! Requires the `remote` feature.
+//!
+//! # Examples
+//!
+//! The forwarding process:
+//!
+//! ```no_run
+//! use std::net::TcpStream;
+//!
+//! use crossterm_input::input;
+//! use crossterm_input::remote::forward_events;
+//!
+//! let stream = TcpStream::connect("127.0.0.1:4000").expect("Unable to connect");
+//! forward_events(input().read_sync(), stream).expect("Unable to forward events");
+//! ```
+//!
+//! The application process:
+//!
+//! ```no_run
+//! use std::net::TcpListener;
+//!
+//! use crossterm_input::remote::RemoteEventSource;
+//!
+//! let listener = TcpListener::bind("127.0.0.1:4000").expect("Unable to bind");
+//! let (stream, _) = listener.accept().expect("Unable to accept");
+//!
+//! // All the existing readers receive the forwarded events
+//! RemoteEventSource::new(stream)
+//!     .inject()
+//!     .expect("Unable to inject events");
+//! ```
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crossterm_utils::Result;
+
+use crate::InputEvent;
+
+/// Writes all the `events` into the `writer`, blocks until the `events` iterator ends.
+///
+/// Every event is flushed immediately. Returns the number of forwarded events.
+pub fn forward_events<I, W>(events: I, mut writer: W) -> Result<usize>
+where
+    I: IntoIterator<Item = InputEvent>,
+    W: Write,
+{
+    let mut forwarded = 0;
+
+    for event in events {
+        write_event(&mut writer, &event)?;
+        forwarded += 1;
+    }
+
+    Ok(forwarded)
+}
+
+/// Writes the `event` as a single line.
+fn write_event<W: Write>(writer: &mut W, event: &InputEvent) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, event)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// A source of the events forwarded with the [`forward_events`](fn.forward_events.html)
+/// function.
+///
+/// It can be used as an iterator (ends with the stream or on the first error) or the events
+/// can be injected into all the existing readers with the `inject` method.
+pub struct RemoteEventSource<R> {
+    reader: BufReader<R>,
+}
+
+impl<R: Read> RemoteEventSource<R> {
+    /// Creates a new `RemoteEventSource` reading from the `reader`.
+    pub fn new(reader: R) -> RemoteEventSource<R> {
+        RemoteEventSource {
+            reader: BufReader::new(reader),
+        }
+    }
+
+    /// Reads the next event, blocks until it's available.
+    ///
+    /// Returns `None` at the end of the stream.
+    pub fn read_event(&mut self) -> Result<Option<InputEvent>> {
+        let mut line = String::new();
+
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let event = serde_json::from_str(&line).map_err(io::Error::from)?;
+        Ok(Some(event))
+    }
+
+    /// Sends all the events to all the existing readers until the end of the stream, blocks
+    /// until then.
+    ///
+    /// Events are produced with the [`SourceId::Remote`](../enum.SourceId.html#variant.Remote)
+    /// source. Returns the number of injected events.
+    ///
+    /// # Notes
+    ///
+    /// UNIX only, an error is returned on Windows.
+    pub fn inject(mut self) -> Result<usize> {
+        let mut injected = 0;
+
+        while let Some(event) = self.read_event()? {
+            inject_event(event)?;
+            injected += 1;
+        }
+
+        Ok(injected)
+    }
+}
+
+impl<R: Read> Iterator for RemoteEventSource<R> {
+    type Item = InputEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_event().ok().and_then(|event| event)
+    }
+}
+
+#[cfg(unix)]
+fn inject_event(event: InputEvent) -> Result<()> {
+    crate::sys::unix::send_internal_event(
+        crate::SourceId::Remote,
+        crate::InternalEvent::Input(event),
+    );
+    Ok(())
+}
+
+#[cfg(windows)]
+fn inject_event(_event: InputEvent) -> Result<()> {
+    Err(crossterm_utils::ErrorKind::IoError(io::Error::new(
+        io::ErrorKind::Other,
+        "Remote event injection isn't supported on Windows",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyCode, KeyEvent, KeyModifiers};
+    #[cfg(not(feature = "keyboard-only"))]
+    use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+    #[test]
+    fn test_forward_events() {
+        let events = vec![
+            InputEvent::Keyboard(KeyCode::Char('a').into()),
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            InputEvent::Resize(80, 24),
+        ];
+        #[cfg(not(feature = "keyboard-only"))]
+        let events = [
+            events,
+            vec![InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                3,
+                4,
+            ))],
+        ]
+        .concat();
+
+        let mut stream = Vec::new();
+        assert_eq!(
+            forward_events(events.clone(), &mut stream).ok(),
+            Some(events.len())
+        );
+        assert_eq!(
+            stream.iter().filter(|byte| **byte == b'\n').count(),
+            events.len()
+        );
+
+        let source = RemoteEventSource::new(&stream[..]);
+        assert_eq!(source.collect::<Vec<_>>(), events);
+    }
+
+    #[test]
+    fn test_malformed_event() {
+        let mut source = RemoteEventSource::new(&b"{\"Keyboard\":\n"[..]);
+        assert!(source.read_event().is_err());
+
+        let mut source = RemoteEventSource::new(&b""[..]);
+        assert_eq!(source.read_event().ok(), Some(None));
+    }
+}
@@ -0,0 +1,140 @@
+//! A keyboard auto-repeat measurement.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{AsyncReader, InputEvent, KeyEvent, KeyEventKind};
+
+/// Maximum number of key events used for the measurement.
+const MAX_SAMPLES: usize = 32;
+
+/// No key event for this duration means that the key was released.
+///
+/// It's longer than the longest auto-repeat delay configurable on common platforms (1s).
+const RELEASE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Polling interval of the `AsyncReader`.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A measured keyboard auto-repeat.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct KeyRepeat {
+    /// The delay between the key press and the first repeated key event.
+    pub delay: Duration,
+    /// The interval between two repeated key events.
+    pub interval: Duration,
+}
+
+impl KeyRepeat {
+    /// Creates a new `KeyRepeat` from the timestamps of the repeated key events.
+    ///
+    /// At least three timestamps (press & two repeats) are required.
+    fn from_timestamps(timestamps: &[Instant]) -> Option<KeyRepeat> {
+        if timestamps.len() < 3 {
+            return None;
+        }
+
+        let repeats = (timestamps.len() - 2) as u32;
+
+        Some(KeyRepeat {
+            delay: timestamps[1] - timestamps[0],
+            interval: (timestamps[timestamps.len() - 1] - timestamps[1]) / repeats,
+        })
+    }
+
+    /// Returns the number of repeated key events per second.
+    pub fn rate(&self) -> f64 {
+        let interval =
+            self.interval.as_secs() as f64 + f64::from(self.interval.subsec_nanos()) / 1e9;
+
+        if interval == 0.0 {
+            0.0
+        } else {
+            1.0 / interval
+        }
+    }
+}
+
+/// Measures the auto-repeat of a key held by the user.
+///
+/// Waits up to `timeout` for the first key event. The measurement ends when another key
+/// is pressed, the key is released or when enough events were collected.
+pub(crate) fn measure_key_repeat(reader: &mut AsyncReader, timeout: Duration) -> Option<KeyRepeat> {
+    let started = Instant::now();
+    let mut key_event = None;
+    let mut timestamps = Vec::with_capacity(MAX_SAMPLES);
+
+    while timestamps.len() < MAX_SAMPLES {
+        let now = Instant::now();
+
+        match timestamps.last() {
+            None if now - started > timeout => break,
+            Some(last) if now - *last > RELEASE_TIMEOUT => break,
+            _ => {}
+        };
+
+        match reader.next() {
+            Some(InputEvent::Keyboard(event)) => {
+                let is_held =
+                    |held: &KeyEvent| held.code == event.code && held.modifiers == event.modifiers;
+
+                if event.kind == KeyEventKind::Release {
+                    // The held key released (if reported), stop measuring
+                    if key_event.as_ref().map(is_held).unwrap_or(false) {
+                        break;
+                    }
+                    continue;
+                }
+
+                match key_event {
+                    None => key_event = Some(event),
+                    // Another key pressed, stop measuring
+                    Some(ref held) if !is_held(held) => break,
+                    Some(_) => {}
+                };
+
+                timestamps.push(now);
+            }
+            Some(_) => {}
+            None => thread::sleep(POLL_INTERVAL),
+        };
+    }
+
+    KeyRepeat::from_timestamps(&timestamps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_repeat_from_timestamps() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        assert_eq!(KeyRepeat::from_timestamps(&[]), None);
+        assert_eq!(KeyRepeat::from_timestamps(&[at(0), at(500)]), None);
+
+        let repeat =
+            KeyRepeat::from_timestamps(&[at(0), at(500), at(530), at(560), at(590)]).unwrap();
+        assert_eq!(repeat.delay, Duration::from_millis(500));
+        assert_eq!(repeat.interval, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_key_repeat_rate() {
+        let repeat = KeyRepeat {
+            delay: Duration::from_millis(250),
+            interval: Duration::from_millis(40),
+        };
+        assert!((repeat.rate() - 25.0).abs() < 1e-9);
+
+        let repeat = KeyRepeat {
+            delay: Duration::from_millis(250),
+            interval: Duration::from_millis(0),
+        };
+        assert_eq!(repeat.rate(), 0.0);
+    }
+}
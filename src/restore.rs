@@ -0,0 +1,64 @@
+//! A panic hook & exit handler restoring the terminal input modes.
+
+use std::{panic, sync::Once};
+
+use crossterm_screen::RawScreen;
+
+use crate::modes::{disable_modes, try_enabled_modes};
+
+static INSTALL: Once = Once::new();
+
+/// Installs a panic hook (and an exit handler on UNIX systems) disabling all the enabled
+/// terminal input modes & the raw mode.
+///
+/// A crashing application leaves the terminal in the mouse reporting mode, bracketed paste
+/// mode, ... and the user's shell receives the escape sequences. The hook disables the modes
+/// enabled with the [`TerminalInput`](struct.TerminalInput.html) at the time of the panic
+/// (or exit) and calls the previously installed hook.
+///
+/// # Notes
+///
+/// * It's opt-in, call it once at the start of the application. Further calls do nothing.
+/// * Install your own panic hook before this one, the previous hook is called after
+///   the terminal is restored (the panic message isn't garbled by the raw mode).
+/// * The exit handler runs when `main` returns or `std::process::exit` is called. There's
+///   no exit handler on Windows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{input, install_restore_hook, RawScreen};
+///
+/// install_restore_hook();
+///
+/// let _raw = RawScreen::into_raw_mode();
+/// input().enable_bracketed_paste().unwrap();
+///
+/// panic!("The terminal is restored before this message is printed");
+/// ```
+pub fn install_restore_hook() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+
+        #[cfg(unix)]
+        unsafe {
+            libc::atexit(restore_at_exit);
+        }
+    });
+}
+
+#[cfg(unix)]
+extern "C" fn restore_at_exit() {
+    // Unwinding out of the exit handler aborts the process
+    let _ = panic::catch_unwind(restore_terminal);
+}
+
+/// Disables all the enabled modes & the raw mode.
+fn restore_terminal() {
+    disable_modes(try_enabled_modes());
+    let _ = RawScreen::disable_raw_mode();
+}
@@ -0,0 +1,135 @@
+//! A wheel scroll accumulator producing the line scroll events.
+
+use crate::{config::with_input_config, InputEvent, MouseButton, MouseEvent, MouseEventKind};
+
+/// The wheel delta of one notch (`WHEEL_DELTA` on Windows).
+const WHEEL_DELTA: i32 = 120;
+
+/// Accumulates the wheel events into the `InputEvent::ScrollLines` events.
+///
+/// The terminals report the whole notches, the high-resolution wheels (Windows) report
+/// a fraction of the notch, it's accumulated until it makes a whole line.
+#[derive(Debug, Default)]
+pub(crate) struct ScrollAccumulator {
+    /// The accumulated delta (in the lines * `WHEEL_DELTA`), negative is up.
+    delta: i32,
+}
+
+impl ScrollAccumulator {
+    /// Converts the wheel `event` with the shared input configuration.
+    ///
+    /// The `delta` is the wheel rotation (`WHEEL_DELTA` per notch), a whole notch is assumed
+    /// if it's `None`. Returns the events to deliver.
+    pub(crate) fn accumulate(&mut self, event: InputEvent, delta: Option<u16>) -> Vec<InputEvent> {
+        let lines_per_notch = with_input_config(|config| config.wheel_scroll_lines());
+        self.accumulate_with(event, delta, lines_per_notch)
+    }
+
+    fn accumulate_with(
+        &mut self,
+        event: InputEvent,
+        delta: Option<u16>,
+        lines_per_notch: u16,
+    ) -> Vec<InputEvent> {
+        // Wheel events with the modifiers (Ctrl + wheel zoom, ...) are delivered as they are
+        let direction = match event {
+            InputEvent::Mouse(MouseEvent {
+                kind: MouseEventKind::Press,
+                button: Some(MouseButton::WheelUp),
+                modifiers,
+                ..
+            }) if lines_per_notch > 0 && modifiers.is_empty() => -1,
+            InputEvent::Mouse(MouseEvent {
+                kind: MouseEventKind::Press,
+                button: Some(MouseButton::WheelDown),
+                modifiers,
+                ..
+            }) if lines_per_notch > 0 && modifiers.is_empty() => 1,
+            event => return vec![event],
+        };
+
+        // The remainder is dropped when the direction changes
+        if self.delta.signum() == -direction {
+            self.delta = 0;
+        }
+
+        let delta = delta.map_or(WHEEL_DELTA, i32::from);
+        self.delta += direction * delta * i32::from(lines_per_notch);
+
+        let lines = self.delta / WHEEL_DELTA;
+        self.delta -= lines * WHEEL_DELTA;
+
+        if lines == 0 {
+            Vec::new()
+        } else {
+            let lines = lines.max(i32::from(i16::MIN)).min(i32::from(i16::MAX));
+            vec![InputEvent::ScrollLines(lines as i16)]
+        }
+    }
+}
+
+/// Converts the terminal wheel `event` (a whole notch) with the shared input configuration.
+///
+/// Returns the events to deliver.
+pub(crate) fn scroll_line_events(event: InputEvent) -> Vec<InputEvent> {
+    // Whole notches make whole lines, there's nothing to accumulate
+    ScrollAccumulator::default().accumulate(event, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InputEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+    use super::ScrollAccumulator;
+
+    fn wheel(button: MouseButton) -> InputEvent {
+        InputEvent::Mouse(MouseEvent::new(MouseEventKind::Press, Some(button), 1, 2))
+    }
+
+    #[test]
+    fn test_scroll_accumulator() {
+        let mut accumulator = ScrollAccumulator::default();
+
+        let up = wheel(MouseButton::WheelUp);
+        assert_eq!(
+            accumulator.accumulate_with(up.clone(), None, 0),
+            vec![up.clone()]
+        );
+        assert_eq!(
+            accumulator.accumulate_with(up.clone(), None, 3),
+            vec![InputEvent::ScrollLines(-3)]
+        );
+
+        // High-resolution wheel, 1/4 of the notch is 3/4 of the line
+        let down = wheel(MouseButton::WheelDown);
+        assert_eq!(
+            accumulator.accumulate_with(down.clone(), Some(30), 3),
+            vec![]
+        );
+        assert_eq!(
+            accumulator.accumulate_with(down.clone(), Some(30), 3),
+            vec![InputEvent::ScrollLines(1)]
+        );
+        // The remainder (1/2 of the line) is dropped
+        assert_eq!(accumulator.accumulate_with(up.clone(), Some(30), 3), vec![]);
+        assert_eq!(
+            accumulator.accumulate_with(up, Some(30), 3),
+            vec![InputEvent::ScrollLines(-1)]
+        );
+
+        let zoom = InputEvent::Mouse(MouseEvent {
+            modifiers: KeyModifiers::CONTROL,
+            ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::WheelDown), 1, 2)
+        });
+        assert_eq!(
+            accumulator.accumulate_with(zoom.clone(), None, 3),
+            vec![zoom]
+        );
+
+        let click = wheel(MouseButton::Left);
+        assert_eq!(
+            accumulator.accumulate_with(click.clone(), None, 3),
+            vec![click]
+        );
+    }
+}
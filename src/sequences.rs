@@ -0,0 +1,155 @@
+//! Escape sequences written to & read from the terminal.
+//!
+//! The mode sequences are written by the [`TerminalInput`](../struct.TerminalInput.html) methods,
+//! the input sequences are parsed by the UNIX input parser & produced by the
+//! [`encode`](../encode/index.html) module. Tools (tests, terminal emulators, ...) can reference
+//! the exact bytes without duplicating them.
+//!
+//! # Examples
+//!
+//! ```
+//! use crossterm_input::encode::{encode, MouseEncoding};
+//! use crossterm_input::{sequences, InputEvent};
+//!
+//! let bytes = encode(&InputEvent::FocusGained, MouseEncoding::Sgr);
+//! assert_eq!(bytes, Some(sequences::FOCUS_GAINED.to_vec()));
+//! ```
+
+use crate::KeyCode;
+
+/// Enables the button press & release reporting (X10 compatible).
+pub const MOUSE_ENABLE_CLICKS: &str = "\x1B[?1000h";
+/// Enables the motion with a pressed button reporting.
+pub const MOUSE_ENABLE_DRAG: &str = "\x1B[?1002h";
+/// Enables all the motion reporting.
+pub const MOUSE_ENABLE_MOTION: &str = "\x1B[?1003h";
+/// Enables the urxvt mouse encoding.
+pub const MOUSE_ENABLE_URXVT: &str = "\x1B[?1015h";
+/// Enables the SGR mouse encoding.
+pub const MOUSE_ENABLE_SGR: &str = "\x1B[?1006h";
+/// Disables the button press & release reporting.
+pub const MOUSE_DISABLE_CLICKS: &str = "\x1B[?1000l";
+/// Disables the motion with a pressed button reporting.
+pub const MOUSE_DISABLE_DRAG: &str = "\x1B[?1002l";
+/// Disables all the motion reporting.
+pub const MOUSE_DISABLE_MOTION: &str = "\x1B[?1003l";
+/// Disables the urxvt mouse encoding.
+pub const MOUSE_DISABLE_URXVT: &str = "\x1B[?1015l";
+/// Disables the SGR mouse encoding.
+pub const MOUSE_DISABLE_SGR: &str = "\x1B[?1006l";
+
+/// Enables the DEC locator with the character cell coordinates.
+pub const LOCATOR_ENABLE: &str = "\x1B[1;2'z";
+/// Selects the button down & up locator events.
+pub const LOCATOR_SELECT_EVENTS: &str = "\x1B[1;3'{";
+/// Deselects the button down & up locator events.
+pub const LOCATOR_DESELECT_EVENTS: &str = "\x1B[2;4'{";
+/// Disables the DEC locator.
+pub const LOCATOR_DISABLE: &str = "\x1B[0'z";
+
+/// Enables the bracketed paste mode.
+pub const BRACKETED_PASTE_ENABLE: &str = "\x1B[?2004h";
+/// Disables the bracketed paste mode.
+pub const BRACKETED_PASTE_DISABLE: &str = "\x1B[?2004l";
+/// Starts the pasted text.
+pub const BRACKETED_PASTE_START: &[u8] = b"\x1B[200~";
+/// Ends the pasted text.
+pub const BRACKETED_PASTE_END: &[u8] = b"\x1B[201~";
+
+/// Enables the focus reporting mode.
+pub const FOCUS_REPORTING_ENABLE: &str = "\x1B[?1004h";
+/// Disables the focus reporting mode.
+pub const FOCUS_REPORTING_DISABLE: &str = "\x1B[?1004l";
+/// The terminal gained the focus.
+pub const FOCUS_GAINED: &[u8] = b"\x1B[I";
+/// The terminal lost the focus.
+pub const FOCUS_LOST: &[u8] = b"\x1B[O";
+
+/// Pops the kitty keyboard protocol flags (they're pushed with `ESC [ > flags u`).
+pub const KEYBOARD_ENHANCEMENT_POP: &str = "\x1B[<u";
+/// Asks for the current kitty keyboard protocol flags.
+pub const KEYBOARD_ENHANCEMENT_QUERY: &str = "\x1B[?u";
+
+/// Enables the xterm modifyOtherKeys mode (level 2).
+pub const MODIFY_OTHER_KEYS_ENABLE: &str = "\x1B[>4;2m";
+/// Disables the xterm modifyOtherKeys mode.
+pub const MODIFY_OTHER_KEYS_DISABLE: &str = "\x1B[>4;0m";
+
+/// Enables the Windows Terminal win32-input-mode (the key records are sent as
+/// `ESC [ Vk ; Sc ; Uc ; Kd ; Cs ; Rc _`).
+pub const WIN32_INPUT_MODE_ENABLE: &str = "\x1B[?9001h";
+/// Disables the Windows Terminal win32-input-mode.
+pub const WIN32_INPUT_MODE_DISABLE: &str = "\x1B[?9001l";
+
+/// Asks for the device status.
+pub const DEVICE_STATUS_QUERY: &str = "\x1B[5n";
+/// The device status report (the terminal is OK).
+pub const DEVICE_STATUS_OK: &[u8] = b"\x1B[0n";
+
+/// Asks for the cursor position (the terminal answers with `ESC [ row ; column R`).
+pub const CURSOR_POSITION_QUERY: &str = "\x1B[6n";
+
+/// Asks for the terminal name & version (XTVERSION, the terminal answers with
+/// `ESC P > | text ST`).
+pub const TERMINAL_VERSION_QUERY: &str = "\x1B[>q";
+/// Asks for the secondary device attributes (DA2, the terminal answers with
+/// `ESC [ > Pp ; Pv ; Pc c`).
+pub const DEVICE_ATTRIBUTES_QUERY: &str = "\x1B[>c";
+
+/// Sequences of the unmodified special keys (the default xterm encoding).
+pub const KEYS: &[(KeyCode, &[u8])] = &[
+    (KeyCode::Backspace, b"\x7F"),
+    (KeyCode::Enter, b"\r"),
+    (KeyCode::Tab, b"\t"),
+    (KeyCode::BackTab, b"\x1B[Z"),
+    (KeyCode::Esc, b"\x1B"),
+    (KeyCode::Left, b"\x1B[D"),
+    (KeyCode::Right, b"\x1B[C"),
+    (KeyCode::Up, b"\x1B[A"),
+    (KeyCode::Down, b"\x1B[B"),
+    (KeyCode::Home, b"\x1B[H"),
+    (KeyCode::End, b"\x1B[F"),
+    (KeyCode::Insert, b"\x1B[2~"),
+    (KeyCode::Delete, b"\x1B[3~"),
+    (KeyCode::PageUp, b"\x1B[5~"),
+    (KeyCode::PageDown, b"\x1B[6~"),
+    (KeyCode::F(1), b"\x1BOP"),
+    (KeyCode::F(2), b"\x1BOQ"),
+    (KeyCode::F(3), b"\x1BOR"),
+    (KeyCode::F(4), b"\x1BOS"),
+    (KeyCode::F(5), b"\x1B[15~"),
+    (KeyCode::F(6), b"\x1B[17~"),
+    (KeyCode::F(7), b"\x1B[18~"),
+    (KeyCode::F(8), b"\x1B[19~"),
+    (KeyCode::F(9), b"\x1B[20~"),
+    (KeyCode::F(10), b"\x1B[21~"),
+    (KeyCode::F(11), b"\x1B[23~"),
+    (KeyCode::F(12), b"\x1B[24~"),
+    (KeyCode::F(13), b"\x1B[25~"),
+    (KeyCode::F(14), b"\x1B[26~"),
+    (KeyCode::F(15), b"\x1B[28~"),
+    (KeyCode::F(16), b"\x1B[29~"),
+    (KeyCode::F(17), b"\x1B[31~"),
+    (KeyCode::F(18), b"\x1B[32~"),
+    (KeyCode::F(19), b"\x1B[33~"),
+    (KeyCode::F(20), b"\x1B[34~"),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::encode::encode_key;
+
+    use super::KEYS;
+
+    #[test]
+    fn test_keys_match_encoder() {
+        for (code, bytes) in KEYS {
+            assert_eq!(
+                encode_key(&(*code).into()),
+                Some(bytes.to_vec()),
+                "{:?}",
+                code
+            );
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Input of multiple sessions (PTYs) read by a single polling thread.
+
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+};
+use std::time::Duration;
+use std::{io, thread};
+
+use crossterm_utils::Result;
+use mio::unix::EventedFd;
+use mio::{Events, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+
+use crate::sys::ansi::{parse_session_event, ParserState};
+use crate::InputEvent;
+
+const SHUTDOWN_TOKEN: Token = Token(0);
+
+/// A session input state.
+struct Session {
+    /// A PTY file descriptor (not owned).
+    fd: RawFd,
+    /// Bytes of a partially read event.
+    buffer: Vec<u8>,
+    /// The parser state carried between the events.
+    parser_state: ParserState,
+    /// A channel to the `SessionReader`.
+    tx: Sender<InputEvent>,
+}
+
+/// A state shared by the `SessionManager`, the polling thread & all the `SessionReader`s.
+struct Shared {
+    poll: Poll,
+    sessions: Mutex<HashMap<usize, Session>>,
+    next_id: AtomicUsize,
+}
+
+impl Shared {
+    /// Removes the session & stops polling its file descriptor.
+    fn remove_session(&self, id: usize) {
+        if let Some(session) = self.sessions.lock().unwrap().remove(&id) {
+            // Errors are ignored, the file descriptor can be already closed
+            let _ = self.poll.deregister(&EventedFd(&session.fd));
+        }
+    }
+
+    /// Reads the available bytes of the session & sends parsed events to its reader.
+    ///
+    /// The session is removed at the end of the stream or in case of any error.
+    fn read_session(&self, id: usize) {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        let session = match sessions.get_mut(&id) {
+            Some(session) => session,
+            None => return,
+        };
+
+        let mut buf = [0u8; 1024];
+        let count =
+            unsafe { libc::read(session.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+
+        if count < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::WouldBlock
+                || error.kind() == io::ErrorKind::Interrupted
+            {
+                return;
+            }
+        }
+
+        if count <= 0 {
+            // End of the stream or an error, the reader is disconnected
+            drop(sessions);
+            self.remove_session(id);
+            return;
+        }
+
+        let input = &buf[..count as usize];
+        let mut events = Vec::new();
+
+        for (index, byte) in input.iter().enumerate() {
+            // We need this information to distinguish between Esc key and possible
+            // Esc sequence.
+            let input_available = index + 1 < input.len();
+
+            session.buffer.push(*byte);
+            parse_session_event(
+                &mut session.buffer,
+                &mut session.parser_state,
+                input_available,
+                &mut events,
+            );
+        }
+
+        for event in events {
+            // The reader removes the session once dropped
+            let _ = session.tx.send(event);
+        }
+    }
+}
+
+/// A manager of multiple sessions input.
+///
+/// Server applications hosting multiple user sessions (one PTY per session) can read input
+/// of all the sessions with a single polling thread. Every session has its own parser &
+/// event queue, events are read with the `SessionReader` returned from the `add_session`
+/// method.
+///
+/// The polling thread is stopped once you drop the `SessionManager`.
+///
+/// # Notes
+///
+/// * UNIX only.
+/// * Session events are independent of the shared readers
+///   ([`AsyncReader`](struct.AsyncReader.html), ...), they aren't tracked by the
+///   [`KeyboardState`](struct.KeyboardState.html) and the [`MouseState`](struct.MouseState.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::os::unix::io::RawFd;
+///
+/// use crossterm_input::SessionManager;
+///
+/// # let pty_fd: RawFd = 0;
+/// let manager = SessionManager::new().expect("Unable to start the session manager");
+/// let mut reader = manager.add_session(pty_fd).expect("Unable to add the session");
+///
+/// for event in reader.by_ref() {
+///     println!("{:?}", event);
+/// }
+/// ```
+pub struct SessionManager {
+    shared: Arc<Shared>,
+    _shutdown_registration: Registration,
+    shutdown: SetReadiness,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl SessionManager {
+    /// Creates a new `SessionManager` & spawns the polling thread.
+    pub fn new() -> Result<SessionManager> {
+        let shared = Arc::new(Shared {
+            poll: Poll::new()?,
+            sessions: Mutex::new(HashMap::new()),
+            // 0 is the shutdown token
+            next_id: AtomicUsize::new(1),
+        });
+
+        let (registration, shutdown) = Registration::new2();
+        shared.poll.register(
+            &registration,
+            SHUTDOWN_TOKEN,
+            Ready::readable(),
+            PollOpt::level(),
+        )?;
+
+        let handle = thread::spawn({
+            let shared = shared.clone();
+            move || polling_thread(&shared)
+        });
+
+        Ok(SessionManager {
+            shared,
+            _shutdown_registration: registration,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Adds a new session reading from the `fd` (PTY master, ...).
+    ///
+    /// The `fd` isn't owned by the session, it must stay open until the returned
+    /// `SessionReader` is dropped. The session is removed when you drop the `SessionReader` or
+    /// at the end of the `fd` stream.
+    pub fn add_session(&self, fd: RawFd) -> Result<SessionReader> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = mpsc::channel();
+
+        self.shared.sessions.lock().unwrap().insert(
+            id,
+            Session {
+                fd,
+                buffer: Vec::with_capacity(32),
+                parser_state: ParserState::default(),
+                tx,
+            },
+        );
+
+        if let Err(error) = self.shared.poll.register(
+            &EventedFd(&fd),
+            Token(id),
+            Ready::readable(),
+            PollOpt::level(),
+        ) {
+            self.shared.sessions.lock().unwrap().remove(&id);
+            return Err(error.into());
+        }
+
+        Ok(SessionReader {
+            id,
+            rx,
+            shared: self.shared.clone(),
+        })
+    }
+
+    /// Returns the number of living sessions.
+    pub fn session_count(&self) -> usize {
+        self.shared.sessions.lock().unwrap().len()
+    }
+}
+
+impl Drop for SessionManager {
+    fn drop(&mut self) {
+        // Errors are ignored, there's nothing we can do in the drop
+        let _ = self.shutdown.set_readiness(Ready::readable());
+
+        // Safe to unwrap, it's taken in the drop() only
+        let handle = self.handle.take().unwrap();
+        let _ = handle.join();
+
+        // Disconnect all the living readers
+        let ids: Vec<usize> = self
+            .shared
+            .sessions
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        for id in ids {
+            self.shared.remove_session(id);
+        }
+    }
+}
+
+/// A main body of the `SessionManager` polling thread.
+fn polling_thread(shared: &Shared) {
+    let mut events = Events::with_capacity(64);
+
+    loop {
+        if let Err(error) = shared.poll.poll(&mut events, None) {
+            if error.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        }
+
+        for event in events.iter() {
+            match event.token() {
+                SHUTDOWN_TOKEN => return,
+                Token(id) => shared.read_session(id),
+            };
+        }
+    }
+}
+
+/// A reader of the single session events.
+///
+/// See the [`SessionManager`](struct.SessionManager.html) documentation. The session is removed
+/// once you drop the `SessionReader`.
+pub struct SessionReader {
+    id: usize,
+    rx: Receiver<InputEvent>,
+    shared: Arc<Shared>,
+}
+
+impl SessionReader {
+    /// Reads the next event, blocks until it's available or the `timeout` elapses.
+    ///
+    /// Returns `None` if the `timeout` elapsed or if the session was removed.
+    pub fn read_timeout(&mut self, timeout: Duration) -> Option<InputEvent> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+}
+
+impl Iterator for SessionReader {
+    type Item = InputEvent;
+
+    /// Tries to read the next input event (not blocking).
+    ///
+    /// `None` doesn't mean that the iteration is finished.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for SessionReader {
+    fn drop(&mut self) {
+        self.shared.remove_session(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyCode;
+
+    #[test]
+    fn test_session_manager() {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let (read_fd, write_fd) = (fds[0], fds[1]);
+
+        let manager = SessionManager::new().unwrap();
+        let mut reader = manager.add_session(read_fd).unwrap();
+        assert_eq!(manager.session_count(), 1);
+
+        let input = b"\x1B[Aa";
+        let written =
+            unsafe { libc::write(write_fd, input.as_ptr() as *const libc::c_void, input.len()) };
+        assert_eq!(written, input.len() as isize);
+
+        let timeout = Duration::from_secs(1);
+        assert_eq!(
+            reader.read_timeout(timeout),
+            Some(InputEvent::Keyboard(KeyCode::Up.into()))
+        );
+        assert_eq!(
+            reader.read_timeout(timeout),
+            Some(InputEvent::Keyboard(KeyCode::Char('a').into()))
+        );
+
+        // End of the stream removes the session
+        unsafe { libc::close(write_fd) };
+        assert_eq!(reader.read_timeout(timeout), None);
+        assert_eq!(manager.session_count(), 0);
+
+        drop(reader);
+        unsafe { libc::close(read_fd) };
+    }
+}
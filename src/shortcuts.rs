@@ -0,0 +1,188 @@
+//! Global keyboard shortcuts checked before the events are delivered.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use lazy_static::lazy_static;
+
+use crate::{InputEvent, KeyEvent, KeyEventKind};
+
+lazy_static! {
+    /// The registered global shortcuts.
+    static ref SHORTCUTS: Mutex<Vec<Shortcut>> = Mutex::new(Vec::new());
+}
+
+/// Identifier of the next registered shortcut.
+static NEXT_SHORTCUT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Represents whether the key event matching a global shortcut is delivered.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ShortcutDelivery {
+    /// The key event isn't delivered to the readers.
+    Consume,
+    /// The key event is delivered to the readers as well.
+    Deliver,
+}
+
+struct Shortcut {
+    id: usize,
+    key_event: KeyEvent,
+    delivery: ShortcutDelivery,
+    callback: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl Shortcut {
+    fn matches(&self, key_event: &KeyEvent) -> bool {
+        self.key_event.code == key_event.code && self.key_event.modifiers == key_event.modifiers
+    }
+}
+
+/// An application-wide keyboard shortcut.
+///
+/// The key events are checked before they're delivered to the readers & streams, the callback
+/// is called on the reading thread when the key is pressed (or repeated). The shortcut is
+/// unregistered when it's dropped.
+///
+/// # Notes
+///
+/// * The key events are matched after the
+///   [`InputConfig`](struct.InputConfig.html) was applied (key profile, control actions, ...),
+///   the kind of the event is ignored.
+/// * Don't block in the callback, no input is read until it returns.
+/// * The bytes parsed by the [`Parser`](struct.Parser.html) & the
+///   [`SessionManager`](struct.SessionManager.html) aren't checked.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::{
+///     atomic::{AtomicBool, Ordering},
+///     Arc,
+/// };
+///
+/// use crossterm_input::{GlobalShortcut, KeyCode, KeyEvent, KeyModifiers, ShortcutDelivery};
+///
+/// let quit = Arc::new(AtomicBool::new(false));
+/// let quit_flag = quit.clone();
+///
+/// let _shortcut = GlobalShortcut::register(
+///     KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+///     ShortcutDelivery::Consume,
+///     move || quit_flag.store(true, Ordering::SeqCst),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct GlobalShortcut {
+    id: usize,
+}
+
+impl GlobalShortcut {
+    /// Registers the `callback` called when the `key_event` is pressed.
+    pub fn register<F>(
+        key_event: KeyEvent,
+        delivery: ShortcutDelivery,
+        callback: F,
+    ) -> GlobalShortcut
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let id = NEXT_SHORTCUT_ID.fetch_add(1, Ordering::SeqCst);
+
+        SHORTCUTS.lock().unwrap().push(Shortcut {
+            id,
+            key_event,
+            delivery,
+            callback: Arc::new(callback),
+        });
+
+        GlobalShortcut { id }
+    }
+}
+
+impl Drop for GlobalShortcut {
+    fn drop(&mut self) {
+        SHORTCUTS
+            .lock()
+            .unwrap()
+            .retain(|shortcut| shortcut.id != self.id);
+    }
+}
+
+/// Calls the callbacks of the global shortcuts matching the `event`.
+///
+/// Returns `false` if the `event` was consumed by a shortcut.
+pub(crate) fn dispatch_shortcuts(event: &InputEvent) -> bool {
+    let key_event = match event {
+        InputEvent::Keyboard(key_event) => key_event,
+        _ => return true,
+    };
+
+    let (callbacks, deliver) = {
+        let shortcuts = SHORTCUTS.lock().unwrap();
+
+        let matching = shortcuts
+            .iter()
+            .filter(|shortcut| shortcut.matches(key_event))
+            .collect::<Vec<_>>();
+
+        let deliver = matching
+            .iter()
+            .all(|shortcut| shortcut.delivery == ShortcutDelivery::Deliver);
+        let callbacks = matching
+            .iter()
+            .map(|shortcut| shortcut.callback.clone())
+            .collect::<Vec<_>>();
+
+        (callbacks, deliver)
+    };
+
+    // Called with the unlocked registry, the callback can register shortcuts
+    if key_event.kind != KeyEventKind::Release {
+        for callback in callbacks {
+            callback();
+        }
+    }
+
+    deliver
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use crate::{InputEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+    use super::{dispatch_shortcuts, GlobalShortcut, ShortcutDelivery};
+
+    #[test]
+    fn test_dispatch_shortcuts() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let key_event = KeyEvent::new(KeyCode::F(24), KeyModifiers::CONTROL);
+
+        let counter = calls.clone();
+        let shortcut =
+            GlobalShortcut::register(key_event.clone(), ShortcutDelivery::Consume, move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+
+        assert!(!dispatch_shortcuts(&InputEvent::Keyboard(
+            key_event.clone()
+        )));
+        assert!(!dispatch_shortcuts(&InputEvent::Keyboard(
+            KeyEvent::new_with_kind(key_event.code, key_event.modifiers, KeyEventKind::Release)
+        )));
+        assert!(dispatch_shortcuts(&InputEvent::Keyboard(
+            KeyCode::F(24).into()
+        )));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        drop(shortcut);
+        assert!(dispatch_shortcuts(&InputEvent::Keyboard(key_event)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,307 @@
+//! Simulation of the complete user flows against the real readers.
+//!
+//! The scripted events are produced by the shared input provider, the `SyncReader`,
+//! `AsyncReader` & `EventStream` of the tested event loop consume them like the user input.
+//! The applications can test their event loops end-to-end without a pty harness.
+//!
+//! Requires the `testing` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use crossterm_input::simulate::Simulation;
+//! use crossterm_input::testing::{ctrl, EventScript};
+//! use crossterm_input::{input, InputEvent};
+//!
+//! let simulation = Simulation::new(
+//!     EventScript::new()
+//!         .text("hello")
+//!         .wait(Duration::from_millis(500))
+//!         .key(ctrl('c')),
+//! )
+//! // The half-second pause takes 5ms
+//! .with_time_scale(0.01)
+//! .spawn();
+//!
+//! // The event loop of the application
+//! let mut typed = String::new();
+//! for event in input().read_sync() {
+//!     match event {
+//!         InputEvent::Keyboard(key_event) if key_event == ctrl('c') => break,
+//!         InputEvent::Keyboard(key_event) => typed.push_str(&format!("{:?}", key_event.code)),
+//!         _ => {}
+//!     }
+//! }
+//!
+//! assert_eq!(simulation.join().expect("Unable to simulate the input"), 6);
+//! ```
+
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm_utils::{ErrorKind, Result};
+
+use crate::modes::reader_count;
+use crate::testing::{send_event, EventScript, ScriptStep};
+use crate::SourceId;
+
+/// How often the readers are checked while the simulation waits for them.
+const READER_CHECK_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A simulated user input played to the readers of the tested event loop.
+///
+/// The events are produced with the
+/// [`SourceId::Playback`](../enum.SourceId.html#variant.Playback) source once the readers
+/// exist, the events produced before the event loop creates its reader would be lost.
+///
+/// # Notes
+///
+/// * UNIX only, an error is returned on Windows.
+/// * The readers read the terminal input as well, use the
+///   [`ReadMode::Pump`](../enum.ReadMode.html#variant.Pump) read mode if there's no
+///   terminal (CI).
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    script: EventScript,
+    readers: usize,
+    reader_timeout: Duration,
+    time_scale: f64,
+    event_interval: Duration,
+}
+
+impl Simulation {
+    /// Creates a new `Simulation` of the `script`.
+    pub fn new(script: EventScript) -> Simulation {
+        Simulation {
+            script,
+            readers: 1,
+            reader_timeout: Duration::from_secs(1),
+            time_scale: 1.0,
+            event_interval: Duration::from_millis(0),
+        }
+    }
+
+    /// Waits for the `count` readers (`SyncReader`, `AsyncReader`, `EventStream`) before
+    /// the first event is produced.
+    ///
+    /// The default is one reader. The simulation fails if the readers don't exist within
+    /// the `timeout` (one second by default).
+    pub fn with_readers(mut self, count: usize, timeout: Duration) -> Simulation {
+        self.readers = count;
+        self.reader_timeout = timeout;
+        self
+    }
+
+    /// Scales the waits of the script.
+    ///
+    /// The default is `1.0`, the waits take as long as they were scripted. Scale them down to
+    /// speed the tests up (the timeouts of the event loop must be scaled too), zero skips them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `scale` is negative or not finite.
+    pub fn with_time_scale(mut self, scale: f64) -> Simulation {
+        assert!(
+            scale >= 0.0 && scale.is_finite(),
+            "The time scale must be a non-negative number"
+        );
+        self.time_scale = scale;
+        self
+    }
+
+    /// Pauses for the `interval` between the consecutive events (typing speed).
+    ///
+    /// The default is zero, the events without a scripted wait are produced at once. It isn't
+    /// scaled.
+    pub fn with_event_interval(mut self, interval: Duration) -> Simulation {
+        self.event_interval = interval;
+        self
+    }
+
+    /// Plays the simulation, blocks until all the steps are done.
+    ///
+    /// Returns the number of produced events.
+    pub fn run(self) -> Result<usize> {
+        self.play(&AtomicBool::new(false))
+    }
+
+    /// Plays the simulation in a background thread, the event loop is run by the caller.
+    ///
+    /// The simulation is stopped once the returned `SimulationHandle` is dropped.
+    pub fn spawn(self) -> SimulationHandle {
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let stopped = stopped.clone();
+            move || self.play(&stopped)
+        });
+
+        SimulationHandle {
+            stopped,
+            handle: Some(handle),
+        }
+    }
+
+    /// Returns the scripted `wait` scaled with the time scale.
+    fn scaled(&self, wait: Duration) -> Duration {
+        Duration::from_secs_f64(wait.as_secs_f64() * self.time_scale)
+    }
+
+    /// Plays the steps until they're done or the simulation is `stopped`.
+    fn play(&self, stopped: &AtomicBool) -> Result<usize> {
+        self.wait_for_readers(stopped)?;
+
+        let mut sent = 0;
+        let mut previous_event = false;
+
+        for step in self.script.steps() {
+            if stopped.load(Ordering::SeqCst) {
+                break;
+            }
+
+            match step {
+                ScriptStep::Event(event) => {
+                    if previous_event {
+                        sleep_unless_stopped(self.event_interval, stopped);
+                    }
+                    send_event(SourceId::Playback, event.clone())?;
+                    sent += 1;
+                    previous_event = true;
+                }
+                ScriptStep::Wait(wait) => {
+                    sleep_unless_stopped(self.scaled(*wait), stopped);
+                    previous_event = false;
+                }
+            };
+        }
+
+        Ok(sent)
+    }
+
+    /// Waits until there're enough readers.
+    fn wait_for_readers(&self, stopped: &AtomicBool) -> Result<()> {
+        let start = Instant::now();
+
+        while reader_count() < self.readers {
+            if stopped.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            if start.elapsed() >= self.reader_timeout {
+                return Err(ErrorKind::IoError(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "The readers of the simulated input weren't created",
+                )));
+            }
+            thread::sleep(READER_CHECK_INTERVAL);
+        }
+
+        Ok(())
+    }
+}
+
+/// Sleeps for the `duration`, wakes up early if the simulation is `stopped`.
+fn sleep_unless_stopped(duration: Duration, stopped: &AtomicBool) {
+    let deadline = Instant::now() + duration;
+
+    while !stopped.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        // Unparked by the `SimulationHandle` when it's stopped
+        thread::park_timeout(deadline - now);
+    }
+}
+
+/// A handle of the background `Simulation`.
+///
+/// The simulation is stopped once you drop the `SimulationHandle`.
+#[derive(Debug)]
+pub struct SimulationHandle {
+    stopped: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<Result<usize>>>,
+}
+
+impl SimulationHandle {
+    /// Returns `true` if all the steps were played (or the simulation failed).
+    pub fn is_finished(&self) -> bool {
+        // Safe to unwrap, it's taken in the join() & the drop() only
+        self.handle.as_ref().unwrap().is_finished()
+    }
+
+    /// Blocks until all the steps are played.
+    ///
+    /// Returns the number of produced events.
+    pub fn join(mut self) -> Result<usize> {
+        // Safe to unwrap, it's taken here & in the drop() only
+        match self.handle.take().unwrap().join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// Stops the simulation, the remaining steps aren't played.
+    ///
+    /// Returns the number of produced events.
+    pub fn stop(self) -> Result<usize> {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Safe to unwrap, it's taken in the join() & the drop() only
+        self.handle.as_ref().unwrap().thread().unpark();
+        self.join()
+    }
+}
+
+impl Drop for SimulationHandle {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::testing::EventScript;
+
+    use super::Simulation;
+
+    #[test]
+    fn test_simulation_timing() {
+        let simulation = Simulation::new(EventScript::new()).with_time_scale(0.5);
+        assert_eq!(
+            simulation.scaled(Duration::from_millis(100)),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            simulation
+                .with_time_scale(0.0)
+                .scaled(Duration::from_secs(60)),
+            Duration::from_millis(0)
+        );
+
+        // The readers don't exist, nothing is produced
+        let result = Simulation::new(EventScript::new().text("lost"))
+            .with_readers(usize::MAX, Duration::from_millis(10))
+            .run();
+        assert!(result.is_err());
+
+        let handle = Simulation::new(EventScript::new().wait(Duration::from_secs(60)))
+            .with_readers(0, Duration::from_millis(0))
+            .spawn();
+        assert_eq!(handle.stop().unwrap(), 0);
+    }
+}
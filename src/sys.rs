@@ -1,2 +1,3 @@
+pub mod ansi;
 #[cfg(unix)]
 pub mod unix;
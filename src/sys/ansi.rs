@@ -0,0 +1,2891 @@
+//! The ANSI input parser shared by the UNIX & the Windows virtual terminal input.
+
+use std::io;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crossterm_utils::{ErrorKind, Result};
+
+use crate::config::{
+    control_key_event, filter_input_event, normalize_key_event, with_input_config, InputConfig,
+    InputEncoding,
+};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::config::{mouse_event_with_origin, wheel_arrow_events};
+use crate::passthrough::{dispatch_sequence, is_subscribed_prefix};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::scroll::scroll_line_events;
+use crate::sequences::{BRACKETED_PASTE_END, BRACKETED_PASTE_START};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::terminal_info::{active_quirks, TerminalQuirks};
+use crate::{
+    InputEvent, InternalEvent, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers,
+    KeyboardEnhancementFlags, MediaKey, ModifierKey,
+};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+/// The kitty keyboard protocol flags reported by the terminal.
+///
+/// They're updated when the flags report is parsed, the keys typed before the terminal
+/// switched the protocol are parsed & tracked with the previous flags.
+pub(super) static KEYBOARD_ENHANCEMENT_FLAGS: AtomicU8 = AtomicU8::new(0);
+
+/// Returns the kitty keyboard protocol flags reported by the terminal.
+pub(crate) fn keyboard_enhancement_flags() -> KeyboardEnhancementFlags {
+    KeyboardEnhancementFlags::from_bits_truncate(KEYBOARD_ENHANCEMENT_FLAGS.load(Ordering::SeqCst))
+}
+
+/// The parser state carried from one event of the input stream to the next one.
+///
+/// Every input stream (the reading thread, a `Parser`, a session, ...) has its own state.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ParserState {
+    /// The win32-input-mode high surrogates waiting for the low surrogate of the next key
+    /// record, the key up & key down records are paired separately (indexed by the `Kd`).
+    win32_high_surrogates: [Option<u16>; 2],
+    /// The last pressed SGR mouse button, it's reported with the release if the terminal
+    /// doesn't report it (`TerminalQuirks::SGR_RELEASE_WITHOUT_BUTTON`).
+    #[cfg(not(feature = "keyboard-only"))]
+    sgr_pressed_button: Option<MouseButton>,
+    /// The streamed pasted text read, but not delivered yet (`None` if there's no streamed
+    /// paste). The input buffer contains the text read after it.
+    paste_stream: Option<Vec<u8>>,
+    /// The bytes following the rejected sequence prefix, they're parsed again once
+    /// the prefix event is delivered.
+    reparse: Vec<u8>,
+}
+
+impl ParserState {
+    /// Returns `true` if the streamed pasted text is being read.
+    pub(crate) fn is_pasting(&self) -> bool {
+        self.paste_stream.is_some()
+    }
+
+    /// Takes the bytes following the rejected sequence prefix, they have to be parsed again.
+    pub(crate) fn take_reparse(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.reparse)
+    }
+}
+
+/// Parses the `buffer` of a session not bound to the shared provider.
+///
+/// The `buffer` is cleared once the event is parsed or if it's malformed. The parsed input
+/// events are appended to the `events`, a wheel event can be translated into multiple
+/// key events.
+pub(crate) fn parse_session_event(
+    buffer: &mut Vec<u8>,
+    state: &mut ParserState,
+    input_available: bool,
+    events: &mut Vec<InputEvent>,
+) {
+    let event = match parse_event(buffer, state, input_available) {
+        // Not enough info to parse the event, wait for more bytes
+        Ok(None) => return,
+        Ok(Some(event)) => {
+            buffer.clear();
+            event
+        }
+        // Malformed sequence, clear the buffer
+        Err(_) => {
+            buffer.clear();
+            return;
+        }
+    };
+
+    let event: Option<InputEvent> = configured_event(event)
+        .filter(|event| !is_filtered_key_release(event))
+        .and_then(|event| event.into());
+
+    match event {
+        // Only the wheel events are translated, the other events are appended as they are
+        #[cfg(not(feature = "keyboard-only"))]
+        Some(event @ InputEvent::Mouse(_)) => events.extend(
+            scroll_line_events(event)
+                .into_iter()
+                .flat_map(wheel_arrow_events),
+        ),
+        Some(event) => events.push(event),
+        None => {}
+    }
+
+    // The bytes following the rejected sequence prefix are parsed again
+    let reparse = state.take_reparse();
+    for (index, byte) in reparse.iter().enumerate() {
+        buffer.push(*byte);
+        parse_session_event(
+            buffer,
+            state,
+            index + 1 < reparse.len() || input_available,
+            events,
+        );
+    }
+}
+
+/// Maps the mouse event coordinates to the configured origin, normalizes the key event
+/// with the configured key profile and filters the pasted & typed input.
+///
+/// Returns `None` if the event was dropped by the key profile or the input filter.
+pub(super) fn configured_event(event: InternalEvent) -> Option<InternalEvent> {
+    match event {
+        #[cfg(not(feature = "keyboard-only"))]
+        InternalEvent::Input(InputEvent::Mouse(mouse_event)) => Some(InternalEvent::Input(
+            InputEvent::Mouse(mouse_event_with_origin(mouse_event)),
+        )),
+        InternalEvent::Input(InputEvent::Keyboard(key_event)) => normalize_key_event(key_event)
+            .and_then(|key_event| filter_input_event(InputEvent::Keyboard(key_event)))
+            .map(InternalEvent::Input),
+        InternalEvent::Input(event @ InputEvent::Paste(_))
+        | InternalEvent::Input(event @ InputEvent::PasteChunk(_)) => {
+            filter_input_event(event).map(InternalEvent::Input)
+        }
+        event => Some(event),
+    }
+}
+
+/// Returns `true` if the `event` is a key release which shouldn't be delivered.
+pub(super) fn is_filtered_key_release(event: &InternalEvent) -> bool {
+    match event {
+        InternalEvent::Input(InputEvent::Keyboard(KeyEvent {
+            kind: KeyEventKind::Release,
+            ..
+        })) => !with_input_config(InputConfig::key_release_events),
+        _ => false,
+    }
+}
+
+/// Parses the complete event from the `buffer`.
+#[cfg(feature = "testing")]
+pub(crate) fn parse_complete_event(buffer: &[u8]) -> Option<InternalEvent> {
+    parse_event(buffer, &mut ParserState::default(), false)
+        .ok()
+        .and_then(|event| event)
+}
+
+//
+// Event parsing
+//
+// This code (& previous one) are kind of ugly. We have to think about this,
+// because it's really not maintainable, no tests, etc.
+//
+// Every fn returns Result<Option<InputEvent>>
+//
+// Ok(None) -> wait for more bytes
+// Err(_) -> failed to parse event (or the event was dropped), clear the buffer
+// Ok(Some(event)) -> we have event, clear the buffer
+// Ok(Some(InternalEvent::Consumed)) -> consumed without an event, clear the buffer
+//
+
+fn could_not_parse_event_error() -> ErrorKind {
+    ErrorKind::IoError(io::Error::new(
+        io::ErrorKind::Other,
+        "Could not parse an event",
+    ))
+}
+
+/// Parses the event from the `buffer`, the unknown sequences are passed to the matching
+/// sequence subscription (they're dropped then).
+pub(super) fn parse_event(
+    buffer: &[u8],
+    state: &mut ParserState,
+    input_available: bool,
+) -> Result<Option<InternalEvent>> {
+    match parse_known_event(buffer, state, input_available) {
+        Ok(Some(InternalEvent::Input(InputEvent::Unknown))) | Err(_)
+            if dispatch_sequence(buffer) =>
+        {
+            Err(could_not_parse_event_error())
+        }
+        result => result,
+    }
+}
+
+fn parse_known_event(
+    buffer: &[u8],
+    state: &mut ParserState,
+    input_available: bool,
+) -> Result<Option<InternalEvent>> {
+    if buffer.is_empty() {
+        return Ok(None);
+    }
+
+    // Everything is pasted text until the end sequence is read
+    if state.is_pasting() {
+        return parse_paste_stream(
+            buffer,
+            state,
+            with_input_config(InputConfig::paste_stream_threshold),
+        );
+    }
+
+    match buffer[0] {
+        b'\x1B' => {
+            if buffer.len() == 1 {
+                if input_available {
+                    // Possible Esc sequence
+                    Ok(None)
+                } else {
+                    parse_control(buffer[0])
+                }
+            } else {
+                match buffer[1] {
+                    b'O' => parse_ss3(buffer),
+                    b'[' => parse_csi(buffer, state),
+                    b'P' if buffer.len() > 2 || input_available => parse_dcs(buffer, state),
+                    b']' if buffer.len() > 2 || input_available => parse_osc(buffer, state),
+                    b'\x1B' => parse_control(buffer[1]),
+                    _ => parse_char(&buffer[1..]).map(|maybe_char| {
+                        maybe_char
+                            .map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT))
+                            .map(InputEvent::Keyboard)
+                            .map(InternalEvent::Input)
+                    }),
+                }
+            }
+        }
+        b'\x7F' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
+            KeyEvent::from(KeyCode::Backspace),
+        )))),
+        c @ b'\0'..=b'\x1F' => parse_control(c),
+        _ => parse_char(buffer).map(|maybe_char| {
+            maybe_char
+                .map(|c| KeyEvent::from(KeyCode::Char(c)))
+                .map(InputEvent::Keyboard)
+                .map(InternalEvent::Input)
+        }),
+    }
+}
+
+fn parse_control(byte: u8) -> Result<Option<InternalEvent>> {
+    // C0 controls are mapped with the input configuration, dropped control
+    // clears the buffer
+    match control_key_event(byte) {
+        Some(key_event) => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(key_event)))),
+        None => Err(could_not_parse_event_error()),
+    }
+}
+
+fn parse_ss3(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'O'])); // ESC O
+
+    if buffer.len() == 2 {
+        return Ok(None);
+    }
+
+    // Modified keys of some terminals (Konsole, older xterm, ...) - ESC O Cm Ck
+    //   Cm - modifier parameter
+    //   Ck - key
+    let (modifiers, key) = match buffer[2] {
+        b'0'..=b'9' => {
+            let key = buffer[buffer.len() - 1];
+
+            match key {
+                // The modifier parameter has at most 2 digits
+                b'0'..=b'9' if buffer.len() < 5 => return Ok(None),
+                b'0'..=b'9' => return Err(could_not_parse_event_error()),
+                _ => {}
+            };
+
+            let parameter = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+                .map_err(|_| could_not_parse_event_error())?;
+            let modifiers = parameter
+                .parse::<u16>()
+                .map(parse_modifiers)
+                .map_err(|_| could_not_parse_event_error())?;
+            (modifiers, key)
+        }
+        key => (KeyModifiers::empty(), key),
+    };
+
+    // Cursor keys in the application mode & keypad keys in the application keypad
+    // mode (Termux, xterm, ...)
+    let code = match key {
+        b'D' => KeyCode::Left,
+        b'C' => KeyCode::Right,
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        // F1-F4
+        val @ b'P'..=b'S' => KeyCode::F(1 + val - b'P'),
+        key => {
+            return Ok(Some(keypad_key_event(
+                parse_ss3_keypad_key(key)?,
+                modifiers,
+            )))
+        }
+    };
+
+    Ok(Some(key_event(code, modifiers, KeyEventKind::Press)))
+}
+
+/// Maps the SS3 keypad `key` (application keypad mode) to the `KeyCode`.
+fn parse_ss3_keypad_key(key: u8) -> Result<KeyCode> {
+    let code = match key {
+        b'M' => KeyCode::Enter,
+        // Keypad 0-9
+        val @ b'p'..=b'y' => KeyCode::Char((b'0' + val - b'p') as char),
+        b'j' => KeyCode::Char('*'),
+        b'k' => KeyCode::Char('+'),
+        b'l' => KeyCode::Char(','),
+        b'm' => KeyCode::Char('-'),
+        b'n' => KeyCode::Char('.'),
+        b'o' => KeyCode::Char('/'),
+        b'X' => KeyCode::Char('='),
+        _ => return Err(could_not_parse_event_error()),
+    };
+    Ok(code)
+}
+
+/// Creates the keypad key press event.
+fn keypad_key_event(code: KeyCode, modifiers: KeyModifiers) -> InternalEvent {
+    InternalEvent::Input(InputEvent::Keyboard(
+        KeyEvent::new(code, modifiers).with_state(KeyEventState::KEYPAD),
+    ))
+}
+
+fn parse_csi(buffer: &[u8], state: &mut ParserState) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+
+    if buffer.len() == 2 {
+        return Ok(None);
+    }
+
+    if buffer.starts_with(BRACKETED_PASTE_START) {
+        return parse_csi_bracketed_paste(
+            buffer,
+            state,
+            with_input_config(InputConfig::paste_stream_threshold),
+        );
+    }
+
+    let input_event = match buffer[2] {
+        b'[' => {
+            if buffer.len() == 3 {
+                None
+            } else {
+                match buffer[3] {
+                    // NOTE (@imdaveho): cannot find when this occurs;
+                    // having another '[' after ESC[ not a likely scenario
+                    #[cfg(not(feature = "kitty-only"))]
+                    val @ b'A'..=b'E' => {
+                        Some(InputEvent::Keyboard(KeyCode::F(1 + val - b'A').into()))
+                    }
+                    _ => Some(InputEvent::Unknown),
+                }
+            }
+        }
+        b'D' => Some(InputEvent::Keyboard(KeyCode::Left.into())),
+        b'C' => Some(InputEvent::Keyboard(KeyCode::Right.into())),
+        b'A' => Some(InputEvent::Keyboard(KeyCode::Up.into())),
+        b'B' => Some(InputEvent::Keyboard(KeyCode::Down.into())),
+        b'H' => Some(InputEvent::Keyboard(KeyCode::Home.into())),
+        b'F' => Some(InputEvent::Keyboard(KeyCode::End.into())),
+        b'Z' => Some(InputEvent::Keyboard(KeyCode::BackTab.into())),
+        b'I' => Some(InputEvent::FocusGained),
+        b'O' => Some(InputEvent::FocusLost),
+        #[cfg(not(feature = "no-x10-mouse"))]
+        b'M' => return parse_csi_x10_mouse(buffer),
+        // The X10 mouse report isn't parsed, but its 3 bytes are skipped
+        #[cfg(feature = "no-x10-mouse")]
+        b'M' if buffer.len() < 6 => None,
+        #[cfg(not(feature = "keyboard-only"))]
+        b'<' => return parse_csi_xterm_mouse(buffer, state),
+        // The SGR mouse report isn't parsed, but it's skipped up to the final byte
+        #[cfg(feature = "keyboard-only")]
+        b'<' if !buffer.ends_with(&[b'M']) && !buffer.ends_with(&[b'm']) => None,
+        b'?' => return parse_csi_private(buffer),
+        b'>' => return parse_csi_secondary(buffer),
+        b'0'..=b'9' => {
+            // Numbered escape code.
+            if buffer.len() == 3 {
+                None
+            } else {
+                // The final byte of a CSI sequence can be in the range 64-126, so
+                // let's keep reading anything else.
+                let last_byte = *buffer.last().unwrap();
+                if last_byte < 64 || last_byte > 126 {
+                    None
+                } else {
+                    match buffer[buffer.len() - 1] {
+                        #[cfg(not(feature = "keyboard-only"))]
+                        b'w' if buffer.ends_with(&[b'&', b'w']) => {
+                            return parse_csi_dec_locator(buffer)
+                        }
+                        #[cfg(feature = "keyboard-only")]
+                        b'w' if buffer.ends_with(&[b'&', b'w']) => Some(InputEvent::Unknown),
+                        #[cfg(not(feature = "no-rxvt-mouse"))]
+                        b'M' => return parse_csi_rxvt_mouse(buffer),
+                        b'~' => return parse_csi_special_key_code(buffer),
+                        b'R' => return parse_csi_cursor_position(buffer),
+                        b'u' => return parse_csi_u_encoded_key_code(buffer),
+                        b'_' => return parse_csi_win32_input(buffer, state),
+                        _ => return parse_csi_modifier_key_code(buffer),
+                    }
+                }
+            }
+        }
+        _ => Some(InputEvent::Unknown),
+    };
+
+    Ok(input_event.map(InternalEvent::Input))
+}
+
+fn next_parsed<T>(iter: &mut dyn Iterator<Item = &str>) -> Result<T>
+where
+    T: std::str::FromStr,
+{
+    iter.next()
+        .ok_or_else(|| could_not_parse_event_error())?
+        .parse::<T>()
+        .map_err(|_| could_not_parse_event_error())
+}
+
+/// The OSC notification prefixes (`ESC ] 9 ;`, `ESC ] 777 ;`).
+#[cfg(feature = "notifications")]
+const OSC_NOTIFICATION_PREFIXES: &[&[u8]] = &[b"\x1B]9;", b"\x1B]777;"];
+
+/// The maximum length of the OSC sequence, the longer sequence is dropped.
+const MAX_OSC_LENGTH: usize = 4096;
+
+/// Parses the OSC sequence (`ESC ] content ST`), the notifications are parsed (`notifications`
+/// feature) & the subscribed sequences are passed through.
+///
+/// The sequence can be terminated with the BEL (`0x07`) or the ST (`ESC \`).
+fn parse_osc(buffer: &[u8], state: &mut ParserState) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b']'])); // ESC ]
+
+    // Reject anything else early (it's Alt + ]), the buffer would grow until the terminator
+    // otherwise (the subscribed sequences are passed through once terminated)
+    if !is_osc_notification_prefix(buffer) && !is_subscribed_prefix(buffer) {
+        return parse_rejected_prefix(buffer, state);
+    }
+    if buffer.len() > MAX_OSC_LENGTH {
+        return Err(could_not_parse_event_error());
+    }
+
+    let content = if buffer.ends_with(&[b'\x07']) {
+        &buffer[2..buffer.len() - 1]
+    } else if buffer.len() > 3 && buffer.ends_with(&[b'\x1B', b'\\']) {
+        &buffer[2..buffer.len() - 2]
+    } else {
+        // Everything is the OSC content until the terminator is read
+        return Ok(None);
+    };
+
+    Ok(Some(parse_osc_content(content)))
+}
+
+/// Returns `true` if the `buffer` is (or can continue as) an OSC notification.
+#[cfg(feature = "notifications")]
+fn is_osc_notification_prefix(buffer: &[u8]) -> bool {
+    OSC_NOTIFICATION_PREFIXES
+        .iter()
+        .any(|prefix| buffer.starts_with(prefix) || prefix.starts_with(buffer))
+}
+
+#[cfg(not(feature = "notifications"))]
+fn is_osc_notification_prefix(_buffer: &[u8]) -> bool {
+    false
+}
+
+/// Parses the `content` of the OSC notification (`9 ; text` or `777 ; notify ; title ; body`).
+///
+/// Anything else is unknown, it's passed to the matching sequence subscription.
+#[cfg(feature = "notifications")]
+fn parse_osc_content(content: &[u8]) -> InternalEvent {
+    let content = String::from_utf8_lossy(content);
+    let mut split = content.splitn(2, ';');
+
+    let text = match (split.next(), split.next()) {
+        (Some("9"), Some(text)) => text.to_string(),
+        (Some("777"), Some(notification)) => {
+            let mut split = notification.splitn(3, ';');
+
+            match (split.next(), split.next(), split.next()) {
+                (Some("notify"), Some(""), Some(body)) => body.to_string(),
+                (Some("notify"), Some(title), Some(body)) => format!("{}: {}", title, body),
+                _ => return InternalEvent::Input(InputEvent::Unknown),
+            }
+        }
+        _ => return InternalEvent::Input(InputEvent::Unknown),
+    };
+
+    InternalEvent::Input(InputEvent::Notification(text))
+}
+
+/// The OSC sequences are unknown without the `notifications` feature, they're passed to
+/// the matching sequence subscription.
+#[cfg(not(feature = "notifications"))]
+fn parse_osc_content(_content: &[u8]) -> InternalEvent {
+    InternalEvent::Input(InputEvent::Unknown)
+}
+
+/// Parses the rejected sequence prefix (`ESC` & the introducer, `ESC P` or `ESC ]`) as
+/// the Alt + introducer key event, the bytes following it are parsed again.
+fn parse_rejected_prefix(buffer: &[u8], state: &mut ParserState) -> Result<Option<InternalEvent>> {
+    state.reparse = buffer[2..].to_vec();
+
+    Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
+        KeyEvent::new(KeyCode::Char(buffer[1] as char), KeyModifiers::ALT),
+    ))))
+}
+
+/// Parses the XTGETTCAP report (`ESC P 1 + r name = value ST` or `ESC P 0 + r name ST`).
+///
+/// The capability name & value are hex encoded.
+fn parse_dcs(buffer: &[u8], state: &mut ParserState) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'P'])); // ESC P
+
+    if buffer.len() > 2 && buffer[2] == b'>' {
+        return parse_dcs_version(buffer);
+    }
+
+    // Reject anything else early (it's Alt + P), the buffer would grow until the ST otherwise
+    for (index, byte) in buffer.iter().enumerate().skip(2).take(3) {
+        let valid = match index {
+            2 => *byte == b'0' || *byte == b'1',
+            3 => *byte == b'+',
+            _ => *byte == b'r',
+        };
+        if !valid {
+            return parse_rejected_prefix(buffer, state);
+        }
+    }
+
+    if buffer.len() < 7 || !buffer.ends_with(&[b'\x1B', b'\\']) {
+        return Ok(None);
+    }
+
+    let s = std::str::from_utf8(&buffer[5..buffer.len() - 2])
+        .map_err(|_| could_not_parse_event_error())?;
+    // Only the first capability is reported, one is queried
+    let mut split = s.split(';').next().unwrap_or_default().splitn(2, '=');
+
+    let name = split
+        .next()
+        .and_then(decode_hex)
+        .ok_or_else(could_not_parse_event_error)?;
+    let value = match (buffer[2], split.next()) {
+        (b'1', Some(value)) => Some(decode_hex(value).ok_or_else(could_not_parse_event_error)?),
+        _ => None,
+    };
+
+    Ok(Some(InternalEvent::TermcapReport(name, value)))
+}
+
+/// Parses the XTVERSION report (`ESC P > | name(version) ST`).
+fn parse_dcs_version(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'P', b'>'])); // ESC P >
+
+    // Reject anything else early, the buffer would grow until the ST otherwise
+    if buffer.len() > 3 && buffer[3] != b'|' {
+        return Err(could_not_parse_event_error());
+    }
+
+    if buffer.len() < 6 || !buffer.ends_with(&[b'\x1B', b'\\']) {
+        return Ok(None);
+    }
+
+    let version = std::str::from_utf8(&buffer[4..buffer.len() - 2])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    Ok(Some(InternalEvent::TerminalVersion(version.to_string())))
+}
+
+/// Decodes the hex encoded string.
+fn decode_hex(s: &str) -> Option<String> {
+    let bytes = (0..s.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(s.get(index..index + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    String::from_utf8(bytes).ok()
+}
+
+fn parse_csi_bracketed_paste(
+    buffer: &[u8],
+    state: &mut ParserState,
+    threshold: usize,
+) -> Result<Option<InternalEvent>> {
+    // ESC [ 200 ~ ... ESC [ 201 ~
+    assert!(buffer.starts_with(BRACKETED_PASTE_START));
+
+    let text = &buffer[BRACKETED_PASTE_START.len()..];
+
+    if text.ends_with(BRACKETED_PASTE_END) {
+        let text = &text[..text.len() - BRACKETED_PASTE_END.len()];
+
+        return Ok(Some(InternalEvent::Input(InputEvent::Paste(
+            String::from_utf8_lossy(text).into_owned(),
+        ))));
+    }
+
+    // Everything is pasted text until the end sequence is read, the text longer than
+    // the `threshold` is streamed (the buffer doesn't grow over it)
+    if text.len() < threshold || !is_paste_split_point(text) {
+        return Ok(None);
+    }
+
+    state.paste_stream = Some(text.to_vec());
+    Ok(Some(InternalEvent::Input(InputEvent::PasteStart)))
+}
+
+/// Parses the streamed pasted text, the text read but not delivered yet is held in
+/// the `state`.
+///
+/// The text is delivered once there's at least `threshold` bytes of it. The text preceding
+/// the `ESC [ 201` is delivered right away, the end sequence can't be delivered with it.
+fn parse_paste_stream(
+    buffer: &[u8],
+    state: &mut ParserState,
+    threshold: usize,
+) -> Result<Option<InternalEvent>> {
+    // The end sequence without the final byte
+    let end_prefix = &BRACKETED_PASTE_END[..BRACKETED_PASTE_END.len() - 1];
+
+    let mut text = state.paste_stream.take().unwrap_or_default();
+    let held = text.len();
+    text.extend_from_slice(buffer);
+
+    if text == BRACKETED_PASTE_END {
+        return Ok(Some(InternalEvent::Input(InputEvent::PasteEnd)));
+    }
+
+    let (chunk, held) = if text.len() > end_prefix.len() && text.ends_with(end_prefix) {
+        (&text[..text.len() - end_prefix.len()], end_prefix.to_vec())
+    } else if text.len() >= threshold && is_paste_split_point(&text) {
+        (&text[..], Vec::new())
+    } else {
+        // Wait for more text, the buffer is kept
+        text.truncate(held);
+        state.paste_stream = Some(text);
+        return Ok(None);
+    };
+
+    let chunk = String::from_utf8_lossy(chunk).into_owned();
+    state.paste_stream = Some(held);
+    Ok(Some(InternalEvent::Input(InputEvent::PasteChunk(chunk))))
+}
+
+/// Returns `true` if the pasted `text` can be delivered, it doesn't end with a part of
+/// the end sequence or of a UTF-8 character.
+fn is_paste_split_point(text: &[u8]) -> bool {
+    if (1..BRACKETED_PASTE_END.len()).any(|len| text.ends_with(&BRACKETED_PASTE_END[..len])) {
+        return false;
+    }
+
+    // The leading byte of the last character is one of the last 4 bytes
+    for (index, byte) in text.iter().rev().take(4).enumerate() {
+        let len = match byte {
+            0x80..=0xBF => continue,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return index + 1 >= len;
+    }
+    true
+}
+
+fn parse_csi_private(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'?'])); // ESC [ ?
+
+    // The final byte of a CSI sequence can be in the range 64-126, so
+    // let's keep reading anything else.
+    let last_byte = *buffer.last().unwrap();
+    if buffer.len() == 3 || !(64..=126).contains(&last_byte) {
+        return Ok(None);
+    }
+
+    if buffer.ends_with(&[b'$', b'y']) {
+        return parse_csi_mode_report(buffer);
+    }
+
+    if last_byte == b'u' {
+        return parse_csi_keyboard_enhancement_flags(buffer);
+    }
+
+    Ok(Some(InternalEvent::Input(InputEvent::Unknown)))
+}
+
+fn parse_csi_secondary(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'>'])); // ESC [ >
+
+    // The final byte of a CSI sequence can be in the range 64-126, so
+    // let's keep reading anything else.
+    let last_byte = *buffer.last().unwrap();
+    if buffer.len() == 3 || !(64..=126).contains(&last_byte) {
+        return Ok(None);
+    }
+
+    if last_byte == b'c' {
+        return parse_csi_device_attributes(buffer);
+    }
+
+    Ok(Some(InternalEvent::Input(InputEvent::Unknown)))
+}
+
+fn parse_csi_device_attributes(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ > Pp ; Pv ; Pc c
+    //   Pp - terminal type
+    //   Pv - firmware version
+    //   Pc - ROM cartridge registration number (always 0)
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'>'])); // ESC [ >
+    assert!(buffer.ends_with(&[b'c']));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+
+    let terminal_type = next_parsed::<u16>(&mut split)?;
+    let firmware_version = next_parsed::<u32>(&mut split)?;
+
+    Ok(Some(InternalEvent::DeviceAttributes(
+        terminal_type,
+        firmware_version,
+    )))
+}
+
+fn parse_csi_mode_report(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? Pd ; Ps $ y
+    //   Pd - DEC private mode number
+    //   Ps - mode state (0 - not recognized, 1 - set, 2 - reset, 3 - permanently set,
+    //        4 - permanently reset)
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'?'])); // ESC [ ?
+    assert!(buffer.ends_with(&[b'$', b'y']));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 2])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+
+    let mode = next_parsed::<u16>(&mut split)?;
+    let state = next_parsed::<u8>(&mut split)?;
+
+    Ok(Some(InternalEvent::ModeReport(mode, state)))
+}
+
+fn parse_csi_keyboard_enhancement_flags(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? flags u
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'?'])); // ESC [ ?
+    assert!(buffer.ends_with(&[b'u']));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let flags = s.parse::<u8>().map_err(|_| could_not_parse_event_error())?;
+
+    Ok(Some(InternalEvent::KeyboardEnhancementFlags(flags)))
+}
+
+fn parse_csi_cursor_position(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ Cy ; Cx R
+    //   Cy - cursor row number (starting from 1)
+    //   Cx - cursor column number (starting from 1)
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.ends_with(&[b'R']));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+
+    // The zero row or column is malformed
+    let y = next_parsed::<u16>(&mut split)?
+        .checked_sub(1)
+        .ok_or_else(could_not_parse_event_error)?;
+    let x = next_parsed::<u16>(&mut split)?
+        .checked_sub(1)
+        .ok_or_else(could_not_parse_event_error)?;
+
+    Ok(Some(InternalEvent::CursorPosition(x, y)))
+}
+
+/// Maps the xterm modifier parameter (`1 + Shift (1) + Alt (2) + Ctrl (4)`) extended by
+/// the kitty keyboard protocol (`Super (8) + Hyper (16) + Meta (32)`) to the `KeyModifiers`.
+///
+/// The Caps Lock & Num Lock states are ignored.
+fn parse_modifiers(parameter: u16) -> KeyModifiers {
+    let mask = parameter.saturating_sub(1);
+    let mut modifiers = KeyModifiers::empty();
+
+    if mask & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if mask & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if mask & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    if mask & 8 != 0 {
+        modifiers |= KeyModifiers::SUPER;
+    }
+    if mask & 16 != 0 {
+        modifiers |= KeyModifiers::HYPER;
+    }
+    if mask & 32 != 0 {
+        modifiers |= KeyModifiers::META;
+    }
+
+    modifiers
+}
+
+/// Parses the modifier parameter optionally followed by the kitty keyboard protocol event type
+/// (`Cm:Ce`, `1` - press, `2` - repeat, `3` - release).
+///
+/// Returns the modifiers & the kind of the key event.
+fn parse_modifiers_parameter(parameter: &str) -> Option<(KeyModifiers, KeyEventKind)> {
+    let mut split = parameter.split(':');
+
+    let modifiers = match split.next() {
+        Some("") | None => KeyModifiers::empty(),
+        Some(modifiers) => parse_modifiers(modifiers.parse::<u16>().ok()?),
+    };
+
+    let kind = match split.next() {
+        None | Some("1") => KeyEventKind::Press,
+        Some("2") => KeyEventKind::Repeat,
+        Some("3") => KeyEventKind::Release,
+        Some(_) => return None,
+    };
+
+    Some((modifiers, kind))
+}
+
+/// Creates a key event of the given `kind`.
+fn key_event(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> InternalEvent {
+    InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new_with_kind(
+        code, modifiers, kind,
+    )))
+}
+
+fn parse_csi_modifier_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ 1 ; Cm Ck
+    //   Cm - modifier parameter
+    //   Ck - key
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    // The modifier is the last parameter, some terminals send `ESC [ Cm Ck`
+    let (modifiers, kind) = match s.rsplit(';').next().and_then(parse_modifiers_parameter) {
+        Some(parameter) => parameter,
+        None => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+    };
+
+    let code = match buffer[buffer.len() - 1] {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        // F1-F4
+        val @ b'P'..=b'S' => KeyCode::F(1 + val - b'P'),
+        _ => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+    };
+
+    Ok(Some(key_event(code, modifiers, kind)))
+}
+
+fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.ends_with(&[b'~']));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+    let mut split = s.split(';');
+
+    // This CSI sequence can be a list of semicolon-separated numbers, the key code
+    // optionally followed by the modifier parameter (ex: values [3, 2] means Shift+Delete)
+    // and the Unicode key code (modifyOtherKeys, ex: values [27, 5, 105] means Ctrl+i).
+    let first = next_parsed::<u8>(&mut split)?;
+    let (modifiers, kind) = match split.next() {
+        Some(parameter) => match parse_modifiers_parameter(parameter) {
+            Some(parameter) => parameter,
+            None => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+        },
+        None => (KeyModifiers::empty(), KeyEventKind::Press),
+    };
+
+    let code = match first {
+        // modifyOtherKeys - ESC [ 27 ; Cm ; Ck ~
+        #[cfg(not(feature = "kitty-only"))]
+        27 => {
+            let mut modifiers = modifiers;
+            let code =
+                parse_unicode_key_code(next_parsed::<u32>(&mut split)?, None, &mut modifiers)?;
+            return Ok(Some(key_event(code, modifiers, kind)));
+        }
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        v @ 11..=15 => KeyCode::F(v - 10),
+        v @ 17..=21 => KeyCode::F(v - 11),
+        v @ 23..=24 => KeyCode::F(v - 12),
+        // F13-F20 (VT220)
+        v @ 25..=26 => KeyCode::F(v - 12),
+        v @ 28..=29 => KeyCode::F(v - 13),
+        v @ 31..=34 => KeyCode::F(v - 14),
+        _ => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+    };
+
+    Ok(Some(key_event(code, modifiers, kind)))
+}
+
+fn parse_csi_u_encoded_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ Ck [: Cs [: Cb]] [; Cm [: Ce] [; text]] u
+    //   Ck - Unicode key code (or the kitty functional key code)
+    //   Cs - shifted key code (alternate keys only)
+    //   Cb - base layout key code (alternate keys only)
+    //   Cm - modifier parameter
+    //   Ce - event type
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.ends_with(&[b'u']));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+    let mut split = s.split(';');
+
+    let mut codes = split.next().unwrap_or_default().split(':');
+    let key_code = next_parsed::<u32>(&mut codes)?;
+    let shifted_key_code = codes.next().and_then(|code| code.parse::<u32>().ok());
+
+    let (mut modifiers, kind) = match split.next() {
+        Some(parameter) => match parse_modifiers_parameter(parameter) {
+            Some(parameter) => parameter,
+            None => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+        },
+        None => (KeyModifiers::empty(), KeyEventKind::Press),
+    };
+
+    let code = match key_code {
+        // F13-F35
+        v @ 57376..=57398 => KeyCode::F((v - 57376 + 13) as u8),
+        57363 => KeyCode::Menu,
+        v @ 57428..=57440 => KeyCode::Media(parse_kitty_media_key(v)),
+        v @ 57441..=57454 => KeyCode::Modifier(parse_kitty_modifier_key(v)),
+        // Keypad keys
+        v @ 57399..=57426 => {
+            let event = KeyEvent::new_with_kind(parse_kitty_keypad_key(v), modifiers, kind)
+                .with_state(KeyEventState::KEYPAD);
+            return Ok(Some(InternalEvent::Input(InputEvent::Keyboard(event))));
+        }
+        // Other functional keys (Caps Lock, Print Screen, ...)
+        57344..=63743 => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+        v => parse_unicode_key_code(v, shifted_key_code, &mut modifiers)?,
+    };
+
+    Ok(Some(key_event(code, modifiers, kind)))
+}
+
+fn parse_csi_win32_input(buffer: &[u8], state: &mut ParserState) -> Result<Option<InternalEvent>> {
+    // ESC [ Vk ; Sc ; Uc ; Kd ; Cs ; Rc _
+    //   Vk - virtual key code
+    //   Sc - virtual scan code
+    //   Uc - UTF-16 code unit of the character (0 if none)
+    //   Kd - key down (1) or up (0)
+    //   Cs - control key state
+    //   Rc - repeat count
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.ends_with(&[b'_']));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    // The omitted parameters are zeros, the repeat count is one
+    let mut parameters = [0u32, 0, 0, 0, 0, 1];
+    for (parameter, value) in parameters.iter_mut().zip(s.split(';')) {
+        if !value.is_empty() {
+            *parameter = value.parse().map_err(|_| could_not_parse_event_error())?;
+        }
+    }
+    let [virtual_key_code, virtual_scan_code, unicode_char, key_down, control_key_state, _] =
+        parameters;
+
+    let key_down = key_down != 0;
+    let kind = if key_down {
+        KeyEventKind::Press
+    } else {
+        KeyEventKind::Release
+    };
+    let mut modifiers = parse_win32_modifiers(control_key_state);
+
+    let code = match virtual_key_code {
+        0x08 => KeyCode::Backspace,
+        0x09 if modifiers.contains(KeyModifiers::SHIFT) => {
+            modifiers.remove(KeyModifiers::SHIFT);
+            KeyCode::BackTab
+        }
+        0x09 => KeyCode::Tab,
+        0x0D => KeyCode::Enter,
+        0x1B => KeyCode::Esc,
+        0x21 => KeyCode::PageUp,
+        0x22 => KeyCode::PageDown,
+        0x23 => KeyCode::End,
+        0x24 => KeyCode::Home,
+        0x25 => KeyCode::Left,
+        0x26 => KeyCode::Up,
+        0x27 => KeyCode::Right,
+        0x28 => KeyCode::Down,
+        0x2D => KeyCode::Insert,
+        0x2E => KeyCode::Delete,
+        // F1 - F24
+        v @ 0x70..=0x87 => KeyCode::F((v - 0x6F) as u8),
+        0x10..=0x12 | 0x5B | 0x5C => KeyCode::Modifier(parse_win32_modifier_key(
+            virtual_key_code,
+            virtual_scan_code,
+            control_key_state,
+        )),
+        // A key without a character (Caps Lock, ...)
+        _ if unicode_char == 0 => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+        // C0 control (Ctrl + key), mapped in the same way as the legacy encoding
+        _ if unicode_char < 0x20 => {
+            // Dropped control clears the buffer
+            let event =
+                control_key_event(unicode_char as u8).ok_or_else(could_not_parse_event_error)?;
+            return Ok(Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent {
+                modifiers: event.modifiers | modifiers,
+                kind,
+                ..event
+            }))));
+        }
+        _ => match decode_win32_utf16_unit(
+            unicode_char as u16,
+            &mut state.win32_high_surrogates[key_down as usize],
+        ) {
+            Some(c) => {
+                // The character produced with the AltGr (Ctrl + Alt) isn't modified
+                if modifiers.contains(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                    modifiers.remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                }
+                KeyCode::Char(c)
+            }
+            // The high surrogate is held for the low one
+            None => return Ok(Some(InternalEvent::Input(InputEvent::Unknown))),
+        },
+    };
+
+    Ok(Some(key_event(code, modifiers, kind)))
+}
+
+/// Maps the win32-input-mode control key state to the `KeyModifiers`.
+fn parse_win32_modifiers(control_key_state: u32) -> KeyModifiers {
+    let mut modifiers = KeyModifiers::empty();
+
+    // SHIFT_PRESSED
+    if control_key_state & 0x10 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    // LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED
+    if control_key_state & 0x0C != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    // LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED
+    if control_key_state & 0x03 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+
+    modifiers
+}
+
+/// Maps the win32-input-mode modifier key record to the `ModifierKey`.
+fn parse_win32_modifier_key(
+    virtual_key_code: u32,
+    virtual_scan_code: u32,
+    control_key_state: u32,
+) -> ModifierKey {
+    // The right Control & Alt keys are the enhanced keys (`ENHANCED_KEY`), the Shift keys
+    // differ by the scan code
+    let right = match virtual_key_code {
+        0x10 => virtual_scan_code == 0x36,
+        _ => control_key_state & 0x100 != 0,
+    };
+
+    match (virtual_key_code, right) {
+        (0x10, false) => ModifierKey::LeftShift,
+        (0x10, true) => ModifierKey::RightShift,
+        (0x11, false) => ModifierKey::LeftControl,
+        (0x11, true) => ModifierKey::RightControl,
+        (0x12, false) => ModifierKey::LeftAlt,
+        (0x12, true) => ModifierKey::RightAlt,
+        (0x5B, _) => ModifierKey::LeftSuper,
+        (0x5C, _) => ModifierKey::RightSuper,
+        _ => unreachable!(),
+    }
+}
+
+/// Decodes the UTF-16 code unit of the win32-input-mode key record.
+///
+/// The characters outside of the BMP are sent as two key records, the high surrogate is held
+/// in the `pending` until the low surrogate arrives. Returns `None` for the held & unpaired
+/// surrogates.
+fn decode_win32_utf16_unit(unit: u16, pending: &mut Option<u16>) -> Option<char> {
+    match unit {
+        0xD800..=0xDBFF => {
+            *pending = Some(unit);
+            None
+        }
+        0xDC00..=0xDFFF => {
+            let high = pending.take()?;
+            std::char::decode_utf16([high, unit].iter().cloned())
+                .next()
+                .and_then(|c| c.ok())
+        }
+        unit => {
+            *pending = None;
+            std::char::from_u32(u32::from(unit))
+        }
+    }
+}
+
+/// Maps the kitty keypad `key_code` (`57399` - `57426`) to the `KeyCode`.
+fn parse_kitty_keypad_key(key_code: u32) -> KeyCode {
+    match key_code {
+        // Keypad 0-9
+        v @ 57399..=57408 => KeyCode::Char((b'0' + (v - 57399) as u8) as char),
+        57409 => KeyCode::Char('.'),
+        57410 => KeyCode::Char('/'),
+        57411 => KeyCode::Char('*'),
+        57412 => KeyCode::Char('-'),
+        57413 => KeyCode::Char('+'),
+        57414 => KeyCode::Enter,
+        57415 => KeyCode::Char('='),
+        57416 => KeyCode::Char(','),
+        57417 => KeyCode::Left,
+        57418 => KeyCode::Right,
+        57419 => KeyCode::Up,
+        57420 => KeyCode::Down,
+        57421 => KeyCode::PageUp,
+        57422 => KeyCode::PageDown,
+        57423 => KeyCode::Home,
+        57424 => KeyCode::End,
+        57425 => KeyCode::Insert,
+        57426 => KeyCode::Delete,
+        _ => unreachable!(),
+    }
+}
+
+/// Maps the kitty media `key_code` (`57428` - `57440`) to the `MediaKey`.
+fn parse_kitty_media_key(key_code: u32) -> MediaKey {
+    match key_code {
+        57428 => MediaKey::Play,
+        57429 => MediaKey::Pause,
+        57430 => MediaKey::PlayPause,
+        57431 => MediaKey::Reverse,
+        57432 => MediaKey::Stop,
+        57433 => MediaKey::FastForward,
+        57434 => MediaKey::Rewind,
+        57435 => MediaKey::TrackNext,
+        57436 => MediaKey::TrackPrevious,
+        57437 => MediaKey::Record,
+        57438 => MediaKey::LowerVolume,
+        57439 => MediaKey::RaiseVolume,
+        57440 => MediaKey::MuteVolume,
+        _ => unreachable!(),
+    }
+}
+
+/// Maps the kitty modifier `key_code` (`57441` - `57454`) to the `ModifierKey`.
+fn parse_kitty_modifier_key(key_code: u32) -> ModifierKey {
+    match key_code {
+        57441 => ModifierKey::LeftShift,
+        57442 => ModifierKey::LeftControl,
+        57443 => ModifierKey::LeftAlt,
+        57444 => ModifierKey::LeftSuper,
+        57445 => ModifierKey::LeftHyper,
+        57446 => ModifierKey::LeftMeta,
+        57447 => ModifierKey::RightShift,
+        57448 => ModifierKey::RightControl,
+        57449 => ModifierKey::RightAlt,
+        57450 => ModifierKey::RightSuper,
+        57451 => ModifierKey::RightHyper,
+        57452 => ModifierKey::RightMeta,
+        57453 => ModifierKey::IsoLevel3Shift,
+        57454 => ModifierKey::IsoLevel5Shift,
+        _ => unreachable!(),
+    }
+}
+
+/// Maps the Unicode key code (`CSI u`, modifyOtherKeys) to the `KeyCode`.
+///
+/// The Shift key is reflected in the character itself (like in the legacy encoding) if
+/// the shifted character is known and it's removed from the `modifiers`.
+fn parse_unicode_key_code(
+    key_code: u32,
+    shifted_key_code: Option<u32>,
+    modifiers: &mut KeyModifiers,
+) -> Result<KeyCode> {
+    let shift = modifiers.contains(KeyModifiers::SHIFT);
+
+    let code = match key_code {
+        9 if shift => KeyCode::BackTab,
+        9 => return Ok(KeyCode::Tab),
+        13 => return Ok(KeyCode::Enter),
+        27 => return Ok(KeyCode::Esc),
+        127 => return Ok(KeyCode::Backspace),
+        v => {
+            let c = std::char::from_u32(v).ok_or_else(could_not_parse_event_error)?;
+
+            match shifted_key_code.and_then(std::char::from_u32) {
+                Some(shifted) if shift => KeyCode::Char(shifted),
+                _ if shift && c.is_ascii_lowercase() => KeyCode::Char(c.to_ascii_uppercase()),
+                // Already shifted (modifyOtherKeys)
+                _ if shift && c.is_ascii_uppercase() => KeyCode::Char(c),
+                _ => return Ok(KeyCode::Char(c)),
+            }
+        }
+    };
+
+    modifiers.remove(KeyModifiers::SHIFT);
+    Ok(code)
+}
+
+#[cfg(not(feature = "no-rxvt-mouse"))]
+fn parse_csi_rxvt_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // rxvt mouse encoding:
+    // ESC [ Cb ; Cx ; Cy ; M
+
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.ends_with(&[b'M']));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+    let mut split = s.split(';');
+
+    let cb = next_parsed::<u16>(&mut split)?;
+    let cx = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let cy = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+
+    // The button value has the X10 offset
+    let mouse_input_event = match cb.checked_sub(32) {
+        Some(cb) => parse_mouse_event(cb, false, cx, cy),
+        None => MouseEvent::new(MouseEventKind::Unknown, None, cx, cy),
+    };
+
+    Ok(Some(InternalEvent::Input(InputEvent::Mouse(
+        mouse_input_event,
+    ))))
+}
+
+/// Decodes the xterm mouse button value `cb` (without the X10 offset).
+///
+/// The low two bits & bits 64/128 encode the button (`3` is the release of any button, `128` -
+/// `131` are the buttons 8 - 11), bits
+/// 4/8/16 the Shift/Meta/Control modifiers & bit 32 the motion. The SGR encoding reports
+/// the release with the button (`released`).
+#[cfg(not(feature = "keyboard-only"))]
+fn parse_mouse_event(cb: u16, released: bool, column: u16, row: u16) -> MouseEvent {
+    let mut modifiers = KeyModifiers::empty();
+    if cb & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if cb & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if cb & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    let button = match cb & !(4 | 8 | 16 | 32) {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        3 => None,
+        64 => Some(MouseButton::WheelUp),
+        65 => Some(MouseButton::WheelDown),
+        128 => Some(MouseButton::Back),
+        129 => Some(MouseButton::Forward),
+        v @ 130..=131 => Some(MouseButton::Other((v - 128 + 8) as u8)),
+        // Horizontal wheel
+        _ => {
+            return MouseEvent {
+                modifiers,
+                ..MouseEvent::new(MouseEventKind::Unknown, None, column, row)
+            }
+        }
+    };
+
+    let kind = match (cb & 32 != 0, button) {
+        (true, Some(_)) => MouseEventKind::Drag,
+        (true, None) => MouseEventKind::Moved,
+        (false, None) => MouseEventKind::Release,
+        (false, Some(_)) if released => MouseEventKind::Release,
+        (false, Some(_)) => MouseEventKind::Press,
+    };
+
+    MouseEvent {
+        kind,
+        button,
+        column,
+        row,
+        modifiers,
+    }
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+fn parse_csi_dec_locator(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // DEC locator report (DECLRP):
+    // ESC [ Pe ; Pb ; Pr ; Pc ; Pp & w
+    //   Pe - event code
+    //   Pb - button mask
+    //   Pr - row number (starting from 1)
+    //   Pc - column number (starting from 1)
+    //   Pp - page number
+
+    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.ends_with(&[b'&', b'w']));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 2])
+        .map_err(|_| could_not_parse_event_error())?;
+    let mut split = s.split(';');
+
+    let pe = next_parsed::<u8>(&mut split)?;
+
+    // 0 - locator unavailable, 1 - response to a request, 8/9 - M4 button, 10 - locator
+    // outside filter rectangle, the remaining parameters can be omitted
+    if !(2..=7).contains(&pe) {
+        return Ok(Some(InternalEvent::Input(InputEvent::Mouse(
+            MouseEvent::new(MouseEventKind::Unknown, None, 0, 0),
+        ))));
+    }
+
+    let _ = next_parsed::<u16>(&mut split)?;
+    let cy = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let cx = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+
+    // Even event codes are button down, odd ones button up
+    let (kind, button) = match pe {
+        2 => (MouseEventKind::Press, MouseButton::Left),
+        3 => (MouseEventKind::Release, MouseButton::Left),
+        4 => (MouseEventKind::Press, MouseButton::Middle),
+        5 => (MouseEventKind::Release, MouseButton::Middle),
+        6 => (MouseEventKind::Press, MouseButton::Right),
+        _ => (MouseEventKind::Release, MouseButton::Right),
+    };
+    let mouse_input_event = MouseEvent::new(kind, Some(button), cx, cy);
+
+    Ok(Some(InternalEvent::Input(InputEvent::Mouse(
+        mouse_input_event,
+    ))))
+}
+
+#[cfg(not(feature = "no-x10-mouse"))]
+fn parse_csi_x10_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // X10 emulation mouse encoding: ESC [ M CB Cx Cy (6 characters only).
+    // NOTE (@imdaveho): cannot find documentation on this
+
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'M'])); // ESC [ M
+
+    if buffer.len() < 6 {
+        return Ok(None);
+    }
+
+    // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
+    // The upper left character position on the terminal is denoted as 1,1.
+    // Subtract 1 to keep it synced with cursor
+    let cx = (buffer[4].saturating_sub(32) as u16).saturating_sub(1);
+    let cy = (buffer[5].saturating_sub(32) as u16).saturating_sub(1);
+
+    // The button value has the X10 offset
+    let mouse_input_event = match buffer[3].checked_sub(32) {
+        Some(cb) => parse_mouse_event(u16::from(cb), false, cx, cy),
+        None => MouseEvent::new(MouseEventKind::Unknown, None, cx, cy),
+    };
+
+    Ok(Some(InternalEvent::Input(InputEvent::Mouse(
+        mouse_input_event,
+    ))))
+}
+
+#[cfg(not(feature = "keyboard-only"))]
+fn parse_csi_xterm_mouse(buffer: &[u8], state: &mut ParserState) -> Result<Option<InternalEvent>> {
+    // ESC [ < Cb ; Cx ; Cy (;) (M or m)
+
+    assert!(buffer.starts_with(&[b'\x1B', b'[', b'<'])); // ESC [ <
+
+    if !buffer.ends_with(&[b'm']) && !buffer.ends_with(&[b'M']) {
+        return Ok(None);
+    }
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+    let mut split = s.split(';');
+
+    let cb = next_parsed::<u16>(&mut split)?;
+
+    // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
+    // The upper left character position on the terminal is denoted as 1,1.
+    // Subtract 1 to keep it synced with cursor
+    let cx = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let cy = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+
+    // The release is reported with the lowercase `m`
+    let released = buffer.ends_with(&[b'm']);
+
+    let mouse_event = apply_sgr_quirks(
+        parse_mouse_event(cb, released, cx, cy),
+        active_quirks(),
+        &mut state.sgr_pressed_button,
+    );
+    match mouse_event {
+        Some(mouse_event) => Ok(Some(InternalEvent::Input(InputEvent::Mouse(mouse_event)))),
+        // Dropped by the workaround, it isn't an unknown sequence
+        None => Ok(Some(InternalEvent::Consumed)),
+    }
+}
+
+/// Works the terminal bugs (`quirks`) around in the SGR `mouse_event`.
+///
+/// The last pressed button is kept in the `pressed_button`. Returns `None` if the event
+/// should be dropped.
+#[cfg(not(feature = "keyboard-only"))]
+fn apply_sgr_quirks(
+    mouse_event: MouseEvent,
+    quirks: TerminalQuirks,
+    pressed_button: &mut Option<MouseButton>,
+) -> Option<MouseEvent> {
+    match (mouse_event.kind, mouse_event.button) {
+        (MouseEventKind::Release, Some(MouseButton::WheelUp))
+        | (MouseEventKind::Release, Some(MouseButton::WheelDown))
+            if quirks.contains(TerminalQuirks::SGR_WHEEL_RELEASE) =>
+        {
+            None
+        }
+        (MouseEventKind::Press, Some(MouseButton::WheelUp))
+        | (MouseEventKind::Press, Some(MouseButton::WheelDown)) => Some(mouse_event),
+        (MouseEventKind::Press, Some(button)) => {
+            *pressed_button = Some(button);
+            Some(mouse_event)
+        }
+        (MouseEventKind::Release, None)
+            if quirks.contains(TerminalQuirks::SGR_RELEASE_WITHOUT_BUTTON) =>
+        {
+            Some(MouseEvent {
+                button: pressed_button.take(),
+                ..mouse_event
+            })
+        }
+        _ => Some(mouse_event),
+    }
+}
+
+/// Parses the character with the configured input encoding.
+fn parse_char(buffer: &[u8]) -> Result<Option<char>> {
+    decode_char(buffer, with_input_config(InputConfig::input_encoding))
+}
+
+fn decode_char(buffer: &[u8], encoding: InputEncoding) -> Result<Option<char>> {
+    match encoding {
+        InputEncoding::Utf8 => parse_utf8_char(buffer),
+        // The whole malformed sequence is replaced
+        InputEncoding::Utf8Lossy => parse_utf8_char(buffer).or(Ok(Some('\u{FFFD}'))),
+        // Latin-1 code points are the first 256 Unicode code points
+        InputEncoding::Latin1 => Ok(Some(char::from(buffer[0]))),
+    }
+}
+
+fn parse_utf8_char(buffer: &[u8]) -> Result<Option<char>> {
+    match std::str::from_utf8(buffer) {
+        Ok(s) => {
+            let ch = s
+                .chars()
+                .next()
+                .ok_or_else(|| could_not_parse_event_error())?;
+
+            Ok(Some(ch))
+        }
+        Err(_) => {
+            // from_utf8 failed, but we have to check if we need more bytes for code point
+            // and if all the bytes we have no are valid
+
+            let required_bytes = match buffer[0] {
+                // https://en.wikipedia.org/wiki/UTF-8#Description
+                (0x00..=0x7F) => 1, // 0xxxxxxx
+                (0xC0..=0xDF) => 2, // 110xxxxx 10xxxxxx
+                (0xE0..=0xEF) => 3, // 1110xxxx 10xxxxxx 10xxxxxx
+                (0xF0..=0xF7) => 4, // 11110xxx 10xxxxxx 10xxxxxx 10xxxxxx
+                (0x80..=0xBF) | (0xF8..=0xFF) => return Err(could_not_parse_event_error()),
+            };
+
+            // More than 1 byte, check them for 10xxxxxx pattern
+            if required_bytes > 1 && buffer.len() > 1 {
+                for byte in &buffer[1..] {
+                    if byte & !0b0011_1111 != 0b1000_0000 {
+                        return Err(could_not_parse_event_error());
+                    }
+                }
+            }
+
+            if buffer.len() < required_bytes {
+                // All bytes looks good so far, but we need more of them
+                Ok(None)
+            } else {
+                Err(could_not_parse_event_error())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    use crate::SequenceSubscription;
+
+    /// Parses the `buffer` with a new parser state.
+    fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option<InternalEvent>> {
+        super::parse_event(buffer, &mut ParserState::default(), input_available)
+    }
+
+    #[test]
+    fn test_parse_ss3() {
+        assert_eq!(parse_event(b"\x1BO", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1BOA", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Up.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1BOF", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::End.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1BOQ", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::F(2).into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1BOM", false).unwrap(),
+            Some(keypad_key_event(KeyCode::Enter, KeyModifiers::NONE)),
+        );
+        assert_eq!(
+            parse_event(b"\x1BOu", false).unwrap(),
+            Some(keypad_key_event(KeyCode::Char('5'), KeyModifiers::NONE)),
+        );
+        assert_eq!(
+            parse_event(b"\x1BOk", false).unwrap(),
+            Some(keypad_key_event(KeyCode::Char('+'), KeyModifiers::NONE)),
+        );
+        assert!(parse_event(b"\x1BO!", false).is_err());
+
+        // Modified keys
+        assert_eq!(parse_event(b"\x1BO5", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1BO5P", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::F(1),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1BO2S", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::F(4),
+                KeyModifiers::SHIFT
+            )))),
+        );
+        assert!(parse_event(b"\x1BO123", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_mode_report() {
+        assert_eq!(parse_event(b"\x1B[?", false).unwrap(), None);
+        assert_eq!(parse_event(b"\x1B[?1000;1$", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[?1000;1$y", false).unwrap(),
+            Some(InternalEvent::ModeReport(1000, 1)),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[?2004;0$y", false).unwrap(),
+            Some(InternalEvent::ModeReport(2004, 0)),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[?1;2c", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        assert!(parse_event(b"\x1B[?1000$y", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_passthrough_sequence() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let subscription = SequenceSubscription::register(&b"\x1B[?64;"[..], {
+            let received = received.clone();
+            move |sequence: &[u8]| received.lock().unwrap().push(sequence.to_vec())
+        });
+
+        assert_eq!(parse_event(b"\x1B[?64;4", true).unwrap(), None);
+        assert!(parse_event(b"\x1B[?64;4c", false).is_err());
+        assert!(parse_event(b"\x1B[?64;4xu", false).is_err());
+        // Understood by the parser
+        assert_eq!(
+            parse_event(b"\x1B[?64;1$y", false).unwrap(),
+            Some(InternalEvent::ModeReport(64, 1)),
+        );
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![b"\x1B[?64;4c".to_vec(), b"\x1B[?64;4xu".to_vec()]
+        );
+
+        drop(subscription);
+        assert_eq!(
+            parse_event(b"\x1B[?64;4c", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_keyboard_enhancement_flags() {
+        assert_eq!(
+            parse_event(b"\x1B[?3u", false).unwrap(),
+            Some(InternalEvent::KeyboardEnhancementFlags(3)),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[?0u", false).unwrap(),
+            Some(InternalEvent::KeyboardEnhancementFlags(0)),
+        );
+        assert!(parse_event(b"\x1B[?xu", false).is_err());
+    }
+
+    #[cfg(feature = "notifications")]
+    #[test]
+    fn test_parse_osc() {
+        assert_eq!(parse_event(b"\x1B]9;Build", true).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B]9;Build done\x07", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Notification(
+                "Build done".to_string()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B]777;notify;make;Build done\x1B\\", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Notification(
+                "make: Build done".to_string()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B]777;notify;;Build done\x07", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Notification(
+                "Build done".to_string()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B]777;title\x07", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // Not a notification, it's Alt + ] & the following input isn't swallowed
+        let alt_bracket = Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+            KeyCode::Char(']'),
+            KeyModifiers::ALT,
+        ))));
+        assert_eq!(parse_event(b"\x1B]", false).unwrap(), alt_bracket);
+        assert_eq!(parse_event(b"\x1B]0", true).unwrap(), alt_bracket);
+        assert_eq!(parse_event(b"\x1B]a", true).unwrap(), alt_bracket);
+        assert_eq!(parse_event(b"\x1B]77x", true).unwrap(), alt_bracket);
+        assert_eq!(parse_event(b"\x1B]77", true).unwrap(), None);
+
+        let mut too_long = b"\x1B]9;".to_vec();
+        too_long.resize(MAX_OSC_LENGTH + 1, b'a');
+        assert!(parse_event(&too_long, true).is_err());
+    }
+
+    #[test]
+    fn test_parse_terminal_identity() {
+        assert_eq!(parse_event(b"\x1B[>1;95", true).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[>1;95;0c", false).unwrap(),
+            Some(InternalEvent::DeviceAttributes(1, 95)),
+        );
+        assert_eq!(parse_event(b"\x1BP>|kitty(0.26.5)", true).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1BP>|kitty(0.26.5)\x1B\\", false).unwrap(),
+            Some(InternalEvent::TerminalVersion("kitty(0.26.5)".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_parse_dcs() {
+        assert_eq!(parse_event(b"\x1BP1+r636F", true).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1BP1+r636F6C6F7273=323536\x1B\\", false).unwrap(),
+            Some(InternalEvent::TermcapReport(
+                "colors".to_string(),
+                Some("256".to_string())
+            )),
+        );
+        assert_eq!(
+            parse_event(b"\x1BP0+r6162\x1B\\", false).unwrap(),
+            Some(InternalEvent::TermcapReport("ab".to_string(), None)),
+        );
+        assert!(parse_event(b"\x1BP1+r6=32\x1B\\", false).is_err());
+        // Alt + P
+        let alt_p = Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+            KeyCode::Char('P'),
+            KeyModifiers::ALT,
+        ))));
+        assert_eq!(parse_event(b"\x1BP", false).unwrap(), alt_p);
+        assert_eq!(parse_event(b"\x1BPx", true).unwrap(), alt_p);
+        assert_eq!(parse_event(b"\x1BP1+x", true).unwrap(), alt_p);
+    }
+
+    #[test]
+    fn test_parse_csi_bracketed_paste() {
+        assert_eq!(parse_event(b"\x1B[200", false).unwrap(), None);
+        assert_eq!(parse_event(b"\x1B[200~", false).unwrap(), None);
+        assert_eq!(parse_event(b"\x1B[200~a\x1B[A\x1B", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[200~a\x1B[Ab\x1B[201~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Paste(
+                "a\x1B[Ab".to_string()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[200~\x1B[201~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Paste(String::new()))),
+        );
+    }
+
+    /// Parses the `input` byte by byte like the readers, the buffer is cleared once an event
+    /// is parsed.
+    fn parse_paste(input: &[u8], threshold: usize) -> (Vec<InputEvent>, ParserState) {
+        let mut state = ParserState::default();
+        let mut buffer = Vec::new();
+        let mut events = Vec::new();
+
+        for byte in input {
+            buffer.push(*byte);
+            let result = if state.is_pasting() {
+                parse_paste_stream(&buffer, &mut state, threshold)
+            } else if buffer.starts_with(BRACKETED_PASTE_START) {
+                parse_csi_bracketed_paste(&buffer, &mut state, threshold)
+            } else {
+                Ok(None)
+            };
+            if let Some(InternalEvent::Input(event)) = result.unwrap() {
+                buffer.clear();
+                events.push(event);
+            }
+        }
+        (events, state)
+    }
+
+    #[test]
+    fn test_parse_bracketed_paste_stream() {
+        let paste =
+            |text: &str| [BRACKETED_PASTE_START, text.as_bytes(), BRACKETED_PASTE_END].concat();
+
+        // Shorter than the threshold
+        assert_eq!(
+            parse_paste(&paste("abc"), 4).0,
+            vec![InputEvent::Paste("abc".to_string())]
+        );
+
+        let (events, state) = parse_paste(&paste("abcdefghij"), 4);
+        assert_eq!(
+            events,
+            vec![
+                InputEvent::PasteStart,
+                InputEvent::PasteChunk("abcde".to_string()),
+                InputEvent::PasteChunk("fghi".to_string()),
+                InputEvent::PasteChunk("j".to_string()),
+                InputEvent::PasteEnd,
+            ]
+        );
+        assert_eq!(state.paste_stream, None);
+
+        // Neither the end sequence nor the characters are split
+        assert_eq!(
+            parse_paste(&paste("abcd\x1B[201;aaažž\x1B[201"), 4).0,
+            vec![
+                InputEvent::PasteStart,
+                InputEvent::PasteChunk("abcd".to_string()),
+                InputEvent::PasteChunk("\x1B[201;".to_string()),
+                InputEvent::PasteChunk("aaaž".to_string()),
+                InputEvent::PasteChunk("ž".to_string()),
+                InputEvent::PasteChunk("\x1B[201".to_string()),
+                InputEvent::PasteEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_paste_split_point() {
+        assert!(is_paste_split_point(b"a"));
+        assert!(is_paste_split_point("ž".as_bytes()));
+        assert!(is_paste_split_point(b"a\x1B[201~"));
+        assert!(!is_paste_split_point(b"a\x1B[20"));
+        assert!(!is_paste_split_point(&"ž".as_bytes()[..1]));
+        assert!(!is_paste_split_point(&"😀".as_bytes()[..3]));
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        use crate::encode::{encode, MouseEncoding};
+
+        let events = vec![
+            InputEvent::Keyboard(KeyCode::Backspace.into()),
+            InputEvent::Keyboard(KeyCode::Enter.into()),
+            InputEvent::Keyboard(KeyCode::Tab.into()),
+            InputEvent::Keyboard(KeyCode::BackTab.into()),
+            InputEvent::Keyboard(KeyCode::Null.into()),
+            InputEvent::Keyboard(KeyCode::Left.into()),
+            InputEvent::Keyboard(KeyCode::Home.into()),
+            InputEvent::Keyboard(KeyCode::End.into()),
+            InputEvent::Keyboard(KeyCode::PageDown.into()),
+            InputEvent::Keyboard(KeyCode::Insert.into()),
+            InputEvent::Keyboard(KeyCode::Delete.into()),
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL)),
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)),
+            InputEvent::Keyboard(KeyCode::F(1).into()),
+            InputEvent::Keyboard(KeyCode::F(4).into()),
+            InputEvent::Keyboard(KeyCode::F(5).into()),
+            InputEvent::Keyboard(KeyCode::F(6).into()),
+            InputEvent::Keyboard(KeyCode::F(10).into()),
+            InputEvent::Keyboard(KeyCode::F(11).into()),
+            InputEvent::Keyboard(KeyCode::F(12).into()),
+            InputEvent::Keyboard(KeyCode::Char('a').into()),
+            InputEvent::Keyboard(KeyCode::Char('ž').into()),
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT)),
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('5'), KeyModifiers::CONTROL)),
+        ];
+        #[cfg(not(feature = "keyboard-only"))]
+        let events = [
+            events,
+            vec![
+                InputEvent::Mouse(MouseEvent::new(
+                    MouseEventKind::Press,
+                    Some(MouseButton::Left),
+                    0,
+                    0,
+                )),
+                InputEvent::Mouse(MouseEvent::new(
+                    MouseEventKind::Press,
+                    Some(MouseButton::WheelDown),
+                    9,
+                    19,
+                )),
+                InputEvent::Mouse(MouseEvent::new(
+                    MouseEventKind::Release,
+                    Some(MouseButton::Right),
+                    300,
+                    400,
+                )),
+                InputEvent::Mouse(MouseEvent {
+                    modifiers: KeyModifiers::SHIFT | KeyModifiers::ALT,
+                    ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Middle), 5, 6)
+                }),
+            ],
+        ]
+        .concat();
+
+        let encodings = [
+            #[cfg(not(feature = "no-x10-mouse"))]
+            MouseEncoding::X10,
+            MouseEncoding::Sgr,
+        ];
+
+        for event in events {
+            for encoding in &encodings {
+                if let Some(bytes) = encode(&event, *encoding) {
+                    assert_eq!(
+                        parse_event(&bytes, false).unwrap(),
+                        Some(InternalEvent::Input(event.clone())),
+                        "{:?} {:?}",
+                        event,
+                        encoding
+                    );
+                }
+            }
+        }
+
+        #[cfg(not(feature = "keyboard-only"))]
+        {
+            let drag = InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Drag,
+                Some(MouseButton::Left),
+                1,
+                2,
+            ));
+            let bytes = encode(&drag, MouseEncoding::Sgr);
+            assert_eq!(
+                parse_event(&bytes.unwrap(), false).unwrap(),
+                Some(InternalEvent::Input(drag)),
+            );
+        }
+
+        // The mouse reports are skipped by the keyboard-only parser
+        #[cfg(feature = "keyboard-only")]
+        assert_eq!(
+            parse_event(b"\x1B[<0;1;2M", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+    }
+
+    #[test]
+    fn test_esc_key() {
+        assert_eq!(
+            parse_event("\x1B".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Esc.into()
+            ))),
+        );
+    }
+
+    #[test]
+    fn test_possible_esc_sequence() {
+        assert_eq!(parse_event("\x1B".as_bytes(), true).unwrap(), None,);
+    }
+
+    #[test]
+    fn test_control_keys() {
+        assert_eq!(
+            parse_event("\r".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Enter.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event("\x03".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event("\x08".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Backspace,
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event("\x7F".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Backspace.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event("\x1C".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('4'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event("\x1B\x1B".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Esc.into()
+            ))),
+        );
+    }
+
+    #[test]
+    fn test_alt_key() {
+        assert_eq!(
+            parse_event("\x1Bc".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::ALT
+            )))),
+        );
+
+        // Alt + non-ASCII character, the incomplete character is waited for
+        assert_eq!(parse_event(&"\x1Bž".as_bytes()[..2], true).unwrap(), None);
+        assert_eq!(
+            parse_event("\x1Bž".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('ž'),
+                KeyModifiers::ALT
+            )))),
+        );
+    }
+
+    #[test]
+    fn test_parse_event_subsequent_calls() {
+        // The main purpose of this test is to check if we're passing
+        // correct slice to other parse_ functions.
+
+        // parse_csi_cursor_position
+        assert_eq!(
+            parse_event("\x1B[20;10R".as_bytes(), false).unwrap(),
+            Some(InternalEvent::CursorPosition(9, 19))
+        );
+
+        // parse_csi
+        assert_eq!(
+            parse_event("\x1B[D".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Left.into()
+            ))),
+        );
+
+        // parse_csi_modifier_key_code
+        assert_eq!(
+            parse_event("\x1B[2D".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Left,
+                KeyModifiers::SHIFT
+            )))),
+        );
+
+        // parse_csi_special_key_code
+        assert_eq!(
+            parse_event("\x1B[3~".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Delete.into()
+            ))),
+        );
+
+        // parse_csi_rxvt_mouse
+        #[cfg(not(feature = "no-rxvt-mouse"))]
+        assert_eq!(
+            parse_event("\x1B[32;30;40;M".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                29,
+                39
+            ))))
+        );
+
+        // parse_csi_dec_locator
+        #[cfg(not(feature = "keyboard-only"))]
+        assert_eq!(
+            parse_event("\x1B[2;1;10;20;1&w".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                19,
+                9
+            ))))
+        );
+
+        // parse_csi_x10_mouse
+        #[cfg(not(feature = "no-x10-mouse"))]
+        assert_eq!(
+            parse_event("\x1B[M0\x60\x70".as_bytes(), false).unwrap(),
+            // Cb 16 is the Control modifier
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), 63, 79)
+            })))
+        );
+
+        // parse_csi_xterm_mouse
+        #[cfg(not(feature = "keyboard-only"))]
+        assert_eq!(
+            parse_event("\x1B[<0;20;10;M".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                19,
+                9
+            ))))
+        );
+
+        // parse_utf8_char
+        assert_eq!(
+            parse_event("Ž".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Char('Ž').into()
+            ))),
+        );
+    }
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(
+            parse_event("\t".as_bytes(), false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Tab.into()
+            ))),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_cursor_position() {
+        assert_eq!(
+            parse_csi_cursor_position("\x1B[20;10R".as_bytes()).unwrap(),
+            Some(InternalEvent::CursorPosition(9, 19))
+        );
+    }
+
+    #[test]
+    fn test_parse_csi() {
+        assert_eq!(
+            parse_csi("\x1B[D".as_bytes(), &mut ParserState::default()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Left.into()
+            ))),
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[I", &mut ParserState::default()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::FocusGained)),
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[O", &mut ParserState::default()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::FocusLost)),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_modifier_key_code() {
+        assert_eq!(
+            parse_csi_modifier_key_code("\x1B[2D".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Left,
+                KeyModifiers::SHIFT
+            )))),
+        );
+        assert_eq!(
+            parse_csi_modifier_key_code("\x1B[1;6A".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Up,
+                KeyModifiers::SHIFT | KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_csi_modifier_key_code("\x1B[1;3P".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::F(1),
+                KeyModifiers::ALT
+            )))),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_special_key_code() {
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[3~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Delete.into()
+            ))),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_vt220_function_keys() {
+        let codes = [25, 26, 28, 29, 31, 32, 33, 34];
+
+        for (n, code) in (13..=20).zip(codes.iter()) {
+            assert_eq!(
+                parse_csi_special_key_code(format!("\x1B[{}~", code).as_bytes()).unwrap(),
+                Some(InternalEvent::Input(InputEvent::Keyboard(
+                    KeyCode::F(n).into()
+                ))),
+            );
+        }
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[30~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_special_key_code_with_modifiers() {
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[3;2~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Delete,
+                KeyModifiers::SHIFT
+            )))),
+        );
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[15;7~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::F(5),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )))),
+        );
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[3;5~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Delete,
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[5;5~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::PageUp,
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[1;2~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Home,
+                KeyModifiers::SHIFT
+            )))),
+        );
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[2;3~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Insert,
+                KeyModifiers::ALT
+            )))),
+        );
+        assert_eq!(
+            parse_csi_special_key_code("\x1B[3;x~".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_u_encoded_key_code() {
+        assert_eq!(parse_event(b"\x1B[105;5", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[105;5u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('i'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;6u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[49:33;2u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Char('!').into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Esc.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[13;2u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Enter,
+                KeyModifiers::SHIFT
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[9;2u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::BackTab.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57376u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::F(13).into()
+            ))),
+        );
+        // Left Shift (dropped by the readers unless the modifier key events are enabled)
+        assert_eq!(
+            parse_event(b"\x1B[57441;2u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Modifier(ModifierKey::LeftShift),
+                KeyModifiers::SHIFT
+            )))),
+        );
+        // Caps Lock
+        assert_eq!(
+            parse_event(b"\x1B[57358u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // Super + Caps Lock, the Caps Lock is ignored
+        assert_eq!(
+            parse_event(b"\x1B[122;73u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('z'),
+                KeyModifiers::SUPER
+            )))),
+        );
+        // Ctrl + Hyper + Meta
+        assert_eq!(
+            parse_event(b"\x1B[112;53u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('p'),
+                KeyModifiers::CONTROL | KeyModifiers::HYPER | KeyModifiers::META
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[122;x;u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // Menu & media keys
+        assert_eq!(
+            parse_event(b"\x1B[57363u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Menu.into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57430u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Media(MediaKey::PlayPause).into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57439;1:3u", false).unwrap(),
+            Some(key_event(
+                KeyCode::Media(MediaKey::RaiseVolume),
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            )),
+        );
+        // Keypad Enter & 7 (Ctrl)
+        assert_eq!(
+            parse_event(b"\x1B[57414u", false).unwrap(),
+            Some(keypad_key_event(KeyCode::Enter, KeyModifiers::NONE)),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57406;5u", false).unwrap(),
+            Some(keypad_key_event(KeyCode::Char('7'), KeyModifiers::CONTROL)),
+        );
+    }
+
+    #[test]
+    fn test_parse_win32_input() {
+        let key = |code, modifiers, kind| {
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyEvent::new_with_kind(code, modifiers, kind),
+            )))
+        };
+
+        assert_eq!(parse_event(b"\x1B[65;30;97;1;0", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[65;30;97;1;0;1_", false).unwrap(),
+            key(
+                KeyCode::Char('a'),
+                KeyModifiers::empty(),
+                KeyEventKind::Press
+            ),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[65;30;65;0;16;1_", false).unwrap(),
+            key(
+                KeyCode::Char('A'),
+                KeyModifiers::SHIFT,
+                KeyEventKind::Release
+            ),
+        );
+        // Ctrl + Alt + A, Ctrl + A
+        assert_eq!(
+            parse_event(b"\x1B[65;30;1;1;10;1_", false).unwrap(),
+            key(
+                KeyCode::Char('a'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+                KeyEventKind::Press
+            ),
+        );
+        // AltGr + Q is `@` on the German layout
+        assert_eq!(
+            parse_event(b"\x1B[81;16;64;1;9;1_", false).unwrap(),
+            key(
+                KeyCode::Char('@'),
+                KeyModifiers::empty(),
+                KeyEventKind::Press
+            ),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[9;15;9;1;16;1_", false).unwrap(),
+            key(KeyCode::BackTab, KeyModifiers::empty(), KeyEventKind::Press),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[38;72;0;1;8;1_", false).unwrap(),
+            key(KeyCode::Up, KeyModifiers::CONTROL, KeyEventKind::Press),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[17;29;0;0;256;1_", false).unwrap(),
+            key(
+                KeyCode::Modifier(ModifierKey::RightControl),
+                KeyModifiers::empty(),
+                KeyEventKind::Release
+            ),
+        );
+        // Caps Lock
+        assert_eq!(
+            parse_event(b"\x1B[20;58;0;1;128;1_", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // U+1F600 is sent as a surrogate pair, the high surrogate is held by the parser state
+        let mut state = ParserState::default();
+        assert_eq!(
+            super::parse_event(b"\x1B[0;0;55357;1;0;1_", &mut state, false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // Not paired with the high surrogate of another input stream
+        assert_eq!(
+            parse_event(b"\x1B[0;0;56832;1;0;1_", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        assert_eq!(
+            super::parse_event(b"\x1B[0;0;56832;1;0;1_", &mut state, false).unwrap(),
+            key(
+                KeyCode::Char('\u{1F600}'),
+                KeyModifiers::empty(),
+                KeyEventKind::Press
+            ),
+        );
+        assert!(parse_event(b"\x1B[65;30;x;1;0;1_", false).is_err());
+    }
+
+    #[cfg(not(feature = "kitty-only"))]
+    #[test]
+    fn test_parse_modify_other_keys() {
+        assert_eq!(parse_event(b"\x1B[27;5;105", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[27;5;105~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('i'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27;6;65~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27;5;13~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Enter,
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27;2;9~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::BackTab.into()
+            ))),
+        );
+        assert!(parse_event(b"\x1B[27;5~", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_key_event_types() {
+        assert_eq!(
+            parse_event(b"\x1B[97;1:1u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyCode::Char('a').into()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;1:2u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyEvent::new_with_kind(
+                    KeyCode::Char('a'),
+                    KeyModifiers::NONE,
+                    KeyEventKind::Repeat
+                )
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;1:3u", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyEvent::new_with_kind(
+                    KeyCode::Char('a'),
+                    KeyModifiers::NONE,
+                    KeyEventKind::Release
+                )
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[1;5:3A", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyEvent::new_with_kind(KeyCode::Up, KeyModifiers::CONTROL, KeyEventKind::Release)
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[3;1:3~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Keyboard(
+                KeyEvent::new_with_kind(KeyCode::Delete, KeyModifiers::NONE, KeyEventKind::Release)
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[3;1:4~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+    }
+
+    #[cfg(not(feature = "no-rxvt-mouse"))]
+    #[test]
+    fn test_parse_csi_rxvt_mouse() {
+        assert_eq!(
+            parse_csi_rxvt_mouse("\x1B[32;30;40;M".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                29,
+                39
+            ))))
+        );
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    #[test]
+    fn test_parse_csi_dec_locator() {
+        assert_eq!(
+            parse_csi_dec_locator("\x1B[6;4;10;20;1&w".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Right),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_dec_locator("\x1B[7;0;10;20;1&w".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Right),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_dec_locator("\x1B[0&w".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Unknown,
+                None,
+                0,
+                0
+            ))))
+        );
+    }
+
+    #[cfg(feature = "kitty-only")]
+    #[test]
+    fn test_parse_kitty_only() {
+        // X10 mouse report skipped
+        assert_eq!(parse_event(b"\x1B[M0\x60", false).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[M0\x60\x70", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // rxvt mouse report
+        assert_eq!(
+            parse_event(b"\x1B[32;30;40;M", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+        // modifyOtherKeys
+        assert_eq!(
+            parse_event(b"\x1B[27;5;105~", false).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Unknown)),
+        );
+    }
+
+    #[cfg(not(feature = "no-x10-mouse"))]
+    #[test]
+    fn test_parse_csi_x10_mouse() {
+        assert_eq!(
+            parse_csi_x10_mouse("\x1B[M0\x60\x70".as_bytes()).unwrap(),
+            // Cb 16 is the Control modifier
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), 63, 79)
+            })))
+        );
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    #[test]
+    fn test_parse_mouse_zero_coordinates() {
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<0;0;0M".as_bytes(), &mut ParserState::default()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                0,
+                0
+            ))))
+        );
+        #[cfg(not(feature = "no-rxvt-mouse"))]
+        assert_eq!(
+            parse_csi_rxvt_mouse("\x1B[32;0;0;M".as_bytes()).unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                0,
+                0
+            ))))
+        );
+        #[cfg(not(feature = "no-x10-mouse"))]
+        assert_eq!(
+            parse_csi_x10_mouse("\x1B[M0  ".as_bytes()).unwrap(),
+            // Cb 16 is the Control modifier
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), 0, 0)
+            })))
+        );
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    #[test]
+    fn test_parse_csi_xterm_mouse() {
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<128;20;10M".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Back),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<161;20;10M".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Drag,
+                Some(MouseButton::Forward),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<131;20;10m".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Other(11)),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<0;20;10;M".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<0;20;10M".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<0;20;10;m".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Left),
+                19,
+                9
+            ))))
+        );
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<0;20;10m".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Left),
+                19,
+                9
+            ))))
+        );
+
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<18;20;10m".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..MouseEvent::new(MouseEventKind::Release, Some(MouseButton::Right), 19, 9)
+            })))
+        );
+        // Any-motion tracking (1003) reports the motion without a pressed button
+        assert_eq!(
+            parse_csi_xterm_mouse("\x1B[<35;20;10M".as_bytes(), &mut ParserState::default())
+                .unwrap(),
+            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Moved,
+                None,
+                19,
+                9
+            ))))
+        );
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    #[test]
+    fn test_apply_sgr_quirks() {
+        let press = MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Right), 1, 2);
+        let release = MouseEvent::new(MouseEventKind::Release, None, 1, 2);
+        let wheel_release =
+            MouseEvent::new(MouseEventKind::Release, Some(MouseButton::WheelUp), 1, 2);
+        let mut pressed_button = None;
+
+        assert_eq!(
+            apply_sgr_quirks(press, TerminalQuirks::empty(), &mut pressed_button),
+            Some(press)
+        );
+        assert_eq!(
+            apply_sgr_quirks(release, TerminalQuirks::empty(), &mut pressed_button),
+            Some(release)
+        );
+        // The pressed button is remembered without the quirks too
+        assert_eq!(
+            apply_sgr_quirks(
+                release,
+                TerminalQuirks::SGR_RELEASE_WITHOUT_BUTTON,
+                &mut pressed_button
+            ),
+            Some(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Right),
+                1,
+                2
+            ))
+        );
+        // Reported once
+        assert_eq!(
+            apply_sgr_quirks(
+                release,
+                TerminalQuirks::SGR_RELEASE_WITHOUT_BUTTON,
+                &mut pressed_button
+            ),
+            Some(release)
+        );
+
+        assert_eq!(
+            apply_sgr_quirks(
+                wheel_release,
+                TerminalQuirks::SGR_WHEEL_RELEASE,
+                &mut pressed_button
+            ),
+            None
+        );
+        assert_eq!(
+            apply_sgr_quirks(wheel_release, TerminalQuirks::empty(), &mut pressed_button),
+            Some(wheel_release)
+        );
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    #[test]
+    fn test_parse_mouse_event() {
+        assert_eq!(
+            parse_mouse_event(16, false, 1, 2),
+            MouseEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), 1, 2)
+            }
+        );
+        assert_eq!(
+            parse_mouse_event(4 | 8 | 65, false, 1, 2),
+            MouseEvent {
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::ALT,
+                ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::WheelDown), 1, 2)
+            }
+        );
+        assert_eq!(
+            parse_mouse_event(3, false, 1, 2),
+            MouseEvent::new(MouseEventKind::Release, None, 1, 2)
+        );
+        assert_eq!(
+            parse_mouse_event(32, false, 1, 2),
+            MouseEvent::new(MouseEventKind::Drag, Some(MouseButton::Left), 1, 2)
+        );
+        assert_eq!(
+            parse_mouse_event(35, false, 1, 2),
+            MouseEvent::new(MouseEventKind::Moved, None, 1, 2)
+        );
+        assert_eq!(
+            parse_mouse_event(66, false, 1, 2).kind,
+            MouseEventKind::Unknown
+        );
+    }
+
+    #[test]
+    fn test_utf8() {
+        // https://www.php.net/manual/en/reference.pcre.pattern.modifiers.php#54805
+
+        // 'Valid ASCII' => "a",
+        assert_eq!(parse_utf8_char("a".as_bytes()).unwrap(), Some('a'),);
+
+        // 'Valid 2 Octet Sequence' => "\xc3\xb1",
+        assert_eq!(parse_utf8_char(&[0xC3, 0xB1]).unwrap(), Some('ñ'),);
+
+        // 'Invalid 2 Octet Sequence' => "\xc3\x28",
+        assert!(parse_utf8_char(&[0xC3, 0x28]).is_err());
+
+        // 'Invalid Sequence Identifier' => "\xa0\xa1",
+        assert!(parse_utf8_char(&[0xA0, 0xA1]).is_err());
+
+        // 'Valid 3 Octet Sequence' => "\xe2\x82\xa1",
+        assert_eq!(
+            parse_utf8_char(&[0xE2, 0x81, 0xA1]).unwrap(),
+            Some('\u{2061}'),
+        );
+
+        // 'Invalid 3 Octet Sequence (in 2nd Octet)' => "\xe2\x28\xa1",
+        assert!(parse_utf8_char(&[0xE2, 0x28, 0xA1]).is_err());
+
+        // 'Invalid 3 Octet Sequence (in 3rd Octet)' => "\xe2\x82\x28",
+        assert!(parse_utf8_char(&[0xE2, 0x82, 0x28]).is_err());
+
+        // 'Valid 4 Octet Sequence' => "\xf0\x90\x8c\xbc",
+        assert_eq!(
+            parse_utf8_char(&[0xF0, 0x90, 0x8C, 0xBC]).unwrap(),
+            Some('𐌼'),
+        );
+
+        // 'Invalid 4 Octet Sequence (in 2nd Octet)' => "\xf0\x28\x8c\xbc",
+        assert!(parse_utf8_char(&[0xF0, 0x28, 0x8C, 0xBC]).is_err());
+
+        // 'Invalid 4 Octet Sequence (in 3rd Octet)' => "\xf0\x90\x28\xbc",
+        assert!(parse_utf8_char(&[0xF0, 0x90, 0x28, 0xBC]).is_err());
+
+        // 'Invalid 4 Octet Sequence (in 4th Octet)' => "\xf0\x28\x8c\x28",
+        assert!(parse_utf8_char(&[0xF0, 0x28, 0x8C, 0x28]).is_err());
+    }
+
+    #[test]
+    fn test_decode_char() {
+        assert_eq!(
+            decode_char(&[0xC3, 0xB1], InputEncoding::Utf8).unwrap(),
+            Some('ñ')
+        );
+        assert_eq!(
+            decode_char(&[0xC3], InputEncoding::Utf8Lossy).unwrap(),
+            None
+        );
+        assert!(decode_char(&[0xC3, 0x28], InputEncoding::Utf8).is_err());
+        assert_eq!(
+            decode_char(&[0xC3, 0x28], InputEncoding::Utf8Lossy).unwrap(),
+            Some('\u{FFFD}')
+        );
+        assert_eq!(
+            decode_char(&[0xF1], InputEncoding::Latin1).unwrap(),
+            Some('ñ')
+        );
+        assert_eq!(decode_char(b"a", InputEncoding::Latin1).unwrap(), Some('a'));
+    }
+}
@@ -1,21 +1,43 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::os::unix::io::IntoRawFd;
 use std::os::unix::io::RawFd;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     mpsc::{self, Receiver, Sender},
     Arc, Mutex,
 };
-use std::time::Duration;
-use std::{fs, io, thread};
+use std::time::{Duration, Instant};
+use std::{env, fs, io, thread};
 
-use crossterm_utils::{ErrorKind, Result};
+use crossterm_utils::{write_cout, ErrorKind, Result};
 use libc::{c_int, c_void, size_t, ssize_t};
 use mio::unix::EventedFd;
 use mio::{Events, Poll, PollOpt, Ready, Token};
+use signal_hook::iterator::Signals;
 
 use lazy_static::lazy_static;
 
-use crate::{InputEvent, InternalEvent, KeyEvent, MouseButton, MouseEvent};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::config::wheel_arrow_events;
+use crate::config::{with_input_config, EscStrategy, InputConfig, ReadMode};
+use crate::error_hook::report_error;
+use crate::keyboard_state::{set_key_releases_reported, track_key_event};
+use crate::modes::reader_count;
+#[cfg(not(feature = "keyboard-only"))]
+use crate::mouse_state::track_mouse_event;
+use crate::rate_limit::KeyRateLimiter;
+#[cfg(not(feature = "keyboard-only"))]
+use crate::scroll::scroll_line_events;
+use crate::sequences::{DEVICE_STATUS_OK, DEVICE_STATUS_QUERY};
+use crate::shortcuts::dispatch_shortcuts;
+use crate::{
+    InputEvent, InternalEvent, KeyCode, KeyEvent, KeyModifiers, KeyboardEnhancementFlags, SourceId,
+};
+
+use super::ansi::{
+    configured_event, is_filtered_key_release, parse_event, ParserState, KEYBOARD_ENHANCEMENT_FLAGS,
+};
 
 use self::utils::{check_for_error, check_for_error_result};
 
@@ -23,6 +45,14 @@ lazy_static! {
     /// A shared internal event provider.
     static ref INTERNAL_EVENT_PROVIDER: Mutex<Box<dyn InternalEventProvider>> =
         Mutex::new(default_internal_event_provider());
+    /// Senders of the pending cursor position queries (the oldest first).
+    static ref CURSOR_POSITION_QUERIES: Mutex<VecDeque<Sender<(u16, u16)>>> =
+        Mutex::new(VecDeque::new());
+}
+
+thread_local! {
+    /// Control sequences buffered by the `batch_csi_sequences` function.
+    static CSI_BATCH: RefCell<Option<String>> = const { RefCell::new(None) };
 }
 
 // TODO 1.0: Enhance utils::sys::unix::wrap_with_result and use it
@@ -56,10 +86,48 @@ pub(crate) trait InternalEventProvider: Send {
     /// This method must be called when all the receivers were dropped.
     fn pause(&mut self);
 
-    /// Creates a new `InternalEvent` receiver.
-    fn receiver(&mut self) -> Result<Receiver<InternalEvent>>;
+    /// Creates a new `SourcedEvent` receiver.
+    ///
+    /// Up to `replay` most recent events are sent to the receiver immediately.
+    fn receiver(&mut self, replay: usize) -> Result<Receiver<SourcedEvent>>;
+
+    /// Reads all the available input without blocking and sends parsed events to
+    /// all receivers.
+    ///
+    /// Returns the number of sent events.
+    fn pump(&mut self) -> Result<usize>;
+
+    /// Creates a new receiver of the raw bytes of all the parsed (or malformed) sequences.
+    fn bytes_receiver(&mut self) -> Receiver<Vec<u8>>;
+
+    /// Returns the tty the only reader waits on (`ReadMode::Direct`).
+    ///
+    /// It's kept open while the reader waits, even if the input is switched to the reading
+    /// thread meanwhile. Returns `None` if the input is read by the reading thread.
+    fn direct_tty_fd(&mut self) -> Result<Option<Arc<FileDesc>>>;
+
+    /// Reads all the available input on the caller's thread (`ReadMode::Direct`).
+    ///
+    /// Returns `false` if the input is read by the reading thread.
+    fn pump_direct(&mut self) -> Result<bool>;
+
+    /// Sends an `InternalEvent` from the `source` to all receivers.
+    #[cfg(any(feature = "testing", feature = "remote"))]
+    fn send(&self, source: SourceId, event: InternalEvent);
+
+    /// Replaces the stuck reading thread (identified by its `heartbeat`) with a new one.
+    ///
+    /// Nothing happens if the thread was already replaced or shut down.
+    fn restart_stuck_thread(&mut self, heartbeat: &Arc<AtomicUsize>);
 }
 
+/// An `InternalEvent` tagged with the source it came from & its sequence number.
+///
+/// The input events are numbered from `1` in the order they're sent to the receivers, so
+/// a receiver gets consecutive numbers. Other internal events (query responses) aren't
+/// numbered (`0`).
+pub(crate) type SourcedEvent = (SourceId, InternalEvent, u64);
+
 /// Creates a new default internal event provider.
 fn default_internal_event_provider() -> Box<dyn InternalEventProvider> {
     #[cfg(unix)]
@@ -67,13 +135,81 @@ fn default_internal_event_provider() -> Box<dyn InternalEventProvider> {
     // TODO 1.0: #[cfg(windows)]
 }
 
+/// The environment variable with the path of the debug dump file.
+const DEBUG_DUMP_ENV_VAR: &str = "CROSSTERM_INPUT_DEBUG";
+
+/// A dump of the raw input bytes & sent events for bug reports.
+///
+/// It's enabled with the `CROSSTERM_INPUT_DEBUG` environment variable set to the dump file
+/// path. The file is appended to.
+#[derive(Clone)]
+struct DebugDump {
+    file: Arc<Mutex<fs::File>>,
+}
+
+impl DebugDump {
+    /// Opens the dump file from the environment variable (if set).
+    fn from_env() -> Option<DebugDump> {
+        let path = env::var_os(DEBUG_DUMP_ENV_VAR)?;
+
+        // Errors are ignored, the debug dump must not break the input
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .ok()?;
+
+        Some(DebugDump {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Writes the `line` to the dump file.
+    fn write(&self, line: &str) {
+        use std::io::Write;
+
+        // Errors are ignored, the debug dump must not break the input
+        let _ = writeln!(self.file.lock().unwrap(), "{}", line);
+    }
+
+    /// Dumps the raw bytes of a parsed (or malformed) sequence.
+    fn bytes(&self, bytes: &[u8], malformed: bool) {
+        let suffix = if malformed { " (malformed)" } else { "" };
+        self.write(&format!("bytes {}{}", hex(bytes), suffix));
+    }
+
+    /// Dumps the event sent from the `source`.
+    fn event(&self, source: SourceId, event: &InternalEvent) {
+        self.write(&format!("event {:?} {:?}", source, event));
+    }
+}
+
+/// Formats the `bytes` as space separated hex numbers.
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// A internal event senders wrapper.
 ///
 /// The main purpose of this structure is to make the list of senders
 /// easily sharable (clone) & maintainable.
 #[derive(Clone)]
 struct UnixInternalEventChannels {
-    senders: Arc<Mutex<Vec<Sender<InternalEvent>>>>,
+    senders: Arc<Mutex<Vec<Sender<SourcedEvent>>>>,
+    /// Most recent input events (bounded by the configured replay capacity).
+    history: Arc<Mutex<VecDeque<SourcedEvent>>>,
+    /// The debug dump (if enabled with the `CROSSTERM_INPUT_DEBUG` environment variable).
+    debug_dump: Option<DebugDump>,
+    /// Raw bytes recipients (diagnostics probe).
+    byte_senders: Arc<Mutex<Vec<Sender<Vec<u8>>>>>,
+    /// The key rate limiter of the terminal input.
+    key_rate_limiter: Arc<Mutex<KeyRateLimiter>>,
+    /// Sequence number of the last sent input event.
+    sequence: Arc<AtomicU64>,
 }
 
 impl UnixInternalEventChannels {
@@ -81,25 +217,104 @@ impl UnixInternalEventChannels {
     fn new() -> UnixInternalEventChannels {
         UnixInternalEventChannels {
             senders: Arc::new(Mutex::new(vec![])),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            debug_dump: DebugDump::from_env(),
+            byte_senders: Arc::new(Mutex::new(vec![])),
+            key_rate_limiter: Arc::new(Mutex::new(KeyRateLimiter::default())),
+            sequence: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Sends the terminal input `event` through the key rate limiter.
+    ///
+    /// Returns the number of sent events.
+    fn send_input(&self, event: InputEvent) -> usize {
+        let events = self.key_rate_limiter.lock().unwrap().limit(event);
+        let sent = events.len();
+
+        for event in events {
+            self.send(SourceId::Terminal, InternalEvent::Input(event));
         }
+        sent
+    }
+
+    /// Sends the events held by the key rate limiter once all the available input was read.
+    ///
+    /// Returns the number of sent events.
+    fn flush_key_rate_limiter(&self) -> usize {
+        let events = self.key_rate_limiter.lock().unwrap().flush();
+        let sent = events.len();
+
+        for event in events {
+            self.send(SourceId::Terminal, InternalEvent::Input(event));
+        }
+        sent
+    }
+
+    /// Sends the raw bytes of a parsed (or malformed) sequence to all bytes receivers.
+    fn send_bytes(&self, bytes: &[u8]) {
+        let mut guard = self.byte_senders.lock().unwrap();
+        if !guard.is_empty() {
+            guard.retain(|sender| sender.send(bytes.to_vec()).is_ok());
+        }
+    }
+
+    /// Creates a new raw bytes receiver.
+    fn bytes_receiver(&self) -> Receiver<Vec<u8>> {
+        let (tx, rx) = mpsc::channel();
+        self.byte_senders.lock().unwrap().push(tx);
+        rx
     }
 
-    /// Sends an `InternalEvent` to all available channels.
+    /// Sends an `InternalEvent` from the `source` to all available channels.
     ///
     /// # Notes
     ///
     /// Channel is removed if the receiving end was dropped.
     ///
-    fn send(&self, event: InternalEvent) {
+    fn send(&self, source: SourceId, event: InternalEvent) {
+        if let Some(ref debug_dump) = self.debug_dump {
+            debug_dump.event(source, &event);
+        }
+
         let mut guard = self.senders.lock().unwrap();
+
+        // Numbered with the senders locked, the numbers follow the delivery order
+        let sequence = match event {
+            InternalEvent::Input(_) => self.sequence.fetch_add(1, Ordering::SeqCst) + 1,
+            _ => 0,
+        };
+        let event = (source, event, sequence);
+
         guard.retain(|sender| sender.send(event.clone()).is_ok());
+
+        // Query responses aren't replayed
+        if let (_, InternalEvent::Input(_), _) = event {
+            let capacity = with_input_config(InputConfig::replay_capacity);
+
+            let mut history = self.history.lock().unwrap();
+            history.push_back(event);
+            while history.len() > capacity {
+                history.pop_front();
+            }
+        }
     }
 
-    /// Creates a new `InternalEvent` receiver.
-    fn receiver(&self) -> Receiver<InternalEvent> {
+    /// Creates a new `SourcedEvent` receiver.
+    ///
+    /// Up to `replay` most recent events are sent to the receiver immediately.
+    fn receiver(&self, replay: usize) -> Receiver<SourcedEvent> {
         let (tx, rx) = mpsc::channel();
 
+        // Keep the senders locked while replaying, so no event is missed or sent twice
         let mut guard = self.senders.lock().unwrap();
+
+        let history = self.history.lock().unwrap();
+        for event in history.iter().skip(history.len().saturating_sub(replay)) {
+            // Can't fail, we own the receiver
+            let _ = tx.send(event.clone());
+        }
+
         guard.push(tx);
 
         rx
@@ -112,6 +327,8 @@ pub(crate) struct UnixInternalEventProvider {
     channels: UnixInternalEventChannels,
     /// A reading thread.
     reading_thread: Option<TtyReadingThread>,
+    /// A reader pumped by the caller (`ReadMode::Pump` & `ReadMode::Direct` only).
+    tty_pump: Option<TtyPump>,
 }
 
 impl UnixInternalEventProvider {
@@ -119,20 +336,49 @@ impl UnixInternalEventProvider {
         UnixInternalEventProvider {
             channels: UnixInternalEventChannels::new(),
             reading_thread: None,
+            tty_pump: None,
         }
     }
 }
 
+impl UnixInternalEventProvider {
+    /// Returns `true` if the input is read on the caller's thread (`ReadMode::Direct`), creates
+    /// the polled reader if it doesn't exist yet.
+    ///
+    /// The reading thread is spawned once there are more readers.
+    fn read_directly(&mut self) -> Result<bool> {
+        if with_input_config(InputConfig::read_mode) != ReadMode::Direct
+            || self.reading_thread.is_some()
+        {
+            return Ok(false);
+        }
+
+        if reader_count() > 1 {
+            // Broadcast by the reading thread from now on, it continues with the partially
+            // read input
+            let handover = self.tty_pump.take().map(TtyPump::into_handover);
+            self.reading_thread = Some(TtyReadingThread::spawn(self.channels.clone(), handover)?);
+            return Ok(false);
+        }
+
+        if self.tty_pump.is_none() {
+            self.tty_pump = Some(TtyPump::polled()?);
+        }
+        Ok(true)
+    }
+}
+
 impl InternalEventProvider for UnixInternalEventProvider {
     /// Shuts down the reading thread (if exists).
     fn pause(&mut self) {
         // Thread will shutdown on it's own once dropped.
         self.reading_thread = None;
+        self.tty_pump = None;
     }
 
-    /// Creates a new `InternalEvent` receiver and spawns a new reading
+    /// Creates a new `SourcedEvent` receiver and spawns a new reading
     /// thread (or reuses the existing one).
-    fn receiver(&mut self) -> Result<Receiver<InternalEvent>> {
+    fn receiver(&mut self, replay: usize) -> Result<Receiver<SourcedEvent>> {
         // If we have the `TtyReadingThread` value, but the thread itself isn't
         // running, drop it, so we can spawn a new one below.
         if !self
@@ -144,15 +390,102 @@ impl InternalEventProvider for UnixInternalEventProvider {
             self.reading_thread = None;
         }
 
-        let rx = self.channels.receiver();
+        let rx = self.channels.receiver(replay);
 
-        if self.reading_thread.is_none() {
-            let reading_thread = TtyReadingThread::new(self.channels.clone())?;
-            self.reading_thread = Some(reading_thread);
-        }
+        match with_input_config(InputConfig::read_mode) {
+            ReadMode::Poll => {
+                self.tty_pump = None;
+
+                if self.reading_thread.is_none() {
+                    let reading_thread = TtyReadingThread::new(self.channels.clone())?;
+                    self.reading_thread = Some(reading_thread);
+                }
+            }
+            ReadMode::Pump => {
+                // Events are read by the caller in the `pump` method
+                self.reading_thread = None;
+            }
+            // Events are read by the only reader, see the `read_direct` method
+            ReadMode::Direct => {}
+        };
 
         Ok(rx)
     }
+
+    /// Reads all the available input without blocking, creates the non-blocking
+    /// reader if it doesn't exist yet.
+    fn pump(&mut self) -> Result<usize> {
+        if self.tty_pump.is_none() {
+            self.tty_pump = Some(TtyPump::new()?);
+        }
+
+        // Safe to unwrap, it was created above
+        self.tty_pump.as_mut().unwrap().pump(&self.channels)
+    }
+
+    fn bytes_receiver(&mut self) -> Receiver<Vec<u8>> {
+        self.channels.bytes_receiver()
+    }
+
+    fn direct_tty_fd(&mut self) -> Result<Option<Arc<FileDesc>>> {
+        if !self.read_directly()? {
+            return Ok(None);
+        }
+
+        // Safe to unwrap, it was created in the `read_directly` method
+        Ok(Some(self.tty_pump.as_ref().unwrap().tty_fd.clone()))
+    }
+
+    fn pump_direct(&mut self) -> Result<bool> {
+        if !self.read_directly()? {
+            return Ok(false);
+        }
+
+        // Safe to unwrap, it was created in the `read_directly` method
+        self.tty_pump.as_mut().unwrap().pump(&self.channels)?;
+        Ok(true)
+    }
+
+    #[cfg(any(feature = "testing", feature = "remote"))]
+    fn send(&self, source: SourceId, event: InternalEvent) {
+        self.channels.send(source, event);
+    }
+
+    fn restart_stuck_thread(&mut self, heartbeat: &Arc<AtomicUsize>) {
+        let is_stuck = self
+            .reading_thread
+            .as_ref()
+            .map(|thread| Arc::ptr_eq(&thread.heartbeat, heartbeat))
+            .unwrap_or(false);
+
+        if !is_stuck {
+            return;
+        }
+
+        // Safe to unwrap, it was checked above
+        let stuck_thread = self.reading_thread.take().unwrap();
+
+        // The thread stuck in the middle of parsing holds the input, the new one starts over
+        let input = if stuck_thread.input.try_lock().is_ok() {
+            stuck_thread.input.clone()
+        } else {
+            Arc::default()
+        };
+        stuck_thread.detach();
+
+        // The stuck thread's tty is abandoned, it can still finish its read
+        let reading_thread = open_tty().and_then(|tty_fd| {
+            let handover = TtyHandover {
+                tty_fd: Arc::new(tty_fd),
+                input,
+            };
+            TtyReadingThread::spawn(self.channels.clone(), Some(handover))
+        });
+        match reading_thread {
+            Ok(reading_thread) => self.reading_thread = Some(reading_thread),
+            Err(error) => report_error(&error),
+        }
+    }
 }
 
 // libstd::sys::unix::fd.rs
@@ -161,11 +494,11 @@ fn max_len() -> usize {
     // with the man page quoting that if the count of bytes to read is
     // greater than `SSIZE_MAX` the result is "unspecified".
     //
-    // On macOS, however, apparently the 64-bit libc is either buggy or
-    // intentionally showing odd behavior by rejecting any read with a size
-    // larger than or equal to INT_MAX. To handle both of these the read
-    // size is capped on both platforms.
-    if cfg!(target_os = "macos") {
+    // On macOS (and iOS, which shares the libc), however, apparently the
+    // 64-bit libc is either buggy or intentionally showing odd behavior by
+    // rejecting any read with a size larger than or equal to INT_MAX. To
+    // handle both of these the read size is capped on both platforms.
+    if cfg!(any(target_os = "macos", target_os = "ios")) {
         <c_int>::max_value() as usize - 1
     } else {
         <ssize_t>::max_value() as usize
@@ -176,7 +509,7 @@ fn max_len() -> usize {
 ///
 /// It allows to retrieve raw file descriptor, write to the file descriptor and
 /// mainly it closes the file descriptor once dropped.
-struct FileDesc {
+pub(crate) struct FileDesc {
     fd: RawFd,
     close_on_drop: bool,
 }
@@ -190,13 +523,15 @@ impl FileDesc {
         FileDesc { fd, close_on_drop }
     }
 
-    fn read_byte(&self) -> Result<u8> {
-        let mut buf: [u8; 1] = [0];
-        utils::check_for_error(unsafe {
-            libc::read(self.fd, buf.as_mut_ptr() as *mut libc::c_void, 1) as c_int
+    fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let ret = check_for_error_result(unsafe {
+            libc::read(
+                self.fd,
+                buf.as_mut_ptr() as *mut c_void,
+                std::cmp::min(buf.len(), max_len()) as size_t,
+            ) as c_int
         })?;
-
-        Ok(buf[0])
+        Ok(ret as usize)
     }
 
     fn write(&self, buf: &[u8]) -> io::Result<usize> {
@@ -248,35 +583,112 @@ fn pipe() -> Result<(FileDesc, FileDesc)> {
 
 /// Creates a file descriptor pointing to the standard input or `/dev/tty`.
 fn tty_fd() -> Result<FileDesc> {
-    let (fd, close_on_drop) = if unsafe { libc::isatty(libc::STDIN_FILENO) == 1 } {
-        (libc::STDIN_FILENO, false)
+    if unsafe { libc::isatty(libc::STDIN_FILENO) == 1 } {
+        Ok(FileDesc::with_close_on_drop(libc::STDIN_FILENO, false))
     } else {
-        (
-            fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open("/dev/tty")?
-                .into_raw_fd(),
-            true,
-        )
-    };
+        open_tty()
+    }
+}
 
-    Ok(FileDesc::with_close_on_drop(fd, close_on_drop))
+/// Opens a new file descriptor pointing to `/dev/tty`.
+fn open_tty() -> Result<FileDesc> {
+    let fd = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")?
+        .into_raw_fd();
+
+    Ok(FileDesc::new(fd))
 }
 
+/// Returns the current stdin (or /dev/tty) termios as a comparable snapshot.
+pub(crate) fn tty_mode() -> Result<Vec<u64>> {
+    let tty_fd = tty_fd()?;
+
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    check_for_error(unsafe { libc::tcgetattr(tty_fd.raw_fd(), &mut termios) })?;
+
+    let mut mode = vec![
+        termios.c_iflag as u64,
+        termios.c_oflag as u64,
+        termios.c_cflag as u64,
+        termios.c_lflag as u64,
+    ];
+    mode.extend(termios.c_cc.iter().map(|cc| u64::from(*cc)));
+
+    Ok(mode)
+}
+
+/// Returns `true` if the canonical mode (`ICANON`) of the stdin (or /dev/tty) is disabled.
+pub(crate) fn is_raw_mode_enabled() -> Result<bool> {
+    let tty_fd = tty_fd()?;
+
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    check_for_error(unsafe { libc::tcgetattr(tty_fd.raw_fd(), &mut termios) })?;
+
+    Ok(termios.c_lflag & libc::ICANON == 0)
+}
+
+/// Signals notifying about the terminal resize.
+const RESIZE_SIGNALS: &[c_int] = &[signal_hook::SIGWINCH];
+
+/// Returns the size of the `tty_fd` terminal (columns, rows).
+fn tty_size(tty_fd: &FileDesc) -> Result<(u16, u16)> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    check_for_error(unsafe { libc::ioctl(tty_fd.raw_fd(), libc::TIOCGWINSZ, &mut size) })?;
+    Ok((size.ws_col, size.ws_row))
+}
+
+/// Sends the `Resize` event with the current `tty_fd` terminal size to all `channels`.
+///
+/// Returns `true` if the event was sent.
+fn send_resize(tty_fd: &FileDesc, channels: &UnixInternalEventChannels) -> bool {
+    // Errors are ignored, the event is skipped if the size isn't available
+    match tty_size(tty_fd) {
+        Ok((columns, rows)) => {
+            channels.send(
+                SourceId::Terminal,
+                InternalEvent::Input(InputEvent::Resize(columns, rows)),
+            );
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Size of the buffer the tty is read into.
+const TTY_READ_BUFFER_SIZE: usize = 1024;
+
 /// A main body of the `TtyReadingThread` reading thread.
 ///
 /// # Arguments
 ///
 /// * `channels` - `InternalEvent` recipients.
 /// * `shutdown_rx_fd` - shutdown pipe reading end file descriptor.
-fn tty_reading_thread(channels: UnixInternalEventChannels, shutdown_rx_fd: FileDesc) -> Result<()> {
+/// * `heartbeat` - incremented on every loop iteration.
+/// * `tick` - the longest poll (if watched), the idle thread keeps the `heartbeat` going.
+/// * `tty` - the handed over tty (if any).
+/// * `input` - the partially read input, shared with the replacement of the stuck thread.
+/// * `detached` - set once the thread was replaced, the read input is handed over.
+fn tty_reading_thread(
+    channels: UnixInternalEventChannels,
+    shutdown_rx_fd: FileDesc,
+    heartbeat: &AtomicUsize,
+    tick: Option<Duration>,
+    tty: Option<Arc<FileDesc>>,
+    input: &Mutex<TtyInput>,
+    detached: &AtomicBool,
+) -> Result<()> {
     // Tokens to identify file descriptor
     const TTY_TOKEN: Token = Token(0);
     const SHUTDOWN_TOKEN: Token = Token(1);
+    const SIGNAL_TOKEN: Token = Token(2);
 
-    // Get stdin (if a tty) or open /dev/tty
-    let tty_fd = tty_fd()?;
+    // Get stdin (if a tty) or open /dev/tty, the handed over tty is kept
+    let tty_fd = match tty {
+        Some(tty_fd) => tty_fd,
+        None => Arc::new(tty_fd()?),
+    };
 
     // Get raw file descriptors for
     let tty_raw_fd = tty_fd.raw_fd();
@@ -295,58 +707,468 @@ fn tty_reading_thread(channels: UnixInternalEventChannels, shutdown_rx_fd: FileD
         PollOpt::level(),
     )?;
 
-    let mut events = Events::with_capacity(2);
-    let mut buffer: Vec<u8> = Vec::with_capacity(32);
+    // Terminal resize notifications
+    let signals = Signals::new(RESIZE_SIGNALS)?;
+    poll.register(&signals, SIGNAL_TOKEN, Ready::readable(), PollOpt::level())?;
+
+    let mut events = Events::with_capacity(3);
+    let mut read_buffer = [0u8; TTY_READ_BUFFER_SIZE];
+    let mut hold_timeout = esc_hold_timeout();
 
     let get_tokens =
         |events: &Events| -> Vec<Token> { events.iter().map(|ev| ev.token()).collect() };
 
     loop {
+        heartbeat.fetch_add(1, Ordering::SeqCst);
+
         // Wait for an event on provided raw file descriptors
-        // No timeout means indefinitely
-        poll.poll(&mut events, None)?;
+        // No timeout means indefinitely, the ambiguous `ESC` prefix is held for the esc timeout
+        let esc_timeout = hold_timeout.filter(|_| {
+            let input = input.lock().unwrap();
+            is_esc_prefix(&input.buffer, &input.parser_state) || input.esc_probe.is_holding()
+        });
+        let timeout = match (esc_timeout, tick) {
+            (Some(esc_timeout), Some(tick)) => Some(esc_timeout.min(tick)),
+            (esc_timeout, tick) => esc_timeout.or(tick),
+        };
+        let poll_start = Instant::now();
+        poll.poll(&mut events, timeout)?;
 
         // Get tokens to identify file descriptors
         let tokens = get_tokens(&events);
 
+        if tokens.is_empty() {
+            // The esc timeout expired, the rest of the sequence (or the status response)
+            // didn't arrive (unless the thread woke up for the watchdog tick)
+            match esc_timeout {
+                Some(timeout) if poll_start.elapsed() >= timeout => {
+                    let mut input = input.lock().unwrap();
+                    let TtyInput {
+                        buffer,
+                        parser_state,
+                        esc_probe,
+                    } = &mut *input;
+                    esc_probe.expire(buffer, parser_state, &channels);
+                }
+                _ => {}
+            };
+            continue;
+        }
+
         if tokens.contains(&SHUTDOWN_TOKEN) {
             break;
         }
 
+        // Multiple pending resizes are coalesced, the current size is sent only
+        if tokens.contains(&SIGNAL_TOKEN) && signals.pending().count() > 0 {
+            send_resize(&tty_fd, &channels);
+        }
+
         if tokens.contains(&TTY_TOKEN) {
-            // There's an event on tty
-            if let Ok(byte) = tty_fd.read_byte() {
-                // Poll again to check if there's still anything to read when we read one byte.
-                // This time with 0 timeout which means return immediately.
-                //
-                // We need this information to distinguish between Esc key and possible
-                // Esc sequence.
-                poll.poll(&mut events, Some(Duration::from_secs(0)))?;
-
-                let tokens = get_tokens(&events);
-
-                if tokens.contains(&SHUTDOWN_TOKEN) {
+            // There's an event on tty, read all the available bytes at once
+            match tty_fd.read(&mut read_buffer) {
+                Ok(count) if count > 0 => {
+                    hold_timeout = esc_hold_timeout();
+
+                    // Poll again to check if there's still anything to read after the
+                    // last byte. This time with 0 timeout which means return immediately.
+                    //
+                    // We need this information to distinguish between Esc key and possible
+                    // Esc sequence.
+                    poll.poll(&mut events, Some(Duration::from_secs(0)))?;
+
+                    let tokens = get_tokens(&events);
+
+                    // The detached thread hands the read bytes over to its replacement
+                    if tokens.contains(&SHUTDOWN_TOKEN) && !detached.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    let more_available = tokens.contains(&TTY_TOKEN);
+                    let mut input = input.lock().unwrap();
+                    let TtyInput {
+                        buffer,
+                        parser_state,
+                        esc_probe,
+                    } = &mut *input;
+                    let bytes =
+                        esc_probe.resolve(buffer, parser_state, &read_buffer[..count], &channels);
+
+                    // The last byte is held for the esc timeout (if set)
+                    parse_and_send_bytes(
+                        buffer,
+                        parser_state,
+                        &bytes,
+                        more_available || hold_timeout.is_some(),
+                        &channels,
+                    );
+
+                    if !more_available
+                        && with_input_config(InputConfig::esc_strategy) == EscStrategy::Probe
+                    {
+                        // Errors are ignored, the prefix is resolved by the timeout
+                        let _ = esc_probe.query(buffer, parser_state);
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // End of file or an error, the terminal hung up
+                _ => {
+                    send_disconnected(&channels);
                     break;
                 }
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Sends the `Disconnected` event to all `channels`.
+fn send_disconnected(channels: &UnixInternalEventChannels) {
+    channels.send(
+        SourceId::Terminal,
+        InternalEvent::Input(InputEvent::Disconnected),
+    );
+}
+
+/// Feeds the `bytes` to the parser one by one and sends the events to all `channels`.
+///
+/// `more_available` tells whether there are more bytes to read after the last one.
+///
+/// Returns the number of sent events.
+fn parse_and_send_bytes(
+    buffer: &mut Vec<u8>,
+    state: &mut ParserState,
+    bytes: &[u8],
+    more_available: bool,
+    channels: &UnixInternalEventChannels,
+) -> usize {
+    let mut sent = 0;
+
+    for (index, byte) in bytes.iter().enumerate() {
+        // We need this information to distinguish between Esc key and possible
+        // Esc sequence.
+        let input_available = index + 1 < bytes.len() || more_available;
+
+        buffer.push(*byte);
+        if parse_and_send(buffer, state, input_available, channels) {
+            sent += 1;
+        }
+    }
+
+    sent + channels.flush_key_rate_limiter()
+}
+
+/// How long the `EscStrategy::Probe` holds the prefix for the response if the esc timeout
+/// isn't set.
+const ESC_PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Returns `true` if the `buffer` is an ambiguous `ESC` prefix (the Esc key, Alt + key or
+/// a sequence start).
+///
+/// The `ESC` in the streamed pasted text isn't ambiguous, it's pasted.
+fn is_esc_prefix(buffer: &[u8], state: &ParserState) -> bool {
+    buffer.first() == Some(&b'\x1B') && buffer.len() <= 2 && !state.is_pasting()
+}
+
+/// Decides the ambiguous `ESC` prefix held in the `buffer` once the esc timeout expired.
+///
+/// A lone `ESC` is the Esc key, `ESC [` & `ESC O` are Alt + `[` & Alt + `O`. Longer
+/// incomplete sequences are held until the remaining bytes arrive.
+///
+/// Returns `true` if an event was sent.
+fn flush_esc_prefix(
+    buffer: &mut Vec<u8>,
+    state: &mut ParserState,
+    channels: &UnixInternalEventChannels,
+) -> bool {
+    if !is_esc_prefix(buffer, state) {
+        return false;
+    }
+
+    let result = match *buffer.as_slice() {
+        [b'\x1B', c] if c == b'[' || c == b'O' => Ok(Some(InternalEvent::Input(
+            InputEvent::Keyboard(KeyEvent::new(KeyCode::Char(c as char), KeyModifiers::ALT)),
+        ))),
+        _ => parse_event(buffer, state, false),
+    };
+
+    let sent = send_parse_result(buffer, result, channels);
+    channels.flush_key_rate_limiter() > 0 || sent
+}
 
-                let input_available = tokens.contains(&TTY_TOKEN);
+/// Returns how long the ambiguous `ESC` prefix is held with the shared input configuration.
+///
+/// `None` means that it isn't held, the lone `ESC` is the Esc key if there're no more bytes
+/// to read.
+fn esc_hold_timeout() -> Option<Duration> {
+    let (strategy, esc_timeout) =
+        with_input_config(|config| (config.esc_strategy(), config.esc_timeout()));
+
+    match strategy {
+        EscStrategy::Timeout if esc_timeout > Duration::from_secs(0) => Some(esc_timeout),
+        EscStrategy::Timeout => None,
+        EscStrategy::Probe if esc_timeout > Duration::from_secs(0) => Some(esc_timeout),
+        EscStrategy::Probe => Some(ESC_PROBE_TIMEOUT),
+    }
+}
+
+/// A state of the `EscStrategy::Probe` resolution of the ambiguous `ESC` prefix.
+#[derive(Debug, Default)]
+struct EscProbe {
+    /// The status query was written, the prefix is held for the response.
+    awaiting: bool,
+    /// Number of the responses to the queries resolved without them (to be dropped).
+    stale: usize,
+    /// The start of a response split across the reads.
+    pending: Vec<u8>,
+}
+
+impl EscProbe {
+    /// Writes the status query if the `buffer` holds the ambiguous `ESC` prefix.
+    fn query(&mut self, buffer: &[u8], state: &ParserState) -> Result<()> {
+        if self.awaiting || !is_esc_prefix(buffer, state) {
+            return Ok(());
+        }
+
+        write_sequences(&[DEVICE_STATUS_QUERY])?;
+        self.awaiting = true;
+        Ok(())
+    }
+
+    /// Returns `true` if the start of a response is held for the rest of it.
+    fn is_holding(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Resolves the held prefix with the read `bytes` & drops the stale responses.
+    ///
+    /// Returns the bytes to parse.
+    fn resolve(
+        &mut self,
+        buffer: &mut Vec<u8>,
+        state: &mut ParserState,
+        bytes: &[u8],
+        channels: &UnixInternalEventChannels,
+    ) -> Vec<u8> {
+        let mut bytes = [std::mem::take(&mut self.pending).as_slice(), bytes].concat();
+
+        if self.awaiting {
+            if bytes.starts_with(DEVICE_STATUS_OK) {
+                // Nothing followed the prefix
+                self.awaiting = false;
+                flush_esc_prefix(buffer, state, channels);
+                bytes.drain(..DEVICE_STATUS_OK.len());
+            } else if DEVICE_STATUS_OK.starts_with(&bytes) {
+                // The response is split, wait for the rest of it
+                self.pending = bytes;
+                return Vec::new();
+            } else {
+                // The prefix continues, the response follows
+                self.awaiting = false;
+                self.stale += 1;
+            }
+        }
+
+        while self.stale > 0 {
+            match bytes
+                .windows(DEVICE_STATUS_OK.len())
+                .position(|window| window == DEVICE_STATUS_OK)
+            {
+                Some(index) => {
+                    bytes.drain(index..index + DEVICE_STATUS_OK.len());
+                    self.stale -= 1;
+                }
+                None => break,
+            };
+        }
 
-                buffer.push(byte);
-                match parse_event(&buffer, input_available) {
-                    // Not enough info to parse the event, wait for more bytes
-                    Ok(None) => {}
-                    // Clear the input buffer and send the event
-                    Ok(Some(event)) => {
-                        buffer.clear();
-                        channels.send(event);
+        if self.stale > 0 {
+            // The stale response may be split too, hold its start
+            if let Some(index) = (bytes.len().saturating_sub(DEVICE_STATUS_OK.len() - 1)
+                ..bytes.len())
+                .find(|&index| DEVICE_STATUS_OK.starts_with(&bytes[index..]))
+            {
+                self.pending = bytes.split_off(index);
+            }
+        }
+        bytes
+    }
+
+    /// Resolves the held prefix without the response once the hold timeout expired.
+    ///
+    /// Returns `true` if an event was sent.
+    fn expire(
+        &mut self,
+        buffer: &mut Vec<u8>,
+        state: &mut ParserState,
+        channels: &UnixInternalEventChannels,
+    ) -> bool {
+        if self.awaiting {
+            self.awaiting = false;
+            self.stale += 1;
+            // The start of the response is held for the rest of it once more
+            return flush_esc_prefix(buffer, state, channels);
+        }
+
+        // The rest of the response didn't arrive, the held bytes are input
+        let pending = std::mem::take(&mut self.pending);
+        let sent = flush_esc_prefix(buffer, state, channels);
+        parse_and_send_bytes(buffer, state, &pending, false, channels) > 0 || sent
+    }
+}
+
+/// Parses the `buffer` and sends the event (if any) to all `channels`.
+///
+/// Returns `true` if an event was sent.
+fn parse_and_send(
+    buffer: &mut Vec<u8>,
+    state: &mut ParserState,
+    input_available: bool,
+    channels: &UnixInternalEventChannels,
+) -> bool {
+    let result = parse_event(buffer, state, input_available);
+    let reparse = state.take_reparse();
+    // The bytes following the rejected sequence prefix aren't a part of the parsed event
+    buffer.truncate(buffer.len() - reparse.len());
+    let mut sent = send_parse_result(buffer, result, channels);
+
+    // They're parsed again
+    for (index, byte) in reparse.iter().enumerate() {
+        buffer.push(*byte);
+        sent |= parse_and_send(
+            buffer,
+            state,
+            index + 1 < reparse.len() || input_available,
+            channels,
+        );
+    }
+    sent
+}
+
+/// Sends the event parsed from the `buffer` (if any) to all `channels`.
+///
+/// Returns `true` if an event was sent.
+fn send_parse_result(
+    buffer: &mut Vec<u8>,
+    result: Result<Option<InternalEvent>>,
+    channels: &UnixInternalEventChannels,
+) -> bool {
+    // Incomplete sequences are dumped & recorded once completed
+    if result.as_ref().map(Option::is_some).unwrap_or(true) {
+        if let Some(ref debug_dump) = channels.debug_dump {
+            debug_dump.bytes(buffer, result.is_err());
+        }
+        channels.send_bytes(buffer);
+    }
+
+    match result {
+        // Not enough info to parse the event, wait for more bytes
+        Ok(None) => false,
+        // Clear the input buffer and send the event
+        Ok(Some(event)) => {
+            buffer.clear();
+
+            let event = match configured_event(event) {
+                Some(event) => event,
+                // Dropped by the key profile
+                None => return false,
+            };
+            match event {
+                #[cfg(not(feature = "keyboard-only"))]
+                InternalEvent::Input(InputEvent::Mouse(ref mouse_event)) => {
+                    track_mouse_event(mouse_event)
+                }
+                InternalEvent::Input(InputEvent::Keyboard(ref key_event)) => {
+                    track_key_event(key_id(key_event.code), key_event)
+                }
+                _ => {}
+            };
+            // Tracked, but not delivered
+            if is_filtered_key_release(&event) {
+                return false;
+            }
+
+            match event {
+                InternalEvent::Input(event) => {
+                    // Consumed by a global shortcut
+                    if !dispatch_shortcuts(&event) {
+                        return false;
                     }
-                    // Malformed sequence, clear the buffer
-                    Err(_) => buffer.clear(),
+
+                    #[cfg(not(feature = "keyboard-only"))]
+                    let events = scroll_line_events(event)
+                        .into_iter()
+                        .flat_map(wheel_arrow_events);
+                    #[cfg(feature = "keyboard-only")]
+                    let events = std::iter::once(event);
+
+                    let mut sent = 0;
+                    for event in events {
+                        sent += channels.send_input(event);
+                    }
+                    // Held by the key rate limiter
+                    sent > 0
+                }
+                // Dropped by a terminal quirk workaround
+                #[cfg(not(feature = "keyboard-only"))]
+                InternalEvent::Consumed => false,
+                // Not broadcast, the report belongs to one query
+                InternalEvent::CursorPosition(x, y) => {
+                    send_cursor_position(x, y);
+                    false
+                }
+                // The keys parsed from now on are encoded with the reported flags
+                InternalEvent::KeyboardEnhancementFlags(flags) => {
+                    set_keyboard_enhancement_flags(KeyboardEnhancementFlags::from_bits_truncate(
+                        flags,
+                    ));
+                    channels.send(SourceId::Terminal, event);
+                    true
+                }
+                event => {
+                    channels.send(SourceId::Terminal, event);
+                    true
                 }
             }
         }
+        // Malformed sequence, clear the buffer
+        Err(_) => {
+            buffer.clear();
+            false
+        }
+    }
+}
+
+/// Returns the key identifier used by the keyboard state tracker.
+///
+/// The identifier doesn't depend on the Shift key, the key can be released after the Shift one.
+fn key_id(code: KeyCode) -> u32 {
+    // Identifiers of the non-character keys are above the Unicode range
+    let special = |index: u32| 0x11_0000 + index;
+
+    match code {
+        KeyCode::Char(c) => c.to_lowercase().next().unwrap_or(c) as u32,
+        KeyCode::F(n) => special(0x100 + u32::from(n)),
+        KeyCode::Backspace => special(0),
+        KeyCode::Enter => special(1),
+        KeyCode::Left => special(2),
+        KeyCode::Right => special(3),
+        KeyCode::Up => special(4),
+        KeyCode::Down => special(5),
+        KeyCode::Home => special(6),
+        KeyCode::End => special(7),
+        KeyCode::PageUp => special(8),
+        KeyCode::PageDown => special(9),
+        KeyCode::Tab | KeyCode::BackTab => special(10),
+        KeyCode::Delete => special(11),
+        KeyCode::Insert => special(12),
+        KeyCode::Null => special(13),
+        KeyCode::Esc => special(14),
+        KeyCode::Menu => special(15),
+        KeyCode::Media(media_key) => special(0x200 + media_key as u32),
+        KeyCode::Modifier(modifier_key) => special(0x300 + modifier_key as u32),
     }
-    Ok(())
 }
 
 /// A stdin (or /dev/tty) reading thread.
@@ -361,6 +1183,14 @@ fn tty_reading_thread(channels: UnixInternalEventChannels, shutdown_rx_fd: FileD
 struct TtyReadingThread {
     /// Says if the thread is actually running or not.
     running: Arc<AtomicBool>,
+    /// Incremented by the thread on every loop iteration.
+    heartbeat: Arc<AtomicUsize>,
+    /// Set once the thread is shut down, stops the watchdog (if any).
+    stopped: Arc<AtomicBool>,
+    /// Set once the stuck thread is replaced, it hands the read input over.
+    detached: Arc<AtomicBool>,
+    /// The partially read input, the replacement of the stuck thread continues with it.
+    input: Arc<Mutex<TtyInput>>,
     /// A write end of the shutdown pipe.
     shutdown_tx: FileDesc,
     /// A reading thread join handle (if exists).
@@ -374,21 +1204,61 @@ impl TtyReadingThread {
     ///
     /// * `channels` - a list of channels to send all `InternalEvent`s to.
     fn new(channels: UnixInternalEventChannels) -> Result<TtyReadingThread> {
+        TtyReadingThread::spawn(channels, None)
+    }
+
+    /// Creates a new `TtyReadingThread` which continues with the `handover` tty & input
+    /// (if any).
+    fn spawn(
+        channels: UnixInternalEventChannels,
+        handover: Option<TtyHandover>,
+    ) -> Result<TtyReadingThread> {
         let (shutdown_rx, shutdown_tx) = pipe()?;
         let running = Arc::new(AtomicBool::new(false));
+        let heartbeat = Arc::new(AtomicUsize::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let detached = Arc::new(AtomicBool::new(false));
+        let (tty, input) = match handover {
+            Some(TtyHandover { tty_fd, input }) => (Some(tty_fd), input),
+            None => (None, Arc::default()),
+        };
+
+        let watchdog_timeout = with_input_config(InputConfig::watchdog_timeout);
+        let watched = watchdog_timeout > Duration::from_secs(0);
+        // The idle thread wakes up twice per the watchdog timeout at least
+        let tick = Some(watchdog_timeout / 2).filter(|_| watched);
 
         let handle = thread::spawn({
             let running = running.clone();
+            let heartbeat = heartbeat.clone();
+            let detached = detached.clone();
+            let input = input.clone();
             move || -> Result<()> {
                 running.store(true, Ordering::SeqCst);
-                let result = tty_reading_thread(channels, shutdown_rx);
+                let result = tty_reading_thread(
+                    channels,
+                    shutdown_rx,
+                    &heartbeat,
+                    tick,
+                    tty,
+                    &input,
+                    &detached,
+                );
                 running.store(false, Ordering::SeqCst);
                 result
             }
         });
 
+        if watched {
+            spawn_watchdog(watchdog_timeout, heartbeat.clone(), stopped.clone());
+        }
+
         Ok(TtyReadingThread {
             running,
+            heartbeat,
+            stopped,
+            detached,
+            input,
             shutdown_tx,
             handle: Some(handle),
         })
@@ -400,656 +1270,887 @@ impl TtyReadingThread {
     }
 
     fn shutdown(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
         let _ = self.shutdown_tx.write("My precious, shutdown.".as_bytes());
     }
+
+    /// Shuts down the stuck thread without waiting for it, it hands the read input over to
+    /// its replacement & exits once it unblocks.
+    fn detach(mut self) {
+        self.detached.store(true, Ordering::SeqCst);
+        // Dropping the join handle detaches the thread
+        self.handle.take();
+    }
 }
 
 impl Drop for TtyReadingThread {
     fn drop(&mut self) {
         self.shutdown();
 
-        // Safe to unwrap, it's taken in the drop() only
-        let handle = self.handle.take().unwrap();
-        let _ = handle.join();
+        // The handle was taken if the thread was detached
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
     }
 }
 
-pub(crate) fn internal_event_receiver() -> Result<Receiver<InternalEvent>> {
-    INTERNAL_EVENT_PROVIDER.lock().unwrap().receiver()
-}
+/// Spawns the watchdog of a reading thread, the thread whose `heartbeat` didn't change for
+/// the `timeout` is reported to the error hook & restarted.
+///
+/// The watchdog exits once it's `stopped` (the thread was dropped) or the thread was restarted.
+fn spawn_watchdog(timeout: Duration, heartbeat: Arc<AtomicUsize>, stopped: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut last_heartbeat = heartbeat.load(Ordering::SeqCst);
 
-//
-// Event parsing
-//
-// This code (& previous one) are kind of ugly. We have to think about this,
-// because it's really not maintainable, no tests, etc.
-//
-// Every fn returns Result<Option<InputEvent>>
-//
-// Ok(None) -> wait for more bytes
-// Err(_) -> failed to parse event, clear the buffer
-// Ok(Some(event)) -> we have event, clear the buffer
-//
-
-fn could_not_parse_event_error() -> ErrorKind {
-    ErrorKind::IoError(io::Error::new(
-        io::ErrorKind::Other,
-        "Could not parse an event",
-    ))
-}
+        loop {
+            thread::sleep(timeout);
 
-fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option<InternalEvent>> {
-    if buffer.is_empty() {
-        return Ok(None);
-    }
-
-    match buffer[0] {
-        b'\x1B' => {
-            if buffer.len() == 1 {
-                if input_available {
-                    // Possible Esc sequence
-                    Ok(None)
-                } else {
-                    Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-                        KeyEvent::Esc,
-                    ))))
-                }
-            } else {
-                match buffer[1] {
-                    b'O' => {
-                        if buffer.len() == 2 {
-                            Ok(None)
-                        } else {
-                            match buffer[2] {
-                                // F1-F4
-                                val @ b'P'..=b'S' => Ok(Some(InternalEvent::Input(
-                                    InputEvent::Keyboard(KeyEvent::F(1 + val - b'P')),
-                                ))),
-                                _ => Err(could_not_parse_event_error()),
-                            }
-                        }
-                    }
-                    b'[' => parse_csi(buffer),
-                    b'\x1B' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-                        KeyEvent::Esc,
-                    )))),
-                    _ => parse_utf8_char(&buffer[1..]).map(|maybe_char| {
-                        maybe_char
-                            .map(KeyEvent::Alt)
-                            .map(InputEvent::Keyboard)
-                            .map(InternalEvent::Input)
-                    }),
-                }
+            if stopped.load(Ordering::SeqCst) {
+                return;
             }
+
+            let current_heartbeat = heartbeat.load(Ordering::SeqCst);
+            if current_heartbeat != last_heartbeat {
+                last_heartbeat = current_heartbeat;
+                continue;
+            }
+
+            report_error(&ErrorKind::IoError(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "The input reading thread is stuck, restarting it.",
+            )));
+            INTERNAL_EVENT_PROVIDER
+                .lock()
+                .unwrap()
+                .restart_stuck_thread(&heartbeat);
+            return;
         }
-        b'\r' | b'\n' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-            KeyEvent::Enter,
-        )))),
-        b'\t' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-            KeyEvent::Tab,
-        )))),
-        b'\x7F' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-            KeyEvent::Backspace,
-        )))),
-        c @ b'\x01'..=b'\x1A' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-            KeyEvent::Ctrl((c as u8 - 0x1 + b'a') as char),
-        )))),
-        c @ b'\x1C'..=b'\x1F' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-            KeyEvent::Ctrl((c as u8 - 0x1C + b'4') as char),
-        )))),
-        b'\0' => Ok(Some(InternalEvent::Input(InputEvent::Keyboard(
-            KeyEvent::Null,
-        )))),
-        _ => parse_utf8_char(buffer).map(|maybe_char| {
-            maybe_char
-                .map(KeyEvent::Char)
-                .map(InputEvent::Keyboard)
-                .map(InternalEvent::Input)
-        }),
-    }
+    });
 }
 
-fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+/// The tty & the input partially read by the direct reader (or the stuck reading thread),
+/// the reading thread continues with them.
+struct TtyHandover {
+    /// The tty, it's kept open across the switch (a new one replaces the stuck thread's).
+    tty_fd: Arc<FileDesc>,
+    /// The partially read input.
+    input: Arc<Mutex<TtyInput>>,
+}
+
+/// The input partially read by the reading thread.
+#[derive(Default)]
+struct TtyInput {
+    /// Bytes of a partially read event.
+    buffer: Vec<u8>,
+    /// The parser state carried between the events.
+    parser_state: ParserState,
+    /// The `EscStrategy::Probe` state.
+    esc_probe: EscProbe,
+}
 
-    if buffer.len() == 2 {
-        return Ok(None);
+/// A non-blocking stdin (or /dev/tty) reader pumped by the caller.
+///
+/// It's used in restricted environments where `poll` on the tty isn't allowed
+/// (`ReadMode::Pump`) and by the only reader (`ReadMode::Direct`).
+///
+/// # Notes
+///
+/// The `ReadMode::Pump` tty is switched to the non-blocking mode (`O_NONBLOCK`) and
+/// the original mode is restored once you drop the `TtyPump`. The `ReadMode::Direct` tty
+/// is polled before every read instead, the mode of the open file description shared with
+/// other processes (stdin) isn't changed.
+struct TtyPump {
+    /// The tty, shared with the direct reader waiting on it.
+    tty_fd: Arc<FileDesc>,
+    /// The original file status flags if the tty was switched to the non-blocking mode,
+    /// `None` if it's polled before every read.
+    flags: Option<c_int>,
+    /// Bytes of a partially read event.
+    buffer: Vec<u8>,
+    /// The parser state carried between the events.
+    parser_state: ParserState,
+    /// When the last byte was read.
+    last_read: Instant,
+    /// Terminal resize notifications.
+    signals: Signals,
+    /// The `EscStrategy::Probe` state.
+    esc_probe: EscProbe,
+    /// The terminal hung up, the `Disconnected` event was sent.
+    disconnected: bool,
+}
+
+impl TtyPump {
+    /// Creates a new non-blocking `TtyPump`.
+    fn new() -> Result<TtyPump> {
+        let tty_fd = tty_fd()?;
+
+        let flags = check_for_error_result(unsafe { libc::fcntl(tty_fd.raw_fd(), libc::F_GETFL) })?;
+        check_for_error(unsafe {
+            libc::fcntl(tty_fd.raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK)
+        })?;
+
+        TtyPump::with_flags(tty_fd, Some(flags))
     }
 
-    let input_event = match buffer[2] {
-        b'[' => {
-            if buffer.len() == 3 {
-                None
-            } else {
-                match buffer[3] {
-                    // NOTE (@imdaveho): cannot find when this occurs;
-                    // having another '[' after ESC[ not a likely scenario
-                    val @ b'A'..=b'E' => Some(InputEvent::Keyboard(KeyEvent::F(1 + val - b'A'))),
-                    _ => Some(InputEvent::Unknown),
-                }
-            }
+    /// Creates a new `TtyPump` polling the tty before every read.
+    fn polled() -> Result<TtyPump> {
+        TtyPump::with_flags(tty_fd()?, None)
+    }
+
+    fn with_flags(tty_fd: FileDesc, flags: Option<c_int>) -> Result<TtyPump> {
+        Ok(TtyPump {
+            tty_fd: Arc::new(tty_fd),
+            flags,
+            buffer: Vec::with_capacity(32),
+            parser_state: ParserState::default(),
+            last_read: Instant::now(),
+            signals: Signals::new(RESIZE_SIGNALS)?,
+            esc_probe: EscProbe::default(),
+            disconnected: false,
+        })
+    }
+
+    /// Reads all the available bytes and sends parsed events to all `channels`.
+    ///
+    /// Returns the number of sent events.
+    fn pump(&mut self, channels: &UnixInternalEventChannels) -> Result<usize> {
+        if self.disconnected {
+            return Ok(0);
         }
-        b'D' => Some(InputEvent::Keyboard(KeyEvent::Left)),
-        b'C' => Some(InputEvent::Keyboard(KeyEvent::Right)),
-        b'A' => Some(InputEvent::Keyboard(KeyEvent::Up)),
-        b'B' => Some(InputEvent::Keyboard(KeyEvent::Down)),
-        b'H' => Some(InputEvent::Keyboard(KeyEvent::Home)),
-        b'F' => Some(InputEvent::Keyboard(KeyEvent::End)),
-        b'Z' => Some(InputEvent::Keyboard(KeyEvent::BackTab)),
-        b'M' => return parse_csi_x10_mouse(buffer),
-        b'<' => return parse_csi_xterm_mouse(buffer),
-        b'0'..=b'9' => {
-            // Numbered escape code.
-            if buffer.len() == 3 {
-                None
-            } else {
-                // The final byte of a CSI sequence can be in the range 64-126, so
-                // let's keep reading anything else.
-                let last_byte = *buffer.last().unwrap();
-                if last_byte < 64 || last_byte > 126 {
-                    None
-                } else {
-                    match buffer[buffer.len() - 1] {
-                        b'M' => return parse_csi_rxvt_mouse(buffer),
-                        b'~' => return parse_csi_special_key_code(buffer),
-                        b'R' => return parse_csi_cursor_position(buffer),
-                        _ => return parse_csi_modifier_key_code(buffer),
-                    }
-                }
+
+        let mut input = Vec::new();
+        let mut buf = [0u8; TTY_READ_BUFFER_SIZE];
+        let mut hung_up = false;
+
+        loop {
+            // The blocking tty is read only if there's anything to read
+            if self.flags.is_none()
+                && !poll_readable(self.tty_fd.raw_fd(), Duration::from_millis(0))?
+            {
+                break;
             }
+
+            match self.tty_fd.read(&mut buf) {
+                Ok(0) => {
+                    // End of file, the terminal hung up
+                    hung_up = true;
+                    break;
+                }
+                Ok(count) => input.extend_from_slice(&buf[..count]),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(ref e) if e.raw_os_error() == Some(libc::EIO) => {
+                    hung_up = true;
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            };
         }
-        _ => Some(InputEvent::Unknown),
-    };
 
-    Ok(input_event.map(InternalEvent::Input))
-}
+        let mut sent = 0;
 
-fn next_parsed<T>(iter: &mut dyn Iterator<Item = &str>) -> Result<T>
-where
-    T: std::str::FromStr,
-{
-    iter.next()
-        .ok_or_else(|| could_not_parse_event_error())?
-        .parse::<T>()
-        .map_err(|_| could_not_parse_event_error())
-}
+        // Multiple pending resizes are coalesced, the current size is sent only
+        if self.signals.pending().count() > 0 && send_resize(&self.tty_fd, channels) {
+            sent += 1;
+        }
 
-fn parse_csi_cursor_position(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    // ESC [ Cy ; Cx R
-    //   Cy - cursor row number (starting from 1)
-    //   Cx - cursor column number (starting from 1)
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'R']));
+        // The last byte is held for the esc timeout (if set)
+        let hold_timeout = esc_hold_timeout();
+
+        if !input.is_empty() {
+            self.last_read = Instant::now();
+            let input =
+                self.esc_probe
+                    .resolve(&mut self.buffer, &mut self.parser_state, &input, channels);
+            sent += parse_and_send_bytes(
+                &mut self.buffer,
+                &mut self.parser_state,
+                &input,
+                hold_timeout.is_some(),
+                channels,
+            );
+
+            if with_input_config(InputConfig::esc_strategy) == EscStrategy::Probe {
+                self.esc_probe.query(&self.buffer, &self.parser_state)?;
+            }
+        }
 
-    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
-        .map_err(|_| could_not_parse_event_error())?;
+        if let Some(timeout) = hold_timeout {
+            if self.last_read.elapsed() >= timeout
+                && self
+                    .esc_probe
+                    .expire(&mut self.buffer, &mut self.parser_state, channels)
+            {
+                sent += 1;
+            }
+        }
 
-    let mut split = s.split(';');
+        if hung_up {
+            send_disconnected(channels);
+            self.disconnected = true;
+            sent += 1;
+        }
 
-    let y = next_parsed::<u16>(&mut split)? - 1;
-    let x = next_parsed::<u16>(&mut split)? - 1;
+        Ok(sent)
+    }
 
-    Ok(Some(InternalEvent::CursorPosition(x, y)))
+    /// Hands the tty & the partially read input over to the reading thread.
+    fn into_handover(mut self) -> TtyHandover {
+        let input = TtyInput {
+            buffer: std::mem::take(&mut self.buffer),
+            parser_state: std::mem::take(&mut self.parser_state),
+            esc_probe: std::mem::take(&mut self.esc_probe),
+        };
+        TtyHandover {
+            tty_fd: self.tty_fd.clone(),
+            input: Arc::new(Mutex::new(input)),
+        }
+    }
 }
 
-fn parse_csi_modifier_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-
-    let modifier = buffer[buffer.len() - 2];
-    let key = buffer[buffer.len() - 1];
-
-    let input_event = match (modifier, key) {
-        (53, 65) => InputEvent::Keyboard(KeyEvent::CtrlUp),
-        (53, 66) => InputEvent::Keyboard(KeyEvent::CtrlDown),
-        (53, 67) => InputEvent::Keyboard(KeyEvent::CtrlRight),
-        (53, 68) => InputEvent::Keyboard(KeyEvent::CtrlLeft),
-        (50, 65) => InputEvent::Keyboard(KeyEvent::ShiftUp),
-        (50, 66) => InputEvent::Keyboard(KeyEvent::ShiftDown),
-        (50, 67) => InputEvent::Keyboard(KeyEvent::ShiftRight),
-        (50, 68) => InputEvent::Keyboard(KeyEvent::ShiftLeft),
-        _ => InputEvent::Unknown,
-    };
-
-    Ok(Some(InternalEvent::Input(input_event)))
+impl Drop for TtyPump {
+    fn drop(&mut self) {
+        // Restore the original mode, errors are ignored, there's nothing we can do
+        if let Some(flags) = self.flags {
+            let _ = unsafe { libc::fcntl(self.tty_fd.raw_fd(), libc::F_SETFL, flags) };
+        }
+    }
 }
 
-fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'~']));
-
-    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
-        .map_err(|_| could_not_parse_event_error())?;
-    let mut split = s.split(';');
-
-    // This CSI sequence can be a list of semicolon-separated numbers.
-    let first = next_parsed::<u8>(&mut split)?;
-
-    if next_parsed::<u8>(&mut split).is_ok() {
-        // TODO: handle multiple values for key modifiers (ex: values [3, 2] means Shift+Delete)
-        return Ok(Some(InternalEvent::Input(InputEvent::Unknown)));
-    }
-
-    let input_event = match first {
-        1 | 7 => InputEvent::Keyboard(KeyEvent::Home),
-        2 => InputEvent::Keyboard(KeyEvent::Insert),
-        3 => InputEvent::Keyboard(KeyEvent::Delete),
-        4 | 8 => InputEvent::Keyboard(KeyEvent::End),
-        5 => InputEvent::Keyboard(KeyEvent::PageUp),
-        6 => InputEvent::Keyboard(KeyEvent::PageDown),
-        v @ 11..=15 => InputEvent::Keyboard(KeyEvent::F(v - 10)),
-        v @ 17..=21 => InputEvent::Keyboard(KeyEvent::F(v - 11)),
-        v @ 23..=24 => InputEvent::Keyboard(KeyEvent::F(v - 12)),
-        _ => InputEvent::Unknown,
-    };
-
-    Ok(Some(InternalEvent::Input(input_event)))
+pub(crate) fn internal_event_receiver() -> Result<Receiver<SourcedEvent>> {
+    internal_event_receiver_with_replay(0)
 }
 
-fn parse_csi_rxvt_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    // rxvt mouse encoding:
-    // ESC [ Cb ; Cx ; Cy ; M
-
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'M']));
-
-    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
-        .map_err(|_| could_not_parse_event_error())?;
-    let mut split = s.split(';');
-
-    let cb = next_parsed::<u16>(&mut split)?;
-    let cx = next_parsed::<u16>(&mut split)? - 1;
-    let cy = next_parsed::<u16>(&mut split)? - 1;
-
-    let mouse_input_event = match cb {
-        32 => MouseEvent::Press(MouseButton::Left, cx, cy),
-        33 => MouseEvent::Press(MouseButton::Middle, cx, cy),
-        34 => MouseEvent::Press(MouseButton::Right, cx, cy),
-        35 => MouseEvent::Release(cx, cy),
-        64 => MouseEvent::Hold(cx, cy),
-        96 | 97 => MouseEvent::Press(MouseButton::WheelUp, cx, cy),
-        _ => MouseEvent::Unknown,
-    };
+/// Creates a new `SourcedEvent` receiver with up to `replay` most recent events
+/// already queued.
+pub(crate) fn internal_event_receiver_with_replay(replay: usize) -> Result<Receiver<SourcedEvent>> {
+    INTERNAL_EVENT_PROVIDER.lock().unwrap().receiver(replay)
+}
 
-    Ok(Some(InternalEvent::Input(InputEvent::Mouse(
-        mouse_input_event,
-    ))))
+/// Creates a new receiver of the raw bytes of all the parsed (or malformed) sequences.
+pub(crate) fn bytes_receiver() -> Receiver<Vec<u8>> {
+    INTERNAL_EVENT_PROVIDER.lock().unwrap().bytes_receiver()
 }
 
-fn parse_csi_x10_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    // X10 emulation mouse encoding: ESC [ M CB Cx Cy (6 characters only).
-    // NOTE (@imdaveho): cannot find documentation on this
+/// Applies the kitty keyboard protocol `flags` reported by the terminal (or popped).
+///
+/// The key releases are tracked only if they're reported.
+pub(crate) fn set_keyboard_enhancement_flags(flags: KeyboardEnhancementFlags) {
+    KEYBOARD_ENHANCEMENT_FLAGS.store(flags.bits(), Ordering::SeqCst);
+    set_key_releases_reported(flags.contains(KeyboardEnhancementFlags::REPORT_EVENT_TYPES));
+}
 
-    assert!(buffer.starts_with(&[b'\x1B', b'[', b'M'])); // ESC [ M
+/// Registers a cursor position query, the response is sent to the returned receiver.
+///
+/// The terminal answers the queries in order, every report is routed to the oldest pending
+/// query only (the query could be abandoned already, the report is dropped then).
+pub(crate) fn cursor_position_receiver() -> Receiver<(u16, u16)> {
+    let (tx, rx) = mpsc::channel();
+    CURSOR_POSITION_QUERIES.lock().unwrap().push_back(tx);
+    rx
+}
 
-    if buffer.len() < 6 {
-        return Ok(None);
+/// Sends the cursor position report to the oldest pending query (if any).
+fn send_cursor_position(x: u16, y: u16) {
+    if let Some(tx) = CURSOR_POSITION_QUERIES.lock().unwrap().pop_front() {
+        let _ = tx.send((x, y));
     }
-
-    let cb = buffer[3] as i8 - 32;
-    // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
-    // The upper left character position on the terminal is denoted as 1,1.
-    // Subtract 1 to keep it synced with cursor
-    let cx = buffer[4].saturating_sub(32) as u16 - 1;
-    let cy = buffer[5].saturating_sub(32) as u16 - 1;
-
-    let mouse_input_event = match cb & 0b11 {
-        0 => {
-            if cb & 0x40 != 0 {
-                MouseEvent::Press(MouseButton::WheelUp, cx, cy)
-            } else {
-                MouseEvent::Press(MouseButton::Left, cx, cy)
-            }
-        }
-        1 => {
-            if cb & 0x40 != 0 {
-                MouseEvent::Press(MouseButton::WheelDown, cx, cy)
-            } else {
-                MouseEvent::Press(MouseButton::Middle, cx, cy)
-            }
-        }
-        2 => MouseEvent::Press(MouseButton::Right, cx, cy),
-        3 => MouseEvent::Release(cx, cy),
-        _ => MouseEvent::Unknown,
-    };
-
-    Ok(Some(InternalEvent::Input(InputEvent::Mouse(
-        mouse_input_event,
-    ))))
 }
 
-fn parse_csi_xterm_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    // ESC [ < Cb ; Cx ; Cy (;) (M or m)
+/// Reads all the available input without blocking (`ReadMode::Pump`).
+///
+/// Returns the number of events sent to all receivers.
+pub(crate) fn pump_internal_events() -> Result<usize> {
+    INTERNAL_EVENT_PROVIDER.lock().unwrap().pump()
+}
 
-    assert!(buffer.starts_with(&[b'\x1B', b'[', b'<'])); // ESC [ <
+/// Reads the input on the caller's thread while there's a single reader (`ReadMode::Direct`).
+///
+/// Waits up to the `timeout` for the input. Returns `false` if the input is read by the reading
+/// thread (the events are received from the channel only).
+pub(crate) fn read_direct(timeout: Duration) -> Result<bool> {
+    let tty_fd = match INTERNAL_EVENT_PROVIDER.lock().unwrap().direct_tty_fd()? {
+        Some(tty_fd) => tty_fd,
+        None => return Ok(false),
+    };
 
-    if !buffer.ends_with(&[b'm']) && !buffer.ends_with(&[b'M']) {
-        return Ok(None);
+    // The provider isn't locked while waiting, other readers & queries can use it (the tty
+    // is kept open by the `tty_fd` even if the input is switched to the reading thread)
+    if timeout > Duration::from_millis(0) {
+        poll_readable(tty_fd.raw_fd(), timeout)?;
     }
 
-    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
-        .map_err(|_| could_not_parse_event_error())?;
-    let mut split = s.split(';');
+    INTERNAL_EVENT_PROVIDER.lock().unwrap().pump_direct()
+}
 
-    let cb = next_parsed::<u16>(&mut split)?;
+/// Waits up to the `timeout` until the `fd` is readable.
+///
+/// Returns `true` if the `fd` is readable (or hung up).
+fn poll_readable(fd: RawFd, timeout: Duration) -> Result<bool> {
+    let mut poll_fd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
 
-    // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
-    // The upper left character position on the terminal is denoted as 1,1.
-    // Subtract 1 to keep it synced with cursor
-    let cx = next_parsed::<u16>(&mut split)? - 1;
-    let cy = next_parsed::<u16>(&mut split)? - 1;
+    match check_for_error_result(unsafe {
+        libc::poll(&mut poll_fd, 1, timeout.as_millis() as c_int)
+    }) {
+        Ok(count) => Ok(count > 0),
+        // Interrupted by the resize signal, the new size is sent by the pump
+        Err(ref e) if e.kind() == io::ErrorKind::Interrupted => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
 
-    let input_event = match cb {
-        0..=2 | 64..=65 => {
-            let button = match cb {
-                0 => MouseButton::Left,
-                1 => MouseButton::Middle,
-                2 => MouseButton::Right,
-                64 => MouseButton::WheelUp,
-                65 => MouseButton::WheelDown,
-                _ => unreachable!(),
-            };
-            match buffer.last().unwrap() {
-                b'M' => InputEvent::Mouse(MouseEvent::Press(button, cx, cy)),
-                b'm' => InputEvent::Mouse(MouseEvent::Release(cx, cy)),
-                _ => InputEvent::Unknown,
+/// Writes the escape sequences with one write & flush.
+///
+/// The sequences are buffered if called inside the `batch_csi_sequences` function.
+pub(crate) fn write_sequences(sequences: &[&str]) -> Result<()> {
+    let buffer = sequences.concat();
+
+    // The batch is destroyed at the thread exit (restore hook), the sequences are written
+    let buffered = CSI_BATCH
+        .try_with(|batch| match batch.borrow_mut().as_mut() {
+            Some(batch) => {
+                batch.push_str(&buffer);
+                true
             }
-        }
-        // TODO 1.0: Add MouseButton to Hold and report which button is pressed
-        // 33 - middle, 34 - right
-        32 => InputEvent::Mouse(MouseEvent::Hold(cx, cy)),
-        3 => InputEvent::Mouse(MouseEvent::Release(cx, cy)),
-        _ => InputEvent::Unknown,
-    };
+            None => false,
+        })
+        .unwrap_or(false);
 
-    Ok(Some(InternalEvent::Input(input_event)))
+    if !buffered {
+        write_cout!(&buffer)?;
+    }
+    Ok(())
 }
 
-fn parse_utf8_char(buffer: &[u8]) -> Result<Option<char>> {
-    match std::str::from_utf8(buffer) {
-        Ok(s) => {
-            let ch = s
-                .chars()
-                .next()
-                .ok_or_else(|| could_not_parse_event_error())?;
+/// Calls `f` & writes all the CSI sequences written by it with one write & flush.
+///
+/// Nested calls are written by the outermost one.
+pub(crate) fn batch_csi_sequences<F: FnOnce()>(f: F) -> Result<()> {
+    let outermost = CSI_BATCH
+        .try_with(|batch| {
+            let mut batch = batch.borrow_mut();
+            batch.is_none() && batch.replace(String::new()).is_none()
+        })
+        .unwrap_or(false);
 
-            Ok(Some(ch))
-        }
-        Err(_) => {
-            // from_utf8 failed, but we have to check if we need more bytes for code point
-            // and if all the bytes we have no are valid
-
-            let required_bytes = match buffer[0] {
-                // https://en.wikipedia.org/wiki/UTF-8#Description
-                (0x00..=0x7F) => 1, // 0xxxxxxx
-                (0xC0..=0xDF) => 2, // 110xxxxx 10xxxxxx
-                (0xE0..=0xEF) => 3, // 1110xxxx 10xxxxxx 10xxxxxx
-                (0xF0..=0xF7) => 4, // 11110xxx 10xxxxxx 10xxxxxx 10xxxxxx
-                (0x80..=0xBF) | (0xF8..=0xFF) => return Err(could_not_parse_event_error()),
-            };
+    f();
 
-            // More than 1 byte, check them for 10xxxxxx pattern
-            if required_bytes > 1 && buffer.len() > 1 {
-                for byte in &buffer[1..] {
-                    if byte & !0b0011_1111 != 0b1000_0000 {
-                        return Err(could_not_parse_event_error());
-                    }
-                }
-            }
+    if !outermost {
+        return Ok(());
+    }
 
-            if buffer.len() < required_bytes {
-                // All bytes looks good so far, but we need more of them
-                Ok(None)
-            } else {
-                Err(could_not_parse_event_error())
-            }
-        }
+    let buffer = CSI_BATCH.with(|batch| batch.borrow_mut().take().unwrap_or_default());
+    if !buffer.is_empty() {
+        write_cout!(&buffer)?;
     }
+    Ok(())
+}
+
+/// Sends an `InternalEvent` from the `source` to all receivers.
+#[cfg(any(feature = "testing", feature = "remote"))]
+pub(crate) fn send_internal_event(source: SourceId, event: InternalEvent) {
+    INTERNAL_EVENT_PROVIDER.lock().unwrap().send(source, event);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::sequences::{BRACKETED_PASTE_ENABLE, FOCUS_REPORTING_ENABLE, MOUSE_ENABLE_CLICKS};
+    use crate::sys::ansi::keyboard_enhancement_flags;
+    use crate::MediaKey;
+
     #[test]
-    fn test_esc_key() {
+    fn test_reading_thread_continues_with_handover() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let input = Arc::new(Mutex::new(TtyInput::default()));
+
+        let (stuck_tty_rx, stuck_tty_tx) = pipe().unwrap();
+        let stuck_thread = TtyReadingThread::spawn(
+            channels.clone(),
+            Some(TtyHandover {
+                tty_fd: Arc::new(stuck_tty_rx),
+                input: input.clone(),
+            }),
+        )
+        .unwrap();
+        stuck_tty_tx.write(b"\x1B[1;5").unwrap();
+        while input.lock().unwrap().buffer.len() < 5 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        stuck_thread.detach();
+
+        // The replacement reads a new tty & continues with the partially read input
+        let (tty_rx, tty_tx) = pipe().unwrap();
+        let reading_thread = TtyReadingThread::spawn(
+            channels.clone(),
+            Some(TtyHandover {
+                tty_fd: Arc::new(tty_rx),
+                input,
+            }),
+        )
+        .unwrap();
+        tty_tx.write(b"A").unwrap();
         assert_eq!(
-            parse_event("\x1B".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Esc))),
+            rx.recv_timeout(Duration::from_secs(1)).unwrap().1,
+            InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                KeyCode::Up,
+                KeyModifiers::CONTROL
+            )))
         );
-    }
 
-    #[test]
-    fn test_possible_esc_sequence() {
-        assert_eq!(parse_event("\x1B".as_bytes(), true).unwrap(), None,);
+        drop(reading_thread);
     }
 
     #[test]
-    fn test_alt_key() {
+    fn test_channels_replay() {
+        let channels = UnixInternalEventChannels::new();
+        let event = |c, sequence| {
+            (
+                SourceId::Terminal,
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Char(c).into())),
+                sequence,
+            )
+        };
+
+        // Replay is disabled by default
+        channels.send(SourceId::Terminal, event('a', 1).1);
+        assert!(channels.receiver(1).try_recv().is_err());
+
+        channels
+            .history
+            .lock()
+            .unwrap()
+            .extend(vec![event('a', 1), event('b', 2)]);
+        channels.sequence.store(2, Ordering::SeqCst);
+        let rx = channels.receiver(1);
+        assert_eq!(rx.try_recv().unwrap(), event('b', 2));
+        assert!(rx.try_recv().is_err());
+
+        channels.send(SourceId::Synthetic, event('c', 3).1);
         assert_eq!(
-            parse_event("\x1Bc".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Alt(
-                'c'
-            )))),
+            rx.try_recv().unwrap(),
+            (SourceId::Synthetic, event('c', 3).1, 3)
         );
     }
 
     #[test]
-    fn test_parse_event_subsequent_calls() {
-        // The main purpose of this test is to check if we're passing
-        // correct slice to other parse_ functions.
+    fn test_channels_sequence() {
+        let channels = UnixInternalEventChannels::new();
+        let first = channels.receiver(0);
 
-        // parse_csi_cursor_position
-        assert_eq!(
-            parse_event("\x1B[20;10R".as_bytes(), false).unwrap(),
-            Some(InternalEvent::CursorPosition(9, 19))
+        channels.send(
+            SourceId::Terminal,
+            InternalEvent::Input(InputEvent::FocusGained),
         );
-
-        // parse_csi
-        assert_eq!(
-            parse_event("\x1B[D".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Left))),
+        let second = channels.receiver(0);
+        // Query responses aren't numbered
+        channels.send(SourceId::Terminal, InternalEvent::ModeReport(1000, 1));
+        channels.send(
+            SourceId::Terminal,
+            InternalEvent::Input(InputEvent::FocusLost),
         );
 
-        // parse_csi_modifier_key_code
+        let sequences = |rx: &Receiver<SourcedEvent>| {
+            rx.try_iter()
+                .map(|(_, _, sequence)| sequence)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sequences(&first), vec![1, 0, 2]);
+        assert_eq!(sequences(&second), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_poll_readable() {
+        let mut fds = [0; 2];
+        check_for_error(unsafe { libc::pipe(fds.as_mut_ptr()) }).unwrap();
+        let (read_fd, write_fd) = (FileDesc::new(fds[0]), FileDesc::new(fds[1]));
+
+        let started = Instant::now();
+        assert!(!poll_readable(read_fd.raw_fd(), Duration::from_millis(10)).unwrap());
+        assert!(started.elapsed() >= Duration::from_millis(10));
+
+        write_fd.write(b"a").unwrap();
+        let started = Instant::now();
+        assert!(poll_readable(read_fd.raw_fd(), Duration::from_secs(10)).unwrap());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_polled_pump_hands_over_partial_input() {
+        let mut fds = [0; 2];
+        check_for_error(unsafe { libc::pipe(fds.as_mut_ptr()) }).unwrap();
+        let (read_fd, write_fd) = (FileDesc::new(fds[0]), FileDesc::new(fds[1]));
+        let channels = UnixInternalEventChannels::new();
+
+        let mut pump = TtyPump::with_flags(read_fd, None).unwrap();
+        // Nothing to read, the blocking pipe isn't read
+        assert_eq!(pump.pump(&channels).unwrap(), 0);
+
+        write_fd.write(b"\x1B[1;").unwrap();
+        assert_eq!(pump.pump(&channels).unwrap(), 0);
+
+        let tty_fd = pump.tty_fd.clone();
+        let handover = pump.into_handover();
+        assert_eq!(handover.input.lock().unwrap().buffer, b"\x1B[1;".to_vec());
+        // Still open, the waiting reader & the reading thread use the same tty
+        assert!(Arc::ptr_eq(&tty_fd, &handover.tty_fd));
+        write_fd.write(b"a").unwrap();
+        assert!(poll_readable(tty_fd.raw_fd(), Duration::from_millis(0)).unwrap());
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(hex(b""), "");
+        assert_eq!(hex(b"\x1B[A"), "1b 5b 41");
+    }
+
+    #[test]
+    fn test_parse_and_send() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let mut buffer = Vec::new();
+        let mut parser_state = ParserState::default();
+
+        buffer.push(b'\x1B');
+        assert!(!parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            true,
+            &channels
+        ));
+        assert_eq!(buffer, vec![b'\x1B']);
+
+        buffer.push(b'[');
+        buffer.push(b'A');
+        assert!(parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
+        assert!(buffer.is_empty());
         assert_eq!(
-            parse_event("\x1B[2D".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(
-                KeyEvent::ShiftLeft
-            ))),
+            rx.try_recv().unwrap(),
+            (
+                SourceId::Terminal,
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Up.into())),
+                1
+            )
         );
 
-        // parse_csi_special_key_code
+        let bytes_rx = channels.bytes_receiver();
+
+        // Malformed sequence
+        buffer.extend_from_slice(b"\x1BO!");
+        assert!(!parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
+        assert!(buffer.is_empty());
+        assert!(rx.try_recv().is_err());
+        assert_eq!(bytes_rx.try_recv().unwrap(), b"\x1BO!".to_vec());
+
+        // Rejected DCS prefix, the following byte is parsed again
+        buffer.extend_from_slice(b"\x1BPx");
+        assert!(parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
+        assert!(buffer.is_empty());
         assert_eq!(
-            parse_event("\x1B[3~".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Delete))),
+            rx.try_iter().map(|(_, event, _)| event).collect::<Vec<_>>(),
+            vec![
+                InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                    KeyCode::Char('P'),
+                    KeyModifiers::ALT
+                ))),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Char('x').into())),
+            ]
         );
-
-        // parse_csi_rxvt_mouse
         assert_eq!(
-            parse_event("\x1B[32;30;40;M".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                29,
-                39
-            ))))
+            bytes_rx.try_iter().collect::<Vec<_>>(),
+            vec![b"\x1BP".to_vec(), b"x".to_vec()]
         );
+    }
 
-        // parse_csi_x10_mouse
+    #[test]
+    fn test_parse_and_send_keyboard_enhancement_flags() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let mut buffer = Vec::new();
+        let mut parser_state = ParserState::default();
+
+        // Applied in the order of the input
+        buffer.extend_from_slice(b"\x1B[?3u");
+        assert!(parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
         assert_eq!(
-            parse_event("\x1B[M0\x60\x70".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                63,
-                79
-            ))))
+            keyboard_enhancement_flags(),
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
         );
-
-        // parse_csi_xterm_mouse
         assert_eq!(
-            parse_event("\x1B[<0;20;10;M".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                19,
-                9
-            ))))
+            rx.try_recv().unwrap().1,
+            InternalEvent::KeyboardEnhancementFlags(3)
         );
 
-        // parse_utf8_char
+        buffer.extend_from_slice(b"\x1B[?0u");
+        assert!(parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
         assert_eq!(
-            parse_event("Ž".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Char(
-                'Ž'
-            )))),
+            keyboard_enhancement_flags(),
+            KeyboardEnhancementFlags::empty()
         );
     }
 
     #[test]
-    fn test_parse_event() {
-        assert_eq!(
-            parse_event("\t".as_bytes(), false).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Tab))),
-        );
+    fn test_parse_and_send_cursor_position() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let mut buffer = Vec::new();
+        let mut parser_state = ParserState::default();
+
+        let first = cursor_position_receiver();
+        let second = cursor_position_receiver();
+
+        buffer.extend_from_slice(b"\x1B[20;10R");
+        assert!(!parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
+        buffer.extend_from_slice(b"\x1B[5;1R");
+        assert!(!parse_and_send(
+            &mut buffer,
+            &mut parser_state,
+            false,
+            &channels
+        ));
+
+        // Routed to the queries in order, not broadcast
+        assert_eq!(first.try_recv().unwrap(), (9, 19));
+        assert_eq!(second.try_recv().unwrap(), (0, 4));
+        assert!(rx.try_recv().is_err());
     }
 
     #[test]
-    fn test_parse_csi_cursor_position() {
+    fn test_parse_and_send_bytes() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let mut buffer = Vec::new();
+        let mut parser_state = ParserState::default();
+
+        // The sequence is split between two reads
         assert_eq!(
-            parse_csi_cursor_position("\x1B[20;10R".as_bytes()).unwrap(),
-            Some(InternalEvent::CursorPosition(9, 19))
+            parse_and_send_bytes(
+                &mut buffer,
+                &mut parser_state,
+                b"a\x1B[A\x1B",
+                true,
+                &channels
+            ),
+            2
         );
-    }
-
-    #[test]
-    fn test_parse_csi() {
+        assert_eq!(buffer, vec![b'\x1B']);
         assert_eq!(
-            parse_csi("\x1B[D".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Left))),
+            parse_and_send_bytes(&mut buffer, &mut parser_state, b"[B", false, &channels),
+            1
         );
-    }
+        assert!(buffer.is_empty());
 
-    #[test]
-    fn test_parse_csi_modifier_key_code() {
+        let events = rx
+            .try_iter()
+            .map(|(_, event, _)| event)
+            .collect::<Vec<InternalEvent>>();
         assert_eq!(
-            parse_csi_modifier_key_code("\x1B[2D".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(
-                KeyEvent::ShiftLeft
-            ))),
+            events,
+            vec![
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Char('a').into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Up.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Down.into())),
+            ]
         );
-    }
 
-    #[test]
-    fn test_parse_csi_special_key_code() {
+        // A lone Esc at the end of the read
         assert_eq!(
-            parse_csi_special_key_code("\x1B[3~".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Keyboard(KeyEvent::Delete))),
+            parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", false, &channels),
+            1
         );
     }
 
     #[test]
-    fn test_parse_csi_special_key_code_multiple_values_not_supported() {
+    fn test_esc_probe_resolve() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let mut buffer = Vec::new();
+        let mut parser_state = ParserState::default();
+        let mut esc_probe = EscProbe::default();
+
+        // The response follows the prefix, the Esc key
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels);
+        esc_probe.awaiting = true;
         assert_eq!(
-            parse_csi_special_key_code("\x1B[3;2~".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Unknown)),
+            esc_probe.resolve(&mut buffer, &mut parser_state, b"\x1B[0nx", &channels),
+            b"x".to_vec()
         );
-    }
+        assert!(buffer.is_empty());
+
+        // The prefix continues, the late response is dropped
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels);
+        esc_probe.awaiting = true;
+        let bytes = esc_probe.resolve(&mut buffer, &mut parser_state, b"[A\x1B[0n", &channels);
+        assert_eq!(bytes, b"[A".to_vec());
+        parse_and_send_bytes(&mut buffer, &mut parser_state, &bytes, false, &channels);
+
+        // No response in time, the response arrives with the next read
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels);
+        esc_probe.awaiting = true;
+        assert!(esc_probe.expire(&mut buffer, &mut parser_state, &channels));
+        assert_eq!(
+            esc_probe.resolve(&mut buffer, &mut parser_state, b"\x1B[0n", &channels),
+            Vec::<u8>::new()
+        );
+        assert_eq!(esc_probe.stale, 0);
 
-    #[test]
-    fn test_parse_csi_rxvt_mouse() {
+        // The response is split across the reads, the Esc key
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels);
+        esc_probe.awaiting = true;
         assert_eq!(
-            parse_csi_rxvt_mouse("\x1B[32;30;40;M".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                29,
-                39
-            ))))
+            esc_probe.resolve(&mut buffer, &mut parser_state, b"\x1B[0", &channels),
+            Vec::<u8>::new()
         );
-    }
+        assert_eq!(buffer, b"\x1B".to_vec());
+        assert_eq!(
+            esc_probe.resolve(&mut buffer, &mut parser_state, b"nx", &channels),
+            b"x".to_vec()
+        );
+        assert!(buffer.is_empty());
+        assert_eq!(esc_probe.stale, 0);
+
+        // The late response is split across the reads
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels);
+        esc_probe.awaiting = true;
+        let bytes = esc_probe.resolve(&mut buffer, &mut parser_state, b"[B\x1B[", &channels);
+        assert_eq!(bytes, b"[B".to_vec());
+        parse_and_send_bytes(&mut buffer, &mut parser_state, &bytes, false, &channels);
+        assert_eq!(
+            esc_probe.resolve(&mut buffer, &mut parser_state, b"0n", &channels),
+            Vec::<u8>::new()
+        );
+        assert_eq!(esc_probe.stale, 0);
 
-    #[test]
-    fn test_parse_csi_x10_mouse() {
+        let events = rx
+            .try_iter()
+            .map(|(_, event, _)| event)
+            .collect::<Vec<InternalEvent>>();
         assert_eq!(
-            parse_csi_x10_mouse("\x1B[M0\x60\x70".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                63,
-                79
-            ))))
+            events,
+            vec![
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Esc.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Up.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Esc.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Esc.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Down.into())),
+            ]
         );
     }
 
     #[test]
-    fn test_parse_csi_xterm_mouse() {
+    fn test_esc_prefix_reassembly() {
+        let channels = UnixInternalEventChannels::new();
+        let rx = channels.receiver(0);
+        let mut buffer = Vec::new();
+        let mut parser_state = ParserState::default();
+
+        // The sequence split between reads is held & reassembled
         assert_eq!(
-            parse_csi_xterm_mouse("\x1B[<0;20;10;M".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                19,
-                9
-            ))))
+            parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels),
+            0
         );
+        assert!(is_esc_prefix(&buffer, &parser_state));
         assert_eq!(
-            parse_csi_xterm_mouse("\x1B[<0;20;10M".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(MouseEvent::Press(
-                MouseButton::Left,
-                19,
-                9
-            ))))
+            parse_and_send_bytes(&mut buffer, &mut parser_state, b"[A", true, &channels),
+            1
         );
+        assert!(buffer.is_empty());
+
+        // Expired esc timeout
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B", true, &channels);
+        assert!(flush_esc_prefix(&mut buffer, &mut parser_state, &channels));
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B[", true, &channels);
+        assert!(flush_esc_prefix(&mut buffer, &mut parser_state, &channels));
+
+        // Longer incomplete sequence is held
+        parse_and_send_bytes(&mut buffer, &mut parser_state, b"\x1B[1;", true, &channels);
+        assert!(!is_esc_prefix(&buffer, &parser_state));
+        assert!(!flush_esc_prefix(&mut buffer, &mut parser_state, &channels));
         assert_eq!(
-            parse_csi_xterm_mouse("\x1B[<0;20;10;m".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(
-                MouseEvent::Release(19, 9)
-            )))
+            parse_and_send_bytes(&mut buffer, &mut parser_state, b"5D", true, &channels),
+            1
         );
+
+        let events = rx
+            .try_iter()
+            .map(|(_, event, _)| event)
+            .collect::<Vec<InternalEvent>>();
         assert_eq!(
-            parse_csi_xterm_mouse("\x1B[<0;20;10m".as_bytes()).unwrap(),
-            Some(InternalEvent::Input(InputEvent::Mouse(
-                MouseEvent::Release(19, 9)
-            )))
+            events,
+            vec![
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Up.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyCode::Esc.into())),
+                InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                    KeyCode::Char('['),
+                    KeyModifiers::ALT
+                ))),
+                InternalEvent::Input(InputEvent::Keyboard(KeyEvent::new(
+                    KeyCode::Left,
+                    KeyModifiers::CONTROL
+                ))),
+            ]
         );
     }
 
     #[test]
-    fn test_utf8() {
-        // https://www.php.net/manual/en/reference.pcre.pattern.modifiers.php#54805
-
-        // 'Valid ASCII' => "a",
-        assert_eq!(parse_utf8_char("a".as_bytes()).unwrap(), Some('a'),);
-
-        // 'Valid 2 Octet Sequence' => "\xc3\xb1",
-        assert_eq!(parse_utf8_char(&[0xC3, 0xB1]).unwrap(), Some('ñ'),);
+    fn test_write_sequences_batched() {
+        CSI_BATCH.with(|batch| *batch.borrow_mut() = Some(String::new()));
 
-        // 'Invalid 2 Octet Sequence' => "\xc3\x28",
-        assert!(parse_utf8_char(&[0xC3, 0x28]).is_err());
+        write_sequences(&[FOCUS_REPORTING_ENABLE, BRACKETED_PASTE_ENABLE]).unwrap();
+        write_sequences(&[MOUSE_ENABLE_CLICKS]).unwrap();
 
-        // 'Invalid Sequence Identifier' => "\xa0\xa1",
-        assert!(parse_utf8_char(&[0xA0, 0xA1]).is_err());
-
-        // 'Valid 3 Octet Sequence' => "\xe2\x82\xa1",
         assert_eq!(
-            parse_utf8_char(&[0xE2, 0x81, 0xA1]).unwrap(),
-            Some('\u{2061}'),
+            CSI_BATCH.with(|batch| batch.borrow_mut().take()),
+            Some("\x1B[?1004h\x1B[?2004h\x1B[?1000h".to_string())
         );
+    }
 
-        // 'Invalid 3 Octet Sequence (in 2nd Octet)' => "\xe2\x28\xa1",
-        assert!(parse_utf8_char(&[0xE2, 0x28, 0xA1]).is_err());
-
-        // 'Invalid 3 Octet Sequence (in 3rd Octet)' => "\xe2\x82\x28",
-        assert!(parse_utf8_char(&[0xE2, 0x82, 0x28]).is_err());
-
-        // 'Valid 4 Octet Sequence' => "\xf0\x90\x8c\xbc",
-        assert_eq!(
-            parse_utf8_char(&[0xF0, 0x90, 0x8C, 0xBC]).unwrap(),
-            Some('𐌼'),
+    #[test]
+    fn test_key_id() {
+        assert_eq!(key_id(KeyCode::Char('A')), key_id(KeyCode::Char('a')));
+        assert_eq!(key_id(KeyCode::BackTab), key_id(KeyCode::Tab));
+        assert_ne!(key_id(KeyCode::F(1)), key_id(KeyCode::Backspace));
+        assert_ne!(key_id(KeyCode::Esc), key_id(KeyCode::Char('\x1B')));
+        assert_ne!(
+            key_id(KeyCode::Media(MediaKey::Play)),
+            key_id(KeyCode::F(255))
         );
-
-        // 'Invalid 4 Octet Sequence (in 2nd Octet)' => "\xf0\x28\x8c\xbc",
-        assert!(parse_utf8_char(&[0xF0, 0x28, 0x8C, 0xBC]).is_err());
-
-        // 'Invalid 4 Octet Sequence (in 3rd Octet)' => "\xf0\x90\x28\xbc",
-        assert!(parse_utf8_char(&[0xF0, 0x90, 0x28, 0xBC]).is_err());
-
-        // 'Invalid 4 Octet Sequence (in 4th Octet)' => "\xf0\x28\x8c\x28",
-        assert!(parse_utf8_char(&[0xF0, 0x28, 0x8C, 0x28]).is_err());
     }
 }
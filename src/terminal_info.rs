@@ -0,0 +1,242 @@
+//! The terminal identification & the workarounds of the known terminal bugs.
+
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Mutex,
+};
+
+use bitflags::bitflags;
+use crossterm_utils::Result;
+use lazy_static::lazy_static;
+
+use crate::input::Input;
+
+bitflags! {
+    /// Represents the known terminal bugs worked around by the input parser.
+    #[derive(Default)]
+    pub struct TerminalQuirks: u8 {
+        /// The SGR mouse release doesn't report the released button (`ESC [ < 3 ; x ; y m`),
+        /// the last pressed button is reported instead.
+        const SGR_RELEASE_WITHOUT_BUTTON = 0b0000_0001;
+        /// The wheel notches are followed by the SGR releases (`ESC [ < 64 ; x ; y m`), they're
+        /// dropped.
+        const SGR_WHEEL_RELEASE = 0b0000_0010;
+    }
+}
+
+/// Identifies the terminals in the workaround registry.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TerminalMatch {
+    /// The terminal name reported by the XTVERSION (`"WezTerm"`, `"kitty"`, ...).
+    Name(&'static str),
+    /// The terminal type reported by the secondary device attributes (`Pp`).
+    DeviceType(u16),
+}
+
+impl TerminalMatch {
+    fn matches(&self, info: &TerminalInfo) -> bool {
+        match *self {
+            TerminalMatch::Name(name) => info.name() == Some(name),
+            TerminalMatch::DeviceType(device_type) => info.device_type() == Some(device_type),
+        }
+    }
+}
+
+/// The known terminal bugs.
+const KNOWN_QUIRKS: &[(TerminalMatch, TerminalQuirks)] = &[
+    // rxvt-unicode
+    (
+        TerminalMatch::DeviceType(85),
+        TerminalQuirks::SGR_RELEASE_WITHOUT_BUTTON,
+    ),
+];
+
+lazy_static! {
+    /// The identification of the terminal, the terminal is asked once.
+    static ref TERMINAL_INFO: Mutex<Option<TerminalInfo>> = Mutex::new(None);
+    /// The terminal bugs registered by the applications.
+    static ref REGISTERED_QUIRKS: Mutex<Vec<(TerminalMatch, TerminalQuirks)>> =
+        Mutex::new(Vec::new());
+}
+
+/// The XTVERSION report & the secondary device attributes (`terminal type`,
+/// `firmware version`).
+pub(crate) type TerminalIdentity = (Option<String>, Option<(u16, u32)>);
+
+/// The workarounds applied by the parser (`TerminalQuirks` bits).
+static ACTIVE_QUIRKS: AtomicU8 = AtomicU8::new(0);
+
+/// Represents the identification of the terminal.
+///
+/// See the [`terminal_info`](fn.terminal_info.html) function.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
+pub struct TerminalInfo {
+    version: Option<String>,
+    device_attributes: Option<(u16, u32)>,
+    quirks: TerminalQuirks,
+}
+
+impl TerminalInfo {
+    /// Returns the XTVERSION report (`"WezTerm 20230408-112425-69ae8472"`, `"kitty(0.26.5)"`,
+    /// ...) or `None` if the terminal doesn't support it.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Returns the terminal name, the XTVERSION report without the version.
+    pub fn name(&self) -> Option<&str> {
+        self.version()
+            .and_then(|version| version.split(&[' ', '('][..]).next())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Returns the terminal type reported by the secondary device attributes (`Pp`).
+    pub fn device_type(&self) -> Option<u16> {
+        self.device_attributes.map(|(device_type, _)| device_type)
+    }
+
+    /// Returns the firmware version reported by the secondary device attributes (`Pv`).
+    pub fn firmware_version(&self) -> Option<u32> {
+        self.device_attributes.map(|(_, version)| version)
+    }
+
+    /// Returns the known bugs of the terminal worked around by the input parser.
+    pub fn quirks(&self) -> TerminalQuirks {
+        self.quirks
+    }
+
+    /// Returns `true` if the terminal answered any of the queries.
+    pub fn is_identified(&self) -> bool {
+        self.version.is_some() || self.device_attributes.is_some()
+    }
+}
+
+/// Returns the identification of the terminal.
+///
+/// The terminal is asked for the XTVERSION & the secondary device attributes (DA2) on
+/// the first call, the result is cached. The known bugs of the identified terminal are
+/// worked around by the input parser from then on.
+///
+/// # Notes
+///
+/// * It requires enabled raw mode (see the
+///   [`crossterm_screen`](https://docs.rs/crossterm_screen/) crate documentation to learn more).
+/// * The terminal isn't identified on Windows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_input::{terminal_info, RawScreen, Result};
+///
+/// fn main() -> Result<()> {
+///     let _raw = RawScreen::into_raw_mode()?;
+///
+///     if let Some(name) = terminal_info()?.name() {
+///         println!("Running in {}\r", name);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn terminal_info() -> Result<TerminalInfo> {
+    crate::input().terminal_info()
+}
+
+/// Registers the terminal bugs (`quirks`) of the `terminal` (on top of the known ones).
+///
+/// The workarounds are applied once the terminal is identified, see the
+/// [`terminal_info`](fn.terminal_info.html) function.
+pub fn register_terminal_quirks(terminal: TerminalMatch, quirks: TerminalQuirks) {
+    REGISTERED_QUIRKS.lock().unwrap().push((terminal, quirks));
+
+    if let Some(info) = TERMINAL_INFO.lock().unwrap().as_mut() {
+        if terminal.matches(info) {
+            info.quirks |= quirks;
+            ACTIVE_QUIRKS.store(info.quirks.bits(), Ordering::SeqCst);
+        }
+    }
+}
+
+/// Returns the workarounds applied by the parser.
+#[cfg(not(feature = "keyboard-only"))]
+pub(crate) fn active_quirks() -> TerminalQuirks {
+    TerminalQuirks::from_bits_truncate(ACTIVE_QUIRKS.load(Ordering::SeqCst))
+}
+
+/// Returns the cached identification of the terminal, asks the terminal with the `input`
+/// if it wasn't identified yet.
+pub(crate) fn identify_terminal(input: &dyn Input) -> Result<TerminalInfo> {
+    // Locked while the terminal is asked, the concurrent callers get the same result
+    let mut cached = TERMINAL_INFO.lock().unwrap();
+
+    if let Some(info) = cached.as_ref() {
+        return Ok(info.clone());
+    }
+
+    let (version, device_attributes) = input.query_terminal_identity()?;
+    let mut info = TerminalInfo {
+        version,
+        device_attributes,
+        quirks: TerminalQuirks::empty(),
+    };
+    info.quirks = terminal_quirks(&info, &REGISTERED_QUIRKS.lock().unwrap());
+
+    ACTIVE_QUIRKS.store(info.quirks.bits(), Ordering::SeqCst);
+    *cached = Some(info.clone());
+
+    Ok(info)
+}
+
+/// Returns the known & the `registered` bugs of the terminal.
+fn terminal_quirks(
+    info: &TerminalInfo,
+    registered: &[(TerminalMatch, TerminalQuirks)],
+) -> TerminalQuirks {
+    KNOWN_QUIRKS
+        .iter()
+        .chain(registered.iter())
+        .filter(|(terminal, _)| terminal.matches(info))
+        .fold(TerminalQuirks::empty(), |quirks, (_, terminal_quirks)| {
+            quirks | *terminal_quirks
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{terminal_quirks, TerminalInfo, TerminalMatch, TerminalQuirks};
+
+    fn info(version: Option<&str>, device_type: Option<u16>) -> TerminalInfo {
+        TerminalInfo {
+            version: version.map(str::to_string),
+            device_attributes: device_type.map(|device_type| (device_type, 95)),
+            quirks: TerminalQuirks::empty(),
+        }
+    }
+
+    #[test]
+    fn test_terminal_quirks() {
+        let kitty = info(Some("kitty(0.26.5)"), Some(1));
+        assert_eq!(kitty.name(), Some("kitty"));
+        assert_eq!(kitty.firmware_version(), Some(95));
+        assert!(kitty.is_identified());
+        assert!(!TerminalInfo::default().is_identified());
+        assert_eq!(info(Some("WezTerm 2023"), None).name(), Some("WezTerm"));
+
+        assert_eq!(terminal_quirks(&kitty, &[]), TerminalQuirks::empty());
+        assert_eq!(
+            terminal_quirks(&info(None, Some(85)), &[]),
+            TerminalQuirks::SGR_RELEASE_WITHOUT_BUTTON
+        );
+
+        let registered = [
+            (
+                TerminalMatch::Name("kitty"),
+                TerminalQuirks::SGR_WHEEL_RELEASE,
+            ),
+            (TerminalMatch::Name("kit"), TerminalQuirks::all()),
+        ];
+        assert_eq!(
+            terminal_quirks(&kitty, &registered),
+            TerminalQuirks::SGR_WHEEL_RELEASE
+        );
+    }
+}
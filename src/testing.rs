@@ -0,0 +1,660 @@
+//! Utilities for testing applications reading the input.
+//!
+//! Requires the `testing` feature.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm_utils::Result;
+
+use crate::encode::MouseEncoding;
+use crate::{InputEvent, KeyCode, KeyEvent, KeyModifiers, SourceId};
+#[cfg(not(feature = "keyboard-only"))]
+use crate::{MouseButton, MouseEvent, MouseEventKind};
+
+/// Creates a new `Ctrl + c` key event.
+pub fn ctrl(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+/// Creates a new `Alt + c` key event.
+pub fn alt(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)
+}
+
+/// A step of the `EventScript`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ScriptStep {
+    /// Produce the event.
+    Event(InputEvent),
+    /// Wait before the next step.
+    Wait(Duration),
+}
+
+/// A builder of synthetic input event sequences.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm_input::input;
+/// use crossterm_input::testing::{ctrl, EventScript};
+///
+/// let mut reader = input().read_async();
+///
+/// EventScript::new()
+///     .text("ls")
+///     .wait(Duration::from_millis(10))
+///     .key(ctrl('c'))
+///     .play()
+///     .expect("Unable to play the script");
+///
+/// assert_eq!(reader.count(), 3);
+/// ```
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct EventScript {
+    steps: Vec<ScriptStep>,
+}
+
+impl EventScript {
+    /// Creates a new empty `EventScript`.
+    pub fn new() -> EventScript {
+        EventScript::default()
+    }
+
+    /// Adds the input event.
+    pub fn event(mut self, event: InputEvent) -> EventScript {
+        self.steps.push(ScriptStep::Event(event));
+        self
+    }
+
+    /// Adds the key event.
+    pub fn key(self, key_event: KeyEvent) -> EventScript {
+        self.event(InputEvent::Keyboard(key_event))
+    }
+
+    /// Adds the key events for all the characters of the `text`.
+    pub fn text(self, text: &str) -> EventScript {
+        text.chars().fold(self, |script, c| {
+            script.key(KeyEvent::from(KeyCode::Char(c)))
+        })
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    /// Adds the mouse event.
+    pub fn mouse(self, mouse_event: MouseEvent) -> EventScript {
+        self.event(InputEvent::Mouse(mouse_event))
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    /// Adds the mouse button press event.
+    pub fn mouse_press(self, button: MouseButton, x: u16, y: u16) -> EventScript {
+        self.mouse(MouseEvent::new(MouseEventKind::Press, Some(button), x, y))
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    /// Adds the mouse button release event.
+    pub fn mouse_release(self, x: u16, y: u16) -> EventScript {
+        self.mouse(MouseEvent::new(MouseEventKind::Release, None, x, y))
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    /// Adds the left mouse button drag event.
+    pub fn mouse_hold(self, x: u16, y: u16) -> EventScript {
+        self.mouse(MouseEvent::new(
+            MouseEventKind::Drag,
+            Some(MouseButton::Left),
+            x,
+            y,
+        ))
+    }
+
+    /// Adds the wait before the next step.
+    pub fn wait(mut self, duration: Duration) -> EventScript {
+        self.steps.push(ScriptStep::Wait(duration));
+        self
+    }
+
+    /// Returns all the steps.
+    pub fn steps(&self) -> &[ScriptStep] {
+        &self.steps
+    }
+
+    /// Returns all the events (waits are skipped).
+    pub fn events(&self) -> Vec<InputEvent> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                ScriptStep::Event(event) => Some(event.clone()),
+                ScriptStep::Wait(_) => None,
+            })
+            .collect()
+    }
+
+    /// Plays the script, blocks until all the steps are done.
+    ///
+    /// Events are produced by all the existing readers with the
+    /// [`SourceId::Playback`](../enum.SourceId.html#variant.Playback) source.
+    ///
+    /// # Notes
+    ///
+    /// * UNIX only, an error is returned on Windows.
+    /// * The mouse coordinates are produced as they are, the
+    ///   [`MouseOrigin`](../enum.MouseOrigin.html) isn't applied.
+    pub fn play(self) -> Result<()> {
+        for step in self.steps {
+            match step {
+                ScriptStep::Event(event) => send_event(SourceId::Playback, event)?,
+                ScriptStep::Wait(duration) => thread::sleep(duration),
+            };
+        }
+        Ok(())
+    }
+}
+
+/// A generator of synthetic input events at a configurable rate.
+///
+/// It's useful to stress test the backpressure & coalescing of the input consumers. Events
+/// are produced by all the existing readers with the
+/// [`SourceId::Synthetic`](../enum.SourceId.html#variant.Synthetic) source. Key, mouse button
+/// & mouse motion events are generated by default, the key events only in the `keyboard-only`
+/// build.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm_input::input;
+/// use crossterm_input::testing::FloodEventSource;
+///
+/// let mut reader = input().read_async();
+///
+/// let flood = FloodEventSource::new(10_000).spawn();
+/// // Consume the events with the `reader` ...
+/// let sent = flood.stop().expect("Unable to flood the readers");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct FloodEventSource {
+    rate: u32,
+    keys: bool,
+    #[cfg(not(feature = "keyboard-only"))]
+    mouse: bool,
+    #[cfg(not(feature = "keyboard-only"))]
+    motion: bool,
+}
+
+impl FloodEventSource {
+    /// Creates a new `FloodEventSource` producing `rate` events per second.
+    ///
+    /// Events are produced as fast as possible if the `rate` is `0`.
+    pub fn new(rate: u32) -> FloodEventSource {
+        FloodEventSource {
+            rate,
+            keys: true,
+            #[cfg(not(feature = "keyboard-only"))]
+            mouse: true,
+            #[cfg(not(feature = "keyboard-only"))]
+            motion: true,
+        }
+    }
+
+    /// Enables or disables the key events (`KeyCode::Char('a')` - `KeyCode::Char('z')`).
+    pub fn keys(mut self, enabled: bool) -> FloodEventSource {
+        self.keys = enabled;
+        self
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    /// Enables or disables the mouse button events (alternating press & release).
+    pub fn mouse(mut self, enabled: bool) -> FloodEventSource {
+        self.mouse = enabled;
+        self
+    }
+
+    #[cfg(not(feature = "keyboard-only"))]
+    /// Enables or disables the mouse motion events (`MouseEventKind::Drag`).
+    pub fn motion(mut self, enabled: bool) -> FloodEventSource {
+        self.motion = enabled;
+        self
+    }
+
+    /// Returns the `index`th generated event.
+    ///
+    /// Returns `None` if all the event kinds are disabled.
+    pub fn event(&self, index: usize) -> Option<InputEvent> {
+        #[cfg(not(feature = "keyboard-only"))]
+        let kinds = [self.keys, self.mouse, self.motion];
+        #[cfg(feature = "keyboard-only")]
+        let kinds = [self.keys];
+        let enabled = kinds.iter().filter(|enabled| **enabled).count();
+        if enabled == 0 {
+            return None;
+        }
+
+        // Safe to unwrap, there's at least one enabled kind
+        let kind = kinds
+            .iter()
+            .enumerate()
+            .filter(|(_, enabled)| **enabled)
+            .map(|(kind, _)| kind)
+            .nth(index % enabled)
+            .unwrap();
+        let n = index / enabled;
+
+        // Positions wander around the 80x24 screen
+        #[cfg(not(feature = "keyboard-only"))]
+        let (x, y) = ((n % 80) as u16, (n % 24) as u16);
+
+        let event = match (kind, n % 2) {
+            #[cfg(not(feature = "keyboard-only"))]
+            (1, 0) => InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Press,
+                Some(MouseButton::Left),
+                x,
+                y,
+            )),
+            #[cfg(not(feature = "keyboard-only"))]
+            (1, _) => InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Left),
+                x,
+                y,
+            )),
+            #[cfg(not(feature = "keyboard-only"))]
+            (2, _) => InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Drag,
+                Some(MouseButton::Left),
+                x,
+                y,
+            )),
+            _ => InputEvent::Keyboard(KeyCode::Char((b'a' + (n % 26) as u8) as char).into()),
+        };
+
+        Some(event)
+    }
+
+    /// Produces events for the `duration`, blocks until it elapses.
+    ///
+    /// Returns the number of produced events.
+    ///
+    /// # Notes
+    ///
+    /// UNIX only, an error is returned on Windows.
+    pub fn run(&self, duration: Duration) -> Result<usize> {
+        let start = Instant::now();
+        self.flood(|| start.elapsed() < duration)
+    }
+
+    /// Produces events in a background thread until the returned `FloodHandle` is stopped or
+    /// dropped.
+    pub fn spawn(self) -> FloodHandle {
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let shutdown = shutdown.clone();
+            move || self.flood(|| !shutdown.load(Ordering::SeqCst))
+        });
+
+        FloodHandle {
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    /// Produces paced events while `running` returns `true`.
+    fn flood<F>(&self, mut running: F) -> Result<usize>
+    where
+        F: FnMut() -> bool,
+    {
+        let start = Instant::now();
+        let mut sent = 0;
+
+        while running() {
+            let event = match self.event(sent) {
+                Some(event) => event,
+                None => break,
+            };
+
+            if self.rate > 0 {
+                let due = Duration::from_nanos(sent as u64 * 1_000_000_000 / u64::from(self.rate));
+                let elapsed = start.elapsed();
+                if due > elapsed {
+                    thread::sleep(due - elapsed);
+                }
+            }
+
+            send_event(SourceId::Synthetic, event)?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+}
+
+/// A handle of the background `FloodEventSource`.
+///
+/// The flood is stopped once you drop the `FloodHandle`.
+pub struct FloodHandle {
+    shutdown: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<Result<usize>>>,
+}
+
+impl FloodHandle {
+    /// Stops the flood, blocks until the background thread finishes.
+    ///
+    /// Returns the number of produced events.
+    pub fn stop(mut self) -> Result<usize> {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        // Safe to unwrap, it's taken here & in the drop() only
+        match self.handle.take().unwrap().join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+impl Drop for FloodHandle {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Returns the sample of all the events representable with the `encoding`.
+///
+/// Events which are encoded into the same bytes as another event are skipped (`Ctrl + i` is
+/// `Tab`, `Ctrl + h` is `Ctrl + Backspace`, `Ctrl + 3` is `Esc`, `Alt + [` is the CSI
+/// introducer, `F3` with modifiers is the cursor position report, ...). There're no mouse
+/// events in the `keyboard-only` build, the mouse reports aren't parsed.
+#[cfg_attr(feature = "keyboard-only", allow(unused_variables))]
+pub fn representable_events(encoding: MouseEncoding) -> Vec<InputEvent> {
+    let mut key_events: Vec<KeyEvent> = [
+        KeyCode::Backspace,
+        KeyCode::Enter,
+        KeyCode::Tab,
+        KeyCode::BackTab,
+        KeyCode::Null,
+        KeyCode::Esc,
+    ]
+    .iter()
+    .map(|code| KeyEvent::from(*code))
+    .collect();
+    key_events.push(KeyEvent::new(KeyCode::Backspace, KeyModifiers::CONTROL));
+
+    // Keys with the modifier parameter
+    let modifiers = [
+        KeyModifiers::NONE,
+        KeyModifiers::SHIFT,
+        KeyModifiers::CONTROL,
+        KeyModifiers::ALT,
+        KeyModifiers::SHIFT | KeyModifiers::CONTROL,
+        KeyModifiers::CONTROL | KeyModifiers::ALT,
+        KeyModifiers::all(),
+    ];
+    let codes = [
+        KeyCode::Left,
+        KeyCode::Right,
+        KeyCode::Up,
+        KeyCode::Down,
+        KeyCode::Home,
+        KeyCode::End,
+        KeyCode::PageUp,
+        KeyCode::PageDown,
+        KeyCode::Delete,
+        KeyCode::Insert,
+    ];
+    for code in codes.iter().cloned().chain((1..=20).map(KeyCode::F)) {
+        key_events.extend(
+            modifiers
+                .iter()
+                .filter(|modifiers| code != KeyCode::F(3) || modifiers.is_empty())
+                .map(|modifiers| KeyEvent::new(code, *modifiers)),
+        );
+    }
+
+    key_events.extend((b' '..=b'~').map(|c| KeyEvent::from(KeyCode::Char(c as char))));
+    key_events.extend("čžř€😀".chars().map(|c| KeyEvent::from(KeyCode::Char(c))));
+    key_events
+        .extend((b'a'..=b'z').map(|c| KeyEvent::new(KeyCode::Char(c as char), KeyModifiers::ALT)));
+    key_events.extend(
+        (b'a'..=b'z')
+            .chain(b'4'..=b'7')
+            .filter(|c| !b"hijm".contains(c))
+            .map(|c| KeyEvent::new(KeyCode::Char(c as char), KeyModifiers::CONTROL)),
+    );
+
+    let mut events: Vec<InputEvent> = key_events.into_iter().map(InputEvent::Keyboard).collect();
+    #[cfg(not(feature = "keyboard-only"))]
+    events.extend(
+        representable_mouse_events(encoding)
+            .into_iter()
+            .map(InputEvent::Mouse),
+    );
+    events.extend(vec![
+        InputEvent::Paste(String::new()),
+        InputEvent::Paste("pasted\r\n\x1B[A text ž".to_string()),
+        InputEvent::FocusGained,
+        InputEvent::FocusLost,
+    ]);
+    events
+}
+
+/// Returns the sample of all the mouse events representable with the `encoding`.
+#[cfg(not(feature = "keyboard-only"))]
+fn representable_mouse_events(encoding: MouseEncoding) -> Vec<MouseEvent> {
+    let max = match encoding {
+        MouseEncoding::X10 => 222,
+        MouseEncoding::Sgr => 1000,
+    };
+
+    let mut mouse_events = Vec::new();
+    for &(x, y) in &[(0, 0), (9, 19), (max, max)] {
+        mouse_events.extend(
+            [
+                MouseButton::Left,
+                MouseButton::Right,
+                MouseButton::Middle,
+                MouseButton::WheelUp,
+                MouseButton::WheelDown,
+                MouseButton::Back,
+                MouseButton::Forward,
+                MouseButton::Other(11),
+            ]
+            .iter()
+            .map(|button| MouseEvent::new(MouseEventKind::Press, Some(*button), x, y)),
+        );
+
+        // X10 doesn't report the released button
+        let released = match encoding {
+            MouseEncoding::X10 => None,
+            MouseEncoding::Sgr => Some(MouseButton::Right),
+        };
+        mouse_events.push(MouseEvent::new(MouseEventKind::Release, released, x, y));
+        mouse_events.push(MouseEvent::new(
+            MouseEventKind::Drag,
+            Some(MouseButton::Left),
+            x,
+            y,
+        ));
+        mouse_events.push(MouseEvent {
+            modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            ..MouseEvent::new(MouseEventKind::Press, Some(MouseButton::Left), x, y)
+        });
+    }
+
+    mouse_events
+}
+
+/// Encodes the `event` with the `encoding` and parses it back.
+///
+/// Returns `None` if the event can't be encoded or parsed.
+///
+/// # Notes
+///
+/// * UNIX only, there's no parser on Windows.
+/// * The C0 controls are parsed with the shared [`InputConfig`](../struct.InputConfig.html).
+#[cfg(unix)]
+pub fn round_trip(event: &InputEvent, encoding: MouseEncoding) -> Option<InputEvent> {
+    crate::encode::encode(event, encoding)
+        .and_then(|bytes| crate::sys::ansi::parse_complete_event(&bytes))
+        .and_then(|internal_event| internal_event.into())
+}
+
+/// Asserts that all the `events` are parsed back to the same events when encoded with the
+/// `encoding`.
+///
+/// It's a conformance check for downstream protocol implementations.
+///
+/// # Panics
+///
+/// Panics with the first event which doesn't round trip.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm_input::encode::MouseEncoding;
+/// use crossterm_input::testing::{assert_round_trips, representable_events};
+///
+/// assert_round_trips(representable_events(MouseEncoding::Sgr), MouseEncoding::Sgr);
+/// ```
+#[cfg(unix)]
+pub fn assert_round_trips<I>(events: I, encoding: MouseEncoding)
+where
+    I: IntoIterator<Item = InputEvent>,
+{
+    for event in events {
+        assert_eq!(
+            round_trip(&event, encoding).as_ref(),
+            Some(&event),
+            "{:?} doesn't round trip with the {:?} encoding ({:?})",
+            event,
+            encoding,
+            crate::encode::encode(&event, encoding)
+        );
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn send_event(source: SourceId, event: InputEvent) -> Result<()> {
+    crate::sys::unix::send_internal_event(source, crate::InternalEvent::Input(event));
+    Ok(())
+}
+
+#[cfg(windows)]
+pub(crate) fn send_event(_source: SourceId, _event: InputEvent) -> Result<()> {
+    Err(crossterm_utils::ErrorKind::IoError(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "Synthetic events aren't supported on Windows",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_round_trips() {
+        #[cfg(not(feature = "no-x10-mouse"))]
+        assert_round_trips(representable_events(MouseEncoding::X10), MouseEncoding::X10);
+        assert_round_trips(representable_events(MouseEncoding::Sgr), MouseEncoding::Sgr);
+
+        assert_eq!(
+            round_trip(
+                &InputEvent::Keyboard(KeyCode::F(21).into()),
+                MouseEncoding::Sgr
+            ),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_flood_events() {
+        let flood = FloodEventSource::new(0);
+        assert_eq!(
+            (0..4)
+                .filter_map(|index| flood.event(index))
+                .collect::<Vec<_>>(),
+            vec![
+                InputEvent::Keyboard(KeyCode::Char('a').into()),
+                InputEvent::Mouse(MouseEvent::new(
+                    MouseEventKind::Press,
+                    Some(MouseButton::Left),
+                    0,
+                    0
+                )),
+                InputEvent::Mouse(MouseEvent::new(
+                    MouseEventKind::Drag,
+                    Some(MouseButton::Left),
+                    0,
+                    0
+                )),
+                InputEvent::Keyboard(KeyCode::Char('b').into()),
+            ]
+        );
+
+        let flood = FloodEventSource::new(0).keys(false).motion(false);
+        assert_eq!(
+            flood.event(1),
+            Some(InputEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Release,
+                Some(MouseButton::Left),
+                1,
+                1
+            )))
+        );
+
+        let flood = flood.mouse(false);
+        assert_eq!(flood.event(0), None);
+        assert_eq!(flood.run(Duration::from_millis(10)).ok(), Some(0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_flood_rate() {
+        let sent = FloodEventSource::new(1000)
+            .run(Duration::from_millis(10))
+            .unwrap();
+        // Paced at 1 event per millisecond
+        assert!((1..=11).contains(&sent));
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyboard-only"))]
+    fn test_event_script() {
+        let script = EventScript::new()
+            .key(ctrl('c'))
+            .wait(Duration::from_millis(10))
+            .text("ab")
+            .mouse_press(MouseButton::Left, 3, 4);
+
+        assert_eq!(script.steps().len(), 5);
+        assert_eq!(
+            script.events(),
+            vec![
+                InputEvent::Keyboard(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+                InputEvent::Keyboard(KeyCode::Char('a').into()),
+                InputEvent::Keyboard(KeyCode::Char('b').into()),
+                InputEvent::Mouse(MouseEvent::new(
+                    MouseEventKind::Press,
+                    Some(MouseButton::Left),
+                    3,
+                    4
+                )),
+            ]
+        );
+    }
+}